@@ -16,7 +16,10 @@
 
 use std::fmt::{self, Display, Write};
 
-use crate::{ASMFunction, ASMProgram, Instruction, Operand, Register};
+use crate::{
+    ASMFunction, ASMProgram, BinaryOp, Cond, Data, Instruction, Operand, Register, ShiftOp, Size,
+    StaticObject, UnaryOp,
+};
 
 pub trait AssemblyRepr {
     fn generate_asm(&self, writer: &mut impl Write) -> fmt::Result;
@@ -28,6 +31,9 @@ impl Display for ASMProgram<'_> {
         for function in &self.functions {
             function.fmt(f)?;
         }
+        for object in &self.objects {
+            object.fmt(f)?;
+        }
         f.write_str(NOEXECSTACK)
     }
 }
@@ -36,38 +42,252 @@ const TAB: &str = "\t";
 
 impl Display for ASMFunction<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "{TAB}.globl {}", self.name)?;
+        if self.global {
+            writeln!(f, "{TAB}.globl {}", self.name)?;
+        }
         writeln!(f, "{}:", self.name)?;
         for instruction in &self.instructions {
-            writeln!(f, "{TAB}{instruction}")?;
+            match instruction {
+                Instruction::Label(_) => writeln!(f, "{instruction}")?,
+                _ => writeln!(f, "{TAB}{instruction}")?,
+            }
         }
         writeln!(f)
     }
 }
 
-impl Display for Instruction {
+impl Display for StaticObject {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Mov { from, to } => write!(f, "movl {from},{to}"),
-            Self::Ret => f.write_str("ret")
+        if self.global {
+            writeln!(f, "{TAB}.globl {}", self.name)?;
         }
+        let zeroed = self.init.iter().all(|data| matches!(data, Data::Zero(_)));
+        let section = match (self.read_only, zeroed) {
+            (true, _) => ".section .rodata",
+            (false, true) => ".bss",
+            (false, false) => ".data",
+        };
+        writeln!(f, "{TAB}{section}")?;
+        writeln!(f, "{TAB}.balign {}", self.align)?;
+        writeln!(f, "{}:", self.name)?;
+        for data in &self.init {
+            match data {
+                Data::Int(size, value) => {
+                    let directive = match size {
+                        Size::Byte => ".byte",
+                        Size::Word => ".short",
+                        Size::Long => ".long",
+                        Size::Quad => ".quad",
+                    };
+                    writeln!(f, "{TAB}{directive} {value}")?;
+                }
+                Data::Address { symbol, offset } => {
+                    writeln!(f, "{TAB}.quad {symbol}{offset:+}")?;
+                }
+                Data::Zero(len) => writeln!(f, "{TAB}.zero {len}")?,
+            }
+        }
+        writeln!(f)
     }
 }
 
-impl Display for Operand {
+/// An operand along with the size it's used at, which selects the name of registers.
+struct Sized<'a>(&'a Operand, Size);
+
+impl Display for Sized<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Operand::Reg(reg) => reg.fmt(f),
+        match self.0 {
+            Operand::Reg(reg) => f.write_str(reg.name(self.1)),
             Operand::Imm(num) => write!(f, "${num}"),
+            Operand::Memory {
+                base,
+                index,
+                offset,
+            } => {
+                if *offset != 0 {
+                    write!(f, "{offset}")?;
+                }
+                write!(f, "({}", base.name(Size::Quad))?;
+                if let Some((index, scale)) = index {
+                    write!(f, ",{},{scale}", index.name(Size::Quad))?;
+                }
+                f.write_str(")")
+            }
+            Operand::Got(name) => write!(f, "{name}@GOTPCREL(%rip)"),
+            Operand::Rip(name) => write!(f, "{name}(%rip)"),
         }
     }
 }
 
-impl Display for Register {
+impl Display for Instruction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let text = match self {
-            Register::EAX => "%eax",
-        };
-        f.write_str(text)
+        match self {
+            Self::Mov { size, from, to } => {
+                let s = size.suffix();
+                write!(f, "mov{s} {},{}", Sized(from, *size), Sized(to, *size))
+            }
+            Self::Movsx {
+                from_size,
+                to_size,
+                from,
+                to,
+            } => {
+                let (fs, ts) = (from_size.suffix(), to_size.suffix());
+                let (from, to) = (Sized(from, *from_size), Sized(to, *to_size));
+                write!(f, "movs{fs}{ts} {from},{to}")
+            }
+            Self::Movzx {
+                from_size,
+                to_size,
+                from,
+                to,
+            } => {
+                let (fs, ts) = (from_size.suffix(), to_size.suffix());
+                let (from, to) = (Sized(from, *from_size), Sized(to, *to_size));
+                write!(f, "movz{fs}{ts} {from},{to}")
+            }
+            Self::Lea { from, to } => {
+                write!(
+                    f,
+                    "leaq {},{}",
+                    Sized(from, Size::Quad),
+                    Sized(to, Size::Quad)
+                )
+            }
+            Self::Push(operand) => write!(f, "pushq {}", Sized(operand, Size::Quad)),
+            Self::Pop(operand) => write!(f, "popq {}", Sized(operand, Size::Quad)),
+            Self::Binary { op, size, from, to } => {
+                let s = size.suffix();
+                write!(f, "{op}{s} {},{}", Sized(from, *size), Sized(to, *size))
+            }
+            Self::Unary { op, size, operand } => {
+                write!(f, "{op}{} {}", size.suffix(), Sized(operand, *size))
+            }
+            Self::Shift { op, size, operand } => {
+                write!(f, "{op}{} %cl,{}", size.suffix(), Sized(operand, *size))
+            }
+            Self::Cqo => f.write_str("cqto"),
+            Self::Idiv { size, divisor } => {
+                write!(f, "idiv{} {}", size.suffix(), Sized(divisor, *size))
+            }
+            Self::Div { size, divisor } => {
+                write!(f, "div{} {}", size.suffix(), Sized(divisor, *size))
+            }
+            Self::Cmp { size, rhs, lhs } => {
+                let s = size.suffix();
+                write!(f, "cmp{s} {},{}", Sized(rhs, *size), Sized(lhs, *size))
+            }
+            Self::SetCC { cond, to } => write!(f, "set{cond} {}", Sized(to, Size::Byte)),
+            Self::Jmp(label) => write!(f, "jmp {label}"),
+            Self::JmpCC { cond, label } => write!(f, "j{cond} {label}"),
+            Self::Label(label) => write!(f, "{label}:"),
+            Self::Call(name) => write!(f, "call {name}@PLT"),
+            Self::CallIndirect(operand) => write!(f, "call *{}", Sized(operand, Size::Quad)),
+            Self::Ret => f.write_str("ret"),
+        }
+    }
+}
+
+impl Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Add => "add",
+            Self::Sub => "sub",
+            Self::Imul => "imul",
+            Self::And => "and",
+            Self::Or => "or",
+            Self::Xor => "xor",
+        })
+    }
+}
+
+impl Display for UnaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Neg => "neg",
+            Self::Not => "not",
+        })
+    }
+}
+
+impl Display for ShiftOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Shl => "shl",
+            Self::Sar => "sar",
+            Self::Shr => "shr",
+        })
+    }
+}
+
+impl Display for Cond {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::E => "e",
+            Self::Ne => "ne",
+            Self::L => "l",
+            Self::Le => "le",
+            Self::G => "g",
+            Self::Ge => "ge",
+            Self::B => "b",
+            Self::Be => "be",
+            Self::A => "a",
+            Self::Ae => "ae",
+        })
+    }
+}
+
+impl Register {
+    /// The name of the register when used at the given size.
+    #[must_use]
+    pub const fn name(self, size: Size) -> &'static str {
+        use Register::*;
+        use Size::*;
+        match (self, size) {
+            (AX, Byte) => "%al",
+            (AX, Word) => "%ax",
+            (AX, Long) => "%eax",
+            (AX, Quad) => "%rax",
+            (CX, Byte) => "%cl",
+            (CX, Word) => "%cx",
+            (CX, Long) => "%ecx",
+            (CX, Quad) => "%rcx",
+            (DX, Byte) => "%dl",
+            (DX, Word) => "%dx",
+            (DX, Long) => "%edx",
+            (DX, Quad) => "%rdx",
+            (SI, Byte) => "%sil",
+            (SI, Word) => "%si",
+            (SI, Long) => "%esi",
+            (SI, Quad) => "%rsi",
+            (DI, Byte) => "%dil",
+            (DI, Word) => "%di",
+            (DI, Long) => "%edi",
+            (DI, Quad) => "%rdi",
+            (R8, Byte) => "%r8b",
+            (R8, Word) => "%r8w",
+            (R8, Long) => "%r8d",
+            (R8, Quad) => "%r8",
+            (R9, Byte) => "%r9b",
+            (R9, Word) => "%r9w",
+            (R9, Long) => "%r9d",
+            (R9, Quad) => "%r9",
+            (R10, Byte) => "%r10b",
+            (R10, Word) => "%r10w",
+            (R10, Long) => "%r10d",
+            (R10, Quad) => "%r10",
+            (R11, Byte) => "%r11b",
+            (R11, Word) => "%r11w",
+            (R11, Long) => "%r11d",
+            (R11, Quad) => "%r11",
+            (SP, Byte) => "%spl",
+            (SP, Word) => "%sp",
+            (SP, Long) => "%esp",
+            (SP, Quad) => "%rsp",
+            (BP, Byte) => "%bpl",
+            (BP, Word) => "%bp",
+            (BP, Long) => "%ebp",
+            (BP, Quad) => "%rbp",
+        }
     }
 }
@@ -21,17 +21,201 @@ use utils::VarInt;
 #[derive(Debug, Clone)]
 pub struct ASMProgram<'s> {
     pub functions: Vec<ASMFunction<'s>>,
+    pub objects: Vec<StaticObject>,
 }
 
 #[derive(Debug, Clone)]
 pub struct ASMFunction<'s> {
     pub name: &'s str,
+    /// Whether the function has external linkage, and so is visible to the linker
+    pub global: bool,
     pub instructions: Vec<Instruction>,
 }
 
+/// An object with static storage duration, placed in `.rodata` if it's read-only, in `.bss` if
+/// it's all zeroes and in `.data` otherwise.
+#[derive(Debug, Clone)]
+pub struct StaticObject {
+    pub name: String,
+    /// Whether the object has external linkage, and so is visible to the linker
+    pub global: bool,
+    /// Whether the object is never modified, like the array of a string literal
+    pub read_only: bool,
+    pub align: u64,
+    pub init: Vec<Data>,
+}
+
+/// A piece of the initial contents of a [StaticObject].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Data {
+    Int(Size, i64),
+    /// The address of `symbol` plus `offset` bytes, which the linker fills in
+    Address {
+        symbol: String,
+        offset: i64,
+    },
+    /// The given number of zero bytes
+    Zero(u64),
+}
+
+/// The size of an operand, selecting the instruction suffix and register name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Size {
+    Byte,
+    Word,
+    Long,
+    Quad,
+}
+
+impl Size {
+    /// The size with the given number of bytes.
+    #[must_use]
+    pub const fn from_bytes(bytes: u64) -> Self {
+        match bytes {
+            1 => Self::Byte,
+            2 => Self::Word,
+            4 => Self::Long,
+            8 => Self::Quad,
+            _ => panic!("no operand has this size"),
+        }
+    }
+    #[must_use]
+    pub const fn suffix(self) -> char {
+        match self {
+            Self::Byte => 'b',
+            Self::Word => 'w',
+            Self::Long => 'l',
+            Self::Quad => 'q',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Imul,
+    And,
+    Or,
+    Xor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+/// Shifts by the count in `%cl`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShiftOp {
+    Shl,
+    /// Arithmetic right shift
+    Sar,
+    /// Logical right shift
+    Shr,
+}
+
+/// A condition code, for conditional jumps and `set` instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cond {
+    E,
+    Ne,
+    /// Signed <
+    L,
+    /// Signed <=
+    Le,
+    /// Signed >
+    G,
+    /// Signed >=
+    Ge,
+    /// Unsigned <
+    B,
+    /// Unsigned <=
+    Be,
+    /// Unsigned >
+    A,
+    /// Unsigned >=
+    Ae,
+}
+
 #[derive(Debug, Clone)]
 pub enum Instruction {
-    Mov { from: Operand, to: Operand },
+    Mov {
+        size: Size,
+        from: Operand,
+        to: Operand,
+    },
+    /// Sign-extending move
+    Movsx {
+        from_size: Size,
+        to_size: Size,
+        from: Operand,
+        to: Operand,
+    },
+    /// Zero-extending move. Moves from 32-bit operands zero-extend implicitly, so use
+    /// [Instruction::Mov] for those.
+    Movzx {
+        from_size: Size,
+        to_size: Size,
+        from: Operand,
+        to: Operand,
+    },
+    /// Load the address of a memory operand
+    Lea {
+        from: Operand,
+        to: Operand,
+    },
+    Push(Operand),
+    Pop(Operand),
+    Binary {
+        op: BinaryOp,
+        size: Size,
+        from: Operand,
+        to: Operand,
+    },
+    Unary {
+        op: UnaryOp,
+        size: Size,
+        operand: Operand,
+    },
+    Shift {
+        op: ShiftOp,
+        size: Size,
+        operand: Operand,
+    },
+    /// Sign-extend `%rax` into `%rdx:%rax`
+    Cqo,
+    /// Signed division of `%rdx:%rax`
+    Idiv {
+        size: Size,
+        divisor: Operand,
+    },
+    /// Unsigned division of `%rdx:%rax`
+    Div {
+        size: Size,
+        divisor: Operand,
+    },
+    /// Compares `rhs` to `lhs`, in AT&T operand order
+    Cmp {
+        size: Size,
+        rhs: Operand,
+        lhs: Operand,
+    },
+    SetCC {
+        cond: Cond,
+        to: Operand,
+    },
+    Jmp(String),
+    JmpCC {
+        cond: Cond,
+        label: String,
+    },
+    Label(String),
+    /// Calls a function through the PLT, so it may come from a shared library
+    Call(String),
+    /// Calls the function at the address in the operand
+    CallIndirect(Operand),
     Ret,
 }
 
@@ -39,9 +223,41 @@ pub enum Instruction {
 pub enum Operand {
     Reg(Register),
     Imm(VarInt),
+    /// The memory at `base + index * scale + offset`, where the scale is 1, 2, 4 or 8
+    Memory {
+        base: Register,
+        index: Option<(Register, u8)>,
+        offset: i64,
+    },
+    /// The GOT entry holding the address of a symbol, which may come from a shared library
+    Got(String),
+    /// The memory at a symbol defined in this program, addressed relative to `%rip`
+    Rip(String),
 }
 
-#[derive(Debug, Clone)]
+impl Operand {
+    /// The memory at a constant offset from the address in `base`.
+    #[must_use]
+    pub const fn memory(base: Register, offset: i64) -> Self {
+        Self::Memory {
+            base,
+            index: None,
+            offset,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Register {
-    EAX,
+    AX,
+    CX,
+    DX,
+    SI,
+    DI,
+    R8,
+    R9,
+    R10,
+    R11,
+    SP,
+    BP,
 }
@@ -0,0 +1,788 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! The evaluation of constant expressions (C17 6.6), with the typed integer arithmetic of the
+//! System V x86-64 ABI.
+
+use crate::{
+    BinaryOperator, Cast, CompoundLiteral, Expr, LiteralExpression, LogicalOperator, SExpr,
+    ScalarType, SizeOf, TagKind, Ternary, Type, UnaryExpr, UnaryOperator, conversion::char_type,
+    layout::Layouts,
+};
+use source::Span;
+use utils::StringLiteral;
+
+/// The value of a constant expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constant<'s> {
+    /// The object or function with static storage duration whose address is added to `value`,
+    /// which makes this an address constant
+    pub base: Option<Base<'s>>,
+    /// An integer sign- or zero-extended to 64 bits from its type, or an address
+    pub value: i64,
+    /// An integer or pointer type
+    pub ty: Type,
+}
+
+/// An object or function with static storage duration, whose address is a constant.
+#[derive(Debug, Clone)]
+pub enum Base<'s> {
+    /// A declared object or function
+    Named(&'s str),
+    /// The array of the string literal at a span, which is a different object from those of
+    /// other literals with the same contents
+    String(Span, StringLiteral),
+    /// The unnamed object of the compound literal at a span, which has static storage duration
+    /// outside of functions
+    Compound(Span, Box<CompoundLiteral<'s>>),
+}
+
+/// Bases are equal if they're the same object.
+impl PartialEq for Base<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Named(a), Self::Named(b)) => a == b,
+            (Self::String(a, a_string), Self::String(b, b_string)) => {
+                a == b && a_string == b_string
+            }
+            (Self::Compound(a, _), Self::Compound(b, _)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Base<'_> {}
+
+/// Why an expression couldn't be evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstError {
+    /// The expression isn't a constant expression
+    NotConstant,
+    /// Evaluating the part of the expression at `span` has undefined behavior, or it's a token
+    /// that failed to lex
+    Invalid { span: Span, message: String },
+}
+
+/// What the identifiers in a constant expression refer to.
+pub trait ConstContext<'s> {
+    /// The value of `name` if it's an enumeration constant.
+    fn enum_constant(&self, name: &str) -> Option<i64>;
+    /// The type of `name` if it designates an object or function with static storage duration,
+    /// whose address is a constant.
+    fn static_object(&self, name: &str) -> Option<Type>;
+    fn layouts(&self) -> &Layouts<'s>;
+    /// Whether compound literals have static storage duration, as they do outside of
+    /// functions, which makes their addresses constants.
+    fn static_compound_literals(&self) -> bool;
+    /// The type of an expression, without converting arrays and functions to pointers, or
+    /// [None] if it isn't known.
+    fn type_of(&mut self, expr: &SExpr<'s>) -> Option<Type>;
+}
+
+/// The integer type of `ty`, with enums having the type of their constants.
+fn integer_type(ty: &Type) -> Option<ScalarType> {
+    match ty.unqualified() {
+        Type::Scalar(scalar) if scalar.is_integer() => Some(*scalar),
+        Type::Tagged(tag) if tag.kind == TagKind::Enum => Some(ScalarType::Int),
+        _ => None,
+    }
+}
+
+/// Whether `ty` is a floating type, which constant expressions can't have since floating-point
+/// values aren't supported.
+fn is_floating(ty: &Type) -> bool {
+    matches!(ty.unqualified(), Type::Scalar(scalar) if scalar.is_floating())
+}
+
+impl<'s> Constant<'s> {
+    /// An integer of type `ty`, wrapped into its range.
+    #[must_use]
+    pub const fn int(value: i128, ty: ScalarType) -> Self {
+        Self {
+            base: None,
+            value: ty.wrap(value),
+            ty: Type::Scalar(ty),
+        }
+    }
+    /// The value of an integer constant, or [None] for an address.
+    #[must_use]
+    pub fn integer(&self) -> Option<i128> {
+        match (&self.base, integer_type(&self.ty)) {
+            (None, Some(ty)) => Some(ty.widen(self.value)),
+            _ => None,
+        }
+    }
+    /// Converts the constant to the scalar type `ty`, as by assignment or a cast. Returns [None]
+    /// if the result isn't a constant, as for an address converted to a narrow integer.
+    #[must_use]
+    pub fn convert(self, ty: &Type) -> Option<Self> {
+        let ty = ty.unqualified();
+        if let Type::Pointer(_) = ty {
+            return Some(Self {
+                ty: ty.clone(),
+                ..self
+            });
+        }
+        let scalar = integer_type(ty)?;
+        Some(match self.base {
+            // The address of an object is never null
+            Some(_) if scalar == ScalarType::Bool => Self::int(1, scalar),
+            // An address only fits in the integer types as wide as a pointer
+            Some(_) if scalar.size() == 8 => Self {
+                ty: Type::Scalar(scalar),
+                ..self
+            },
+            Some(_) => return None,
+            None => {
+                let value = match integer_type(&self.ty) {
+                    Some(from) => from.widen(self.value),
+                    None => i128::from(self.value.cast_unsigned()),
+                };
+                Self::int(value, scalar)
+            }
+        })
+    }
+    /// Whether the constant compares unequal to zero.
+    const fn is_true(&self) -> bool {
+        // The address of an object is never null
+        self.base.is_some() || self.value != 0
+    }
+}
+
+/// Evaluates a constant expression, which may be an address constant as allowed in the
+/// initializers of objects with static storage duration.
+pub fn evaluate<'s>(
+    expr: &SExpr<'s>,
+    ctx: &mut impl ConstContext<'s>,
+) -> Result<Constant<'s>, ConstError> {
+    Evaluator {
+        ctx,
+        evaluated: true,
+        integer: false,
+    }
+    .value(expr)
+}
+
+/// Evaluates an integer constant expression, as for array lengths, `case` labels and the values
+/// of enumeration constants.
+pub fn evaluate_integer<'s>(
+    expr: &SExpr<'s>,
+    ctx: &mut impl ConstContext<'s>,
+) -> Result<i128, ConstError> {
+    Evaluator {
+        ctx,
+        evaluated: true,
+        integer: true,
+    }
+    .value(expr)?
+    .integer()
+    .ok_or(ConstError::NotConstant)
+}
+
+struct Evaluator<'c, C> {
+    ctx: &'c mut C,
+    /// Whether the current subexpression is evaluated, rather than being skipped by `&&`, `||`
+    /// or `?:`. Undefined behavior is only an error when it is.
+    evaluated: bool,
+    /// Whether this is an integer constant expression, where floating constants are only
+    /// allowed as the operands of casts to integer types (C17 6.6p6). Elsewhere they're allowed
+    /// but not supported.
+    integer: bool,
+}
+
+impl<'s, C: ConstContext<'s>> Evaluator<'_, C> {
+    fn value(&mut self, expr: &SExpr<'s>) -> Result<Constant<'s>, ConstError> {
+        let span = expr.span.clone();
+        match &expr.inner {
+            Expr::Lit(LiteralExpression::Int(int)) => {
+                Ok(Constant::int(int.value.into(), int.kind.into()))
+            }
+            Expr::Lit(LiteralExpression::Char(c)) => {
+                Ok(Constant::int(c.value.into(), char_type(c.encoding)))
+            }
+            Expr::Lit(LiteralExpression::Bool(b)) => {
+                Ok(Constant::int((*b).into(), ScalarType::Bool))
+            }
+            // `nullptr` converts to any pointer type, just as a null `void *` does
+            Expr::Lit(LiteralExpression::Nullptr) => Ok(Constant {
+                base: None,
+                value: 0,
+                ty: Type::Pointer(Box::new(Type::Scalar(ScalarType::Void))),
+            }),
+            Expr::Lit(LiteralExpression::Float(_)) => Err(self.floating(span)),
+            Expr::Lit(LiteralExpression::Invalid) => Err(ConstError::Invalid {
+                span,
+                message: "Invalid token in constant expression".to_owned(),
+            }),
+            Expr::Ident(name) => match self.ctx.enum_constant(name) {
+                Some(value) => Ok(Constant::int(value.into(), ScalarType::Int)),
+                None => self.decay(expr),
+            },
+            Expr::Index(_)
+            | Expr::MemberAccess(_)
+            | Expr::CompoundLiteral(_)
+            | Expr::Lit(LiteralExpression::Str(_)) => self.decay(expr),
+            Expr::Unary(unary) => self.unary(expr, unary),
+            Expr::Binary(binary) => self.binary(span, binary.op, &binary.lhs, &binary.rhs),
+            Expr::Logical(logical) => {
+                let lhs = self.value(&logical.lhs)?.is_true();
+                // The right operand is only evaluated if the left one doesn't decide the result
+                let decided = match logical.op {
+                    LogicalOperator::And => !lhs,
+                    LogicalOperator::Or => lhs,
+                };
+                let rhs = self.skipped_if(decided, |this| this.value(&logical.rhs))?;
+                let value = match logical.op {
+                    LogicalOperator::And => lhs && rhs.is_true(),
+                    LogicalOperator::Or => lhs || rhs.is_true(),
+                };
+                Ok(Constant::int(value.into(), ScalarType::Int))
+            }
+            Expr::Ternary(ternary) => self.ternary(ternary),
+            Expr::Cast(cast) => self.cast(span, cast),
+            // The operand of `sizeof` isn't evaluated, so it only needs to have a type
+            Expr::SizeOf(size_of) => {
+                let ty = match &**size_of {
+                    SizeOf::Val(val) => self.ctx.type_of(val),
+                    SizeOf::Type(ty) => Some(ty.clone()),
+                };
+                let size = self.measure(ty.as_ref(), |layouts, ty| layouts.size_of(ty));
+                Ok(Constant::int(size.into(), ScalarType::ULong))
+            }
+            Expr::AlignOf(ty) => {
+                let align = self.measure(Some(ty), |layouts, ty| layouts.align_of(ty));
+                Ok(Constant::int(align.into(), ScalarType::ULong))
+            }
+            _ => Err(ConstError::NotConstant),
+        }
+    }
+    /// Measures the operand of `sizeof` or `_Alignof`, which must have a complete object type.
+    fn measure(
+        &self,
+        ty: Option<&Type>,
+        f: impl FnOnce(&Layouts<'s>, &Type) -> Option<u64>,
+    ) -> u64 {
+        // An operand without a type or with one that can't be measured has already been
+        // reported where it was parsed, so it's given a size that avoids further errors
+        let ty = ty.filter(|ty| {
+            !matches!(
+                ty.unqualified(),
+                Type::Scalar(ScalarType::Void) | Type::Function(_)
+            )
+        });
+        ty.and_then(|ty| f(self.ctx.layouts(), ty)).unwrap_or(1)
+    }
+    /// Evaluates `f` as a subexpression that isn't evaluated if `skipped` is true.
+    fn skipped_if<T>(&mut self, skipped: bool, f: impl FnOnce(&mut Self) -> T) -> T {
+        let evaluated = self.evaluated;
+        self.evaluated &= !skipped;
+        let result = f(self);
+        self.evaluated = evaluated;
+        result
+    }
+    /// Reports undefined behavior in an evaluated subexpression.
+    fn invalid(&self, span: Span, message: impl FnOnce() -> String) -> Result<(), ConstError> {
+        if self.evaluated {
+            return Err(ConstError::Invalid {
+                span,
+                message: message(),
+            });
+        }
+        Ok(())
+    }
+    /// The value of an lvalue, which is only a constant for an array or function that decays
+    /// to its address.
+    fn decay(&mut self, expr: &SExpr<'s>) -> Result<Constant<'s>, ConstError> {
+        let address = self.address(expr)?;
+        let Type::Pointer(pointee) = &address.ty else {
+            unreachable!("addresses are pointers");
+        };
+        match pointee.unqualified() {
+            Type::Array(elem, _) => Ok(Constant {
+                ty: Type::Pointer(elem.clone()),
+                ..address
+            }),
+            Type::Function(_) => Ok(address),
+            _ => Err(ConstError::NotConstant),
+        }
+    }
+    /// The address of an lvalue, as a pointer to its type.
+    fn address(&mut self, expr: &SExpr<'s>) -> Result<Constant<'s>, ConstError> {
+        match &expr.inner {
+            Expr::Ident(name) => {
+                let ty = self
+                    .ctx
+                    .static_object(name)
+                    .ok_or(ConstError::NotConstant)?;
+                Ok(Constant {
+                    base: Some(Base::Named(name)),
+                    value: 0,
+                    ty: Type::Pointer(Box::new(ty)),
+                })
+            }
+            Expr::Lit(lit @ LiteralExpression::Str(string)) => Ok(Constant {
+                base: Some(Base::String(expr.span.clone(), string.clone())),
+                value: 0,
+                ty: Type::Pointer(Box::new(lit.ty())),
+            }),
+            Expr::CompoundLiteral(literal) if self.ctx.static_compound_literals() => Ok(Constant {
+                base: Some(Base::Compound(expr.span.clone(), literal.clone())),
+                value: 0,
+                ty: Type::Pointer(Box::new(literal.ty.clone())),
+            }),
+            Expr::Unary(unary) if unary.op == UnaryOperator::Deref => self.pointer(&unary.val),
+            // `a[i]` is `*(a + i)`
+            Expr::Index(index) => {
+                let span = expr.span.clone();
+                let address =
+                    self.binary(span, BinaryOperator::Add, &index.target, &index.index)?;
+                match address.ty {
+                    Type::Pointer(_) => Ok(address),
+                    _ => Err(ConstError::NotConstant),
+                }
+            }
+            Expr::MemberAccess(access) => {
+                let object = if access.arrow {
+                    self.pointer(&access.object)?
+                } else {
+                    self.address(&access.object)?
+                };
+                let Type::Pointer(pointee) = &object.ty else {
+                    unreachable!("addresses are pointers");
+                };
+                let Type::Tagged(tag) = pointee.unqualified() else {
+                    return Err(ConstError::NotConstant);
+                };
+                let layouts = self.ctx.layouts();
+                let (ty, offset) = layouts
+                    .find_member(tag, access.member)
+                    .ok_or(ConstError::NotConstant)?;
+                Ok(Constant {
+                    value: object.value.wrapping_add_unsigned(offset),
+                    ty: Type::Pointer(Box::new(ty)),
+                    ..object
+                })
+            }
+            _ => Err(ConstError::NotConstant),
+        }
+    }
+    /// The error for a floating constant, or a conversion to a floating type, at `span`.
+    fn floating(&self, span: Span) -> ConstError {
+        if self.integer {
+            return ConstError::NotConstant;
+        }
+        ConstError::Invalid {
+            span,
+            message: "Floating-point types are not supported".to_owned(),
+        }
+    }
+    /// Evaluates an operand that must be a pointer.
+    fn pointer(&mut self, expr: &SExpr<'s>) -> Result<Constant<'s>, ConstError> {
+        let pointer = self.value(expr)?;
+        match pointer.ty {
+            Type::Pointer(_) => Ok(pointer),
+            _ => Err(ConstError::NotConstant),
+        }
+    }
+    /// Evaluates an operand of an arithmetic operator, returning its value and its promoted
+    /// type.
+    fn integer(&mut self, expr: &SExpr<'s>) -> Result<(i128, ScalarType), ConstError> {
+        let value = self.value(expr)?;
+        match (value.integer(), integer_type(&value.ty)) {
+            (Some(integer), Some(ty)) => Ok((integer, ty.promoted())),
+            _ => Err(ConstError::NotConstant),
+        }
+    }
+    /// The result of an arithmetic operator of type `ty`, which wraps around for unsigned types
+    /// and is undefined if it overflows a signed type.
+    fn arithmetic(
+        &self,
+        span: Span,
+        value: i128,
+        ty: ScalarType,
+    ) -> Result<Constant<'s>, ConstError> {
+        if ty.is_signed() && ty.widen(ty.wrap(value)) != value {
+            self.invalid(span, || {
+                format!("Integer overflow in constant expression of type `{ty}`")
+            })?;
+        }
+        Ok(Constant::int(value, ty))
+    }
+    fn unary(
+        &mut self,
+        expr: &SExpr<'s>,
+        unary: &UnaryExpr<'s>,
+    ) -> Result<Constant<'s>, ConstError> {
+        match unary.op {
+            UnaryOperator::AddrOf => self.address(&unary.val),
+            UnaryOperator::Deref => self.decay(expr),
+            UnaryOperator::Not => {
+                let value = !self.value(&unary.val)?.is_true();
+                Ok(Constant::int(value.into(), ScalarType::Int))
+            }
+            UnaryOperator::Neg | UnaryOperator::Plus | UnaryOperator::BitNot => {
+                let (value, ty) = self.integer(&unary.val)?;
+                let value = match unary.op {
+                    UnaryOperator::Neg => -value,
+                    UnaryOperator::Plus => value,
+                    _ => !value,
+                };
+                self.arithmetic(expr.span.clone(), value, ty)
+            }
+            UnaryOperator::PreInc
+            | UnaryOperator::PreDec
+            | UnaryOperator::PostInc
+            | UnaryOperator::PostDec => Err(ConstError::NotConstant),
+        }
+    }
+    fn binary(
+        &mut self,
+        span: Span,
+        op: BinaryOperator,
+        lhs: &SExpr<'s>,
+        rhs: &SExpr<'s>,
+    ) -> Result<Constant<'s>, ConstError> {
+        use BinaryOperator as B;
+        let (lhs, rhs) = (self.value(lhs)?, self.value(rhs)?);
+        if matches!(lhs.ty, Type::Pointer(_)) || matches!(rhs.ty, Type::Pointer(_)) {
+            return self.pointer_arithmetic(op, lhs, rhs);
+        }
+        let (Some(a), Some(b)) = (lhs.integer(), rhs.integer()) else {
+            return Err(ConstError::NotConstant);
+        };
+        let lhs_ty = integer_type(&lhs.ty).unwrap().promoted();
+        let rhs_ty = integer_type(&rhs.ty).unwrap().promoted();
+        if let B::Shl | B::Shr = op {
+            // The result has the type of the promoted left operand
+            let width = 8 * i128::from(lhs_ty.size());
+            if !(0..width).contains(&b) {
+                self.invalid(span.clone(), || {
+                    format!("Shift count {b} is out of range for `{lhs_ty}`")
+                })?;
+                return Ok(Constant::int(0, lhs_ty));
+            }
+            let value = if op == B::Shl { a << b } else { a >> b };
+            return self.arithmetic(span, value, lhs_ty);
+        }
+        // Both operands are converted to their common type first
+        let ty = lhs_ty.common(rhs_ty);
+        let (a, b) = (ty.widen(ty.wrap(a)), ty.widen(ty.wrap(b)));
+        let compare = |result: bool| Ok(Constant::int(result.into(), ScalarType::Int));
+        let value = match op {
+            // The product of two unsigned 64-bit values can overflow, but only its low bits
+            // are kept
+            B::Mul => a.wrapping_mul(b),
+            B::Add => a + b,
+            B::Sub => a - b,
+            B::Div | B::Rem => {
+                if b == 0 {
+                    self.invalid(span, || {
+                        "Division by zero in constant expression".to_string()
+                    })?;
+                    return Ok(Constant::int(0, ty));
+                }
+                // The remainder is undefined when the quotient overflows
+                self.arithmetic(span.clone(), a / b, ty)?;
+                if op == B::Div { a / b } else { a % b }
+            }
+            B::Lt => return compare(a < b),
+            B::Le => return compare(a <= b),
+            B::Gt => return compare(a > b),
+            B::Ge => return compare(a >= b),
+            B::Eq => return compare(a == b),
+            B::Ne => return compare(a != b),
+            B::BitAnd => a & b,
+            B::BitXor => a ^ b,
+            B::BitOr => a | b,
+            B::Shl | B::Shr => unreachable!("shifts have been evaluated"),
+        };
+        self.arithmetic(span, value, ty)
+    }
+    /// Evaluates an operator with a pointer operand: adding an integer to an address,
+    /// subtracting addresses in the same object, or comparing them.
+    fn pointer_arithmetic(
+        &self,
+        op: BinaryOperator,
+        lhs: Constant<'s>,
+        rhs: Constant<'s>,
+    ) -> Result<Constant<'s>, ConstError> {
+        use BinaryOperator as B;
+        let layouts = self.ctx.layouts();
+        // The size of the objects a pointer points to, which scales the integers added to it
+        let scale = |pointer: &Constant<'s>| match &pointer.ty {
+            Type::Pointer(pointee) => layouts
+                .size_of(pointee)
+                .and_then(|size| i64::try_from(size).ok())
+                .ok_or(ConstError::NotConstant),
+            _ => unreachable!("only pointers are scaled"),
+        };
+        let compare = |result: bool| Ok(Constant::int(result.into(), ScalarType::Int));
+        match (op, lhs.integer(), rhs.integer()) {
+            (B::Add | B::Sub, None, Some(offset)) | (B::Add, Some(offset), None) => {
+                let pointer = if lhs.integer().is_none() { lhs } else { rhs };
+                let offset = i64::try_from(offset).map_err(|_| ConstError::NotConstant)?;
+                let offset = offset.wrapping_mul(scale(&pointer)?);
+                let value = match op {
+                    B::Sub => pointer.value.wrapping_sub(offset),
+                    _ => pointer.value.wrapping_add(offset),
+                };
+                Ok(Constant { value, ..pointer })
+            }
+            (B::Sub, None, None) if lhs.base == rhs.base => {
+                let difference = lhs.value.wrapping_sub(rhs.value) / scale(&lhs)?;
+                Ok(Constant::int(difference.into(), ScalarType::Long))
+            }
+            // Addresses in the same object are ordered by their offsets
+            (B::Lt | B::Le | B::Gt | B::Ge | B::Eq | B::Ne, ..) if lhs.base == rhs.base => {
+                let (a, b) = (lhs.value.cast_unsigned(), rhs.value.cast_unsigned());
+                compare(match op {
+                    B::Lt => a < b,
+                    B::Le => a <= b,
+                    B::Gt => a > b,
+                    B::Ge => a >= b,
+                    B::Eq => a == b,
+                    _ => a != b,
+                })
+            }
+            // An address is never equal to a null pointer
+            (B::Eq | B::Ne, ..) if lhs.base.is_none() && lhs.value == 0 => compare(op == B::Ne),
+            (B::Eq | B::Ne, ..) if rhs.base.is_none() && rhs.value == 0 => compare(op == B::Ne),
+            _ => Err(ConstError::NotConstant),
+        }
+    }
+    fn ternary(&mut self, ternary: &Ternary<'s>) -> Result<Constant<'s>, ConstError> {
+        let cond = self.value(&ternary.cond)?.is_true();
+        let then_val = self.skipped_if(!cond, |this| this.value(&ternary.then_val))?;
+        let else_val = self.skipped_if(cond, |this| this.value(&ternary.else_val))?;
+        let (then_ty, else_ty) = (integer_type(&then_val.ty), integer_type(&else_val.ty));
+        let chosen = if cond { then_val } else { else_val };
+        match (then_ty, else_ty, chosen.integer()) {
+            // Integer operands are converted to their common type
+            (Some(a), Some(b), Some(value)) => {
+                Ok(Constant::int(value, a.promoted().common(b.promoted())))
+            }
+            _ => Ok(chosen),
+        }
+    }
+    fn cast(&mut self, span: Span, cast: &Cast<'s>) -> Result<Constant<'s>, ConstError> {
+        // Floating constants are only allowed as the operands of casts to integer types
+        if let Expr::Lit(LiteralExpression::Float(float)) = &cast.val.inner
+            && let Some(ty) = integer_type(&cast.ty)
+        {
+            let value = float.value.trunc();
+            // Every integer type fits in the range of an `i128`
+            #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+            let integer = value as i128;
+            if ty != ScalarType::Bool && ty.widen(ty.wrap(integer)) != integer {
+                self.invalid(span, || format!("`{float}` is out of range for `{ty}`"))?;
+            }
+            return Ok(Constant::int(integer, ty));
+        }
+        if is_floating(&cast.ty) {
+            return Err(self.floating(span));
+        }
+        self.value(&cast.val)?
+            .convert(&cast.ty)
+            .ok_or(ConstError::NotConstant)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use utils::{IntKind, IntLiteral};
+
+    use super::*;
+    use crate::{BinaryExpr, LogicalExpr, Spanned};
+
+    /// A context where no identifiers are declared.
+    struct Empty(Layouts<'static>);
+
+    impl<'s> ConstContext<'s> for Empty {
+        fn enum_constant(&self, _: &str) -> Option<i64> {
+            None
+        }
+        fn static_object(&self, _: &str) -> Option<Type> {
+            None
+        }
+        fn layouts(&self) -> &Layouts<'s> {
+            &self.0
+        }
+        fn static_compound_literals(&self) -> bool {
+            false
+        }
+        fn type_of(&mut self, _: &SExpr<'s>) -> Option<Type> {
+            None
+        }
+    }
+
+    fn eval(expr: &SExpr<'static>) -> Result<Constant<'static>, ConstError> {
+        evaluate(expr, &mut Empty(Layouts::default()))
+    }
+
+    /// An integer literal at the offset `at`, one character long.
+    fn int(value: u64, kind: IntKind, at: usize) -> SExpr<'static> {
+        let lit = LiteralExpression::Int(IntLiteral { value, kind });
+        Spanned::new(Expr::Lit(lit), at..at + 1)
+    }
+
+    fn binary(lhs: SExpr<'static>, op: BinaryOperator, rhs: SExpr<'static>) -> SExpr<'static> {
+        let span = lhs.span.start..rhs.span.end;
+        Spanned::new(Expr::Binary(Box::new(BinaryExpr { lhs, op, rhs })), span)
+    }
+
+    fn neg(val: SExpr<'static>) -> SExpr<'static> {
+        let span = val.span.start - 1..val.span.end;
+        let op = UnaryOperator::Neg;
+        Spanned::new(Expr::Unary(Box::new(UnaryExpr { op, val })), span)
+    }
+
+    fn logical(lhs: SExpr<'static>, op: LogicalOperator, rhs: SExpr<'static>) -> SExpr<'static> {
+        let span = lhs.span.start..rhs.span.end;
+        Spanned::new(Expr::Logical(Box::new(LogicalExpr { lhs, op, rhs })), span)
+    }
+
+    fn ternary(
+        cond: SExpr<'static>,
+        then_val: SExpr<'static>,
+        else_val: SExpr<'static>,
+    ) -> SExpr<'static> {
+        let span = cond.span.start..else_val.span.end;
+        let ternary = Ternary {
+            cond,
+            then_val,
+            else_val,
+        };
+        Spanned::new(Expr::Ternary(Box::new(ternary)), span)
+    }
+
+    fn overflow(span: Span, ty: &str) -> Result<Constant<'static>, ConstError> {
+        let message = format!("Integer overflow in constant expression of type `{ty}`");
+        Err(ConstError::Invalid { span, message })
+    }
+
+    #[test]
+    fn unsigned_wraparound() {
+        use BinaryOperator::{Add, Mul, Sub};
+        use IntKind::{Int, UInt, ULong};
+        assert_eq!(
+            eval(&binary(int(0, UInt, 0), Sub, int(1, Int, 2))),
+            Ok(Constant::int(u32::MAX.into(), ScalarType::UInt))
+        );
+        assert_eq!(
+            eval(&binary(int(u32::MAX.into(), UInt, 0), Add, int(1, Int, 2))),
+            Ok(Constant::int(0, ScalarType::UInt))
+        );
+        // The product wraps around even though it doesn't fit in 64 bits
+        assert_eq!(
+            eval(&binary(int(u64::MAX, ULong, 0), Mul, int(3, Int, 2))),
+            Ok(Constant::int((u64::MAX - 2).into(), ScalarType::ULong))
+        );
+        assert_eq!(
+            eval(&neg(int(1, UInt, 1))),
+            Ok(Constant::int(u32::MAX.into(), ScalarType::UInt))
+        );
+    }
+
+    #[test]
+    fn signed_overflow() {
+        use BinaryOperator::{Add, Mul, Sub};
+        use IntKind::{Int, Long, UInt};
+        let sum = binary(int(i32::MAX as u64, Int, 0), Add, int(1, Int, 2));
+        assert_eq!(eval(&sum), overflow(0..3, "int"));
+        // The error points at the operation that overflows, not the whole expression
+        let sum = binary(int(i32::MAX as u64, Int, 4), Add, int(1, Int, 6));
+        let expr = binary(int(2, Int, 0), Mul, sum);
+        assert_eq!(eval(&expr), overflow(4..7, "int"));
+        let min = binary(neg(int(i64::MAX as u64, Long, 2)), Sub, int(1, Int, 4));
+        assert_eq!(
+            eval(&min),
+            Ok(Constant::int(i64::MIN.into(), ScalarType::Long))
+        );
+        assert_eq!(eval(&neg(min)), overflow(0..5, "long"));
+        // Mixing in an unsigned operand makes the arithmetic unsigned
+        let sum = binary(int(i32::MAX as u64, Int, 0), Add, int(1, UInt, 2));
+        assert_eq!(eval(&sum), Ok(Constant::int(1 << 31, ScalarType::UInt)));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        use BinaryOperator::{Add, Div, Rem};
+        use IntKind::{Int, ULong};
+        let division = Err(ConstError::Invalid {
+            span: 2..5,
+            message: "Division by zero in constant expression".to_owned(),
+        });
+        let quotient = binary(int(1, Int, 2), Div, int(0, Int, 4));
+        assert_eq!(eval(&binary(int(1, Int, 0), Add, quotient)), division);
+        let remainder = binary(int(1, ULong, 2), Rem, int(0, ULong, 4));
+        assert_eq!(eval(&binary(int(1, Int, 0), Add, remainder)), division);
+        // The quotient of the most negative value and -1 overflows, as does the remainder
+        let min = binary(
+            neg(int(i32::MAX as u64, Int, 1)),
+            BinaryOperator::Sub,
+            int(1, Int, 3),
+        );
+        let remainder = binary(min, Rem, neg(int(1, Int, 6)));
+        assert_eq!(eval(&remainder), overflow(0..7, "int"));
+    }
+
+    #[test]
+    fn skipped_operands() {
+        use BinaryOperator::{Add, Div};
+        use IntKind::{Int, ULong};
+        use LogicalOperator::{And, Or};
+        let undefined = || binary(int(1, Int, 4), Div, int(0, Int, 6));
+        let overflowing = || binary(int(i32::MAX as u64, Int, 4), Add, int(1, Int, 6));
+        let int_value = |value| Ok(Constant::int(value, ScalarType::Int));
+        assert_eq!(
+            eval(&logical(int(0, Int, 0), And, undefined())),
+            int_value(0)
+        );
+        assert_eq!(
+            eval(&logical(int(1, Int, 0), Or, overflowing())),
+            int_value(1)
+        );
+        assert_eq!(
+            eval(&ternary(int(1, Int, 0), int(2, Int, 2), undefined())),
+            int_value(2)
+        );
+        assert_eq!(
+            eval(&ternary(int(0, Int, 0), overflowing(), int(3, Int, 8))),
+            int_value(3)
+        );
+        // Skipped operands still take part in the type of `?:`
+        assert_eq!(
+            eval(&ternary(int(1, Int, 0), int(2, Int, 2), int(3, ULong, 4))),
+            Ok(Constant::int(2, ScalarType::ULong))
+        );
+        // Operands that are evaluated are still checked
+        assert_eq!(
+            eval(&logical(int(1, Int, 0), And, overflowing())),
+            overflow(4..7, "int")
+        );
+        assert_eq!(
+            eval(&ternary(int(0, Int, 0), int(2, Int, 2), undefined())),
+            Err(ConstError::Invalid {
+                span: 4..7,
+                message: "Division by zero in constant expression".to_owned(),
+            })
+        );
+        // Nested skipped operands stay skipped when the inner condition would evaluate them
+        let inner = logical(int(1, Int, 2), And, overflowing());
+        assert_eq!(eval(&logical(int(0, Int, 0), And, inner)), int_value(0));
+    }
+}
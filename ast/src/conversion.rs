@@ -0,0 +1,194 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! The integer types of the System V x86-64 ABI and the implicit conversions between them
+//! (C17 6.3.1), along with the types of literals (C17 6.4.4, 6.4.5).
+
+use utils::{Encoding, FloatKind, IntKind};
+
+use crate::{LiteralExpression, ScalarType, Type};
+
+impl ScalarType {
+    /// The size of a value of this type in bytes, which is also its alignment.
+    #[must_use]
+    pub const fn size(self) -> u64 {
+        use ScalarType::*;
+        match self {
+            // GCC gives `void` a size of 1 for pointer arithmetic
+            Void | Bool | Char | SChar | UChar => 1,
+            Short | UShort => 2,
+            Int | UInt | Float => 4,
+            Long | ULong | LongLong | ULongLong | Double | NullPtr => 8,
+            LongDouble => 16,
+        }
+    }
+    #[must_use]
+    pub const fn is_integer(self) -> bool {
+        use ScalarType::*;
+        !matches!(self, Void | Float | Double | LongDouble | NullPtr)
+    }
+    #[must_use]
+    pub const fn is_floating(self) -> bool {
+        use ScalarType::*;
+        matches!(self, Float | Double | LongDouble)
+    }
+    /// Whether values of this type are sign-extended when widened. `char` is signed on x86-64.
+    #[must_use]
+    pub const fn is_signed(self) -> bool {
+        use ScalarType::*;
+        matches!(self, Char | SChar | Short | Int | Long | LongLong)
+    }
+    /// The integer conversion rank of C17 6.3.1.1.
+    const fn rank(self) -> u8 {
+        use ScalarType::*;
+        match self {
+            Bool => 0,
+            Char | SChar | UChar => 1,
+            Short | UShort => 2,
+            Int | UInt => 3,
+            Long | ULong => 4,
+            LongLong | ULongLong => 5,
+            Void | Float | Double | LongDouble | NullPtr => panic!("only integers have a rank"),
+        }
+    }
+    const fn to_unsigned(self) -> Self {
+        use ScalarType::*;
+        match self {
+            Int => UInt,
+            Long => ULong,
+            LongLong => ULongLong,
+            other => other,
+        }
+    }
+    /// Applies the integer promotions, leaving types other than integers unchanged.
+    #[must_use]
+    pub const fn promoted(self) -> Self {
+        // Every type of a lower rank fits in an `int`
+        if self.is_integer() && self.rank() < Self::Int.rank() {
+            Self::Int
+        } else {
+            self
+        }
+    }
+    /// The common type of two promoted integer types, as given by the usual arithmetic
+    /// conversions.
+    #[must_use]
+    pub fn common(self, other: Self) -> Self {
+        let (a, b) = (self, other);
+        if a == b {
+            a
+        } else if a.is_signed() == b.is_signed() {
+            if a.rank() >= b.rank() { a } else { b }
+        } else {
+            let (unsigned, signed) = if a.is_signed() { (b, a) } else { (a, b) };
+            if unsigned.rank() >= signed.rank() {
+                unsigned
+            } else if signed.size() > unsigned.size() {
+                signed
+            } else {
+                signed.to_unsigned()
+            }
+        }
+    }
+    /// Converts an integer to this integer type, wrapping it into the range of the type. The
+    /// result is sign- or zero-extended to 64 bits, as values are kept in registers.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub const fn wrap(self, value: i128) -> i64 {
+        if matches!(self, Self::Bool) {
+            return (value != 0) as i64;
+        }
+        let unused = 128 - 8 * self.size() as u32;
+        let value = if self.is_signed() {
+            (value << unused) >> unused
+        } else {
+            ((value as u128) << unused >> unused) as i128
+        };
+        value as i64
+    }
+    /// The value of an integer of this type that [Self::wrap] has extended to 64 bits.
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub const fn widen(self, value: i64) -> i128 {
+        if self.is_signed() {
+            value as i128
+        } else {
+            value as u64 as i128
+        }
+    }
+}
+
+impl From<IntKind> for ScalarType {
+    /// The type of an integer constant with the given suffix.
+    fn from(kind: IntKind) -> Self {
+        match kind {
+            IntKind::Int => Self::Int,
+            IntKind::UInt => Self::UInt,
+            IntKind::Long => Self::Long,
+            IntKind::ULong => Self::ULong,
+            IntKind::LongLong => Self::LongLong,
+            IntKind::ULongLong => Self::ULongLong,
+        }
+    }
+}
+
+impl From<FloatKind> for ScalarType {
+    /// The type of a floating constant with the given suffix.
+    fn from(kind: FloatKind) -> Self {
+        match kind {
+            FloatKind::Float => Self::Float,
+            FloatKind::Double => Self::Double,
+            FloatKind::LongDouble => Self::LongDouble,
+        }
+    }
+}
+
+/// The type of a character constant with the given prefix.
+pub(crate) const fn char_type(encoding: Encoding) -> ScalarType {
+    match encoding {
+        Encoding::Plain | Encoding::Wide => ScalarType::Int,
+        Encoding::Utf8 => ScalarType::UChar,
+        Encoding::Utf16 => ScalarType::UShort,
+        Encoding::Utf32 => ScalarType::UInt,
+    }
+}
+
+impl LiteralExpression {
+    /// The type of the literal. A string literal is an array of its code units and a
+    /// terminating null character, and a token that failed to lex is taken to be an `int`.
+    #[must_use]
+    pub fn ty(&self) -> Type {
+        match self {
+            Self::Invalid => Type::Scalar(ScalarType::Int),
+            Self::Int(int) => Type::Scalar(int.kind.into()),
+            Self::Float(float) => Type::Scalar(float.kind.into()),
+            Self::Char(c) => Type::Scalar(char_type(c.encoding)),
+            Self::Bool(_) => Type::Scalar(ScalarType::Bool),
+            Self::Nullptr => Type::Scalar(ScalarType::NullPtr),
+            Self::Str(string) => {
+                let elem = match string.encoding {
+                    Encoding::Plain | Encoding::Utf8 => ScalarType::Char,
+                    // `wchar_t` is `int` on x86-64
+                    Encoding::Wide => ScalarType::Int,
+                    Encoding::Utf16 => ScalarType::UShort,
+                    Encoding::Utf32 => ScalarType::UInt,
+                };
+                let len = string.units().len() as u64 + 1;
+                Type::Array(Box::new(Type::Scalar(elem)), Some(len))
+            }
+        }
+    }
+}
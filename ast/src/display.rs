@@ -17,115 +17,359 @@
 use crate::*;
 use std::fmt::{Display, Write};
 
-
-impl Display for LiteralExpression<'_> {
+impl Display for LiteralExpression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use LiteralExpression::*;
         match self {
-            // Nil => f.write_str("nil"),
-            // Bool(x) => x.fmt(f),
-            Str(text) => write!(f, "{text:?}"),
-            // Char(c) => write!(f, "{c:?}"),
-            // Float(x) => x.fmt(f),
+            Str(s) => s.fmt(f),
             Int(x) => x.fmt(f),
+            Float(x) => x.fmt(f),
+            Char(c) => c.fmt(f),
+            Bool(b) => b.fmt(f),
+            Nullptr => f.write_str("nullptr"),
+            Invalid => f.write_str("<invalid>"),
+        }
+    }
+}
+
+impl Display for ScalarType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use ScalarType::*;
+        let name = match self {
+            Void => "void",
+            Bool => "_Bool",
+            Char => "char",
+            SChar => "signed char",
+            UChar => "unsigned char",
+            Short => "short",
+            UShort => "unsigned short",
+            Int => "int",
+            UInt => "unsigned int",
+            Long => "long",
+            ULong => "unsigned long",
+            LongLong => "long long",
+            ULongLong => "unsigned long long",
+            Float => "float",
+            Double => "double",
+            LongDouble => "long double",
+            NullPtr => "nullptr_t",
+        };
+        f.write_str(name)
+    }
+}
+
+impl Display for Qualifiers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = [
+            (self.is_const, "const"),
+            (self.is_volatile, "volatile"),
+            (self.is_restrict, "restrict"),
+            (self.is_atomic, "_Atomic"),
+        ];
+        let mut sep = "";
+        for (_, name) in names.iter().filter(|(present, _)| *present) {
+            write!(f, "{sep}{name}")?;
+            sep = " ";
+        }
+        Ok(())
+    }
+}
+
+impl Display for StorageClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Static => "static",
+            Self::Extern => "extern",
+        })
+    }
+}
+
+impl Display for TagKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TagKind::Struct => "struct",
+            TagKind::Union => "union",
+            TagKind::Enum => "enum",
+        })
+    }
+}
+
+impl Display for Tag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} {name}", self.kind),
+            None => write!(f, "{} (anonymous)", self.kind),
+        }
+    }
+}
+
+impl Type {
+    /// Writes the type as a C declaration of `declarator`, which is empty for a type name.
+    fn fmt_declaration(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        declarator: &str,
+    ) -> std::fmt::Result {
+        let (qualifiers, ty) = match self {
+            Type::Qualified(qualifiers, ty) => (*qualifiers, &**ty),
+            ty => (Qualifiers::default(), ty),
+        };
+        match ty {
+            Type::Pointer(pointee) => {
+                // Qualifiers of the pointer itself follow the `*`
+                let mut inner = String::from("*");
+                if !qualifiers.is_empty() {
+                    write!(inner, "{qualifiers}")?;
+                    if !declarator.is_empty() {
+                        inner.push(' ');
+                    }
+                }
+                inner.push_str(declarator);
+                if matches!(pointee.unqualified(), Type::Array(..) | Type::Function(_)) {
+                    inner = format!("({inner})");
+                }
+                pointee.fmt_declaration(f, &inner)
+            }
+            Type::Array(elem, len) => {
+                let len = len.map(|len| len.to_string()).unwrap_or_default();
+                elem.fmt_declaration(f, &format!("{declarator}[{len}]"))
+            }
+            Type::Function(function) => {
+                let params = function.params_to_string();
+                function
+                    .ret
+                    .fmt_declaration(f, &format!("{declarator}({params})"))
+            }
+            Type::Scalar(scalar) => {
+                if !qualifiers.is_empty() {
+                    write!(f, "{qualifiers} ")?;
+                }
+                scalar.fmt(f)?;
+                if declarator.is_empty() {
+                    return Ok(());
+                }
+                write!(f, " {declarator}")
+            }
+            Type::Tagged(tag) => {
+                if !qualifiers.is_empty() {
+                    write!(f, "{qualifiers} ")?;
+                }
+                tag.fmt(f)?;
+                if declarator.is_empty() {
+                    return Ok(());
+                }
+                write!(f, " {declarator}")
+            }
+            Type::Qualified(..) => unreachable!("qualifiers are never nested"),
+        }
+    }
+}
+
+impl FunctionType {
+    fn params_to_string(&self) -> String {
+        let Some(params) = &self.params else {
+            return String::new();
+        };
+        if params.is_empty() && !self.variadic {
+            return "void".to_string();
+        }
+        let mut text = params
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        if self.variadic {
+            if !params.is_empty() {
+                text.push_str(", ");
+            }
+            text.push_str("...");
+        }
+        text
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_declaration(f, "")
+    }
+}
+
+impl Display for FunctionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Type::Function(Box::new(self.clone())).fmt(f)
+    }
+}
+
+impl Display for BinaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use BinaryOperator::*;
+        let operator = match self {
+            Mul => "*",
+            Div => "/",
+            Rem => "%",
+            Add => "+",
+            Sub => "-",
+            Shl => "<<",
+            Shr => ">>",
+            Lt => "<",
+            Le => "<=",
+            Gt => ">",
+            Ge => ">=",
+            Eq => "==",
+            Ne => "!=",
+            BitAnd => "&",
+            BitXor => "^",
+            BitOr => "|",
+        };
+        f.write_str(operator)
+    }
+}
+
+impl Display for LogicalOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use LogicalOperator::*;
+        let operator = match self {
+            And => "&&",
+            Or => "||",
+        };
+        f.write_str(operator)
+    }
+}
+
+impl Display for UnaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use UnaryOperator::*;
+        let operator = match self {
+            Neg => "-",
+            Plus => "+",
+            Not => "!",
+            BitNot => "~",
+            Deref => "*",
+            AddrOf => "&",
+            PreInc => "++",
+            PreDec => "--",
+            PostInc => "post++",
+            PostDec => "post--",
+        };
+        f.write_str(operator)
+    }
+}
+
+impl Display for BinaryExpr<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self { lhs, op, rhs } = self;
+        write!(f, "({op} {lhs} {rhs})")
+    }
+}
+
+impl Display for LogicalExpr<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self { lhs, op, rhs } = self;
+        write!(f, "({op} {lhs} {rhs})")
+    }
+}
+
+impl Display for UnaryExpr<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self { op, val } = self;
+        write!(f, "({op} {val})")
+    }
+}
+
+impl Display for Assignment<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self { target, op, val } = self;
+        match op {
+            Some(op) => write!(f, "({op}= {target} {val})"),
+            None => write!(f, "(= {target} {val})"),
+        }
+    }
+}
+
+impl Display for Ternary<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            cond,
+            then_val,
+            else_val,
+        } = self;
+        write!(f, "(? {cond} {then_val} {else_val})")
+    }
+}
+
+impl Display for Call<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self { callee, args } = self;
+        write!(f, "(call {callee} [")?;
+        let mut args = args.iter();
+        if let Some(first) = args.next() {
+            write!(f, "{first}")?;
+        }
+        for arg in args {
+            write!(f, ", {arg}")?;
+        }
+        f.write_str("])")
+    }
+}
+
+impl Display for Designator<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Designator::Member(name) => write!(f, ".{name}"),
+            Designator::Index(index) => write!(f, "[{index}]"),
+        }
+    }
+}
+
+impl Display for Initializer<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = match self {
+            Initializer::Expr(expr) => return expr.fmt(f),
+            Initializer::List(list) => &list.inner,
+        };
+        f.write_char('{')?;
+        for (i, item) in list.iter().enumerate() {
+            if i != 0 {
+                f.write_str(", ")?;
+            }
+            for designator in &item.designators {
+                designator.fmt(f)?;
+            }
+            if !item.designators.is_empty() {
+                f.write_str(" = ")?;
+            }
+            item.init.fmt(f)?;
         }
+        f.write_char('}')
     }
 }
-// impl Display for BinaryOperator {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         use BinaryOperator::*;
-//         let operator = match self {
-//             Mul => "*",
-//             Div => "/",
-//             Sub => "-",
-//             Add => "+",
-//             Lt => "<",
-//             Le => "<=",
-//             Gt => ">",
-//             Ge => ">=",
-//             Eq => "==",
-//             Ne => "!=",
-//         };
-//         f.write_str(operator)
-//     }
-// }
-
-// impl Display for LogicalOperator {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         use LogicalOperator::*;
-//         let operator = match self {
-//             And => "and",
-//             Or => "or",
-//         };
-//         f.write_str(operator)
-//     }
-// }
-
-// impl Display for BinaryExpr<'_> {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         let Self { lhs, op, rhs } = self;
-//         write!(f, "({op} {lhs} {rhs})")
-//     }
-// }
-
-// impl Display for LogicalExpr<'_> {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         let Self { lhs, op, rhs } = self;
-//         write!(f, "({op} {lhs} {rhs})")
-//     }
-// }
-
-// impl Display for UnaryOperator {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         use UnaryOperator::*;
-//         let operator = match self {
-//             Neg => '-',
-//             Not => '!',
-//         };
-//         f.write_char(operator)
-//     }
-// }
-
-// impl Display for UnaryExpr<'_> {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         let Self { op, val } = self;
-//         write!(f, "({op} {val})")
-//     }
-// }
-
-// impl Display for Assignment<'_> {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         let Self { target, val } = self;
-//         write!(f, "(= {target} {val})")
-//     }
-// }
-
-// impl Display for Call<'_> {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         let Self { callee, arguments } = self;
-//         write!(f, "(call {callee} [")?;
-//         let mut arguments = arguments.iter();
-//         if let Some(first) = arguments.next() {
-//             write!(f, "{first}")?;
-//         }
-//         for arg in arguments {
-//             write!(f, ", {arg}")?;
-//         }
-//         f.write_str("])")
-//     }
-// }
 
 impl Display for Expr<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            // Expr::Ident(i) => i.fmt(f),
             Expr::Lit(literal) => literal.fmt(f),
-            // Expr::Grouped(expr) => write!(f, "(group {expr})"),
-            // Expr::Binary(binary) => binary.fmt(f),
-            // Expr::Logical(logical) => logical.fmt(f),
-            // Expr::Unary(unary) => unary.fmt(f),
-            // Expr::Assignment(assignment) => assignment.fmt(f),
-            // Expr::Call(call) => call.fmt(f),
+            Expr::Ident(i) => i.fmt(f),
+            Expr::Binary(binary) => binary.fmt(f),
+            Expr::Logical(logical) => logical.fmt(f),
+            Expr::Unary(unary) => unary.fmt(f),
+            Expr::Assignment(assignment) => assignment.fmt(f),
+            Expr::Ternary(ternary) => ternary.fmt(f),
+            Expr::Comma(comma) => write!(f, "(, {} {})", comma.lhs, comma.rhs),
+            Expr::Call(call) => call.fmt(f),
+            Expr::Index(index) => write!(f, "([] {} {})", index.target, index.index),
+            Expr::MemberAccess(access) => {
+                let op = if access.arrow { "->" } else { "." };
+                write!(f, "({op} {} {})", access.object, access.member)
+            }
+            Expr::Cast(cast) => write!(f, "(cast {} {})", cast.ty, cast.val),
+            Expr::CompoundLiteral(literal) => {
+                write!(f, "(compound {} {})", literal.ty, literal.init)
+            }
+            Expr::SizeOf(size_of) => match &**size_of {
+                SizeOf::Val(val) => write!(f, "(sizeof {val})"),
+                SizeOf::Type(ty) => write!(f, "(sizeof type {ty})"),
+            },
+            Expr::AlignOf(ty) => write!(f, "(alignof type {ty})"),
         }
     }
 }
@@ -0,0 +1,393 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+//! The resolution of initializers (C17 6.7.9), which matches each element of a brace-enclosed
+//! list with the subobject it initializes, following designators and elided braces.
+
+use source::Span;
+use utils::Encoding;
+
+use crate::{
+    Designator, Expr, InitItem, Initializer, LiteralExpression, Member, SExpr, ScalarType, Spanned,
+    Tag, TagKind, Type,
+    constant::{self, ConstContext, ConstError},
+};
+
+/// What resolving an initializer needs to know about the program, beyond what array
+/// designators refer to.
+pub trait InitContext<'s>: ConstContext<'s> {
+    /// The members of a struct or union, or [None] if it's incomplete.
+    fn members(&mut self, tag: &Tag) -> Option<Vec<Member<'s>>>;
+}
+
+/// A step from an object to one of its subobjects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// The member of a struct or union at the given index
+    Member(usize),
+    /// The element of an array at the given index
+    Index(u64),
+}
+
+/// A subobject initialized by a single expression.
+#[derive(Debug, Clone)]
+pub struct InitLeaf<'a, 's> {
+    /// The steps from the initialized object to the subobject
+    pub path: Vec<Step>,
+    /// The type of the subobject, which is only an array if it's initialized by a string
+    /// literal
+    pub ty: Type,
+    pub value: &'a SExpr<'s>,
+}
+
+/// The subobjects given a value by an initializer, with every other subobject initialized to
+/// zero.
+#[derive(Debug, Clone)]
+pub struct Resolved<'a, 's> {
+    /// The type of the object, with the length of an array completed by the initializer
+    pub ty: Type,
+    /// The initialized subobjects in the order they're initialized. A later one may be part
+    /// of an earlier one initialized by a struct or string, overriding that part of it.
+    pub leaves: Vec<InitLeaf<'a, 's>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitError {
+    pub span: Span,
+    pub message: String,
+}
+
+fn error(span: Span, message: String) -> InitError {
+    InitError { span, message }
+}
+
+/// Resolves the initializer of an object of type `ty`.
+///
+/// # Errors
+///
+/// Returns the first element that doesn't match the subobject it would initialize.
+pub fn resolve<'a, 's>(
+    ty: &Type,
+    init: &'a Initializer<'s>,
+    ctx: &mut impl InitContext<'s>,
+) -> Result<Resolved<'a, 's>, InitError> {
+    let mut resolver = Resolver {
+        ctx,
+        leaves: Vec::new(),
+        len: 0,
+    };
+    match init {
+        Initializer::List(list) => resolver.braced(ty, &mut Vec::new(), list)?,
+        Initializer::Expr(expr) if matches!(ty.unqualified(), Type::Array(..)) => {
+            if !resolver.string(ty, &[], expr)? {
+                let msg = format!("`{ty}` must be initialized by a brace-enclosed list");
+                return Err(error(expr.as_span(), msg));
+            }
+        }
+        Initializer::Expr(expr) => resolver.leaf(&[], ty, expr),
+    }
+    Ok(Resolved {
+        ty: complete(ty, resolver.len),
+        leaves: resolver.leaves,
+    })
+}
+
+/// Gives an array of unknown length the length `len`.
+fn complete(ty: &Type, len: u64) -> Type {
+    match ty {
+        Type::Array(elem, None) => Type::Array(elem.clone(), Some(len)),
+        Type::Qualified(qualifiers, ty) => {
+            Type::Qualified(*qualifiers, Box::new(complete(ty, len)))
+        }
+        ty => ty.clone(),
+    }
+}
+
+fn is_record(ty: &Type) -> bool {
+    matches!(ty.unqualified(), Type::Tagged(tag) if tag.kind != TagKind::Enum)
+}
+
+/// Whether an array of `elem` can be initialized by a string literal with the given encoding.
+fn is_string_element(elem: &Type, encoding: Encoding) -> bool {
+    use ScalarType::*;
+    let Type::Scalar(elem) = elem.unqualified() else {
+        return false;
+    };
+    match encoding {
+        Encoding::Plain | Encoding::Utf8 => matches!(elem, Char | SChar | UChar),
+        Encoding::Utf16 => *elem == UShort,
+        Encoding::Utf32 => *elem == UInt,
+        // `wchar_t` is `int` on x86-64
+        Encoding::Wide => *elem == Int,
+    }
+}
+
+struct Resolver<'c, 'a, 's, C> {
+    ctx: &'c mut C,
+    leaves: Vec<InitLeaf<'a, 's>>,
+    /// The length of the initialized array so far, if its length is unknown
+    len: u64,
+}
+
+impl<'a, 's, C: InitContext<'s>> Resolver<'_, 'a, 's, C> {
+    fn leaf(&mut self, path: &[Step], ty: &Type, value: &'a SExpr<'s>) {
+        self.leaves.push(InitLeaf {
+            path: path.to_vec(),
+            ty: ty.clone(),
+            value,
+        });
+    }
+    fn has_type(&mut self, value: &SExpr<'s>, ty: &Type) -> bool {
+        // Literals are never structs or unions
+        if matches!(value.inner, Expr::Lit(_)) {
+            return false;
+        }
+        let value_ty = self.ctx.type_of(value);
+        value_ty.is_some_and(|value_ty| value_ty.unqualified() == ty.unqualified())
+    }
+    /// Initializes an array of characters from a string literal, returning whether `value`
+    /// is one that can initialize `ty`.
+    fn string(
+        &mut self,
+        ty: &Type,
+        path: &[Step],
+        value: &'a SExpr<'s>,
+    ) -> Result<bool, InitError> {
+        let (Type::Array(elem, len), Expr::Lit(LiteralExpression::Str(string))) =
+            (ty.unqualified(), &value.inner)
+        else {
+            return Ok(false);
+        };
+        if !is_string_element(elem, string.encoding) {
+            return Ok(false);
+        }
+        let units = string.units().len() as u64;
+        match len {
+            // The terminating null character is left out if there's no room for it
+            Some(len) if units > *len => {
+                let msg = format!("String literal is too long for `{ty}`");
+                return Err(error(value.as_span(), msg));
+            }
+            Some(_) => (),
+            None => self.len = units + 1,
+        }
+        self.leaf(path, ty, value);
+        Ok(true)
+    }
+    /// The members of a struct or union, which are empty if it's incomplete, as that has
+    /// already been reported.
+    fn members(&mut self, ty: &Type) -> Vec<Member<'s>> {
+        match ty.unqualified() {
+            Type::Tagged(tag) => self.ctx.members(tag).unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+    /// Whether `name` is a member of `ty`, or of one of its anonymous members.
+    fn has_member(&mut self, ty: &Type, name: &str) -> bool {
+        self.members(ty).iter().any(|member| match member.name {
+            Some(member) => member == name,
+            None => self.has_member(&member.ty, name),
+        })
+    }
+    /// Finds the index of the subobject of `ty` selected by a designator, and whether the
+    /// designator names a member of that anonymous member instead, and so applies to it too.
+    fn designate(
+        &mut self,
+        ty: &Type,
+        members: &[Member<'s>],
+        designator: &Spanned<Designator<'s>>,
+    ) -> Result<(u64, bool), InitError> {
+        let span = designator.as_span();
+        match (&designator.inner, ty.unqualified()) {
+            (Designator::Index(index), Type::Array(_, len)) => {
+                let value = match constant::evaluate_integer(index, &mut *self.ctx) {
+                    Ok(value) => value,
+                    Err(ConstError::NotConstant) => {
+                        let msg = "Array designator must be an integer constant".to_string();
+                        return Err(error(index.as_span(), msg));
+                    }
+                    Err(ConstError::Invalid { span, message }) => return Err(error(span, message)),
+                };
+                match u64::try_from(value) {
+                    Ok(index) if len.is_none_or(|len| index < len) => Ok((index, false)),
+                    _ => {
+                        let msg =
+                            format!("Array designator index {value} is out of bounds for `{ty}`");
+                        Err(error(index.as_span(), msg))
+                    }
+                }
+            }
+            (Designator::Member(name), Type::Tagged(tag)) if tag.kind != TagKind::Enum => {
+                if let Some(index) = members.iter().position(|m| m.name == Some(*name)) {
+                    return Ok((index as u64, false));
+                }
+                for (index, member) in members.iter().enumerate() {
+                    if member.name.is_none() && self.has_member(&member.ty, name) {
+                        return Ok((index as u64, true));
+                    }
+                }
+                Err(error(span, format!("`{tag}` has no member `{name}`")))
+            }
+            (Designator::Index(_), _) => {
+                let msg = format!("Array designator used for non-array type `{ty}`");
+                Err(error(span, msg))
+            }
+            (Designator::Member(name), _) => {
+                let msg = format!("Member designator `.{name}` used for non-struct type `{ty}`");
+                Err(error(span, msg))
+            }
+        }
+    }
+    /// Initializes an object from a brace-enclosed list.
+    fn braced(
+        &mut self,
+        ty: &Type,
+        path: &mut Vec<Step>,
+        list: &'a Spanned<Vec<InitItem<'s>>>,
+    ) -> Result<(), InitError> {
+        let items = list.inner.as_slice();
+        if matches!(ty.unqualified(), Type::Array(..)) || is_record(ty) {
+            // A string literal initializing an array may be enclosed in braces
+            if let [item] = items
+                && item.designators.is_empty()
+                && let Initializer::Expr(value) = &item.init
+                && self.string(ty, path, value)?
+            {
+                return Ok(());
+            }
+            self.fill(ty, path, items, 0, 0, true)?;
+            return Ok(());
+        }
+        // A scalar may be enclosed in braces, which then hold a single element
+        let Some((item, rest)) = items.split_first() else {
+            return Ok(());
+        };
+        if let Some(designator) = item.designators.first() {
+            self.designate(ty, &[], designator)?;
+        }
+        if let Some(excess) = rest.first() {
+            let msg = format!("Excess elements in initializer for `{ty}`");
+            return Err(error(excess.init.span(), msg));
+        }
+        match &item.init {
+            Initializer::Expr(value) => self.leaf(path, ty, value),
+            Initializer::List(list) => self.braced(ty, path, list)?,
+        }
+        Ok(())
+    }
+    /// Initializes the subobjects of the struct, union or array `ty` from `items`, starting at
+    /// `pos` whose designators up to `depth` have already selected `ty`. Returns the position
+    /// of the first item that's left over.
+    ///
+    /// A braced list takes every item, while one whose braces were elided stops once every
+    /// subobject has been initialized, or at a designator of an enclosing list.
+    fn fill(
+        &mut self,
+        ty: &Type,
+        path: &mut Vec<Step>,
+        items: &'a [InitItem<'s>],
+        mut pos: usize,
+        mut depth: usize,
+        braced: bool,
+    ) -> Result<usize, InitError> {
+        let members = self.members(ty);
+        let (elem, len) = match ty.unqualified() {
+            Type::Array(elem, len) => (Some(&**elem), *len),
+            _ => (None, Some(members.len() as u64)),
+        };
+        let is_union = matches!(ty.unqualified(), Type::Tagged(tag) if tag.kind == TagKind::Union);
+        let start = pos;
+        let mut next = 0;
+        while let Some(item) = items.get(pos) {
+            if let Some(designator) = item.designators.get(depth) {
+                // Only the first item can continue a designation into a list without braces
+                if !braced && pos != start {
+                    break;
+                }
+                let (index, anonymous) = self.designate(ty, &members, designator)?;
+                next = index;
+                if !anonymous {
+                    depth += 1;
+                }
+            } else if len.is_some_and(|len| next >= len) {
+                if !braced {
+                    break;
+                }
+                let msg = format!("Excess elements in initializer for `{ty}`");
+                return Err(error(item.init.span(), msg));
+            }
+            let (step, sub_ty) = match elem {
+                Some(elem) => (Step::Index(next), elem.clone()),
+                None => {
+                    let index = usize::try_from(next).unwrap();
+                    (Step::Member(index), members[index].ty.clone())
+                }
+            };
+            if is_union {
+                // Only one member of a union can hold a value
+                self.leaves.retain(|leaf| {
+                    !leaf.path.starts_with(path) || leaf.path.get(path.len()) == Some(&step)
+                });
+            }
+            if len.is_none() {
+                self.len = self.len.max(next + 1);
+            }
+            path.push(step);
+            pos = self.element(&sub_ty, path, items, pos, depth)?;
+            path.pop();
+            depth = 0;
+            // Elements without a designator after the member of a union are excess
+            next = if is_union {
+                members.len() as u64
+            } else {
+                next + 1
+            };
+        }
+        Ok(pos)
+    }
+    /// Initializes the subobject `ty` from the item at `pos`, which continues a designation
+    /// at `depth`, returning the position of the next item.
+    fn element(
+        &mut self,
+        ty: &Type,
+        path: &mut Vec<Step>,
+        items: &'a [InitItem<'s>],
+        pos: usize,
+        depth: usize,
+    ) -> Result<usize, InitError> {
+        let item = &items[pos];
+        if item.designators.len() > depth {
+            return self.fill(ty, path, items, pos, depth, false);
+        }
+        // The subobject is initialized again, overriding any earlier initializers of its parts
+        self.leaves.retain(|leaf| !leaf.path.starts_with(path));
+        match &item.init {
+            Initializer::List(list) => self.braced(ty, path, list)?,
+            Initializer::Expr(value) => {
+                if self.string(ty, path, value)? {
+                    return Ok(pos + 1);
+                }
+                let is_aggregate = matches!(ty.unqualified(), Type::Array(..)) || is_record(ty);
+                // Without braces, an aggregate takes as many elements as it has subobjects,
+                // unless it's initialized by an expression of its own type
+                if is_aggregate && !(is_record(ty) && self.has_type(value, ty)) {
+                    return self.fill(ty, path, items, pos, 0, false);
+                }
+                self.leaf(path, ty, value);
+            }
+        }
+        Ok(pos + 1)
+    }
+}
@@ -0,0 +1,136 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! The sizes, alignments and member offsets of types, following the System V x86-64 ABI.
+
+use std::collections::HashMap;
+
+use crate::{Member, ScalarType, Spanned, Tag, TagKind, Type};
+
+/// The layout of a struct or union.
+#[derive(Debug, Clone)]
+pub struct RecordLayout<'s> {
+    pub size: u64,
+    pub align: u64,
+    pub members: Vec<MemberLayout<'s>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemberLayout<'s> {
+    pub name: Option<&'s str>,
+    pub ty: Type,
+    /// The alignment given by `_Alignas`
+    pub align: Option<u64>,
+    pub offset: u64,
+}
+
+/// The layouts of the structs and unions defined so far, by the id of their tag.
+#[derive(Debug, Clone, Default)]
+pub struct Layouts<'s> {
+    records: HashMap<usize, RecordLayout<'s>>,
+}
+
+impl<'s> Layouts<'s> {
+    /// The size of a type in bytes, or [None] if it's incomplete.
+    #[must_use]
+    pub fn size_of(&self, ty: &Type) -> Option<u64> {
+        Some(match ty {
+            Type::Scalar(scalar) => scalar.size(),
+            Type::Pointer(_) => 8,
+            Type::Array(elem, len) => self.size_of(elem)? * (*len)?,
+            // GCC gives functions a size of 1 for pointer arithmetic
+            Type::Function(_) => 1,
+            Type::Tagged(tag) if tag.kind == TagKind::Enum => ScalarType::Int.size(),
+            Type::Tagged(tag) => self.record(tag)?.size,
+            Type::Qualified(_, ty) => self.size_of(ty)?,
+        })
+    }
+    /// The alignment of a type in bytes, or [None] if it's an incomplete struct or union.
+    #[must_use]
+    pub fn align_of(&self, ty: &Type) -> Option<u64> {
+        Some(match ty {
+            Type::Array(elem, _) | Type::Qualified(_, elem) => self.align_of(elem)?,
+            Type::Scalar(scalar) => scalar.size(),
+            Type::Pointer(_) => 8,
+            Type::Function(_) => 1,
+            Type::Tagged(tag) if tag.kind == TagKind::Enum => ScalarType::Int.size(),
+            Type::Tagged(tag) => self.record(tag)?.align,
+        })
+    }
+    /// The layout of a struct or union, if it has been defined.
+    #[must_use]
+    pub fn record(&self, tag: &Tag) -> Option<&RecordLayout<'s>> {
+        self.records.get(&tag.id)
+    }
+    /// The members of a struct or union, if it has been defined.
+    #[must_use]
+    pub fn members(&self, tag: &Tag) -> Option<Vec<Member<'s>>> {
+        let members = self.record(tag)?.members.iter().map(|member| Member {
+            name: member.name,
+            ty: member.ty.clone(),
+            align: member.align,
+        });
+        Some(members.collect())
+    }
+    /// Finds the type and offset of the member `name` of a struct or union, including the
+    /// members of its anonymous members.
+    #[must_use]
+    pub fn find_member(&self, tag: &Tag, name: &str) -> Option<(Type, u64)> {
+        self.record(tag)?.members.iter().find_map(|member| {
+            match (member.name, member.ty.unqualified()) {
+                (Some(member_name), _) if member_name == name => {
+                    Some((member.ty.clone(), member.offset))
+                }
+                (None, Type::Tagged(inner)) => {
+                    let (ty, offset) = self.find_member(inner, name)?;
+                    Some((ty, member.offset + offset))
+                }
+                _ => None,
+            }
+        })
+    }
+    /// Lays out a struct or union from its members. Each member is placed at the next offset
+    /// that satisfies its alignment, including any given by `_Alignas`, and the size is rounded
+    /// up to that of the most strictly aligned member, so that it can be used in arrays.
+    pub fn define(&mut self, tag: &Tag, members: &[Spanned<Member<'s>>]) {
+        let mut layout = RecordLayout {
+            size: 0,
+            align: 1,
+            members: Vec::with_capacity(members.len()),
+        };
+        for Spanned { inner: member, .. } in members {
+            // Members of incomplete types have already been reported
+            let size = self.size_of(&member.ty).unwrap_or(0);
+            let align = self.align_of(&member.ty).unwrap_or(1);
+            let align = align.max(member.align.unwrap_or(1));
+            // Every member of a union starts at its beginning
+            let offset = match tag.kind {
+                TagKind::Union => 0,
+                _ => layout.size.next_multiple_of(align),
+            };
+            layout.size = layout.size.max(offset + size);
+            layout.align = layout.align.max(align);
+            layout.members.push(MemberLayout {
+                name: member.name,
+                ty: member.ty.clone(),
+                align: member.align,
+                offset,
+            });
+        }
+        layout.size = layout.size.next_multiple_of(layout.align);
+        self.records.insert(tag.id, layout);
+    }
+}
@@ -15,394 +15,531 @@
 // If not, see <https://www.gnu.org/licenses/>.
 // LICENSE NOTICE END
 
-use std::{borrow::Cow, rc::Rc};
+use std::rc::Rc;
 
-use utils::VarInt;
+use source::Span;
+use syntax::SyntaxNode;
+pub use utils::Spanned;
+use utils::{CharLiteral, FloatLiteral, IntLiteral, StringLiteral, VarInt};
+pub mod constant;
+mod conversion;
 mod display;
+pub mod initializer;
+pub mod layout;
 pub mod tree;
+mod unparse;
+pub mod view;
 
 #[derive(Debug, Clone)]
 pub struct Program<'s> {
-    pub declarations: Vec<Decl<'s>>,
+    pub declarations: Vec<Spanned<Decl<'s>>>,
+    /// The lossless syntax tree the program was parsed along with
+    pub syntax: SyntaxNode,
 }
 
 #[derive(Debug, Clone)]
 pub enum Decl<'s> {
-    // Stmt(Stmt<'s>),
-    // VarDecl {
-    //     name: &'s str,
-    //     init: Option<Expr<'s>>,
-    // },
     Fun(Function<'s>),
+    /// A variable declared at file scope
+    Var(VarDecl<'s>),
+    Type(TypeDecl<'s>),
+}
+
+/// The storage-class specifiers of C17 6.7.1 that affect linkage or storage duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageClass {
+    Static,
+    Extern,
 }
 
 #[derive(Debug, Clone)]
 pub struct Function<'s> {
     pub name: &'s str,
-    // pub parameters: Vec<&'s str>,
-    pub body: Block<'s>,
+    pub storage: Option<StorageClass>,
+    pub ret: Type,
+    /// [None] for an empty parameter list, which leaves the parameters unspecified
+    pub params: Option<Vec<Spanned<Param<'s>>>>,
+    /// Whether the parameter list ends with `...`
+    pub variadic: bool,
+    /// [None] for a declaration that isn't a definition
+    pub body: Option<Block<'s>>,
 }
 
+impl Function<'_> {
+    #[must_use]
+    pub fn ty(&self) -> FunctionType {
+        FunctionType {
+            ret: self.ret.clone(),
+            params: self
+                .params
+                .as_ref()
+                .map(|params| params.iter().map(|param| param.inner.ty.clone()).collect()),
+            variadic: self.variadic,
+        }
+    }
+}
 
+/// A function parameter. The name may be omitted in declarations that aren't definitions.
 #[derive(Debug, Clone)]
-pub enum LiteralExpression<'s> {
-    Str(Cow<'s, str>),
-    Int(VarInt),
-    // Float(f64),
-    // Char(char),
-    // Bool(bool),
-}
-
-// #[derive(Debug, Clone, Copy)]
-// pub enum BinaryOperator {
-//     /// +
-//     Add,
-//     /// -
-//     Sub,
-//     /// *
-//     Mul,
-//     /// /
-//     Div,
-//     /// ==
-//     Eq,
-//     /// !=
-//     Ne,
-//     /// <
-//     Lt,
-//     /// <=
-//     Le,
-//     /// >
-//     Gt,
-//     /// >=
-//     Ge,
-// }
+pub struct Param<'s> {
+    pub name: Option<&'s str>,
+    pub ty: Type,
+}
 
-// #[derive(Debug, Clone, Copy)]
-// pub enum LogicalOperator {
-//     /// and
-//     And,
-//     /// or
-//     Or,
-// }
+/// The type of a function, with the parameter types only known if it was declared with a
+/// prototype.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionType {
+    pub ret: Type,
+    pub params: Option<Vec<Type>>,
+    pub variadic: bool,
+}
 
-// #[derive(Debug, Clone)]
-// pub struct BinaryExpr<'s> {
-//     pub lhs: Expr<'s>,
-//     pub op: BinaryOperator,
-//     pub rhs: Expr<'s>,
-// }
+#[derive(Debug, Clone)]
+pub enum LiteralExpression {
+    Str(StringLiteral),
+    Int(IntLiteral),
+    Float(FloatLiteral),
+    Char(CharLiteral),
+    /// `true` or `false`, of type `bool` since C23
+    Bool(bool),
+    /// `nullptr`, the only value of `nullptr_t` since C23
+    Nullptr,
+    /// A token that failed to lex, whose error has already been reported
+    Invalid,
+}
 
-// #[derive(Debug, Clone)]
-// pub struct LogicalExpr<'s> {
-//     pub lhs: Expr<'s>,
-//     pub op: LogicalOperator,
-//     pub rhs: Expr<'s>,
-// }
+/// An arithmetic or pointer type, as written in a type name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarType {
+    Void,
+    Bool,
+    Char,
+    SChar,
+    UChar,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Long,
+    ULong,
+    LongLong,
+    ULongLong,
+    Float,
+    Double,
+    LongDouble,
+    /// The type of `nullptr`, which has the representation of `void *`
+    NullPtr,
+}
 
-// #[derive(Debug, Clone, Copy)]
-// pub enum UnaryOperator {
-//     /// !
-//     Not,
-//     /// -
-//     Neg,
-// }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagKind {
+    Struct,
+    Union,
+    Enum,
+}
 
-// #[derive(Debug, Clone)]
-// pub struct UnaryExpr<'s> {
-//     pub op: UnaryOperator,
-//     pub val: Expr<'s>,
-// }
+/// The tag of a struct, union or enum type. Tags declared in different scopes are different
+/// types even when they have the same name, so they are compared by their unique id.
+#[derive(Debug, Clone)]
+pub struct Tag {
+    pub kind: TagKind,
+    /// [None] for an anonymous tag
+    pub name: Option<Rc<str>>,
+    pub id: usize,
+}
 
+impl PartialEq for Tag {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Tag {}
+
+/// A member of a struct or union. Only anonymous structs and unions can be members without a
+/// name.
 #[derive(Debug, Clone)]
-pub enum Expr<'s> {
-    Lit(LiteralExpression<'s>),
-    // Ident(&'s str),
-    // Binary(Box<BinaryExpr<'s>>),
-    // Logical(Box<LogicalExpr<'s>>),
-    // Unary(Box<UnaryExpr<'s>>),
-    // Call(Box<Call<'s>>),
-    // Grouped(Box<Self>),
-    // Assignment(Box<Assignment<'s>>),
+pub struct Member<'s> {
+    pub name: Option<&'s str>,
+    pub ty: Type,
+    /// The alignment given by `_Alignas`, which is never less strict than that of `ty`
+    pub align: Option<u64>,
 }
 
-// #[derive(Debug, Clone)]
-// pub struct Call<'s> {
-//     pub callee: Expr<'s>,
-//     pub arguments: Vec<Expr<'s>>,
-// }
+#[derive(Debug, Clone)]
+pub struct Enumerator<'s> {
+    pub name: &'s str,
+    /// The value of the constant, either given by `init` or one more than the previous one
+    pub value: i64,
+    pub init: Option<SExpr<'s>>,
+}
 
-// #[derive(Debug, Clone)]
-// pub struct MemberAccess<'s> {
-//     pub object: Expr<'s>,
-//     pub member: &'s str,
-// }
+/// The declaration of a struct, union or enum tag, or of a typedef name.
+#[derive(Debug, Clone)]
+pub enum TypeDecl<'s> {
+    /// A struct or union, with no members for a forward declaration
+    Record {
+        tag: Tag,
+        members: Option<Vec<Spanned<Member<'s>>>>,
+    },
+    Enum {
+        tag: Tag,
+        enumerators: Vec<Spanned<Enumerator<'s>>>,
+    },
+    /// A typedef name, which the parser replaces with its type wherever it's used
+    Typedef { name: &'s str, ty: Type },
+}
+
+/// The type qualifiers of C17 6.7.3.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Qualifiers {
+    pub is_const: bool,
+    pub is_volatile: bool,
+    pub is_restrict: bool,
+    pub is_atomic: bool,
+}
+
+impl Qualifiers {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        !(self.is_const || self.is_volatile || self.is_restrict || self.is_atomic)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    Scalar(ScalarType),
+    Pointer(Box<Type>),
+    /// An array, whose length is omitted for an incomplete array type
+    Array(Box<Type>, Option<u64>),
+    Function(Box<FunctionType>),
+    /// A struct, union or enum, whose contents are given by the [TypeDecl] that defines its tag
+    Tagged(Tag),
+    /// A type along with qualifiers, which are never empty
+    Qualified(Qualifiers, Box<Type>),
+}
+
+impl Type {
+    /// Adds qualifiers to a type, merging them with any it already has.
+    #[must_use]
+    pub fn qualified(self, qualifiers: Qualifiers) -> Self {
+        if qualifiers.is_empty() {
+            return self;
+        }
+        match self {
+            Self::Qualified(prev, ty) => Self::Qualified(
+                Qualifiers {
+                    is_const: prev.is_const || qualifiers.is_const,
+                    is_volatile: prev.is_volatile || qualifiers.is_volatile,
+                    is_restrict: prev.is_restrict || qualifiers.is_restrict,
+                    is_atomic: prev.is_atomic || qualifiers.is_atomic,
+                },
+                ty,
+            ),
+            ty => Self::Qualified(qualifiers, Box::new(ty)),
+        }
+    }
+    /// The type without its top-level qualifiers.
+    #[must_use]
+    pub fn unqualified(&self) -> &Self {
+        match self {
+            Self::Qualified(_, ty) => ty,
+            ty => ty,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+    /// *
+    Mul,
+    /// /
+    Div,
+    /// %
+    Rem,
+    /// +
+    Add,
+    /// -
+    Sub,
+    /// <<
+    Shl,
+    /// >>
+    Shr,
+    /// <
+    Lt,
+    /// <=
+    Le,
+    /// >
+    Gt,
+    /// >=
+    Ge,
+    /// ==
+    Eq,
+    /// !=
+    Ne,
+    /// &
+    BitAnd,
+    /// ^
+    BitXor,
+    /// |
+    BitOr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogicalOperator {
+    /// &&
+    And,
+    /// ||
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOperator {
+    /// -x
+    Neg,
+    /// +x
+    Plus,
+    /// !x
+    Not,
+    /// ~x
+    BitNot,
+    /// *x
+    Deref,
+    /// &x
+    AddrOf,
+    /// ++x
+    PreInc,
+    /// --x
+    PreDec,
+    /// x++
+    PostInc,
+    /// x--
+    PostDec,
+}
+
+#[derive(Debug, Clone)]
+pub struct BinaryExpr<'s> {
+    pub lhs: SExpr<'s>,
+    pub op: BinaryOperator,
+    pub rhs: SExpr<'s>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogicalExpr<'s> {
+    pub lhs: SExpr<'s>,
+    pub op: LogicalOperator,
+    pub rhs: SExpr<'s>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UnaryExpr<'s> {
+    pub op: UnaryOperator,
+    pub val: SExpr<'s>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Assignment<'s> {
+    pub target: SExpr<'s>,
+    /// The operator of a compound assignment like `+=`
+    pub op: Option<BinaryOperator>,
+    pub val: SExpr<'s>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Ternary<'s> {
+    pub cond: SExpr<'s>,
+    pub then_val: SExpr<'s>,
+    pub else_val: SExpr<'s>,
+}
+
+/// The comma operator, evaluating `lhs` only for its side effects.
+#[derive(Debug, Clone)]
+pub struct Comma<'s> {
+    pub lhs: SExpr<'s>,
+    pub rhs: SExpr<'s>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Call<'s> {
+    pub callee: SExpr<'s>,
+    pub args: Vec<SExpr<'s>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Index<'s> {
+    pub target: SExpr<'s>,
+    pub index: SExpr<'s>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemberAccess<'s> {
+    pub object: SExpr<'s>,
+    pub member: &'s str,
+    /// Whether the member is accessed through a pointer with `->`
+    pub arrow: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cast<'s> {
+    pub ty: Type,
+    pub val: SExpr<'s>,
+}
+
+/// A compound literal `(type){ ... }`, an unnamed object initialized by a list.
+#[derive(Debug, Clone)]
+pub struct CompoundLiteral<'s> {
+    /// The type of the object, with the length of an array completed by its initializer
+    pub ty: Type,
+    /// Always an [Initializer::List]
+    pub init: Initializer<'s>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SizeOf<'s> {
+    Val(SExpr<'s>),
+    Type(Type),
+}
+
+/// An expression along with the span of source it was parsed from.
+pub type SExpr<'s> = Spanned<Expr<'s>>;
+
+#[derive(Debug, Clone)]
+pub enum Expr<'s> {
+    Lit(LiteralExpression),
+    Ident(&'s str),
+    Binary(Box<BinaryExpr<'s>>),
+    Logical(Box<LogicalExpr<'s>>),
+    Unary(Box<UnaryExpr<'s>>),
+    Assignment(Box<Assignment<'s>>),
+    Ternary(Box<Ternary<'s>>),
+    Comma(Box<Comma<'s>>),
+    Call(Box<Call<'s>>),
+    Index(Box<Index<'s>>),
+    MemberAccess(Box<MemberAccess<'s>>),
+    Cast(Box<Cast<'s>>),
+    CompoundLiteral(Box<CompoundLiteral<'s>>),
+    SizeOf(Box<SizeOf<'s>>),
+    /// `_Alignof(type)`, the alignment of a type in bytes
+    AlignOf(Type),
+}
 
 #[derive(Debug, Clone, Default)]
-pub struct Block<'s>(pub Vec<Stmt<'s>>);
+pub struct Block<'s>(pub Vec<BlockItem<'s>>);
 
+/// A declaration or statement inside a block.
 #[derive(Debug, Clone)]
-pub enum Stmt<'s> {
-    // Expr(Expr<'s>),
-    Return(Option<Expr<'s>>),
-    // Break,
-    // Continue,
-    // While {
-    //     cond: Expr<'s>,
-    //     body: Block<'s>,
-    // },
-    // Block(Block<'s>),
-    // /// A function definition with parameter names
-    //     Function(Function<'s>),
-    //     Use {
-    //         module: &'s str,
-    //         alias: Option<&'s str>,
-    //     },
-    //     VarDecl {
-    //         name: &'s str,
-    //         ty: Type<'s>,
-    //         init: Option<Expr<'s>>,
-    //         public: bool,
-    //         mutable: bool,
-    //     },
-    // If {
-    //     cond: Expr<'s>,
-    //     then_body: Block<'s>,
-    //     // TODO: Add else if
-    //     // elifs: Vec<Elif<'s>>,
-    //     else_body: Option<Block<'s>>,
-    // },
-    // For(Box<For<'s>>),
-    //     TypeDecl {
-    //         ty: TypeDecl<'s>,
-    //         public: bool,
-    //         name: &'s str,
-    //     },
-    //     Loop {
-    //         cond: Option<Expr<'s>>,
-    //         initializers: Block<'s>,
-    //         post_ops: Block<'s>,
-    //         body: Block<'s>,
-    //     },
-    //     Defer(Box<Stmt<'s>>),
-    //     Switch {
-    //         value: Expr<'s>,
-    //         cases: Vec<SwitchCase<'s>>,
-    //         default: Option<Box<Stmt<'s>>>,
-    //     },
+pub enum BlockItem<'s> {
+    Decl(Vec<Spanned<VarDecl<'s>>>),
+    /// The declaration of a tag or typedef name. A tag comes right before the item that
+    /// declared it.
+    Type(Spanned<TypeDecl<'s>>),
+    Stmt(SStmt<'s>),
 }
 
-// #[derive(Debug, Clone)]
-// pub struct For<'s> {
-//     pub initializer: Option<Decl<'s>>,
-//     pub cond: Option<Expr<'s>>,
-//     pub increment: Option<Expr<'s>>,
-//     pub body: Block<'s>,
-// }
+/// Selects the subobject of the current object that the next initializer applies to.
+#[derive(Debug, Clone)]
+pub enum Designator<'s> {
+    /// `.member`
+    Member(&'s str),
+    /// `[index]`, whose index is an integer constant expression
+    Index(SExpr<'s>),
+}
 
-// #[derive(Debug, Clone)]
-// pub struct Assignment<'s> {
-//     pub target: &'s str,
-//     pub val: Expr<'s>,
-// }
+/// An element of a brace-enclosed initializer list, along with the designators before it.
+#[derive(Debug, Clone)]
+pub struct InitItem<'s> {
+    pub designators: Vec<Spanned<Designator<'s>>>,
+    pub init: Initializer<'s>,
+}
 
-// #[derive(Debug, Clone)]
-// pub enum BinOpKind {
-//     /// +
-//     Add,
-//     /// -
-//     Sub,
-//     /// *
-//     Mul,
-//     /// /
-//     Div,
-//     /// %
-//     Mod,
-//     /// **
-//     Pow,
-//     /// ==
-//     Eq,
-//     /// !=
-//     Ne,
-//     /// <
-//     Lt,
-//     /// <=
-//     Le,
-//     /// >
-//     Gt,
-//     /// >=
-//     Ge,
-//     /// &&
-//     And,
-//     /// ||
-//     Or,
-//     /// &
-//     BitAnd,
-//     /// |
-//     BitOr,
-//     /// ^
-//     BitXor,
-//     /// <<
-//     Shl,
-//     /// >>
-//     Shr,
-//     /// ..
-//     Range,
-// }
+/// The initializer of an object (C17 6.7.9).
+#[derive(Debug, Clone)]
+pub enum Initializer<'s> {
+    Expr(SExpr<'s>),
+    /// A brace-enclosed list, whose span includes the braces
+    List(Spanned<Vec<InitItem<'s>>>),
+}
 
-// #[derive(Debug, Clone, Copy)]
-// pub enum UnaryOpKind {
-//     /// !
-//     Not,
-//     /// -
-//     Neg,
-//     /// +
-//     Pos,
-//     /// ~
-//     BitNot,
-//     /// ++x
-//     PreInc,
-//     /// --x
-//     PreDec,
-//     /// x++
-//     PostInc,
-//     /// x--
-//     PostDec,
-//     /// *x
-//     Deref,
-//     /// &x
-//     Addr,
-// }
+impl Initializer<'_> {
+    #[must_use]
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Expr(expr) => expr.as_span(),
+            Self::List(list) => list.as_span(),
+        }
+    }
+}
 
-// #[derive(Debug, Clone)]
-// pub struct Function<'s> {
-//     pub params: Vec<(Type<'s>, &'s str)>,
-//     pub ret: Type<'s>,
-//     pub body: Block<'s>,
-// }
-//
-// #[derive(Debug, Clone)]
-// pub enum Type<'s> {
-//     Void,
-//     Bool,
-//     Char,
-//     Int,
-//     UInt,
-//     Float,
-//     Double,
-//     Str,
-//     Named(&'s str),
-//     Pointer { pointee: Box<Type<'s>> },
-//     Array(Box<Type<'s>>, Expr<'s>),
-//     Function(Box<Function<'s>>),
-// }
-//
-// #[derive(Debug, Clone)]
-// pub struct StructField<'s> {
-//     pub name: &'s str,
-//     pub ty: Type<'s>,
-//     pub public: bool,
-// }
-//
-// #[derive(Debug, Clone)]
-// pub enum TypeDecl<'s> {
-//     Struct { fields: Vec<StructField<'s>> },
-//     Enum { members: Vec<&'s str> },
-// }
-//
-// #[derive(Debug, Clone)]
-// pub struct Elif<'s> {
-//     pub cond: Expr<'s>,
-//     pub body: Block<'s>,
-// }
+/// A single declarator of a declaration, along with its initializer.
+#[derive(Debug, Clone)]
+pub struct VarDecl<'s> {
+    pub name: &'s str,
+    pub storage: Option<StorageClass>,
+    /// The declared type, with the length of an array completed by its initializer
+    pub ty: Type,
+    /// The alignment given by `_Alignas`, which is never less strict than that of `ty`
+    pub align: Option<u64>,
+    pub init: Option<Initializer<'s>>,
+}
 
-//
-// #[derive(Debug, Clone)]
-// pub struct SwitchCase<'s> {
-//     pub body: Stmt<'s>,
-//     pub cases: Vec<Expr<'s>>,
-// }
-//
-//
-// #[derive(Debug, Clone)]
-// pub struct Ternary<'s> {
-//     pub then_val: Expr<'s>,
-//     pub else_val: Expr<'s>,
-//     pub cond: Expr<'s>,
-// }
-//
-//
-// #[derive(Debug, Clone)]
-// pub struct UnaryOp<'s> {
-//     pub val: Expr<'s>,
-//     pub op: UnaryOpKind,
-// }
-//
-// #[derive(Debug, Clone)]
-// pub struct Call<'s> {
-//     pub callee: Box<Expr<'s>>,
-//     pub args: Vec<Expr<'s>>,
-// }
-//
+#[derive(Debug, Clone)]
+pub enum ForInit<'s> {
+    Decl(Vec<Spanned<VarDecl<'s>>>),
+    Expr(SExpr<'s>),
+}
+
+#[derive(Debug, Clone)]
+pub struct For<'s> {
+    pub init: Option<ForInit<'s>>,
+    pub cond: Option<SExpr<'s>>,
+    pub step: Option<SExpr<'s>>,
+    pub body: SStmt<'s>,
+}
+
+/// A statement along with the span of source it was parsed from.
+pub type SStmt<'s> = Spanned<Stmt<'s>>;
+
+#[derive(Debug, Clone)]
+pub enum Stmt<'s> {
+    Expr(SExpr<'s>),
+    Return(Option<SExpr<'s>>),
+    Block(Block<'s>),
+    If {
+        cond: SExpr<'s>,
+        then_body: Box<SStmt<'s>>,
+        else_body: Option<Box<SStmt<'s>>>,
+    },
+    While {
+        cond: SExpr<'s>,
+        body: Box<SStmt<'s>>,
+    },
+    DoWhile {
+        body: Box<SStmt<'s>>,
+        cond: SExpr<'s>,
+    },
+    For(Box<For<'s>>),
+    Break,
+    Continue,
+    Goto(&'s str),
+    Labeled {
+        label: &'s str,
+        body: Box<SStmt<'s>>,
+    },
+    /// The cases of a switch are labels anywhere in its body
+    Switch {
+        value: SExpr<'s>,
+        body: Box<SStmt<'s>>,
+    },
+    Case {
+        value: SExpr<'s>,
+        body: Box<SStmt<'s>>,
+    },
+    Default(Box<SStmt<'s>>),
+    /// The null statement, a lone `;`
+    Null,
+}
 
-// #[derive(Debug, Clone)]
-// pub struct NamespaceAccess<'s> {
-//     pub object: Expr<'s>,
-//     pub member: &'s str,
-// }
-//
-// #[derive(Debug, Clone)]
-// pub enum SizeOf<'s> {
-//     Val(Expr<'s>),
-//     Type(Type<'s>),
-// }
-//
-// #[derive(Debug, Clone)]
-// pub enum Intrinsic<'s> {
-//     Index {
-//         target: Expr<'s>,
-//         index: Expr<'s>,
-//     },
-//     Memcpy {
-//         from: Expr<'s>,
-//         to: Expr<'s>,
-//         size: Expr<'s>,
-//     },
-//     Deref {
-//         addr: Expr<'s>,
-//     },
-//     Addr {
-//         of: Expr<'s>,
-//     },
-//     Alloc {
-//         size: Expr<'s>,
-//     },
-//     Free {
-//         ptr: Expr<'s>,
-//     },
-//     Cast {
-//         ty: Type<'s>,
-//         val: Expr<'s>,
-//     },
-//     SizeOf(SizeOf<'s>),
-// }
-//
-// #[derive(Debug, Clone)]
-// pub enum Expr<'s> {
-//     Lit(LiteralExpression<'s>),
-//     BinOp(Box<BinOp<'s>>),
-//     UnaryOp(Box<UnaryOp<'s>>),
-//     Ternary(Box<Ternary<'s>>),
-//     Call(Call<'s>),
-//     Assignment(Box<Assignment<'s>>),
-//     MemberAccess(Box<MemberAccess<'s>>),
-//     NamespaceAccess(Box<NamespaceAccess<'s>>),
-//     Group(Box<Expr<'s>>),
-//     Array(Vec<Expr<'s>>),
-//     Intrinsic(Box<Intrinsic<'s>>),
-// }
-//
-//
-//
 // #[derive(Debug, Clone)]
 // pub struct Module<'s> {
 //     pub body: Block<'s>,
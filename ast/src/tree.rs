@@ -16,9 +16,15 @@
 
 use std::fmt::{self, Display, Write};
 
-use utils::VarInt;
+use source::{SourceFile, Span};
+use utils::{CharLiteral, FloatLiteral, IntLiteral, Spanned, StringLiteral, VarInt};
 
-use crate::{Block, Decl, Expr, Function, LiteralExpression, Program, Stmt};
+use crate::{
+    Assignment, BinaryExpr, Block, BlockItem, Call, Cast, Comma, CompoundLiteral, Decl, Designator,
+    Enumerator, Expr, ForInit, Function, Index, InitItem, Initializer, LiteralExpression,
+    LogicalExpr, Member, MemberAccess, Param, Program, SizeOf, Stmt, Ternary, Type, TypeDecl,
+    UnaryExpr, VarDecl,
+};
 
 #[derive(Debug, Clone, Copy)]
 enum Section {
@@ -33,6 +39,10 @@ pub struct TreeCtx {
     pub connector: &'static str,
     pub vertical: &'static str,
     pub end_connector: &'static str,
+    /// The file the tree was parsed from, used to print the span of each node
+    source: Option<SourceFile>,
+    /// The span of the node whose header is printed next
+    span: Option<Span>,
 }
 impl TreeCtx {
     #[must_use]
@@ -43,8 +53,16 @@ impl TreeCtx {
             connector: "├──",
             vertical: "│  ",
             end_connector: "└──",
+            source: None,
+            span: None,
         }
     }
+    /// Prints the span of every node after its name, as a `line:col-line:col` range in `source`.
+    #[must_use]
+    pub fn with_spans(mut self, source: SourceFile) -> Self {
+        self.source = Some(source);
+        self
+    }
     fn add_level(&mut self) {
         self.prefix.push(Section::Middle);
     }
@@ -63,9 +81,21 @@ impl Default for TreeCtx {
 }
 
 impl TreeCtx {
-    fn with_indentation(&self, writer: &mut impl Write, name: impl Display) -> fmt::Result {
+    fn with_indentation(&mut self, writer: &mut impl Write, name: impl Display) -> fmt::Result {
         self.write_identation(writer)?;
-        writeln!(writer, "{name}")
+        write!(writer, "{name}")?;
+        let span = self.span.take();
+        if let (Some(source), Some(span)) = (&self.source, span) {
+            let [start, end] = source.span_to_pos(&span);
+            // Lines are counted in the file the node came from, past any `#include`s
+            let start_line = source
+                .line_location(start.line_0idx())
+                .map_or(start.line(), |l| l.line);
+            let end_line = start_line + (end.line() - start.line());
+            let (start_col, end_col) = (start.col(), end.col());
+            write!(writer, " @ {start_line}:{start_col}-{end_line}:{end_col}")?;
+        }
+        writeln!(writer)
     }
     fn write_identation(&self, writer: &mut impl Write) -> fmt::Result {
         for section in self.prefix.iter().rev().skip(1).rev() {
@@ -150,6 +180,13 @@ impl<T: TreeDisplay> TreeDisplay for &[T] {
     }
 }
 
+impl<T: TreeDisplay> TreeDisplay for Spanned<T> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.span = Some(self.as_span());
+        self.inner.fmt_tree(ctx, writer)
+    }
+}
+
 impl TreeDisplay for &str {
     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
         ctx.write_identation(writer)?;
@@ -166,6 +203,34 @@ impl TreeDisplay for VarInt {
     }
 }
 
+impl TreeDisplay for IntLiteral {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.write_identation(writer)?;
+        writeln!(writer, "{self}")
+    }
+}
+
+impl TreeDisplay for StringLiteral {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.write_identation(writer)?;
+        writeln!(writer, "{self}")
+    }
+}
+
+impl TreeDisplay for FloatLiteral {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.write_identation(writer)?;
+        writeln!(writer, "{self}")
+    }
+}
+
+impl TreeDisplay for CharLiteral {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.write_identation(writer)?;
+        writeln!(writer, "{self}")
+    }
+}
+
 impl TreeDisplay for u64 {
     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
         ctx.write_identation(writer)?;
@@ -205,145 +270,431 @@ impl TreeDisplay for Stmt<'_> {
     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
         use Stmt::*;
         match self {
-            // Break => ctx.with_indentation(writer, "Break")?,
-            // Continue => ctx.with_indentation(writer, "Continue")?,
-            // Expr(e) => e.fmt_tree(ctx, writer)?,
+            Break => ctx.with_indentation(writer, "Break")?,
+            Continue => ctx.with_indentation(writer, "Continue")?,
+            Null => ctx.with_indentation(writer, "Null")?,
+            Expr(e) => e.fmt_tree(ctx, writer)?,
             Return(e) => ctx.fmt_single_field(writer, "Return", &e.as_ref())?,
-            // Block(b) => b.fmt_tree(ctx, writer)?,
-            // While { cond, body } => {
-            //     ctx.with_indentation(writer, "While")?;
-            //     ctx.add_level();
-            //     ctx.fmt_single_field(writer, "Cond", cond)?;
-            //     ctx.make_last();
-            //     ctx.fmt_single_field(writer, "Body", body)?;
-            //     ctx.pop_level();
-            // }
-            // If {
-            //     cond,
-            //     then_body,
-            //     else_body,
-            // } => {
-            //     ctx.with_indentation(writer, "If")?;
-            //     ctx.add_level();
-            //     ctx.fmt_single_field(writer, "Cond", cond)?;
-            //     if else_body.is_none() {
-            //         ctx.make_last();
-            //     }
-            //     ctx.fmt_single_field(writer, "Then", then_body)?;
-            //     ctx.make_last();
-            //     if let Some(else_body) = else_body {
-            //         ctx.fmt_single_field(writer, "Else", else_body)?;
-            //     }
-            //     ctx.pop_level();
-            // }
-            // For(f) => {
-            //     let crate::For {
-            //         initializer,
-            //         cond,
-            //         increment,
-            //         body,
-            //     } = &**f;
-            //     ctx.with_indentation(writer, "For")?;
-            //     ctx.add_level();
-            //     ctx.fmt_optional_field(writer, "Initializer", initializer.as_ref())?;
-            //     ctx.fmt_optional_field(writer, "Condition", cond.as_ref())?;
-            //     ctx.fmt_optional_field(writer, "Increment", increment.as_ref())?;
-            //     ctx.make_last();
-            //     body.fmt_tree(ctx, writer)?;
-            //     ctx.pop_level();
-            // }
+            Block(b) => b.fmt_tree(ctx, writer)?,
+            Goto(label) => ctx.fmt_single_field(writer, "Goto", label)?,
+            Default(body) => ctx.fmt_single_field(writer, "Default", &**body)?,
+            While { cond, body } => {
+                ctx.with_indentation(writer, "While")?;
+                ctx.add_level();
+                ctx.fmt_single_field(writer, "Cond", cond)?;
+                ctx.make_last();
+                ctx.fmt_single_field(writer, "Body", &**body)?;
+                ctx.pop_level();
+            }
+            DoWhile { body, cond } => {
+                ctx.with_indentation(writer, "Do While")?;
+                ctx.add_level();
+                ctx.fmt_single_field(writer, "Body", &**body)?;
+                ctx.make_last();
+                ctx.fmt_single_field(writer, "Cond", cond)?;
+                ctx.pop_level();
+            }
+            If {
+                cond,
+                then_body,
+                else_body,
+            } => {
+                ctx.with_indentation(writer, "If")?;
+                ctx.add_level();
+                ctx.fmt_single_field(writer, "Cond", cond)?;
+                if else_body.is_none() {
+                    ctx.make_last();
+                }
+                ctx.fmt_single_field(writer, "Then", &**then_body)?;
+                ctx.make_last();
+                if let Some(else_body) = else_body {
+                    ctx.fmt_single_field(writer, "Else", &**else_body)?;
+                }
+                ctx.pop_level();
+            }
+            For(f) => {
+                let crate::For {
+                    init,
+                    cond,
+                    step,
+                    body,
+                } = &**f;
+                ctx.with_indentation(writer, "For")?;
+                ctx.add_level();
+                ctx.fmt_optional_field(writer, "Init", init.as_ref())?;
+                ctx.fmt_optional_field(writer, "Cond", cond.as_ref())?;
+                ctx.fmt_optional_field(writer, "Step", step.as_ref())?;
+                ctx.make_last();
+                ctx.fmt_single_field(writer, "Body", body)?;
+                ctx.pop_level();
+            }
+            Labeled { label, body } => {
+                ctx.with_indentation(writer, format_args!("Label {label}"))?;
+                ctx.add_level();
+                ctx.make_last();
+                body.fmt_tree(ctx, writer)?;
+                ctx.pop_level();
+            }
+            Switch { value, body } => {
+                ctx.with_indentation(writer, "Switch")?;
+                ctx.add_level();
+                ctx.fmt_single_field(writer, "Value", value)?;
+                ctx.make_last();
+                ctx.fmt_single_field(writer, "Body", &**body)?;
+                ctx.pop_level();
+            }
+            Case { value, body } => {
+                ctx.with_indentation(writer, "Case")?;
+                ctx.add_level();
+                ctx.fmt_single_field(writer, "Value", value)?;
+                ctx.make_last();
+                ctx.fmt_single_field(writer, "Body", &**body)?;
+                ctx.pop_level();
+            }
         }
         Ok(())
     }
 }
 
-// impl TreeDisplay for UnaryExpr<'_> {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         self.op.fmt_tree(ctx, writer)?;
-//         ctx.add_level();
-//         ctx.make_last();
-//         self.val.fmt_tree(ctx, writer)?;
-//         ctx.pop_level();
-//         Ok(())
-//     }
-// }
+impl TreeDisplay for BlockItem<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        match self {
+            BlockItem::Decl(decls) => {
+                ctx.fmt_single_field_flat(writer, "Declaration", &decls.as_slice())
+            }
+            BlockItem::Type(decl) => decl.fmt_tree(ctx, writer),
+            BlockItem::Stmt(stmt) => stmt.fmt_tree(ctx, writer),
+        }
+    }
+}
 
-// impl TreeDisplay for BinaryExpr<'_> {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         self.op.fmt_tree(ctx, writer)?;
-//         ctx.add_level();
-//         self.lhs.fmt_tree(ctx, writer)?;
-//         ctx.make_last();
-//         self.rhs.fmt_tree(ctx, writer)?;
-//         ctx.pop_level();
-//         Ok(())
-//     }
-// }
+impl TreeDisplay for TypeDecl<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        match self {
+            TypeDecl::Record { tag, members } => {
+                let header = format!("Type Declaration {tag}");
+                match members {
+                    Some(members) => {
+                        ctx.fmt_single_field_flat(writer, &header, &members.as_slice())
+                    }
+                    None => ctx.with_indentation(writer, header),
+                }
+            }
+            TypeDecl::Enum { tag, enumerators } => {
+                let header = format!("Type Declaration {tag}");
+                ctx.fmt_single_field_flat(writer, &header, &enumerators.as_slice())
+            }
+            TypeDecl::Typedef { name, ty } => {
+                ctx.with_indentation(writer, "Typedef")?;
+                ctx.add_level();
+                ctx.fmt_single_field(writer, "Name", name)?;
+                ctx.make_last();
+                ctx.fmt_single_field(writer, "Type", ty)?;
+                ctx.pop_level();
+                Ok(())
+            }
+        }
+    }
+}
 
-// impl TreeDisplay for LogicalExpr<'_> {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         self.op.fmt_tree(ctx, writer)?;
-//         ctx.add_level();
-//         self.lhs.fmt_tree(ctx, writer)?;
-//         ctx.make_last();
-//         self.rhs.fmt_tree(ctx, writer)?;
-//         ctx.pop_level();
-//         Ok(())
-//     }
-// }
+impl TreeDisplay for Member<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, "Member")?;
+        ctx.add_level();
+        if let Some(name) = &self.name {
+            ctx.fmt_single_field(writer, "Name", name)?;
+        }
+        if let Some(align) = self.align {
+            ctx.with_indentation(writer, format_args!("Alignment: {align}"))?;
+        }
+        ctx.make_last();
+        ctx.fmt_single_field(writer, "Type", &self.ty)?;
+        ctx.pop_level();
+        Ok(())
+    }
+}
 
-// impl TreeDisplay for MemberAccess<'_> {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         ctx.with_indentation(writer, ".")?;
-//         ctx.add_level();
-//         self.object.fmt_tree(ctx, writer)?;
-//         ctx.make_last();
-//         self.member.fmt_tree(ctx, writer)?;
-//         ctx.pop_level();
-//         Ok(())
-//     }
-// }
+impl TreeDisplay for Enumerator<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, "Enumerator")?;
+        ctx.add_level();
+        ctx.fmt_single_field(writer, "Name", &self.name)?;
+        if self.init.is_none() {
+            ctx.make_last();
+        }
+        ctx.fmt_single_field(writer, "Value", &self.value)?;
+        ctx.make_last();
+        ctx.fmt_optional_field(writer, "Init", self.init.as_ref())?;
+        ctx.pop_level();
+        Ok(())
+    }
+}
+
+impl TreeDisplay for ForInit<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        match self {
+            ForInit::Decl(decls) => {
+                ctx.fmt_single_field_flat(writer, "Declaration", &decls.as_slice())
+            }
+            ForInit::Expr(e) => e.fmt_tree(ctx, writer),
+        }
+    }
+}
+
+impl TreeDisplay for VarDecl<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, "Variable")?;
+        ctx.add_level();
+        ctx.fmt_single_field(writer, "Name", &self.name)?;
+        if let Some(storage) = self.storage {
+            ctx.with_indentation(writer, format_args!("Storage: {storage}"))?;
+        }
+        if let Some(align) = self.align {
+            ctx.with_indentation(writer, format_args!("Alignment: {align}"))?;
+        }
+        if self.init.is_none() {
+            ctx.make_last();
+        }
+        ctx.fmt_single_field(writer, "Type", &self.ty)?;
+        if let Some(init) = &self.init {
+            ctx.make_last();
+            ctx.fmt_single_field(writer, "Init", init)?;
+        }
+        ctx.pop_level();
+        Ok(())
+    }
+}
+
+impl TreeDisplay for UnaryExpr<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, self.op)?;
+        ctx.add_level();
+        ctx.make_last();
+        self.val.fmt_tree(ctx, writer)?;
+        ctx.pop_level();
+        Ok(())
+    }
+}
+
+impl TreeDisplay for BinaryExpr<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, self.op)?;
+        ctx.add_level();
+        self.lhs.fmt_tree(ctx, writer)?;
+        ctx.make_last();
+        self.rhs.fmt_tree(ctx, writer)?;
+        ctx.pop_level();
+        Ok(())
+    }
+}
+
+impl TreeDisplay for LogicalExpr<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, self.op)?;
+        ctx.add_level();
+        self.lhs.fmt_tree(ctx, writer)?;
+        ctx.make_last();
+        self.rhs.fmt_tree(ctx, writer)?;
+        ctx.pop_level();
+        Ok(())
+    }
+}
+
+impl TreeDisplay for Assignment<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        match self.op {
+            Some(op) => ctx.with_indentation(writer, format_args!("Assignment {op}="))?,
+            None => ctx.with_indentation(writer, "Assignment")?,
+        }
+        ctx.add_level();
+        ctx.fmt_single_field(writer, "Target", &self.target)?;
+        ctx.make_last();
+        ctx.fmt_single_field(writer, "Value", &self.val)?;
+        ctx.pop_level();
+        Ok(())
+    }
+}
+
+impl TreeDisplay for Ternary<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, "Ternary")?;
+        ctx.add_level();
+        ctx.fmt_single_field(writer, "Cond", &self.cond)?;
+        ctx.fmt_single_field(writer, "Then", &self.then_val)?;
+        ctx.make_last();
+        ctx.fmt_single_field(writer, "Else", &self.else_val)?;
+        ctx.pop_level();
+        Ok(())
+    }
+}
+
+impl TreeDisplay for Comma<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, ",")?;
+        ctx.add_level();
+        self.lhs.fmt_tree(ctx, writer)?;
+        ctx.make_last();
+        self.rhs.fmt_tree(ctx, writer)?;
+        ctx.pop_level();
+        Ok(())
+    }
+}
+
+impl TreeDisplay for Call<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, "Call")?;
+        ctx.add_level();
+        if self.args.is_empty() {
+            ctx.make_last();
+        }
+        ctx.fmt_single_field(writer, "Callee", &self.callee)?;
+        if !self.args.is_empty() {
+            ctx.make_last();
+            ctx.fmt_single_field_flat(writer, "Args", &self.args.as_slice())?;
+        }
+        ctx.pop_level();
+        Ok(())
+    }
+}
+
+impl TreeDisplay for Index<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, "Index")?;
+        ctx.add_level();
+        ctx.fmt_single_field(writer, "Target", &self.target)?;
+        ctx.make_last();
+        ctx.fmt_single_field(writer, "Index", &self.index)?;
+        ctx.pop_level();
+        Ok(())
+    }
+}
+
+impl TreeDisplay for MemberAccess<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, if self.arrow { "->" } else { "." })?;
+        ctx.add_level();
+        self.object.fmt_tree(ctx, writer)?;
+        ctx.make_last();
+        self.member.fmt_tree(ctx, writer)?;
+        ctx.pop_level();
+        Ok(())
+    }
+}
+
+impl TreeDisplay for Cast<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, "Cast")?;
+        ctx.add_level();
+        ctx.fmt_single_field(writer, "To", &self.ty)?;
+        ctx.make_last();
+        ctx.fmt_single_field(writer, "Value", &self.val)?;
+        ctx.pop_level();
+        Ok(())
+    }
+}
+
+impl TreeDisplay for CompoundLiteral<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, "Compound Literal")?;
+        ctx.add_level();
+        ctx.fmt_single_field(writer, "Type", &self.ty)?;
+        ctx.make_last();
+        ctx.fmt_single_field(writer, "Init", &self.init)?;
+        ctx.pop_level();
+        Ok(())
+    }
+}
+
+impl TreeDisplay for Initializer<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        match self {
+            Initializer::Expr(expr) => expr.fmt_tree(ctx, writer),
+            Initializer::List(list) => {
+                ctx.span = Some(list.as_span());
+                ctx.fmt_single_field_flat(writer, "Initializer List", &list.inner.as_slice())
+            }
+        }
+    }
+}
+
+impl TreeDisplay for InitItem<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        if self.designators.is_empty() {
+            return self.init.fmt_tree(ctx, writer);
+        }
+        ctx.with_indentation(writer, "Designation")?;
+        ctx.add_level();
+        for designator in &self.designators {
+            designator.fmt_tree(ctx, writer)?;
+        }
+        ctx.make_last();
+        ctx.fmt_single_field(writer, "Value", &self.init)?;
+        ctx.pop_level();
+        Ok(())
+    }
+}
+
+impl TreeDisplay for Designator<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        match self {
+            Designator::Member(name) => ctx.fmt_single_field(writer, "Member", name),
+            Designator::Index(index) => ctx.fmt_single_field(writer, "Index", index),
+        }
+    }
+}
+
+impl TreeDisplay for SizeOf<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        match self {
+            SizeOf::Val(v) => ctx.fmt_single_field(writer, "SizeOf value", v),
+            SizeOf::Type(ty) => ctx.fmt_single_field(writer, "SizeOf type", ty),
+        }
+    }
+}
+
+impl TreeDisplay for Type {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, format_args!("Type: {self}"))
+    }
+}
 
 impl TreeDisplay for Expr<'_> {
     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
         use Expr::*;
         match self {
             Lit(lit) => lit.fmt_tree(ctx, writer),
-            // Ident(name) => ctx.fmt_single_field(writer, "Ident", name),
-            // Binary(op) => op.fmt_tree(ctx, writer),
-            // Logical(op) => op.fmt_tree(ctx, writer),
-            // Unary(op) => op.fmt_tree(ctx, writer),
-            // Grouped(g) => ctx.fmt_single_field(writer, "Group", &**g),
-            // Assignment(assignment) => assignment.fmt_tree(ctx, writer),
-            // Call(call) => call.fmt_tree(ctx, writer),
-            // Ternary(tern) => tern.fmt_tree(ctx, writer),
-            // Call(c) => c.fmt_tree(ctx, writer),
-            // Assignment(a) => a.fmt_tree(ctx, writer),
-            // MemberAccess(a) => a.fmt_tree(ctx, writer),
-            // NamespaceAccess(n) => n.fmt_tree(ctx, writer),
-            // Array(val) => ctx.fmt_single_field_flat(writer, "Array", &&val[..]),
-            // Intrinsic(i) => i.fmt_tree(ctx, writer),
+            Ident(name) => ctx.fmt_single_field(writer, "Ident", name),
+            Binary(op) => op.fmt_tree(ctx, writer),
+            Logical(op) => op.fmt_tree(ctx, writer),
+            Unary(op) => op.fmt_tree(ctx, writer),
+            Assignment(assignment) => assignment.fmt_tree(ctx, writer),
+            Ternary(tern) => tern.fmt_tree(ctx, writer),
+            Comma(comma) => comma.fmt_tree(ctx, writer),
+            Call(call) => call.fmt_tree(ctx, writer),
+            Index(index) => index.fmt_tree(ctx, writer),
+            MemberAccess(access) => access.fmt_tree(ctx, writer),
+            Cast(cast) => cast.fmt_tree(ctx, writer),
+            CompoundLiteral(literal) => literal.fmt_tree(ctx, writer),
+            SizeOf(size_of) => size_of.fmt_tree(ctx, writer),
+            AlignOf(ty) => ctx.fmt_single_field(writer, "AlignOf type", ty),
         }
     }
 }
 
-impl TreeDisplay for LiteralExpression<'_> {
+impl TreeDisplay for LiteralExpression {
     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
         use LiteralExpression::*;
-        ctx.write_identation(writer)?;
-        writeln!(writer, "Literal")?;
+        ctx.with_indentation(writer, "Literal")?;
         ctx.add_level();
         ctx.make_last();
         match self {
-            Str(text) => ctx.fmt_single_field(writer, "Text", &text.as_ref())?,
+            Str(s) => ctx.fmt_single_field(writer, "Str", s)?,
             Int(i) => ctx.fmt_single_field(writer, "Int", i)?,
-            // Float(f) => ctx.fmt_single_field(writer, "Float", f)?,
-            // Char(c) => ctx.fmt_single_field(writer, "Char", c)?,
-            // Bool(b) => ctx.fmt_single_field(writer, "Bool", b)?,
-            // Nil => {
-            //     ctx.write_identation(writer)?;
-            //     writeln!(writer, "Nil")?;
-            // }
+            Float(f) => ctx.fmt_single_field(writer, "Float", f)?,
+            Char(c) => ctx.fmt_single_field(writer, "Char", c)?,
+            Bool(b) => ctx.fmt_single_field(writer, "Bool", b)?,
+            Nullptr => ctx.with_indentation(writer, "Nullptr")?,
+            Invalid => ctx.with_indentation(writer, "Invalid")?,
         }
         ctx.pop_level();
         Ok(())
@@ -356,61 +707,6 @@ impl TreeDisplay for Block<'_> {
     }
 }
 
-// impl TreeDisplay for UnaryOperator {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         use UnaryOperator::*;
-//         let text = match self {
-//             Not => "!",
-//             Neg => "-",
-//             // BitNot => "~",
-//             // PreInc => "++x",
-//             // PreDec => "--x",
-//             // PostInc => "x++",
-//             // PostDec => "x--",
-//             // Deref => "*x",
-//             // Addr => "&x",
-//         };
-//         ctx.with_indentation(writer, text)
-//     }
-// }
-//
-// impl TreeDisplay for BinaryOperator {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         ctx.with_indentation(writer, self)
-//     }
-// }
-//
-// impl TreeDisplay for LogicalOperator {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         ctx.with_indentation(writer, self)
-//     }
-// }
-//
-// impl TreeDisplay for Assignment<'_> {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         ctx.with_indentation(writer, "Assignment")?;
-//         ctx.add_level();
-//         ctx.fmt_single_field(writer, "Target", &self.target)?;
-//         ctx.make_last();
-//         ctx.fmt_single_field(writer, "Value", &self.val)?;
-//         ctx.pop_level();
-//         Ok(())
-//     }
-// }
-//
-// impl TreeDisplay for Call<'_> {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         let Self { callee, arguments } = self;
-//         ctx.with_indentation(writer, "Call")?;
-//         ctx.add_level();
-//         ctx.fmt_single_field(writer, "Callee", callee)?;
-//         ctx.make_last();
-//         ctx.fmt_single_field(writer, "Arguments", &&**arguments)?;
-//         ctx.pop_level();
-//         Ok(())
-//     }
-// }
-
 impl TreeDisplay for Program<'_> {
     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
         ctx.fmt_single_field_flat(writer, "Program", &self.declarations.as_slice())
@@ -436,122 +732,65 @@ impl TreeDisplay for Decl<'_> {
             //     ctx.pop_level();
             //     Ok(())
             // }
-            Fun(Function { name, body }) => {
-                ctx.with_indentation(writer, "Function Declaration")?;
-                ctx.add_level();
-                ctx.fmt_single_field(writer, "Name", name)?;
-                // ctx.fmt_single_field(writer, "Parameters", &parameters.as_slice())?;
+            Fun(function) => function.fmt_tree(ctx, writer),
+            Var(decl) => decl.fmt_tree(ctx, writer),
+            Type(decl) => decl.fmt_tree(ctx, writer),
+        }
+    }
+}
+
+impl TreeDisplay for Function<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        let Function {
+            name,
+            storage,
+            ret,
+            params,
+            variadic,
+            body,
+        } = self;
+        ctx.with_indentation(writer, "Function Declaration")?;
+        ctx.add_level();
+        ctx.fmt_single_field(writer, "Name", name)?;
+        if let Some(storage) = storage {
+            ctx.with_indentation(writer, format_args!("Storage: {storage}"))?;
+        }
+        if params.is_none() && !variadic && body.is_none() {
+            ctx.make_last();
+        }
+        ctx.fmt_single_field(writer, "Return", ret)?;
+        if let Some(params) = params {
+            if !variadic && body.is_none() {
+                ctx.make_last();
+            }
+            ctx.fmt_single_field_flat(writer, "Parameters", &params.as_slice())?;
+        }
+        if *variadic {
+            if body.is_none() {
                 ctx.make_last();
-                ctx.fmt_single_field(writer, "Body", body)?;
-                ctx.pop_level();
-                Ok(())
             }
+            ctx.with_indentation(writer, "Variadic")?;
         }
+        ctx.make_last();
+        ctx.fmt_optional_field(writer, "Body", body.as_ref())?;
+        ctx.pop_level();
+        Ok(())
     }
 }
-// impl TreeDisplay for Ternary<'_> {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         ctx.struct_header(writer, "Ternary")?;
-//         ctx.add_level();
-//         ctx.fmt_single_field(writer, "Cond", &self.cond)?;
-//         ctx.fmt_single_field(writer, "Then", &self.then_val)?;
-//         ctx.make_last();
-//         ctx.fmt_single_field(writer, "Else", &self.else_val)?;
-//         ctx.pop_level();
-//         Ok(())
-//     }
-// }
-
-// impl TreeDisplay for Call<'_> {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         ctx.struct_header(writer, "Call")?;
-//         ctx.add_level();
-//         ctx.fmt_single_field(writer, "Callee", &*self.callee)?;
-//         ctx.make_last();
-//         ctx.fmt_single_field_flat(writer, "Args", &&self.args[..])?;
-//         ctx.pop_level();
-//         Ok(())
-//     }
-// }
 
-// impl TreeDisplay for Assignment<'_> {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         ctx.struct_header(writer, "Assignment")?;
-//         ctx.add_level();
-//         ctx.fmt_single_field(writer, "Target", &self.target)?;
-//         ctx.make_last();
-//         ctx.fmt_single_field(writer, "Value", &self.val)?;
-//         ctx.pop_level();
-//         Ok(())
-//     }
-// }
-// impl TreeDisplay for Function<'_> {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         ctx.pop_level();
-//         ctx.add_level();
-//         let crate::Function { body, params, ret } = self;
-//         ctx.struct_header(writer, "Params")?;
-//         ctx.add_level();
-//         for (ty, name) in params.iter().take(params.len().saturating_sub(1)) {
-//             ctx.fmt_single_field(writer, name, ty)?;
-//         }
-//         ctx.make_last();
-//         if let Some((ty, name)) = params.last() {
-//             ctx.fmt_single_field(writer, name, ty)?;
-//         }
-//         ctx.pop_level();
-//         ctx.fmt_single_field(writer, "Return", ret)?;
-//         ctx.make_last();
-//         ctx.fmt_single_field(writer, "Body", body)?;
-//         Ok(())
-//     }
-// }
-
-// impl TreeDisplay for Type<'_> {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         use Type::*;
-//         let name = match self {
-//             Void => "void",
-//             Bool => "bool",
-//             Char => "char",
-//             Int => "int",
-//             UInt => "uint",
-//             Float => "float",
-//             Double => "double",
-//             Str => "str",
-//             Named(_) => "Named",
-//             Pointer { .. } => "Pointer",
-//             Array(_, _) => "Array",
-//             Function(_) => "Function",
-//         };
-//         ctx.write_identation(writer)?;
-//         writeln!(writer, "Type: {name}")?;
-//         ctx.add_level();
-//         match self {
-//             Named(..) | Pointer { .. } | Array(..) | Function(..) => {
-//                 ctx.make_last();
-//             }
-//             _ => (),
-//         };
-//         match self {
-//             Named(name) => {
-//                 ctx.write_identation(writer)?;
-//                 writer.write_str(name)?;
-//             }
-//             Pointer { pointee } => pointee.fmt_tree(ctx, writer)?,
-//             Array(ty, len) => {
-//                 ctx.fmt_single_field(writer, "Length", len)?;
-//                 ctx.fmt_single_field(writer, "Type", &**ty)?;
-//             }
-//             Function(func) => {
-//                 func.fmt_tree(ctx, writer)?;
-//             }
-//             _ => (),
-//         }
-//         ctx.pop_level();
-//         Ok(())
-//     }
-// }
+impl TreeDisplay for Param<'_> {
+    fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
+        ctx.with_indentation(writer, "Parameter")?;
+        ctx.add_level();
+        if let Some(name) = &self.name {
+            ctx.fmt_single_field(writer, "Name", name)?;
+        }
+        ctx.make_last();
+        ctx.fmt_single_field(writer, "Type", &self.ty)?;
+        ctx.pop_level();
+        Ok(())
+    }
+}
 
 //
 // impl TreeDisplay for NamespaceAccess<'_> {
@@ -566,53 +805,6 @@ impl TreeDisplay for Decl<'_> {
 //     }
 // }
 
-// impl TreeDisplay for SizeOf<'_> {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         match self {
-//             SizeOf::Val(v) => ctx.fmt_single_field(writer, "SizeOf value", v),
-//             SizeOf::Type(ty) => ctx.fmt_single_field(writer, "SizeOf type", ty),
-//         }
-//     }
-// }
-
-// impl TreeDisplay for Intrinsic<'_> {
-//     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
-//         use Intrinsic::*;
-//         match self {
-//             Addr { of } => ctx.fmt_single_field(writer, "Addrof", of)?,
-//             Index { target, index } => {
-//                 ctx.struct_header(writer, "Indexed")?;
-//                 ctx.add_level();
-//                 ctx.fmt_single_field(writer, "Target", target)?;
-//                 ctx.make_last();
-//                 ctx.fmt_single_field(writer, "Idx", index)?;
-//                 ctx.pop_level();
-//             }
-//             Deref { addr } => ctx.fmt_single_field(writer, "Deref", addr)?,
-//             Alloc { size } => ctx.fmt_single_field(writer, "Alloc", size)?,
-//             Free { ptr } => ctx.fmt_single_field(writer, "Free", ptr)?,
-//             Memcpy { from, to, size } => {
-//                 ctx.struct_header(writer, "Memcpy")?;
-//                 ctx.add_level();
-//                 ctx.fmt_single_field(writer, "From", from)?;
-//                 ctx.fmt_single_field(writer, "To", to)?;
-//                 ctx.make_last();
-//                 ctx.fmt_single_field(writer, "Size", size)?;
-//                 ctx.pop_level();
-//             }
-//             Cast { ty, val } => {
-//                 ctx.struct_header(writer, "Cast")?;
-//                 ctx.add_level();
-//                 ctx.fmt_single_field(writer, "To", ty)?;
-//                 ctx.make_last();
-//                 ctx.fmt_single_field(writer, "Value", val)?;
-//                 ctx.pop_level();
-//             }
-//             SizeOf(s) => s.fmt_tree(ctx, writer)?,
-//         }
-//         Ok(())
-//     }
-// }
 // impl TreeDisplay for Elif<'_> {
 //     fn fmt_tree(&self, ctx: &mut TreeCtx, writer: &mut impl Write) -> fmt::Result {
 //         ctx.struct_header(writer, "Elif")?;
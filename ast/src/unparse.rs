@@ -0,0 +1,827 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! Printing the AST back as C source, which parses into the same tree.
+
+use std::fmt::Write;
+
+use crate::*;
+
+/// How tightly an expression binds, mirroring the binding powers of the parser. An operand is
+/// parenthesized only if it binds more loosely than its position requires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Comma,
+    Assign,
+    Ternary,
+    LogicalOr,
+    LogicalAnd,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Equality,
+    Relational,
+    Shift,
+    Sum,
+    Product,
+    /// Prefix operators, casts and sizeof
+    Unary,
+    /// Postfix operators, calls, indexing and member access
+    Postfix,
+    /// Literals and identifiers
+    Primary,
+}
+
+impl Precedence {
+    const fn of_binary(op: BinaryOperator) -> Self {
+        use BinaryOperator::*;
+        match op {
+            Mul | Div | Rem => Self::Product,
+            Add | Sub => Self::Sum,
+            Shl | Shr => Self::Shift,
+            Lt | Le | Gt | Ge => Self::Relational,
+            Eq | Ne => Self::Equality,
+            BitAnd => Self::BitAnd,
+            BitXor => Self::BitXor,
+            BitOr => Self::BitOr,
+        }
+    }
+    fn of(expr: &Expr<'_>) -> Self {
+        match expr {
+            Expr::Lit(_) | Expr::Ident(_) => Self::Primary,
+            Expr::Call(_) | Expr::Index(_) | Expr::MemberAccess(_) | Expr::CompoundLiteral(_) => {
+                Self::Postfix
+            }
+            Expr::Unary(unary) => match unary.op {
+                UnaryOperator::PostInc | UnaryOperator::PostDec => Self::Postfix,
+                _ => Self::Unary,
+            },
+            Expr::Cast(_) | Expr::SizeOf(_) | Expr::AlignOf(_) => Self::Unary,
+            Expr::Binary(binary) => Self::of_binary(binary.op),
+            Expr::Logical(logical) => match logical.op {
+                LogicalOperator::And => Self::LogicalAnd,
+                LogicalOperator::Or => Self::LogicalOr,
+            },
+            Expr::Ternary(_) => Self::Ternary,
+            Expr::Assignment(_) => Self::Assign,
+            Expr::Comma(_) => Self::Comma,
+        }
+    }
+    /// The precedence binding one step tighter, required of the right operand of a
+    /// left-associative operator.
+    const fn tighter(self) -> Self {
+        match self {
+            Self::Comma => Self::Assign,
+            Self::Assign => Self::Ternary,
+            Self::Ternary => Self::LogicalOr,
+            Self::LogicalOr => Self::LogicalAnd,
+            Self::LogicalAnd => Self::BitOr,
+            Self::BitOr => Self::BitXor,
+            Self::BitXor => Self::BitAnd,
+            Self::BitAnd => Self::Equality,
+            Self::Equality => Self::Relational,
+            Self::Relational => Self::Shift,
+            Self::Shift => Self::Sum,
+            Self::Sum => Self::Product,
+            Self::Product => Self::Unary,
+            Self::Unary => Self::Postfix,
+            Self::Postfix | Self::Primary => Self::Primary,
+        }
+    }
+}
+
+/// The declaration specifiers a declarator is split from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Base<'a, 's> {
+    /// A scalar or tagged type, possibly qualified
+    Type(&'a Type),
+    Typedef(Qualifiers, &'s str),
+}
+
+/// A single declarator of a declaration, with the specifiers it would share with others.
+struct Declarator<'a, 's> {
+    storage: Option<StorageClass>,
+    align: Option<u64>,
+    /// Empty for an unnamed member or parameter
+    name: &'s str,
+    ty: &'a Type,
+    init: Option<&'a Initializer<'s>>,
+}
+
+struct Unparser<'a, 's> {
+    out: String,
+    indent: usize,
+    /// The anonymous tags declared before the item being printed. As they can't be referred
+    /// to by name, each is defined where the item first uses it.
+    anonymous: Vec<&'a TypeDecl<'s>>,
+    /// The typedef names of types involving an anonymous tag, which are the only way to refer
+    /// to them once it is defined
+    typedefs: Vec<(&'a Type, &'s str)>,
+}
+
+impl<'a, 's> Unparser<'a, 's> {
+    const fn new() -> Self {
+        Self {
+            out: String::new(),
+            indent: 0,
+            anonymous: Vec::new(),
+            typedefs: Vec::new(),
+        }
+    }
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.indent {
+            self.out.push_str("    ");
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+    /// Writes a label, one level out from the statement it labels so that it stands out.
+    fn label(&mut self, text: &str) {
+        let indent = self.indent;
+        self.indent = indent.saturating_sub(1);
+        self.line(text);
+        self.indent = indent;
+    }
+    /// Writes an item, preceded by the anonymous tags declared before it that it didn't use.
+    fn item(&mut self, write: impl FnOnce(&mut Self)) {
+        let before = std::mem::take(&mut self.out);
+        write(self);
+        let item = std::mem::replace(&mut self.out, before);
+        self.flush_anonymous();
+        self.out.push_str(&item);
+    }
+    /// Defines the anonymous tags that haven't been used, like `enum { A, B };`.
+    fn flush_anonymous(&mut self) {
+        for decl in std::mem::take(&mut self.anonymous) {
+            let definition = self.tag_definition(decl);
+            self.line(&format!("{definition};"));
+        }
+    }
+
+    fn program(&mut self, program: &'a Program<'s>) {
+        let mut decls = program
+            .declarations
+            .iter()
+            .map(|decl| &decl.inner)
+            .peekable();
+        // Function definitions are set apart by blank lines
+        let mut after_definition = false;
+        while let Some(decl) = decls.next() {
+            let is_definition = matches!(decl, Decl::Fun(f) if f.body.is_some());
+            if (is_definition || after_definition) && !self.out.is_empty() {
+                self.out.push('\n');
+            }
+            after_definition = is_definition;
+            match decl {
+                Decl::Type(decl) => self.type_decl(decl),
+                Decl::Fun(function) => self.item(|this| this.function(function)),
+                Decl::Var(var) => {
+                    // The tree doesn't record which variables were declared together, so
+                    // consecutive ones with the same specifiers are joined again
+                    let mut declarators = vec![Self::var_declarator(var)];
+                    while let Some(Decl::Var(var)) = decls.peek() {
+                        declarators.push(Self::var_declarator(var));
+                        decls.next();
+                    }
+                    self.item(|this| {
+                        for decl in this.declarations(&declarators) {
+                            this.line(&format!("{decl};"));
+                        }
+                    });
+                }
+            }
+        }
+        self.flush_anonymous();
+    }
+    fn function(&mut self, function: &'a Function<'s>) {
+        let params = function.params.as_ref().map(|params| {
+            let params = params
+                .iter()
+                .map(|param| (&param.inner.ty, param.inner.name.unwrap_or("")));
+            params.collect()
+        });
+        let params = self.params(params, function.variadic);
+        let declarator = format!("{}({params})", function.name);
+        let (base, declarator) = self.split(&function.ret, declarator);
+        let mut head = String::new();
+        if let Some(storage) = function.storage {
+            write!(head, "{storage} ").unwrap();
+        }
+        head.push_str(&self.base(base));
+        write!(head, " {declarator}").unwrap();
+        match &function.body {
+            Some(body) => {
+                self.line(&format!("{head} {{"));
+                self.block_items(body);
+                self.line("}");
+            }
+            None => self.line(&format!("{head};")),
+        }
+    }
+    fn type_decl(&mut self, decl: &'a TypeDecl<'s>) {
+        match decl {
+            TypeDecl::Record {
+                tag: Tag { name: None, .. },
+                ..
+            }
+            | TypeDecl::Enum {
+                tag: Tag { name: None, .. },
+                ..
+            } => self.anonymous.push(decl),
+            TypeDecl::Typedef { name, ty } => {
+                let declarator = Declarator {
+                    storage: None,
+                    align: None,
+                    name,
+                    ty,
+                    init: None,
+                };
+                for decl in self.declarations(&[declarator]) {
+                    self.line(&format!("typedef {decl};"));
+                }
+                if has_anonymous_tag(ty) {
+                    self.typedefs.push((ty, *name));
+                }
+            }
+            // Tags used by the item after them are still declared before it
+            _ => {
+                let definition = self.tag_definition(decl);
+                self.line(&format!("{definition};"));
+            }
+        }
+    }
+    /// The specifier that defines the tag of a struct, union or enum.
+    fn tag_definition(&mut self, decl: &'a TypeDecl<'s>) -> String {
+        let (TypeDecl::Record { tag, .. } | TypeDecl::Enum { tag, .. }) = decl else {
+            unreachable!("typedef names don't define a tag")
+        };
+        let mut text = tag.kind.to_string();
+        if let Some(name) = &tag.name {
+            write!(text, " {name}").unwrap();
+        }
+        match decl {
+            TypeDecl::Record {
+                members: Some(members),
+                ..
+            } => {
+                let declarators: Vec<_> = members
+                    .iter()
+                    .map(|member| &member.inner)
+                    .map(|member| Declarator {
+                        storage: None,
+                        align: member.align,
+                        name: member.name.unwrap_or(""),
+                        ty: &member.ty,
+                        init: None,
+                    })
+                    .collect();
+                let indent = self.indent;
+                self.indent += 1;
+                let members = self.declarations(&declarators);
+                text.push_str(" {\n");
+                for member in members {
+                    writeln!(text, "{}{member};", "    ".repeat(indent + 1)).unwrap();
+                }
+                write!(text, "{}}}", "    ".repeat(indent)).unwrap();
+                self.indent = indent;
+            }
+            TypeDecl::Enum { enumerators, .. } => {
+                let mut sep = " { ";
+                for Spanned {
+                    inner: enumerator, ..
+                } in enumerators
+                {
+                    write!(text, "{sep}{}", enumerator.name).unwrap();
+                    if let Some(init) = &enumerator.init {
+                        write!(text, " = {}", self.expr(init, Precedence::Assign)).unwrap();
+                    }
+                    sep = ", ";
+                }
+                text.push_str(" }");
+            }
+            _ => (),
+        }
+        text
+    }
+
+    fn var_declarator(var: &'a VarDecl<'s>) -> Declarator<'a, 's> {
+        Declarator {
+            storage: var.storage,
+            align: var.align,
+            name: var.name,
+            ty: &var.ty,
+            init: var.init.as_ref(),
+        }
+    }
+    /// Prints declarators as declarations, without the terminating `;`. Consecutive ones with
+    /// the same specifiers share a declaration, so that an anonymous tag is only defined once.
+    fn declarations(&mut self, declarators: &[Declarator<'a, 's>]) -> Vec<String> {
+        let mut split = Vec::new();
+        for declarator in declarators {
+            let (base, mut text) = self.split(declarator.ty, declarator.name.to_string());
+            if let Some(init) = declarator.init {
+                write!(text, " = {}", self.initializer(init)).unwrap();
+            }
+            split.push((declarator.storage, declarator.align, base, text));
+        }
+        let mut decls = Vec::new();
+        for group in split.chunk_by(|a, b| (a.0, a.1, a.2) == (b.0, b.1, b.2)) {
+            let (storage, align, base, _) = group[0];
+            let mut decl = String::new();
+            if let Some(storage) = storage {
+                write!(decl, "{storage} ").unwrap();
+            }
+            if let Some(align) = align {
+                write!(decl, "_Alignas({align}) ").unwrap();
+            }
+            decl.push_str(&self.base(base));
+            let mut sep = " ";
+            for (.., text) in group.iter().filter(|(.., text)| !text.is_empty()) {
+                write!(decl, "{sep}{text}").unwrap();
+                sep = ", ";
+            }
+            decls.push(decl);
+        }
+        decls
+    }
+    /// A type name, as used by casts and `sizeof`.
+    fn type_name(&mut self, ty: &'a Type) -> String {
+        let (base, declarator) = self.split(ty, String::new());
+        let mut text = self.base(base);
+        if !declarator.is_empty() {
+            write!(text, " {declarator}").unwrap();
+        }
+        text
+    }
+    fn params(&mut self, params: Option<Vec<(&'a Type, &'s str)>>, variadic: bool) -> String {
+        let Some(params) = params else {
+            return String::new();
+        };
+        if params.is_empty() && !variadic {
+            return "void".to_string();
+        }
+        let mut text = Vec::new();
+        for (ty, name) in params {
+            let (base, declarator) = self.split(ty, name.to_string());
+            let mut param = self.base(base);
+            if !declarator.is_empty() {
+                write!(param, " {declarator}").unwrap();
+            }
+            text.push(param);
+        }
+        if variadic {
+            text.push("...".to_string());
+        }
+        text.join(", ")
+    }
+    /// Splits `ty` into the specifiers of a declaration and a declarator around `declarator`,
+    /// like [Type::fmt_declaration].
+    fn split(&mut self, ty: &'a Type, declarator: String) -> (Base<'a, 's>, String) {
+        if let Some(name) = self.typedef_name(ty) {
+            return (Base::Typedef(Qualifiers::default(), name), declarator);
+        }
+        let (qualifiers, unqualified) = match ty {
+            Type::Qualified(qualifiers, ty) => (*qualifiers, &**ty),
+            ty => (Qualifiers::default(), ty),
+        };
+        if let Some(name) = self.typedef_name(unqualified) {
+            return (Base::Typedef(qualifiers, name), declarator);
+        }
+        match unqualified {
+            Type::Pointer(pointee) => {
+                // Qualifiers of the pointer itself follow the `*`
+                let mut inner = String::from("*");
+                if !qualifiers.is_empty() {
+                    write!(inner, "{qualifiers}").unwrap();
+                    if !declarator.is_empty() {
+                        inner.push(' ');
+                    }
+                }
+                inner.push_str(&declarator);
+                if matches!(pointee.unqualified(), Type::Array(..) | Type::Function(_)) {
+                    inner = format!("({inner})");
+                }
+                self.split(pointee, inner)
+            }
+            Type::Array(elem, len) => {
+                let len = len.map(|len| len.to_string()).unwrap_or_default();
+                self.split(elem, format!("{declarator}[{len}]"))
+            }
+            Type::Function(function) => {
+                let params = function
+                    .params
+                    .as_ref()
+                    .map(|params| params.iter().map(|ty| (ty, "")).collect());
+                let params = self.params(params, function.variadic);
+                self.split(&function.ret, format!("{declarator}({params})"))
+            }
+            Type::Scalar(_) | Type::Tagged(_) => (Base::Type(ty), declarator),
+            Type::Qualified(..) => unreachable!("qualifiers are never nested"),
+        }
+    }
+    fn typedef_name(&self, ty: &Type) -> Option<&'s str> {
+        let mut typedefs = self.typedefs.iter().rev();
+        typedefs.find(|(def, _)| *def == ty).map(|(_, name)| *name)
+    }
+    /// Prints declaration specifiers, defining the tag they use if it is anonymous.
+    fn base(&mut self, base: Base<'a, 's>) -> String {
+        let (qualifiers, text) = match base {
+            Base::Typedef(qualifiers, name) => (qualifiers, name.to_string()),
+            Base::Type(ty) => {
+                let (qualifiers, ty) = match ty {
+                    Type::Qualified(qualifiers, ty) => (*qualifiers, &**ty),
+                    ty => (Qualifiers::default(), ty),
+                };
+                let text = match ty {
+                    Type::Scalar(scalar) => scalar.to_string(),
+                    Type::Tagged(tag) => {
+                        let pending = self.anonymous.iter().position(|decl| {
+                            matches!(decl, TypeDecl::Record { tag: t, .. }
+                                | TypeDecl::Enum { tag: t, .. } if t == tag)
+                        });
+                        match pending {
+                            Some(index) => {
+                                let decl = self.anonymous.remove(index);
+                                self.tag_definition(decl)
+                            }
+                            None => tag.to_string(),
+                        }
+                    }
+                    _ => unreachable!("declarators are split from their specifiers"),
+                };
+                (qualifiers, text)
+            }
+        };
+        if qualifiers.is_empty() {
+            return text;
+        }
+        format!("{qualifiers} {text}")
+    }
+
+    fn block_items(&mut self, block: &'a Block<'s>) {
+        // Anonymous tags and typedef names declared in the block go out of scope with it
+        let anonymous = std::mem::take(&mut self.anonymous);
+        let typedefs = self.typedefs.len();
+        self.indent += 1;
+        for item in &block.0 {
+            match item {
+                BlockItem::Type(decl) => self.type_decl(&decl.inner),
+                BlockItem::Decl(decls) => self.item(|this| {
+                    let declarators: Vec<_> = decls
+                        .iter()
+                        .map(|decl| Self::var_declarator(&decl.inner))
+                        .collect();
+                    for decl in this.declarations(&declarators) {
+                        this.line(&format!("{decl};"));
+                    }
+                }),
+                BlockItem::Stmt(stmt) => self.item(|this| this.stmt(stmt)),
+            }
+        }
+        self.flush_anonymous();
+        self.indent -= 1;
+        self.typedefs.truncate(typedefs);
+        self.anonymous = anonymous;
+    }
+    /// Writes `header` followed by the statement it applies to, returning whether it was a
+    /// block that the caller has to close.
+    fn open(&mut self, header: &str, body: &'a SStmt<'s>) -> bool {
+        if let Stmt::Block(block) = &body.inner {
+            self.line(&format!("{header} {{"));
+            self.block_items(block);
+            return true;
+        }
+        self.line(header);
+        self.indent += 1;
+        self.stmt(body);
+        self.indent -= 1;
+        false
+    }
+    /// Writes `header` followed by the statement it applies to.
+    fn nested(&mut self, header: &str, body: &'a SStmt<'s>) {
+        if self.open(header, body) {
+            self.line("}");
+        }
+    }
+    /// Writes an if statement, after `prefix` closing the previous branch of an `else if`
+    /// chain.
+    fn if_stmt(
+        &mut self,
+        prefix: &str,
+        cond: &'a SExpr<'s>,
+        then_body: &'a SStmt<'s>,
+        else_body: Option<&'a SStmt<'s>>,
+    ) {
+        let header = format!("{prefix}if ({})", self.expr(cond, Precedence::Comma));
+        let braced = if else_body.is_some() && ends_with_open_if(&then_body.inner) {
+            // Braces keep the `else` from binding to the inner `if`
+            self.line(&format!("{header} {{"));
+            self.indent += 1;
+            self.stmt(then_body);
+            self.indent -= 1;
+            true
+        } else {
+            self.open(&header, then_body)
+        };
+        let Some(else_body) = else_body else {
+            if braced {
+                self.line("}");
+            }
+            return;
+        };
+        let prefix = if braced { "} else " } else { "else " };
+        if let Stmt::If {
+            cond,
+            then_body,
+            else_body,
+        } = &else_body.inner
+        {
+            return self.if_stmt(prefix, cond, then_body, else_body.as_deref());
+        }
+        self.nested(prefix.trim_end(), else_body);
+    }
+    fn stmt(&mut self, stmt: &'a SStmt<'s>) {
+        match &stmt.inner {
+            Stmt::Expr(expr) => {
+                let expr = self.expr(expr, Precedence::Comma);
+                self.line(&format!("{expr};"));
+            }
+            Stmt::Return(None) => self.line("return;"),
+            Stmt::Return(Some(val)) => {
+                let val = self.expr(val, Precedence::Comma);
+                self.line(&format!("return {val};"));
+            }
+            Stmt::Block(block) => {
+                self.line("{");
+                self.block_items(block);
+                self.line("}");
+            }
+            Stmt::If {
+                cond,
+                then_body,
+                else_body,
+            } => self.if_stmt("", cond, then_body, else_body.as_deref()),
+            Stmt::While { cond, body } => {
+                let header = format!("while ({})", self.expr(cond, Precedence::Comma));
+                self.nested(&header, body);
+            }
+            Stmt::DoWhile { body, cond } => {
+                let footer = format!("while ({});", self.expr(cond, Precedence::Comma));
+                if self.open("do", body) {
+                    self.line(&format!("}} {footer}"));
+                } else {
+                    self.line(&footer);
+                }
+            }
+            Stmt::For(f) => {
+                let mut header = String::from("for (");
+                match &f.init {
+                    Some(ForInit::Decl(decls)) => {
+                        let declarators: Vec<_> = decls
+                            .iter()
+                            .map(|decl| Self::var_declarator(&decl.inner))
+                            .collect();
+                        header.push_str(&self.declarations(&declarators).join("; "));
+                    }
+                    Some(ForInit::Expr(init)) => {
+                        header.push_str(&self.expr(init, Precedence::Comma));
+                    }
+                    None => (),
+                }
+                header.push(';');
+                if let Some(cond) = &f.cond {
+                    write!(header, " {}", self.expr(cond, Precedence::Comma)).unwrap();
+                }
+                header.push(';');
+                if let Some(step) = &f.step {
+                    write!(header, " {}", self.expr(step, Precedence::Comma)).unwrap();
+                }
+                header.push(')');
+                self.nested(&header, &f.body);
+            }
+            Stmt::Break => self.line("break;"),
+            Stmt::Continue => self.line("continue;"),
+            Stmt::Goto(label) => self.line(&format!("goto {label};")),
+            Stmt::Labeled { label, body } => {
+                self.label(&format!("{label}:"));
+                self.stmt(body);
+            }
+            Stmt::Switch { value, body } => {
+                let header = format!("switch ({})", self.expr(value, Precedence::Comma));
+                self.nested(&header, body);
+            }
+            Stmt::Case { value, body } => {
+                // The parser reads a case value as a conditional expression
+                let value = self.expr(value, Precedence::Ternary);
+                self.label(&format!("case {value}:"));
+                self.stmt(body);
+            }
+            Stmt::Default(body) => {
+                self.label("default:");
+                self.stmt(body);
+            }
+            Stmt::Null => self.line(";"),
+        }
+    }
+
+    fn initializer(&mut self, init: &'a Initializer<'s>) -> String {
+        let list = match init {
+            Initializer::Expr(expr) => return self.expr(expr, Precedence::Assign),
+            Initializer::List(list) => &list.inner,
+        };
+        if list.is_empty() {
+            return "{}".to_string();
+        }
+        let mut items = Vec::new();
+        for item in list {
+            let mut text = String::new();
+            for designator in &item.designators {
+                match &designator.inner {
+                    Designator::Member(name) => write!(text, ".{name}").unwrap(),
+                    Designator::Index(index) => {
+                        let index = self.expr(index, Precedence::Ternary);
+                        write!(text, "[{index}]").unwrap();
+                    }
+                }
+            }
+            if !text.is_empty() {
+                text.push_str(" = ");
+            }
+            text.push_str(&self.initializer(&item.init));
+            items.push(text);
+        }
+        format!("{{ {} }}", items.join(", "))
+    }
+    /// Prints an expression, parenthesized if it binds more loosely than `min`.
+    fn expr(&mut self, expr: &'a SExpr<'s>, min: Precedence) -> String {
+        let text = self.unparenthesized(&expr.inner);
+        if Precedence::of(&expr.inner) < min {
+            return format!("({text})");
+        }
+        text
+    }
+    fn unparenthesized(&mut self, expr: &'a Expr<'s>) -> String {
+        use Precedence as P;
+        match expr {
+            Expr::Lit(literal) => literal.to_string(),
+            Expr::Ident(name) => (*name).to_string(),
+            Expr::Binary(binary) => {
+                let precedence = P::of_binary(binary.op);
+                let lhs = self.expr(&binary.lhs, precedence);
+                let rhs = self.expr(&binary.rhs, precedence.tighter());
+                format!("{lhs} {} {rhs}", binary.op)
+            }
+            Expr::Logical(logical) => {
+                let precedence = P::of(expr);
+                let lhs = self.expr(&logical.lhs, precedence);
+                let rhs = self.expr(&logical.rhs, precedence.tighter());
+                format!("{lhs} {} {rhs}", logical.op)
+            }
+            Expr::Unary(unary) => {
+                let op = match unary.op {
+                    UnaryOperator::PostInc => return self.expr(&unary.val, P::Postfix) + "++",
+                    UnaryOperator::PostDec => return self.expr(&unary.val, P::Postfix) + "--",
+                    op => op.to_string(),
+                };
+                let val = self.expr(&unary.val, P::Unary);
+                // Keeps `- -x` from being lexed as `--x`, and `& &x` as `&&x`
+                let last = op.chars().last();
+                if last.is_some_and(|c| "+-&".contains(c)) && val.starts_with(last.unwrap()) {
+                    return format!("{op} {val}");
+                }
+                op + &val
+            }
+            Expr::Assignment(assignment) => {
+                let target = self.expr(&assignment.target, P::Unary);
+                let val = self.expr(&assignment.val, P::Assign);
+                match assignment.op {
+                    Some(op) => format!("{target} {op}= {val}"),
+                    None => format!("{target} = {val}"),
+                }
+            }
+            Expr::Ternary(ternary) => {
+                let cond = self.expr(&ternary.cond, P::LogicalOr);
+                let then_val = self.expr(&ternary.then_val, P::Comma);
+                let else_val = self.expr(&ternary.else_val, P::Ternary);
+                format!("{cond} ? {then_val} : {else_val}")
+            }
+            Expr::Comma(comma) => {
+                let lhs = self.expr(&comma.lhs, P::Comma);
+                let rhs = self.expr(&comma.rhs, P::Assign);
+                format!("{lhs}, {rhs}")
+            }
+            Expr::Call(call) => {
+                let mut text = self.expr(&call.callee, P::Postfix);
+                let args: Vec<_> = call
+                    .args
+                    .iter()
+                    .map(|arg| self.expr(arg, P::Assign))
+                    .collect();
+                write!(text, "({})", args.join(", ")).unwrap();
+                text
+            }
+            Expr::Index(index) => {
+                let target = self.expr(&index.target, P::Postfix);
+                let index = self.expr(&index.index, P::Comma);
+                format!("{target}[{index}]")
+            }
+            Expr::MemberAccess(access) => {
+                let mut object = self.expr(&access.object, P::Postfix);
+                let op = if access.arrow { "->" } else { "." };
+                // `1.x` would be lexed as a floating constant
+                let numeric = matches!(
+                    &access.object.inner,
+                    Expr::Lit(LiteralExpression::Int(_) | LiteralExpression::Float(_))
+                );
+                if numeric && !access.arrow {
+                    object = format!("({object})");
+                }
+                format!("{object}{op}{}", access.member)
+            }
+            Expr::Cast(cast) => {
+                let ty = self.type_name(&cast.ty);
+                let val = self.expr(&cast.val, P::Unary);
+                format!("({ty}){val}")
+            }
+            Expr::CompoundLiteral(literal) => {
+                let ty = self.type_name(&literal.ty);
+                let init = self.initializer(&literal.init);
+                format!("({ty}){init}")
+            }
+            Expr::SizeOf(size_of) => match &**size_of {
+                SizeOf::Type(ty) => format!("sizeof({})", self.type_name(ty)),
+                // `sizeof (int)x` would be read as the size of `int`
+                SizeOf::Val(val) if matches!(val.inner, Expr::Cast(_)) => {
+                    format!("sizeof ({})", self.unparenthesized(&val.inner))
+                }
+                SizeOf::Val(val) => format!("sizeof {}", self.expr(val, P::Unary)),
+            },
+            Expr::AlignOf(ty) => format!("_Alignof({})", self.type_name(ty)),
+        }
+    }
+}
+
+/// Whether `ty` involves a tag that can't be referred to by name.
+fn has_anonymous_tag(ty: &Type) -> bool {
+    match ty {
+        Type::Scalar(_) => false,
+        Type::Pointer(ty) | Type::Array(ty, _) | Type::Qualified(_, ty) => has_anonymous_tag(ty),
+        Type::Function(function) => {
+            has_anonymous_tag(&function.ret)
+                || function.params.iter().flatten().any(has_anonymous_tag)
+        }
+        Type::Tagged(tag) => tag.name.is_none(),
+    }
+}
+
+/// Whether a following `else` would bind to an `if` nested at the end of `stmt`.
+fn ends_with_open_if(stmt: &Stmt<'_>) -> bool {
+    match stmt {
+        Stmt::If {
+            else_body: None, ..
+        } => true,
+        Stmt::If {
+            else_body: Some(body),
+            ..
+        }
+        | Stmt::While { body, .. }
+        | Stmt::Labeled { body, .. }
+        | Stmt::Switch { body, .. }
+        | Stmt::Case { body, .. }
+        | Stmt::Default(body) => ends_with_open_if(&body.inner),
+        Stmt::For(f) => ends_with_open_if(&f.body.inner),
+        _ => false,
+    }
+}
+
+impl Program<'_> {
+    /// Prints the program as C source, which parses back into the same tree. Parentheses are
+    /// only added where precedence requires them.
+    #[must_use]
+    pub fn to_c(&self) -> String {
+        let mut unparser = Unparser::new();
+        unparser.program(self);
+        unparser.out
+    }
+}
+
+impl Expr<'_> {
+    /// Prints the expression as C source, with only the parentheses precedence requires.
+    #[must_use]
+    pub fn to_c(&self) -> String {
+        Unparser::new().unparenthesized(self)
+    }
+}
@@ -0,0 +1,169 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! Typed views over the lossless syntax tree the parser builds along with the `ast`.
+//!
+//! Every `ast` node with a span was parsed along with a node of [Program::syntax] over the same
+//! tokens. A [View] pairs the two up, reading as the `ast` node while giving access to the text
+//! it was parsed from, trivia and all.
+
+use std::ops::Deref;
+
+use syntax::{SyntaxKind, SyntaxNode};
+
+use crate::{
+    Decl, Designator, Enumerator, Expr, Function, InitItem, Member, Param, Program, Spanned, Stmt,
+    TypeDecl, VarDecl,
+};
+
+/// An `ast` node that has a node of its own in the syntax tree.
+pub trait AstNode {
+    fn syntax_kind(&self) -> SyntaxKind;
+}
+
+/// An `ast` node along with its node in the syntax tree.
+#[derive(Debug, Clone)]
+pub struct View<'a, T> {
+    node: &'a T,
+    syntax: SyntaxNode,
+}
+
+impl<'a, T> View<'a, T> {
+    #[must_use]
+    pub const fn ast(&self) -> &'a T {
+        self.node
+    }
+    #[must_use]
+    pub const fn syntax(&self) -> &SyntaxNode {
+        &self.syntax
+    }
+    /// The view of `child`, a node inside this one, or [None] if it wasn't written out, like
+    /// the parameters a function gets from a typedef name.
+    #[must_use]
+    pub fn child<U: AstNode>(&self, child: &'a Spanned<U>) -> Option<View<'a, U>> {
+        let kind = child.inner.syntax_kind();
+        // Nodes that span the same tokens are nested, as `(x)` is around `x`
+        let syntax = self
+            .syntax
+            .covering_node(&child.span)
+            .ancestors()
+            .take_while(|node| node.span() == child.span)
+            .filter(|node| node.kind() == kind)
+            .last()?;
+        Some(View {
+            node: &child.inner,
+            syntax,
+        })
+    }
+}
+
+impl<T> Deref for View<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.node
+    }
+}
+
+impl<'s> Program<'s> {
+    /// Views the program along with its syntax tree.
+    #[must_use]
+    pub fn view(&self) -> View<'_, Self> {
+        View {
+            node: self,
+            syntax: self.syntax.clone(),
+        }
+    }
+}
+
+impl<'a, 's> View<'a, Program<'s>> {
+    pub fn declarations(&self) -> impl Iterator<Item = View<'a, Decl<'s>>> {
+        self.node
+            .declarations
+            .iter()
+            .filter_map(|decl| self.child(decl))
+    }
+}
+
+impl AstNode for Decl<'_> {
+    fn syntax_kind(&self) -> SyntaxKind {
+        match self {
+            Self::Fun(function) => function.syntax_kind(),
+            Self::Var(var) => var.syntax_kind(),
+            Self::Type(ty) => ty.syntax_kind(),
+        }
+    }
+}
+
+impl AstNode for Function<'_> {
+    fn syntax_kind(&self) -> SyntaxKind {
+        SyntaxKind::Function
+    }
+}
+
+impl AstNode for VarDecl<'_> {
+    fn syntax_kind(&self) -> SyntaxKind {
+        SyntaxKind::Variable
+    }
+}
+
+impl AstNode for TypeDecl<'_> {
+    fn syntax_kind(&self) -> SyntaxKind {
+        SyntaxKind::TypeDeclaration
+    }
+}
+
+impl AstNode for Param<'_> {
+    fn syntax_kind(&self) -> SyntaxKind {
+        SyntaxKind::Parameter
+    }
+}
+
+impl AstNode for Member<'_> {
+    fn syntax_kind(&self) -> SyntaxKind {
+        SyntaxKind::Member
+    }
+}
+
+impl AstNode for Enumerator<'_> {
+    fn syntax_kind(&self) -> SyntaxKind {
+        SyntaxKind::Enumerator
+    }
+}
+
+impl AstNode for Stmt<'_> {
+    fn syntax_kind(&self) -> SyntaxKind {
+        SyntaxKind::Statement
+    }
+}
+
+impl AstNode for Expr<'_> {
+    fn syntax_kind(&self) -> SyntaxKind {
+        SyntaxKind::Expression
+    }
+}
+
+/// A brace-enclosed initializer list.
+impl AstNode for Vec<InitItem<'_>> {
+    fn syntax_kind(&self) -> SyntaxKind {
+        SyntaxKind::InitializerList
+    }
+}
+
+impl AstNode for Designator<'_> {
+    fn syntax_kind(&self) -> SyntaxKind {
+        SyntaxKind::Designator
+    }
+}
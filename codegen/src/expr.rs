@@ -0,0 +1,1088 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use std::mem;
+
+use asm::{BinaryOp, Cond, Instruction, Operand, Register, ShiftOp, Size, UnaryOp};
+use ast::{
+    Assignment, BinaryExpr, BinaryOperator, Call, Expr, LiteralExpression, LogicalExpr,
+    LogicalOperator, SExpr, ScalarType, SizeOf, Ternary, Type, UnaryExpr, UnaryOperator,
+};
+use utils::VarInt;
+
+use crate::{ARG_REGISTERS, Binding, Codegen, FLOATING, layout::Class, stack_slot, types};
+
+const RAX: Operand = Operand::Reg(Register::AX);
+const RCX: Operand = Operand::Reg(Register::CX);
+const RDX: Operand = Operand::Reg(Register::DX);
+const RSP: Operand = Operand::Reg(Register::SP);
+const R11: Operand = Operand::Reg(Register::R11);
+
+/// Where an argument of a call is passed.
+#[derive(Debug, Clone, Copy)]
+enum ArgLocation {
+    /// In this many of the next argument registers
+    Registers(usize),
+    /// On the stack, at this offset from the first argument there
+    Stack(usize),
+}
+
+fn imm(value: u64) -> Operand {
+    Operand::Imm(VarInt::Pos(value))
+}
+
+fn signed_imm(value: i64) -> Operand {
+    Operand::Imm(if value < 0 {
+        VarInt::Neg(value)
+    } else {
+        VarInt::Pos(value.unsigned_abs())
+    })
+}
+
+// Every expression leaves its value in `%rax`, sign- or zero-extended to 64 bits according to
+// its type. Operations are then done on the full registers, and the result is truncated back
+// to the size of its type with [Codegen::extend].
+impl<'s> Codegen<'s> {
+    /// Finds the type of an expression without evaluating it, as for `sizeof`.
+    pub(crate) fn type_of(&mut self, expr: &SExpr<'s>) -> Type {
+        let (instructions, strings) = (mem::take(&mut self.instructions), self.strings.len());
+        let ty = self.codegen_expr(expr);
+        self.instructions = instructions;
+        self.strings.truncate(strings);
+        ty
+    }
+    /// Finds the type of the operand of `sizeof`, which doesn't decay arrays to pointers.
+    pub(crate) fn type_of_operand(&mut self, expr: &SExpr<'s>) -> Type {
+        let (instructions, strings) = (mem::take(&mut self.instructions), self.strings.len());
+        let ty = match &expr.inner {
+            Expr::Lit(lit) => lit.ty(),
+            Expr::Ident(name) if !matches!(self.lookup(name), Some(Binding::Constant(_))) => {
+                self.codegen_address(expr)
+            }
+            Expr::Index(_) | Expr::MemberAccess(_) | Expr::CompoundLiteral(_) => {
+                self.codegen_address(expr)
+            }
+            Expr::Unary(unary) if unary.op == UnaryOperator::Deref => self.codegen_address(expr),
+            _ => self.codegen_expr(expr),
+        };
+        self.instructions = instructions;
+        self.strings.truncate(strings);
+        ty
+    }
+    /// Truncates the value in `reg` to the size of `ty`, then extends it back to 64 bits.
+    fn extend(&mut self, reg: Register, ty: &Type) {
+        // Floating-point values have already been reported
+        if *ty == Type::Scalar(ScalarType::Bool) || types::is_floating(ty) {
+            return;
+        }
+        let from = Operand::Reg(reg);
+        let to = Operand::Reg(reg);
+        let from_size = Size::from_bytes(self.size_of(ty));
+        let instruction = match from_size {
+            Size::Quad => return,
+            // Writing a 32-bit register clears the upper half
+            Size::Long if !types::is_signed(ty) => Instruction::Mov {
+                size: Size::Long,
+                from,
+                to,
+            },
+            _ if types::is_signed(ty) => Instruction::Movsx {
+                from_size,
+                to_size: Size::Quad,
+                from,
+                to,
+            },
+            _ => Instruction::Movzx {
+                from_size,
+                to_size: Size::Quad,
+                from,
+                to,
+            },
+        };
+        self.emit(instruction);
+    }
+    /// Converts the value in `%rax` from one scalar type to another.
+    pub(crate) fn convert(&mut self, from: &Type, to: &Type) {
+        if from == to || *to == Type::Scalar(ScalarType::Void) {
+            return;
+        }
+        if types::is_floating(from) || types::is_floating(to) {
+            self.unsupported(FLOATING);
+            return;
+        }
+        if *to == Type::Scalar(ScalarType::Bool) {
+            self.compare_to_zero(Cond::Ne);
+            return;
+        }
+        self.extend(Register::AX, to);
+    }
+    /// Sets `%rax` to 1 if comparing it to zero satisfies `cond`, and to 0 otherwise.
+    fn compare_to_zero(&mut self, cond: Cond) {
+        self.emit(Instruction::Cmp {
+            size: Size::Quad,
+            rhs: imm(0),
+            lhs: RAX,
+        });
+        self.set_from_flags(cond);
+    }
+    fn set_from_flags(&mut self, cond: Cond) {
+        self.emit(Instruction::SetCC { cond, to: RAX });
+        self.emit(Instruction::Movzx {
+            from_size: Size::Byte,
+            to_size: Size::Quad,
+            from: RAX,
+            to: RAX,
+        });
+    }
+    /// Loads a value of type `ty` from the address in `%rax` into `%rax`.
+    fn load(&mut self, ty: &Type) {
+        if types::is_floating(ty) {
+            return;
+        }
+        let from = Operand::memory(Register::AX, 0);
+        let size = Size::from_bytes(self.size_of(ty));
+        let instruction = match size {
+            Size::Quad => Instruction::Mov {
+                size,
+                from,
+                to: RAX,
+            },
+            Size::Long if !types::is_signed(ty) => Instruction::Mov {
+                size,
+                from,
+                to: RAX,
+            },
+            _ if types::is_signed(ty) => Instruction::Movsx {
+                from_size: size,
+                to_size: Size::Quad,
+                from,
+                to: RAX,
+            },
+            _ => Instruction::Movzx {
+                from_size: size,
+                to_size: Size::Quad,
+                from,
+                to: RAX,
+            },
+        };
+        self.emit(instruction);
+    }
+    /// Stores the value in `%rax` as type `ty` to `dest`.
+    pub(crate) fn store(&mut self, ty: &Type, dest: Operand) {
+        if types::is_record(ty) {
+            self.copy_record(self.size_of(ty), dest);
+            return;
+        }
+        if types::is_floating(ty) {
+            return;
+        }
+        self.emit(Instruction::Mov {
+            size: Size::from_bytes(self.size_of(ty)),
+            from: RAX,
+            to: dest,
+        });
+    }
+    /// Copies `size` bytes of a struct or union from the address in `%rax` to `dest`, using
+    /// `%rcx` as a scratch register.
+    fn copy_record(&mut self, size: u64, dest: Operand) {
+        let Operand::Memory {
+            base,
+            index: None,
+            offset,
+        } = dest
+        else {
+            unreachable!("records are stored to a base register and offset");
+        };
+        let mut copied = 0;
+        while copied < size {
+            // The largest move that doesn't go past the end
+            let chunk = [8, 4, 2, 1]
+                .into_iter()
+                .find(|&chunk| copied + chunk <= size)
+                .unwrap();
+            let size = Size::from_bytes(chunk);
+            let at = i64::try_from(copied).unwrap();
+            self.emit(Instruction::Mov {
+                size,
+                from: Operand::memory(Register::AX, at),
+                to: RCX,
+            });
+            self.emit(Instruction::Mov {
+                size,
+                from: RCX,
+                to: Operand::memory(base, offset + at),
+            });
+            copied += chunk;
+        }
+    }
+    /// Returns the struct or union whose address is in `%rax` from the current function, as
+    /// its class requires.
+    pub(crate) fn return_record(&mut self, ty: &Type) {
+        let size = self.size_of(ty);
+        match self.classify(ty) {
+            Class::Memory => {
+                let buffer = self.return_buffer.expect("records in memory have a buffer");
+                self.mov(stack_slot(buffer), RDX);
+                self.copy_record(size, Operand::memory(Register::DX, 0));
+                self.mov(RDX, RAX);
+            }
+            // The record may not fill the registers, so it's copied to a temporary that's
+            // large enough to be loaded from whole
+            Class::Integer(words) => {
+                let align = self.align_of(ty).max(8);
+                let offset = self.allocate_bytes(8 * words as u64, align);
+                self.copy_record(size, stack_slot(offset));
+                for (word, reg) in (0..).zip([RAX, RDX].into_iter().take(words)) {
+                    self.mov(stack_slot(offset + 8 * word), reg);
+                }
+            }
+            Class::Floating => (),
+        }
+    }
+    /// Reserves `words` eightbytes on the stack, as if that many temporaries were pushed.
+    fn reserve(&mut self, words: usize) {
+        if words == 0 {
+            return;
+        }
+        self.emit(Instruction::Binary {
+            op: BinaryOp::Sub,
+            size: Size::Quad,
+            from: imm(8 * words as u64),
+            to: RSP,
+        });
+        self.stack_depth += words;
+    }
+    fn push(&mut self, operand: Operand) {
+        self.stack_depth += 1;
+        self.emit(Instruction::Push(operand));
+    }
+    fn pop(&mut self, operand: Operand) {
+        self.stack_depth -= 1;
+        self.emit(Instruction::Pop(operand));
+    }
+    fn mov(&mut self, from: Operand, to: Operand) {
+        self.emit(Instruction::Mov {
+            size: Size::Quad,
+            from,
+            to,
+        });
+    }
+    /// Computes the address of an lvalue into `%rax`, returning the type of the object.
+    fn codegen_address(&mut self, expr: &SExpr<'s>) -> Type {
+        match &expr.inner {
+            Expr::Ident(name) => {
+                match self.lookup(name) {
+                    Some(Binding::Local(local)) => {
+                        let (offset, ty) = (local.offset, local.ty.clone());
+                        self.emit(Instruction::Lea {
+                            from: stack_slot(offset),
+                            to: RAX,
+                        });
+                        return ty;
+                    }
+                    Some(Binding::Static(object)) => {
+                        let ty = object.ty.clone();
+                        // Objects with external linkage may come from a shared library
+                        if object.internal {
+                            let from = Operand::Rip(object.symbol.clone());
+                            self.emit(Instruction::Lea { from, to: RAX });
+                        } else {
+                            self.mov(Operand::Got(object.symbol.clone()), RAX);
+                        }
+                        return ty;
+                    }
+                    Some(Binding::Constant(_)) => unreachable!("constants have no address"),
+                    None => (),
+                }
+                let function = self
+                    .functions
+                    .get(name)
+                    .expect("the parser should reject undeclared identifiers")
+                    .clone();
+                self.mov(Operand::Got(name.to_string()), RAX);
+                Type::Function(Box::new(function))
+            }
+            Expr::Unary(unary) if unary.op == UnaryOperator::Deref => {
+                let ty = self.codegen_expr(&unary.val);
+                types::pointee(&ty)
+                    .expect("only pointers can be dereferenced")
+                    .clone()
+            }
+            Expr::Index(index) => {
+                let ty = self.codegen_pointer_sum(&index.target, &index.index);
+                types::pointee(&ty)
+                    .expect("only pointers can be subscripted")
+                    .clone()
+            }
+            Expr::MemberAccess(access) => {
+                // The value of a struct or union is its address
+                let ty = self.codegen_expr(&access.object);
+                let ty = if access.arrow {
+                    types::pointee(&ty)
+                        .expect("only pointers can be used with `->`")
+                        .clone()
+                } else {
+                    ty
+                };
+                let Type::Tagged(tag) = ty else {
+                    panic!("only structs and unions have members");
+                };
+                let (ty, offset) = self
+                    .find_member(&tag, access.member)
+                    .unwrap_or_else(|| panic!("`{tag}` has no member `{}`", access.member));
+                if offset != 0 {
+                    self.emit(Instruction::Binary {
+                        op: BinaryOp::Add,
+                        size: Size::Quad,
+                        from: imm(offset),
+                        to: RAX,
+                    });
+                }
+                ty
+            }
+            Expr::CompoundLiteral(literal) => {
+                // Each compound literal is an unnamed object of its own in the enclosing block
+                let ty = types::unqualified(&literal.ty);
+                let offset = self.allocate(&ty);
+                self.initialize_local(&ty, offset, &literal.init);
+                self.emit(Instruction::Lea {
+                    from: stack_slot(offset),
+                    to: RAX,
+                });
+                ty
+            }
+            Expr::Lit(lit @ LiteralExpression::Str(string)) => {
+                let symbol = self.string_literal(string);
+                self.emit(Instruction::Lea {
+                    from: Operand::Rip(symbol),
+                    to: RAX,
+                });
+                lit.ty()
+            }
+            _ => {
+                let outer = mem::replace(&mut self.span, expr.span.clone());
+                self.unsupported(&format!("Cannot take the address of `{expr}`"));
+                self.span = outer;
+                self.codegen_expr(expr)
+            }
+        }
+    }
+    /// Converts the lvalue of type `ty` whose address is in `%rax` to its value (C17 6.3.2.1).
+    /// Arrays and functions decay to pointers instead of being loaded, and structs and unions
+    /// are represented by their address.
+    fn load_lvalue(&mut self, ty: Type) -> Type {
+        match ty {
+            Type::Array(elem, _) => Type::Pointer(elem),
+            ty @ Type::Function(_) => Type::Pointer(Box::new(ty)),
+            ty @ Type::Tagged(_) => ty,
+            ty => {
+                self.load(&ty);
+                ty
+            }
+        }
+    }
+    /// Computes `lhs + rhs` for the operands of a subscript expression.
+    fn codegen_pointer_sum(&mut self, lhs: &SExpr<'s>, rhs: &SExpr<'s>) -> Type {
+        let lhs_ty = self.codegen_expr(lhs);
+        self.push(RAX);
+        let rhs_ty = self.codegen_expr(rhs);
+        self.mov(RAX, RCX);
+        self.pop(RAX);
+        self.binary_op(BinaryOperator::Add, &lhs_ty, &rhs_ty)
+    }
+    /// Multiplies the integer in `reg` by the size of the type `ptr` points to.
+    fn scale(&mut self, reg: Register, ptr: &Type) {
+        let size = self.size_of(types::pointee(ptr).unwrap());
+        if size != 1 {
+            self.emit(Instruction::Binary {
+                op: BinaryOp::Imul,
+                size: Size::Quad,
+                from: imm(size),
+                to: Operand::Reg(reg),
+            });
+        }
+    }
+    /// Applies a binary operator to `%rax` and `%rcx`, leaving the result in `%rax`.
+    fn binary_op(&mut self, op: BinaryOperator, lhs: &Type, rhs: &Type) -> Type {
+        use BinaryOperator as B;
+        let (lhs_ptr, rhs_ptr) = (types::pointee(lhs).is_some(), types::pointee(rhs).is_some());
+        if lhs_ptr || rhs_ptr {
+            return self.pointer_op(op, lhs, rhs);
+        }
+        if types::is_floating(lhs) || types::is_floating(rhs) {
+            self.unsupported(FLOATING);
+            return if types::is_floating(lhs) { lhs } else { rhs }.clone();
+        }
+        let (lhs, rhs) = (types::promote(lhs), types::promote(rhs));
+        let ty = match op {
+            // The type of a shift is that of its promoted left operand
+            B::Shl | B::Shr => lhs.clone(),
+            _ => types::common_type(&lhs, &rhs),
+        };
+        self.extend(Register::AX, &ty);
+        if !matches!(op, B::Shl | B::Shr) {
+            self.extend(Register::CX, &ty);
+        }
+        let signed = types::is_signed(&ty);
+        let binary = |op| Instruction::Binary {
+            op,
+            size: Size::Quad,
+            from: RCX,
+            to: RAX,
+        };
+        let instruction = match op {
+            B::Add => binary(BinaryOp::Add),
+            B::Sub => binary(BinaryOp::Sub),
+            B::Mul => binary(BinaryOp::Imul),
+            B::BitAnd => binary(BinaryOp::And),
+            B::BitOr => binary(BinaryOp::Or),
+            B::BitXor => binary(BinaryOp::Xor),
+            B::Shl | B::Shr => Instruction::Shift {
+                op: match op {
+                    B::Shl => ShiftOp::Shl,
+                    _ if signed => ShiftOp::Sar,
+                    _ => ShiftOp::Shr,
+                },
+                size: Size::Quad,
+                operand: RAX,
+            },
+            B::Div | B::Rem => {
+                if signed {
+                    self.emit(Instruction::Cqo);
+                    self.emit(Instruction::Idiv {
+                        size: Size::Quad,
+                        divisor: RCX,
+                    });
+                } else {
+                    self.emit(Instruction::Binary {
+                        op: BinaryOp::Xor,
+                        size: Size::Long,
+                        from: RDX,
+                        to: RDX,
+                    });
+                    self.emit(Instruction::Div {
+                        size: Size::Quad,
+                        divisor: RCX,
+                    });
+                }
+                if op == B::Rem {
+                    self.mov(RDX, RAX);
+                }
+                self.extend(Register::AX, &ty);
+                return ty;
+            }
+            B::Lt | B::Le | B::Gt | B::Ge | B::Eq | B::Ne => {
+                self.compare(op, signed);
+                return types::INT;
+            }
+        };
+        self.emit(instruction);
+        self.extend(Register::AX, &ty);
+        ty
+    }
+    /// Compares `%rax` to `%rcx`, setting `%rax` to the result of the comparison.
+    fn compare(&mut self, op: BinaryOperator, signed: bool) {
+        use BinaryOperator as B;
+        self.emit(Instruction::Cmp {
+            size: Size::Quad,
+            rhs: RCX,
+            lhs: RAX,
+        });
+        let cond = match (op, signed) {
+            (B::Eq, _) => Cond::E,
+            (B::Ne, _) => Cond::Ne,
+            (B::Lt, true) => Cond::L,
+            (B::Le, true) => Cond::Le,
+            (B::Gt, true) => Cond::G,
+            (B::Ge, true) => Cond::Ge,
+            (B::Lt, false) => Cond::B,
+            (B::Le, false) => Cond::Be,
+            (B::Gt, false) => Cond::A,
+            (B::Ge, false) => Cond::Ae,
+            _ => unreachable!(),
+        };
+        self.set_from_flags(cond);
+    }
+    /// Applies a binary operator where at least one operand is a pointer.
+    fn pointer_op(&mut self, op: BinaryOperator, lhs: &Type, rhs: &Type) -> Type {
+        use BinaryOperator as B;
+        let (lhs_ptr, rhs_ptr) = (types::pointee(lhs).is_some(), types::pointee(rhs).is_some());
+        match op {
+            B::Add if lhs_ptr => {
+                // Elements of these sizes can be indexed directly by the addressing mode
+                let size = self.size_of(types::pointee(lhs).unwrap());
+                if let Ok(scale @ (1 | 2 | 4 | 8)) = u8::try_from(size) {
+                    self.emit(Instruction::Lea {
+                        from: Operand::Memory {
+                            base: Register::AX,
+                            index: Some((Register::CX, scale)),
+                            offset: 0,
+                        },
+                        to: RAX,
+                    });
+                    return lhs.clone();
+                }
+                self.scale(Register::CX, lhs);
+                self.emit(Instruction::Binary {
+                    op: BinaryOp::Add,
+                    size: Size::Quad,
+                    from: RCX,
+                    to: RAX,
+                });
+                lhs.clone()
+            }
+            B::Add => {
+                self.scale(Register::AX, rhs);
+                self.emit(Instruction::Binary {
+                    op: BinaryOp::Add,
+                    size: Size::Quad,
+                    from: RCX,
+                    to: RAX,
+                });
+                rhs.clone()
+            }
+            B::Sub if rhs_ptr => {
+                self.emit(Instruction::Binary {
+                    op: BinaryOp::Sub,
+                    size: Size::Quad,
+                    from: RCX,
+                    to: RAX,
+                });
+                // The difference of two pointers is in elements, not bytes
+                let size = self.size_of(types::pointee(lhs).unwrap());
+                self.mov(imm(size), RCX);
+                self.emit(Instruction::Cqo);
+                self.emit(Instruction::Idiv {
+                    size: Size::Quad,
+                    divisor: RCX,
+                });
+                types::LONG
+            }
+            B::Sub => {
+                self.scale(Register::CX, lhs);
+                self.emit(Instruction::Binary {
+                    op: BinaryOp::Sub,
+                    size: Size::Quad,
+                    from: RCX,
+                    to: RAX,
+                });
+                lhs.clone()
+            }
+            B::Lt | B::Le | B::Gt | B::Ge | B::Eq | B::Ne => {
+                self.compare(op, false);
+                types::INT
+            }
+            _ => {
+                self.unsupported(&format!("`{op}` cannot be applied to pointers"));
+                types::LONG
+            }
+        }
+    }
+    fn codegen_binary(&mut self, binary: &BinaryExpr<'s>) -> Type {
+        let BinaryExpr { lhs, op, rhs } = binary;
+        let lhs_ty = self.codegen_expr(lhs);
+        self.push(RAX);
+        let rhs_ty = self.codegen_expr(rhs);
+        self.mov(RAX, RCX);
+        self.pop(RAX);
+        self.binary_op(*op, &lhs_ty, &rhs_ty)
+    }
+    fn codegen_logical(&mut self, logical: &LogicalExpr<'s>) -> Type {
+        let LogicalExpr { lhs, op, rhs } = logical;
+        let (short_circuit, end) = (self.new_label(), self.new_label());
+        // `&&` stops at the first zero operand, `||` at the first nonzero one
+        let (cond, short_value) = match op {
+            LogicalOperator::And => (Cond::E, 0),
+            LogicalOperator::Or => (Cond::Ne, 1),
+        };
+        for operand in [lhs, rhs] {
+            self.codegen_expr(operand);
+            self.emit(Instruction::Cmp {
+                size: Size::Quad,
+                rhs: imm(0),
+                lhs: RAX,
+            });
+            self.emit(Instruction::JmpCC {
+                cond,
+                label: short_circuit.clone(),
+            });
+        }
+        self.mov(imm(1 - short_value), RAX);
+        self.emit(Instruction::Jmp(end.clone()));
+        self.emit(Instruction::Label(short_circuit));
+        self.mov(imm(short_value), RAX);
+        self.emit(Instruction::Label(end));
+        types::INT
+    }
+    /// Adds `delta` elements to the value of type `ty` in `%rax`.
+    fn increment(&mut self, ty: &Type, delta: i64) {
+        let size = types::pointee(ty).map_or(1, |pointee| self.size_of(pointee));
+        let delta = delta * i64::try_from(size).unwrap();
+        self.emit(Instruction::Binary {
+            op: BinaryOp::Add,
+            size: Size::Quad,
+            from: signed_imm(delta),
+            to: RAX,
+        });
+        self.convert(&types::LONG, ty);
+    }
+    fn codegen_unary(&mut self, unary: &UnaryExpr<'s>) -> Type {
+        use UnaryOperator as U;
+        let UnaryExpr { op, val } = unary;
+        match op {
+            U::Neg | U::BitNot | U::Plus => {
+                let ty = self.codegen_expr(val);
+                if types::is_floating(&ty) {
+                    self.unsupported(FLOATING);
+                    return ty;
+                }
+                let ty = types::promote(&ty);
+                let op = match op {
+                    U::Neg => UnaryOp::Neg,
+                    U::BitNot => UnaryOp::Not,
+                    _ => return ty,
+                };
+                self.emit(Instruction::Unary {
+                    op,
+                    size: Size::Quad,
+                    operand: RAX,
+                });
+                self.extend(Register::AX, &ty);
+                ty
+            }
+            U::Not => {
+                self.codegen_expr(val);
+                self.compare_to_zero(Cond::E);
+                types::INT
+            }
+            U::AddrOf => Type::Pointer(Box::new(self.codegen_address(val))),
+            U::Deref => {
+                let ty = self.codegen_expr(val);
+                let pointee = types::pointee(&ty)
+                    .expect("only pointers can be dereferenced")
+                    .clone();
+                self.load_lvalue(pointee)
+            }
+            U::PreInc | U::PreDec | U::PostInc | U::PostDec => {
+                let ty = self.codegen_address(val);
+                self.mov(RAX, RDX);
+                self.load(&ty);
+                let post = matches!(op, U::PostInc | U::PostDec);
+                if post {
+                    self.push(RAX);
+                }
+                let delta = if matches!(op, U::PreInc | U::PostInc) {
+                    1
+                } else {
+                    -1
+                };
+                self.increment(&ty, delta);
+                self.store(&ty, Operand::memory(Register::DX, 0));
+                if post {
+                    self.pop(RAX);
+                }
+                ty
+            }
+        }
+    }
+    fn codegen_assignment(&mut self, assignment: &Assignment<'s>) -> Type {
+        let Assignment { target, op, val } = assignment;
+        let dest = Operand::memory(Register::DX, 0);
+        let Some(op) = op else {
+            let val_ty = self.codegen_expr(val);
+            self.push(RAX);
+            let ty = self.codegen_address(target);
+            self.mov(RAX, RDX);
+            self.pop(RAX);
+            self.convert(&val_ty, &ty);
+            self.store(&ty, dest);
+            return ty;
+        };
+        let ty = self.codegen_address(target);
+        self.push(RAX);
+        self.load(&ty);
+        self.push(RAX);
+        let val_ty = self.codegen_expr(val);
+        self.mov(RAX, RCX);
+        self.pop(RAX);
+        let result_ty = self.binary_op(*op, &ty, &val_ty);
+        self.convert(&result_ty, &ty);
+        self.pop(RDX);
+        self.store(&ty, dest);
+        ty
+    }
+    fn codegen_ternary(&mut self, ternary: &Ternary<'s>) -> Type {
+        let Ternary {
+            cond,
+            then_val,
+            else_val,
+        } = ternary;
+        let then_ty = self.type_of(then_val);
+        let else_ty = self.type_of(else_val);
+        let ty = if types::pointee(&then_ty).is_some() {
+            then_ty.clone()
+        } else if types::pointee(&else_ty).is_some() {
+            else_ty.clone()
+        } else if types::is_integer(&then_ty) && types::is_integer(&else_ty) {
+            types::common_type(&types::promote(&then_ty), &types::promote(&else_ty))
+        } else {
+            then_ty.clone()
+        };
+        let (else_label, end) = (self.new_label(), self.new_label());
+        self.codegen_expr(cond);
+        self.jump_if_zero(&else_label);
+        self.codegen_expr(then_val);
+        self.convert(&then_ty, &ty);
+        self.emit(Instruction::Jmp(end.clone()));
+        self.emit(Instruction::Label(else_label));
+        self.codegen_expr(else_val);
+        self.convert(&else_ty, &ty);
+        self.emit(Instruction::Label(end));
+        ty
+    }
+    /// Calls a function following the System V AMD64 calling convention, leaving the returned
+    /// value in `%rax`.
+    fn codegen_call(&mut self, call: &Call<'s>) -> Type {
+        let Call { callee, args } = call;
+        // Functions are called directly by name, unless the name is shadowed by a variable
+        let direct = match &callee.inner {
+            Expr::Ident(name) if self.lookup(name).is_none() => Some(*name),
+            _ => None,
+        };
+        let ty = match direct {
+            Some(name) => self.functions[name].clone(),
+            None => match self.type_of(callee) {
+                Type::Pointer(function) => match *function {
+                    Type::Function(function) => *function,
+                    _ => panic!("only functions can be called"),
+                },
+                _ => panic!("only functions can be called"),
+            },
+        };
+        let ret_class = types::is_record(&ty.ret).then(|| self.classify(&ty.ret));
+        // Structs and unions returned in memory take the first register for their address
+        let first_register = usize::from(ret_class == Some(Class::Memory));
+        let mut registers = first_register;
+        let mut stack_words = 0;
+        let mut locations = Vec::new();
+        for arg in args {
+            let arg_ty = self.type_of(arg);
+            let (class, words) = if types::is_record(&arg_ty) {
+                let class = self.classify(&arg_ty);
+                if class == Class::Floating {
+                    self.unsupported(FLOATING);
+                }
+                (class, self.size_of(&arg_ty).div_ceil(8) as usize)
+            } else {
+                (Class::Integer(1), 1)
+            };
+            // Arguments are passed on the stack if there aren't enough registers left for the
+            // whole of them
+            let location = match class {
+                Class::Integer(needed) if registers + needed <= ARG_REGISTERS.len() => {
+                    registers += needed;
+                    ArgLocation::Registers(needed)
+                }
+                _ => {
+                    stack_words += words;
+                    ArgLocation::Stack(8 * (stack_words - words))
+                }
+            };
+            locations.push(location);
+        }
+        if ret_class == Some(Class::Floating) {
+            self.unsupported(FLOATING);
+        }
+        // The stack arguments are stored to space reserved below the temporaries, which must
+        // be 16-byte aligned at the call
+        let padding = (self.stack_depth + stack_words) % 2;
+        let reserved = stack_words + padding;
+        self.reserve(reserved);
+        let base_depth = self.stack_depth;
+        // Pushing the register arguments in reverse leaves the first one on top of the stack
+        for (i, arg) in args.iter().enumerate().rev() {
+            let arg_ty = self.codegen_expr(arg);
+            let size = self.size_of(&arg_ty);
+            // Arguments without a parameter to convert to are promoted instead
+            let param_ty = match ty.params.as_ref().and_then(|params| params.get(i)) {
+                Some(param_ty) => param_ty.clone(),
+                None if types::is_integer(&arg_ty) => types::promote(&arg_ty),
+                None => arg_ty.clone(),
+            };
+            let record = types::is_record(&arg_ty);
+            if !record {
+                self.convert(&arg_ty, &param_ty);
+            }
+            match locations[i] {
+                ArgLocation::Registers(_) if !record => self.push(RAX),
+                ArgLocation::Registers(words) => {
+                    self.reserve(words);
+                    self.copy_record(size, Operand::memory(Register::SP, 0));
+                }
+                ArgLocation::Stack(offset) => {
+                    let pushed = 8 * (self.stack_depth - base_depth);
+                    let dest =
+                        Operand::memory(Register::SP, i64::try_from(pushed + offset).unwrap());
+                    if record {
+                        self.copy_record(size, dest);
+                    } else {
+                        self.mov(RAX, dest);
+                    }
+                }
+            }
+        }
+        // The arguments are evaluated first, so the address doesn't need to be saved
+        if direct.is_none() {
+            self.codegen_expr(callee);
+            self.mov(RAX, R11);
+        }
+        for &reg in &ARG_REGISTERS[first_register..registers] {
+            self.pop(Operand::Reg(reg));
+        }
+        if ret_class == Some(Class::Memory) {
+            let buffer = self.allocate(&ty.ret);
+            self.emit(Instruction::Lea {
+                from: stack_slot(buffer),
+                to: Operand::Reg(ARG_REGISTERS[0]),
+            });
+        }
+        // `%al` is an upper bound on the vector registers used by the arguments to a variadic
+        // function, which may be one without a prototype
+        if ty.variadic || ty.params.is_none() {
+            self.emit(Instruction::Mov {
+                size: Size::Long,
+                from: imm(0),
+                to: RAX,
+            });
+        }
+        match direct {
+            Some(name) => self.emit(Instruction::Call(name.to_string())),
+            None => self.emit(Instruction::CallIndirect(R11)),
+        }
+        if reserved != 0 {
+            self.emit(Instruction::Binary {
+                op: BinaryOp::Add,
+                size: Size::Quad,
+                from: imm(8 * reserved as u64),
+                to: RSP,
+            });
+            self.stack_depth -= reserved;
+        }
+        match ret_class {
+            // The callee returns the address of the buffer in `%rax`
+            Some(Class::Memory) => (),
+            // Records are represented by their address, so they're stored to a temporary
+            Some(Class::Integer(words)) => {
+                let align = self.align_of(&ty.ret).max(8);
+                let offset = self.allocate_bytes(8 * words as u64, align);
+                for (word, reg) in (0..).zip([RAX, RDX].into_iter().take(words)) {
+                    self.mov(reg, stack_slot(offset + 8 * word));
+                }
+                self.emit(Instruction::Lea {
+                    from: stack_slot(offset),
+                    to: RAX,
+                });
+            }
+            Some(Class::Floating) => (),
+            // Only the bits of the return type are defined by the callee
+            None if ty.ret != Type::Scalar(ScalarType::Void) => {
+                self.extend(Register::AX, &ty.ret);
+            }
+            None => (),
+        }
+        ty.ret
+    }
+    pub(crate) fn codegen_expr(&mut self, expr: &SExpr<'s>) -> Type {
+        let outer = mem::replace(&mut self.span, expr.span.clone());
+        let ty = match &expr.inner {
+            Expr::Lit(LiteralExpression::Int(int)) => {
+                self.mov(imm(int.value), RAX);
+                Type::Scalar(int.kind.into())
+            }
+            Expr::Lit(lit @ LiteralExpression::Char(c)) => {
+                self.mov(signed_imm(c.value), RAX);
+                lit.ty()
+            }
+            Expr::Lit(LiteralExpression::Float(float)) => Type::Scalar(float.kind.into()),
+            Expr::Lit(LiteralExpression::Bool(b)) => {
+                self.mov(imm((*b).into()), RAX);
+                Type::Scalar(ScalarType::Bool)
+            }
+            // `nullptr_t` has the representation of `void *`, so it's generated as one
+            Expr::Lit(LiteralExpression::Nullptr) => {
+                self.mov(imm(0), RAX);
+                Type::Pointer(Box::new(Type::Scalar(ScalarType::Void)))
+            }
+            Expr::Lit(LiteralExpression::Invalid) => {
+                unreachable!("programs with lexer errors are never generated")
+            }
+            Expr::Ident(name) => match self.lookup(name) {
+                Some(&Binding::Constant(value)) => {
+                    self.mov(signed_imm(value), RAX);
+                    types::INT
+                }
+                _ => {
+                    let ty = self.codegen_address(expr);
+                    self.load_lvalue(ty)
+                }
+            },
+            Expr::Index(_)
+            | Expr::MemberAccess(_)
+            | Expr::CompoundLiteral(_)
+            | Expr::Lit(LiteralExpression::Str(_)) => {
+                let ty = self.codegen_address(expr);
+                self.load_lvalue(ty)
+            }
+            Expr::Binary(binary) => self.codegen_binary(binary),
+            Expr::Logical(logical) => self.codegen_logical(logical),
+            Expr::Unary(unary) => self.codegen_unary(unary),
+            Expr::Assignment(assignment) => self.codegen_assignment(assignment),
+            Expr::Ternary(ternary) => self.codegen_ternary(ternary),
+            Expr::Comma(comma) => {
+                self.codegen_expr(&comma.lhs);
+                self.codegen_expr(&comma.rhs)
+            }
+            Expr::Cast(cast) => {
+                let ty = self.codegen_expr(&cast.val);
+                let to = types::unqualified(&cast.ty);
+                self.convert(&ty, &to);
+                to
+            }
+            Expr::SizeOf(size_of) => {
+                let ty = match &**size_of {
+                    SizeOf::Val(val) => self.type_of_operand(val),
+                    SizeOf::Type(ty) => ty.clone(),
+                };
+                self.mov(imm(self.size_of(&ty)), RAX);
+                types::ULONG
+            }
+            Expr::AlignOf(ty) => {
+                self.mov(imm(self.align_of(ty)), RAX);
+                types::ULONG
+            }
+            Expr::Call(call) => self.codegen_call(call),
+        };
+        if types::is_floating(&ty) {
+            self.unsupported(FLOATING);
+        }
+        self.span = outer;
+        ty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::tests::{run, run_with};
+
+    #[test]
+    fn calls_with_stack_arguments() {
+        // The stack arguments and the temporary pushed for `+` are kept 16-byte aligned
+        let text = "int snprintf(char *s, unsigned long n, const char *format, ...);
+        int f(int a, int b, int c, int d, int e, int g, char h, long i) {
+            return a - b + c - d + e - g + h * i;
+        }
+        int main(void) {
+            char buf[16];
+            snprintf(buf, sizeof buf, \"%d%d%d%d%d%d\", 1, 2, 3, 4, 5, 6);
+            return 100 + f(1, 2, 3, 4, 5, 6, 263, 8) + (buf[5] - '0') * (buf[0] - '0');
+        }";
+        assert_eq!(run("calls_with_stack_arguments", text), 159);
+    }
+
+    #[test]
+    fn records_by_value() {
+        let declarations = "struct small { char a, b, c; };
+        struct pair { long x; int y; };
+        struct big { long a, b, c; };
+        struct pair make_pair(long x, int y);
+        struct big make_big(long a);
+        long sum(int a, struct pair p, struct big b, struct small s, int c, int d, int e,
+                 struct pair q, int f);
+        long mixed(int a, int b, int c, int d, int e, struct pair p, int f);
+        struct big twice(struct big b, struct pair p);
+        struct pair swap(struct pair p, struct small s);
+        ";
+        // Every function is called from code compiled by GCC as well, so the classes agree
+        // with the ABI
+        let library = format!(
+            "{declarations}
+            struct pair make_pair(long x, int y) {{ struct pair p = {{x, y}}; return p; }}
+            struct big make_big(long a) {{ struct big b = {{a, a * 2, a * 3}}; return b; }}
+            long sum(int a, struct pair p, struct big b, struct small s, int c, int d, int e,
+                     struct pair q, int f) {{
+                long r = a;
+                long v[] = {{p.x, p.y, b.a, b.b, b.c, s.a, s.b, s.c, c, d, e, q.x, q.y, f}};
+                for (int i = 0; i < 14; i++) r = r * 3 + v[i];
+                return r;
+            }}
+            long mixed(int a, int b, int c, int d, int e, struct pair p, int f) {{
+                return a + b * 2 + c * 4 + d * 8 + e * 16 + p.x * 32 + p.y * 64 + f * 128;
+            }}
+            long expected_sum(void) {{
+                struct small s = {{1, 2, 3}};
+                return sum(1, make_pair(10, 20), make_big(5), s, 2, 3, 4, make_pair(100, 200), 5);
+            }}
+            int callback(void) {{
+                struct big b = {{1, 2, 3}}, r;
+                struct pair p = {{4, 5}}, q;
+                struct small s = {{6, 7, 8}};
+                r = twice(b, p);
+                q = swap(p, s);
+                return r.a == 2 && r.b == 2 && r.c == 8 && q.x == 13 && q.y == 4;
+            }}"
+        );
+        let text = format!(
+            "{declarations}
+            long expected_sum(void);
+            int callback(void);
+            struct big twice(struct big b, struct pair p) {{ b.a *= 2; b.c += p.y; return b; }}
+            struct pair swap(struct pair p, struct small s) {{
+                struct pair r;
+                r.x = p.y + s.c;
+                r.y = p.x;
+                return r;
+            }}
+            int main(void) {{
+                struct small s = {{1, 2, 3}};
+                struct pair p = make_pair(10, 20);
+                struct big b = make_big(5);
+                int ok = (p.x == 10 && p.y == 20) + (b.a == 5 && b.c == 15) * 2;
+                ok += (sum(1, p, b, s, 2, 3, 4, make_pair(100, 200), 5) == expected_sum()) * 4;
+                ok += (mixed(1, 2, 3, 4, 5, p, 6) == 1 + 4 + 12 + 32 + 80 + 320 + 1280 + 768) * 8;
+                ok += (twice(b, p).c == 35 && swap(p, s).x == 23) * 16;
+                ok += callback() * 32;
+                return ok;
+            }}"
+        );
+        assert_eq!(run_with("records_by_value", &text, Some(&library)), 63);
+    }
+
+    #[test]
+    fn member_offsets() {
+        let text = "struct s { char c; int i; char d; long l; union { short h; char b[3]; }; };
+        int main(void) {
+            struct s s;
+            char *p = (char *)&s;
+            s.c = 1; s.i = 2; s.d = 3; s.l = 4; s.h = 5; s.b[2] = 6;
+            return (p[0] == 1) + (*(int *)(p + 4) == 2) * 2 + (p[8] == 3) * 4
+                + (*(long *)(p + 16) == 4) * 8 + (*(short *)(p + 24) == 5) * 16
+                + (p[26] == 6) * 32 + (sizeof s == 32) * 64;
+        }";
+        assert_eq!(run("member_offsets", text), 127);
+    }
+}
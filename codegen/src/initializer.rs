@@ -0,0 +1,163 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+//! The initialization of objects from their initializers: stores for locals and compound
+//! literals, and the initial contents of objects with static storage duration.
+
+use std::collections::BTreeMap;
+
+use asm::{Data, Instruction, Operand, Size};
+use ast::{
+    Expr, Initializer, LiteralExpression, Member, Tag, Type,
+    constant::{self, Base, Constant},
+    initializer::{self, InitContext, InitLeaf},
+};
+use utils::VarInt;
+
+use crate::{Codegen, stack_slot};
+
+impl<'s> InitContext<'s> for Codegen<'s> {
+    fn members(&mut self, tag: &Tag) -> Option<Vec<Member<'s>>> {
+        Codegen::members(self, tag)
+    }
+}
+
+/// The code units of the string literal initializing an array.
+fn string_units(leaf: &InitLeaf<'_, '_>) -> Vec<u32> {
+    match &leaf.value.inner {
+        Expr::Lit(LiteralExpression::Str(string)) => string.units(),
+        _ => unreachable!("only string literals initialize whole arrays"),
+    }
+}
+
+impl<'s> Codegen<'s> {
+    /// Initializes the object of type `ty` at `offset` from `%rbp`.
+    pub(crate) fn initialize_local(&mut self, ty: &Type, offset: i64, init: &Initializer<'s>) {
+        let resolved = initializer::resolve(ty, init, self)
+            .expect("the parser should reject initializers that don't match their object");
+        // The subobjects that aren't initialized explicitly are zero. A struct or union
+        // initialized by another is copied whole instead.
+        if matches!(init, Initializer::List(_)) || matches!(ty, Type::Array(..)) {
+            self.zero_stack(offset, self.size_of(ty));
+        }
+        for leaf in &resolved.leaves {
+            let (leaf_ty, at) = self.locate(ty, &leaf.path);
+            let dest = offset + i64::try_from(at).unwrap();
+            if let Type::Array(elem, _) = &leaf_ty {
+                // The terminating null character is already there, as the array was zeroed
+                let elem_size = self.size_of(elem);
+                for (i, unit) in (0..).zip(string_units(leaf)) {
+                    self.emit(Instruction::Mov {
+                        size: Size::from_bytes(elem_size),
+                        from: Operand::Imm(VarInt::Pos(u64::from(unit))),
+                        to: stack_slot(dest + i * i64::try_from(elem_size).unwrap()),
+                    });
+                }
+                continue;
+            }
+            let value_ty = self.codegen_expr(leaf.value);
+            self.convert(&value_ty, &leaf_ty);
+            self.store(&leaf_ty, stack_slot(dest));
+        }
+    }
+    /// Sets `size` bytes of the stack at `offset` from `%rbp` to zero.
+    fn zero_stack(&mut self, offset: i64, size: u64) {
+        let mut zeroed = 0;
+        while zeroed < size {
+            // The largest move that doesn't go past the end
+            let chunk = [8, 4, 2, 1]
+                .into_iter()
+                .find(|&chunk| zeroed + chunk <= size)
+                .unwrap();
+            self.emit(Instruction::Mov {
+                size: Size::from_bytes(chunk),
+                from: Operand::Imm(VarInt::Pos(0)),
+                to: stack_slot(offset + i64::try_from(zeroed).unwrap()),
+            });
+            zeroed += chunk;
+        }
+    }
+    /// Lays out the initial contents of an object of type `ty` with static storage duration,
+    /// whose initializer the parser has checked to be constant.
+    pub(crate) fn static_data(&mut self, ty: &Type, init: &Initializer<'s>) -> Vec<Data> {
+        let resolved = initializer::resolve(ty, init, self)
+            .expect("the parser should reject initializers that don't match their object");
+        // The scalars that make up the object by their offset, with their type and value
+        let mut scalars: BTreeMap<u64, (Type, Constant<'s>)> = BTreeMap::new();
+        for leaf in &resolved.leaves {
+            let (leaf_ty, at) = self.locate(ty, &leaf.path);
+            let values: Vec<(u64, Type, Constant)> = match &leaf_ty {
+                Type::Array(elem, _) => {
+                    let &Type::Scalar(scalar) = &**elem else {
+                        unreachable!("strings initialize arrays of characters");
+                    };
+                    let elem_size = self.size_of(elem);
+                    let units = (0..).zip(string_units(leaf));
+                    units
+                        .map(|(i, unit)| {
+                            let unit = Constant::int(unit.into(), scalar);
+                            (at + i * elem_size, (**elem).clone(), unit)
+                        })
+                        .collect()
+                }
+                _ => {
+                    let value = constant::evaluate(leaf.value, self)
+                        .ok()
+                        .and_then(|value| value.convert(&leaf_ty))
+                        .expect("the parser should reject initializers that aren't constant");
+                    vec![(at, leaf_ty, value)]
+                }
+            };
+            for (at, ty, value) in values {
+                // A later initializer overrides the parts of earlier ones that it overlaps
+                let end = at + self.size_of(&ty);
+                scalars.retain(|&prev, (prev_ty, _)| {
+                    prev >= end || prev + self.size_of(prev_ty) <= at
+                });
+                scalars.insert(at, (ty, value));
+            }
+        }
+        let mut data = Vec::new();
+        let mut end = 0;
+        for (at, (ty, value)) in scalars {
+            if at > end {
+                data.push(Data::Zero(at - end));
+            }
+            let size = self.size_of(&ty);
+            data.push(match value.base {
+                // Only pointers and 64-bit integers can hold addresses
+                Some(Base::Named(name)) => Data::Address {
+                    symbol: self.symbol(name),
+                    offset: value.value,
+                },
+                Some(Base::String(_, string)) => Data::Address {
+                    symbol: self.string_literal(&string),
+                    offset: value.value,
+                },
+                Some(Base::Compound(_, literal)) => Data::Address {
+                    symbol: self.static_compound_literal(&literal),
+                    offset: value.value,
+                },
+                None => Data::Int(Size::from_bytes(size), value.value),
+            });
+            end = at + size;
+        }
+        let size = self.size_of(ty);
+        if size > end {
+            data.push(Data::Zero(size - end));
+        }
+        data
+    }
+}
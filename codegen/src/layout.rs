@@ -0,0 +1,108 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! The sizes, alignments and member offsets of types, following the System V x86-64 ABI.
+
+use ast::{Member, Tag, Type, initializer::Step};
+
+use crate::{Codegen, types};
+
+/// How a struct or union is passed to and returned from functions (System V ABI 3.2.3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Class {
+    /// In this many general-purpose registers, one for each eightbyte
+    Integer(usize),
+    /// In the SSE registers, at least in part, which code isn't generated for
+    Floating,
+    /// In memory, as a copy on the stack for arguments, or in a buffer whose address the caller
+    /// passes in `%rdi` for return values
+    Memory,
+}
+
+impl<'s> Codegen<'s> {
+    pub(crate) fn size_of(&self, ty: &Type) -> u64 {
+        match ty.unqualified() {
+            // Incomplete arrays, like parameters declared as `int a[]`, have no storage of their
+            // own
+            Type::Array(_, None) => 0,
+            _ => self
+                .records
+                .size_of(ty)
+                .unwrap_or_else(|| panic!("`{ty}` should be complete")),
+        }
+    }
+    pub(crate) fn align_of(&self, ty: &Type) -> u64 {
+        self.records
+            .align_of(ty)
+            .unwrap_or_else(|| panic!("`{ty}` should be complete"))
+    }
+    /// The members of a struct or union, if it has been defined.
+    pub(crate) fn members(&self, tag: &Tag) -> Option<Vec<Member<'s>>> {
+        let mut members = self.records.members(tag)?;
+        for member in &mut members {
+            member.ty = types::unqualified(&member.ty);
+        }
+        Some(members)
+    }
+    /// Finds the type and offset of the subobject of `ty` reached by following `path`.
+    pub(crate) fn locate(&self, ty: &Type, path: &[Step]) -> (Type, u64) {
+        let mut ty = ty.clone();
+        let mut offset = 0;
+        for step in path {
+            ty = match (step, ty) {
+                (&Step::Index(index), Type::Array(elem, _)) => {
+                    offset += index * self.size_of(&elem);
+                    *elem
+                }
+                (&Step::Member(index), Type::Tagged(tag)) => {
+                    let layout = self.records.record(&tag);
+                    let member = &layout.expect("the record should be complete").members[index];
+                    offset += member.offset;
+                    types::unqualified(&member.ty)
+                }
+                (step, ty) => unreachable!("`{ty}` has no subobject {step:?}"),
+            };
+        }
+        (ty, offset)
+    }
+    /// Finds the type and offset of the member `name` of a struct or union, including the
+    /// members of its anonymous members.
+    pub(crate) fn find_member(&self, tag: &Tag, name: &str) -> Option<(Type, u64)> {
+        let (ty, offset) = self.records.find_member(tag, name)?;
+        Some((types::unqualified(&ty), offset))
+    }
+    /// Classifies a struct or union for passing it by value. Members can't be misaligned, so
+    /// only the size and the types of the members matter.
+    pub(crate) fn classify(&self, ty: &Type) -> Class {
+        let size = self.size_of(ty);
+        if size > 16 {
+            Class::Memory
+        } else if self.has_floating(ty) {
+            Class::Floating
+        } else {
+            Class::Integer(size.div_ceil(8) as usize)
+        }
+    }
+    fn has_floating(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Array(elem, _) => self.has_floating(elem),
+            Type::Tagged(tag) if types::is_record(ty) => self
+                .members(tag)
+                .is_some_and(|members| members.iter().any(|member| self.has_floating(&member.ty))),
+            _ => types::is_floating(ty),
+        }
+    }
+}
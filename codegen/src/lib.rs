@@ -14,50 +14,482 @@
 // If not, see <https://www.gnu.org/licenses/>.
 // LICENSE NOTICE END
 
-use asm::{ASMFunction, ASMProgram, Instruction, Operand, Register};
-use ast::{Decl, Expr, Function, LiteralExpression, Program, Stmt};
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+};
 
+use asm::{ASMFunction, ASMProgram, BinaryOp, Instruction, Operand, Register, Size, StaticObject};
+use ast::{
+    Decl, Function, FunctionType, Program, Spanned, StorageClass, Type, TypeDecl, layout::Layouts,
+};
+use diagnostics::{AggregateError, ErrorComponent};
+use layout::Class;
+use source::{SourceFile, Span};
+use statics::{Static, StaticDefinition};
+use utils::VarInt;
+
+mod expr;
+mod initializer;
+mod layout;
+mod statics;
+mod stmt;
+mod types;
+
+/// A local variable, stored at a fixed offset from `%rbp`.
+#[derive(Debug, Clone)]
+struct Local {
+    offset: i64,
+    ty: Type,
+}
+
+/// What an ordinary identifier in a block refers to, other than a function.
+#[derive(Debug, Clone)]
+enum Binding {
+    Local(Local),
+    Static(Static),
+    /// An enumeration constant
+    Constant(i64),
+}
+
+/// The registers used for the first integer arguments of a call, in order.
+const ARG_REGISTERS: [Register; 6] = [
+    Register::DI,
+    Register::SI,
+    Register::DX,
+    Register::CX,
+    Register::R8,
+    Register::R9,
+];
+
+/// The error for anything involving floating-point values, which are kept in the SSE registers
+/// that code isn't generated for.
+const FLOATING: &str = "Floating-point types are not supported";
+
+/// The stack memory at `offset` from `%rbp`.
+const fn stack_slot(offset: i64) -> Operand {
+    Operand::memory(Register::BP, offset)
+}
+
+/// The labels for the cases of the switch statement being generated, in the order they appear
+/// in its body.
 #[derive(Debug, Default)]
-pub struct Codegen {}
+struct Switch {
+    cases: Vec<String>,
+    default: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Codegen<'s> {
+    source: SourceFile,
+    /// The constructs that can't be generated, with the span of the outermost expression,
+    /// statement or declaration each was found in
+    unsupported: Vec<(Span, String)>,
+    /// The declarations that can't be generated, which don't hide what's unsupported in them
+    unsupported_declarations: Vec<(Span, String)>,
+    /// The span of the innermost expression, statement or declaration being generated
+    span: Span,
+    instructions: Vec<Instruction>,
+    /// The objects with static storage duration declared so far, by their symbol
+    statics: HashMap<String, StaticDefinition>,
+    /// The symbols of [Self::statics] in the order they were first declared
+    static_order: Vec<String>,
+    /// The arrays of the string literals used so far
+    strings: Vec<StaticObject>,
+    /// The functions with internal linkage
+    internal_functions: HashSet<&'s str>,
+    /// The locals and constants visible at the current point, innermost block last
+    scopes: Vec<HashMap<&'s str, Binding>>,
+    /// The layouts of the structs and unions defined so far
+    records: Layouts<'s>,
+    /// The bytes of stack used for locals by the current function
+    frame_size: u64,
+    /// The number of temporaries currently pushed onto the stack, used to align calls
+    stack_depth: usize,
+    /// Every function declared so far
+    functions: HashMap<&'s str, FunctionType>,
+    function: &'s str,
+    /// The stack slot holding the address to return the value of the current function to, if
+    /// it's a struct or union returned in memory
+    return_buffer: Option<i64>,
+    labels: usize,
+    break_labels: Vec<String>,
+    continue_labels: Vec<String>,
+    switches: Vec<Switch>,
+}
 
-impl Codegen {
-    pub fn new() -> Self {
-        Self {  }
+impl<'s> Codegen<'s> {
+    /// Creates a code generator for a program parsed from `source`, which diagnostics point
+    /// into.
+    pub fn new(source: SourceFile) -> Self {
+        Self {
+            source,
+            unsupported: Vec::new(),
+            unsupported_declarations: Vec::new(),
+            span: 0..0,
+            instructions: Vec::new(),
+            statics: HashMap::new(),
+            static_order: Vec::new(),
+            strings: Vec::new(),
+            internal_functions: HashSet::new(),
+            scopes: Vec::new(),
+            records: Layouts::default(),
+            frame_size: 0,
+            stack_depth: 0,
+            functions: HashMap::new(),
+            function: "",
+            return_buffer: None,
+            labels: 0,
+            break_labels: Vec::new(),
+            continue_labels: Vec::new(),
+            switches: Vec::new(),
+        }
     }
-    pub fn codegen_program<'s>(&self, program: &Program<'s>) -> ASMProgram<'s> {
-        let Program { declarations } = program;
+    /// Generates a program, along with errors for the constructs in it that can't be generated.
+    /// The program should only be assembled if there are none.
+    pub fn codegen_program(&mut self, program: &Program<'s>) -> (ASMProgram<'s>, AggregateError) {
+        let Program { declarations, .. } = program;
         let mut functions = Vec::new();
+        // The file scope, which holds the objects and enumeration constants declared outside
+        // functions
+        self.scopes.push(HashMap::new());
         for decl in declarations {
-            match decl {
-                Decl::Fun(fun) => functions.push(self.codegen_function(fun)),
+            self.span = decl.span.clone();
+            match &decl.inner {
+                Decl::Fun(fun) => {
+                    // Later declarations keep the linkage of a `static` one
+                    if fun.storage == Some(StorageClass::Static) {
+                        self.internal_functions.insert(fun.name);
+                    }
+                    self.declare_function(fun.name, fun.ty());
+                    if fun.body.is_some() {
+                        functions.push(self.codegen_function(fun));
+                    }
+                }
+                Decl::Var(var) => self.declare_global(var),
+                Decl::Type(decl) => self.declare_type(decl),
+            }
+        }
+        self.scopes.pop();
+        let mut objects = self.static_objects();
+        objects.append(&mut self.strings);
+        let mut unsupported = mem::take(&mut self.unsupported_declarations);
+        unsupported.append(&mut self.unsupported);
+        unsupported.sort_by_key(|(span, _)| span.start);
+        let mut errors = AggregateError::new();
+        for (span, message) in unsupported {
+            errors.add_error(ErrorComponent::new(self.source.clone(), message, span));
+        }
+        (ASMProgram { functions, objects }, errors)
+    }
+    /// Generates the definition of a function, which must have a body.
+    pub fn codegen_function(&mut self, function: &Function<'s>) -> ASMFunction<'s> {
+        let Function {
+            name, params, body, ..
+        } = function;
+        let body = body.as_ref().expect("only definitions can be generated");
+        self.function = name;
+        self.frame_size = 0;
+        self.scopes.push(HashMap::new());
+        // The registers not yet taken by parameters, and the offset from `%rbp` of the next
+        // parameter passed on the stack, above the return address and saved `%rbp`
+        let mut registers = ARG_REGISTERS.iter();
+        let mut stack_offset = 16;
+        self.return_buffer = None;
+        let ret = self.functions[name].ret.clone();
+        if types::is_record(&ret) {
+            match self.classify(&ret) {
+                Class::Integer(_) => (),
+                Class::Floating => {
+                    let message = format!("Returning `{ret}`, which has floating-point members");
+                    let span = self.span.clone();
+                    self.unsupported_declarations.push((span, message));
+                }
+                Class::Memory => {
+                    let offset = self.allocate_bytes(8, 8);
+                    let reg = *registers.next().unwrap();
+                    self.emit(Instruction::Mov {
+                        size: Size::Quad,
+                        from: Operand::Reg(reg),
+                        to: stack_slot(offset),
+                    });
+                    self.return_buffer = Some(offset);
+                }
             }
         }
-        ASMProgram { functions }
-    }
-    pub fn codegen_function<'s>(&self, function: &Function<'s>) -> ASMFunction<'s> {
-        let Function { name, body } = function;
-        let mut instructions = Vec::new();
-        for statement in &body.0 {
-            self.codegen_statement(&mut instructions, statement);
-        }
-        ASMFunction { name, instructions }
-    }
-    pub fn codegen_statement<'s>(&self, instructions: &mut Vec<Instruction>, stmt: &Stmt<'s>) {
-        match stmt {
-            Stmt::Return(val) => {
-                if let Some(ret) = val {
-                    match ret {
-                        Expr::Lit(LiteralExpression::Int(int)) => {
-                            instructions.push(Instruction::Mov {
-                                from: Operand::Imm(*int),
-                                to: Operand::Reg(Register::EAX),
-                            })
-                        }
-                        _ => unimplemented!()
+        for Spanned { inner: param, span } in params.iter().flatten() {
+            let ty = types::unqualified(&param.ty);
+            let class = if types::is_record(&ty) {
+                self.classify(&ty)
+            } else {
+                Class::Integer(1)
+            };
+            // Such parameters are passed in other registers, which would leave the rest in the
+            // wrong ones
+            if types::is_floating(&ty) || class == Class::Floating {
+                let message = match param.name {
+                    Some(name) if class == Class::Floating => {
+                        format!("Parameter `{name}` has floating-point members")
                     }
+                    Some(name) => format!("Parameter `{name}` has a floating-point type"),
+                    None => "Floating-point parameters are not supported".to_owned(),
+                };
+                self.unsupported_declarations.push((span.clone(), message));
+                if let Some(name) = param.name {
+                    self.declare_local(name, ty, None);
                 }
-                instructions.push(Instruction::Ret);
+                continue;
             }
+            // Parameters are passed on the stack if there aren't enough registers left for the
+            // whole of them
+            let regs: Vec<Register> = match class {
+                Class::Integer(words) if registers.len() >= words => {
+                    registers.by_ref().take(words).copied().collect()
+                }
+                _ => Vec::new(),
+            };
+            let offset = if regs.is_empty() {
+                let offset = stack_offset;
+                stack_offset += i64::try_from(self.size_of(&ty).next_multiple_of(8)).unwrap();
+                offset
+            } else {
+                // The registers are spilled whole, so the copy may be larger than the type
+                let align = self.align_of(&ty).max(8);
+                let offset = self.allocate_bytes(8 * regs.len() as u64, align);
+                for (word, reg) in (0..).zip(regs) {
+                    self.emit(Instruction::Mov {
+                        size: Size::Quad,
+                        from: Operand::Reg(reg),
+                        to: stack_slot(offset + 8 * word),
+                    });
+                }
+                offset
+            };
+            if let Some(name) = param.name {
+                self.scopes
+                    .last_mut()
+                    .unwrap()
+                    .insert(name, Binding::Local(Local { offset, ty }));
+            }
+        }
+        self.codegen_block(body);
+        self.scopes.pop();
+        // Reaching the closing brace of `main` returns 0 (C17 5.1.2.2.3). Other functions may
+        // return anything in that case, so they do the same.
+        self.emit(Instruction::Mov {
+            size: Size::Long,
+            from: Operand::Imm(VarInt::Pos(0)),
+            to: Operand::Reg(Register::AX),
+        });
+        self.emit_return();
+        let mut instructions = vec![
+            Instruction::Push(Operand::Reg(Register::BP)),
+            Instruction::Mov {
+                size: Size::Quad,
+                from: Operand::Reg(Register::SP),
+                to: Operand::Reg(Register::BP),
+            },
+        ];
+        // The stack stays 16-byte aligned for calls
+        let frame_size = self.frame_size.next_multiple_of(16);
+        if frame_size != 0 {
+            instructions.push(Instruction::Binary {
+                op: BinaryOp::Sub,
+                size: Size::Quad,
+                from: Operand::Imm(VarInt::Pos(frame_size)),
+                to: Operand::Reg(Register::SP),
+            });
+        }
+        instructions.append(&mut self.instructions);
+        ASMFunction {
+            name,
+            global: !self.internal_functions.contains(name),
+            instructions,
         }
     }
+    fn declare_function(&mut self, name: &'s str, ty: FunctionType) {
+        // A declaration without a prototype doesn't hide an earlier one
+        if ty.params.is_some() || !self.functions.contains_key(name) {
+            self.functions
+                .insert(name, types::unqualified_function(&ty));
+        }
+    }
+    /// Lays out a struct or union, or declares the constants of an enum in the innermost scope.
+    fn declare_type(&mut self, decl: &TypeDecl<'s>) {
+        match decl {
+            TypeDecl::Record {
+                tag,
+                members: Some(members),
+            } => self.records.define(tag, members),
+            // Forward declarations leave nothing to lay out yet, and typedef names have already
+            // been replaced by their types
+            TypeDecl::Record { members: None, .. } | TypeDecl::Typedef { .. } => (),
+            TypeDecl::Enum { enumerators, .. } => {
+                let scope = self.scopes.last_mut().unwrap();
+                for Spanned {
+                    inner: enumerator, ..
+                } in enumerators
+                {
+                    scope.insert(enumerator.name, Binding::Constant(enumerator.value));
+                }
+            }
+        }
+    }
+    /// Reports a construct that can't be generated, found in the expression, statement or
+    /// declaration at [Self::span]. Only the outermost of the constructs reported in an
+    /// expression is kept, so each expression gets one error.
+    fn unsupported(&mut self, message: &str) {
+        let span = self.span.clone();
+        let contains =
+            |outer: &Span, inner: &Span| outer.start <= inner.start && inner.end <= outer.end;
+        if self
+            .unsupported
+            .iter()
+            .any(|(reported, _)| contains(reported, &span))
+        {
+            return;
+        }
+        self.unsupported
+            .retain(|(reported, _)| !contains(&span, reported));
+        self.unsupported.push((span, message.to_owned()));
+    }
+    fn emit(&mut self, instruction: Instruction) {
+        self.instructions.push(instruction);
+    }
+    fn emit_return(&mut self) {
+        self.emit(Instruction::Mov {
+            size: Size::Quad,
+            from: Operand::Reg(Register::BP),
+            to: Operand::Reg(Register::SP),
+        });
+        self.emit(Instruction::Pop(Operand::Reg(Register::BP)));
+        self.emit(Instruction::Ret);
+    }
+    fn new_label(&mut self) -> String {
+        self.labels += 1;
+        format!(".L{}", self.labels)
+    }
+    /// Allocates stack storage for a value of the given type, returning its offset from `%rbp`.
+    fn allocate(&mut self, ty: &Type) -> i64 {
+        self.allocate_aligned(ty, self.align_of(ty))
+    }
+    /// Allocates stack storage for a value of the given type at a multiple of `align`.
+    fn allocate_aligned(&mut self, ty: &Type, align: u64) -> i64 {
+        self.allocate_bytes(self.size_of(ty), align)
+    }
+    /// Allocates `size` bytes of stack storage at a multiple of `align`. Alignments stricter
+    /// than the 16 bytes `%rbp` is aligned to are reported, since the frame isn't realigned.
+    fn allocate_bytes(&mut self, size: u64, align: u64) -> i64 {
+        if align > 16 {
+            self.unsupported(
+                "Objects on the stack aligned to more than 16 bytes are not supported",
+            );
+        }
+        self.frame_size = (self.frame_size + size).next_multiple_of(align);
+        -i64::try_from(self.frame_size).unwrap()
+    }
+    /// Allocates stack storage for a local in the innermost scope, returning its offset from
+    /// `%rbp`. Its alignment may be made stricter than that of its type by `_Alignas`.
+    fn declare_local(&mut self, name: &'s str, ty: Type, align: Option<u64>) -> i64 {
+        let align = self.align_of(&ty).max(align.unwrap_or(1));
+        let offset = self.allocate_aligned(&ty, align);
+        let scope = self.scopes.last_mut().unwrap();
+        scope.insert(name, Binding::Local(Local { offset, ty }));
+        offset
+    }
+    /// Finds the object or constant `name` refers to, if it isn't a function.
+    fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use lexer::{ConcatStrings, Logos, SToken, Token};
+    use parser::Parser;
+    use pretty_assertions::assert_eq;
+    use source::SourceFile;
+
+    use crate::Codegen;
+
+    /// Generates the assembly for a program, or returns the messages of the errors found while
+    /// parsing or generating it.
+    pub(crate) fn compile(text: &str) -> Result<String, Vec<String>> {
+        let source = SourceFile::new("test.c".to_owned(), text.to_owned());
+        let tokens = ConcatStrings::new(Token::lexer(text).spanned())
+            .map(|(token, span)| Ok(SToken::new(token.unwrap(), span)));
+        let (program, mut errors) = Parser::new(source.clone(), tokens).parse();
+        if !errors.has_error() {
+            let (asm, mut codegen_errors) = Codegen::new(source).codegen_program(&program);
+            errors.append(&mut codegen_errors);
+            if !errors.has_error() {
+                return Ok(asm.to_string());
+            }
+        }
+        Err(errors
+            .components
+            .into_iter()
+            .map(|c| c.short_message)
+            .collect())
+    }
+
+    /// Compiles a program, then assembles it with `gcc` and runs it, returning its exit status.
+    pub(crate) fn run(name: &str, text: &str) -> i32 {
+        run_with(name, text, None)
+    }
+
+    /// Runs a program after linking it with `library`, C code compiled by GCC.
+    pub(crate) fn run_with(name: &str, text: &str, library: Option<&str>) -> i32 {
+        let asm = compile(text).unwrap();
+        let dir = std::env::temp_dir().join(format!("cccc-codegen-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (asm_path, exe_path) = (dir.join(format!("{name}.s")), dir.join(name));
+        std::fs::write(&asm_path, asm).unwrap();
+        let library_path = dir.join(format!("{name}_library.c"));
+        let mut gcc = Command::new("gcc");
+        gcc.arg(&asm_path);
+        if let Some(library) = library {
+            std::fs::write(&library_path, library).unwrap();
+            gcc.arg(&library_path);
+        }
+        let assembled = gcc.arg("-o").arg(&exe_path).status().unwrap();
+        assert!(assembled.success(), "{name} failed to assemble");
+        let status = Command::new(&exe_path).status().unwrap();
+        if library.is_some() {
+            std::fs::remove_file(&library_path).unwrap();
+        }
+        std::fs::remove_file(&asm_path).unwrap();
+        std::fs::remove_file(&exe_path).unwrap();
+        status.code().unwrap()
+    }
+
+    #[test]
+    fn unsupported_constructs() {
+        assert_eq!(
+            compile("int f(double d) { int i = d * d + d; float g = 2; return 1; }"),
+            Err(vec![
+                "Parameter `d` has a floating-point type".to_owned(),
+                "Floating-point types are not supported".to_owned(),
+                "Floating-point types are not supported".to_owned(),
+            ])
+        );
+        assert_eq!(
+            compile("struct d { double x; }; struct d f(struct d d, int i) { return d; }"),
+            Err(vec![
+                "Returning `struct d`, which has floating-point members".to_owned(),
+                "Parameter `d` has floating-point members".to_owned(),
+            ])
+        );
+        let over_aligned = "Objects on the stack aligned to more than 16 bytes are not supported";
+        assert_eq!(
+            compile(
+                "struct v { _Alignas(32) char b[32]; };
+                 int f(void) { _Alignas(64) int x = 1; struct v v; return x; }"
+            ),
+            Err(vec![over_aligned.to_owned(), over_aligned.to_owned()])
+        );
+        assert!(compile("int f(void) { static _Alignas(64) int x; return x; }").is_ok());
+    }
 }
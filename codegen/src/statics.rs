@@ -0,0 +1,329 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! Objects with static storage duration: those declared at file scope or with `static`.
+
+use asm::{Data, Size, StaticObject};
+use ast::{
+    CompoundLiteral, Initializer, SExpr, StorageClass, Type, VarDecl, constant::ConstContext,
+    layout::Layouts,
+};
+use utils::StringLiteral;
+
+use crate::{Binding, Codegen, types};
+
+/// An object with static storage duration, by the symbol it's emitted as.
+#[derive(Debug, Clone)]
+pub(crate) struct Static {
+    pub(crate) symbol: String,
+    pub(crate) ty: Type,
+    /// Whether the object has internal linkage or none, and so is defined in this program
+    pub(crate) internal: bool,
+}
+
+/// What the declarations of an object with static storage duration have established so far.
+#[derive(Debug)]
+pub(crate) struct StaticDefinition {
+    ty: Type,
+    internal: bool,
+    /// Whether any declaration was a definition, including a tentative one
+    defined: bool,
+    /// The strictest alignment given by `_Alignas` in any declaration
+    align: Option<u64>,
+    init: Option<Vec<Data>>,
+}
+
+impl<'s> Codegen<'s> {
+    /// Declares an object at file scope, merging it with earlier declarations of the same name.
+    pub(crate) fn declare_global(&mut self, decl: &VarDecl<'s>) {
+        let VarDecl {
+            name,
+            storage,
+            ty,
+            align,
+            init,
+        } = decl;
+        let mut ty = types::unqualified(ty);
+        let defines = *storage != Some(StorageClass::Extern) || init.is_some();
+        let definition = match self.statics.get_mut(*name) {
+            Some(prev) => {
+                // The parser has checked that the types are compatible, so only the length of
+                // an array can still be missing
+                if matches!(ty, Type::Array(_, None)) {
+                    ty = prev.ty.clone();
+                } else {
+                    prev.ty = ty.clone();
+                }
+                prev.defined |= defines;
+                prev.align = prev.align.max(*align);
+                prev
+            }
+            None => {
+                self.static_order.push((*name).to_owned());
+                let definition = StaticDefinition {
+                    ty: ty.clone(),
+                    internal: *storage == Some(StorageClass::Static),
+                    defined: defines,
+                    align: *align,
+                    init: None,
+                };
+                self.statics.insert((*name).to_owned(), definition);
+                self.statics.get_mut(*name).unwrap()
+            }
+        };
+        let binding = Binding::Static(Static {
+            symbol: (*name).to_owned(),
+            ty: ty.clone(),
+            internal: definition.internal,
+        });
+        self.scopes[0].insert(name, binding);
+        self.define_static(name, &ty, init.as_ref());
+    }
+    /// Declares a block scope object with static storage duration in the innermost scope. It
+    /// is either defined here with `static`, or refers to one with linkage with `extern`.
+    pub(crate) fn declare_block_static(&mut self, decl: &VarDecl<'s>) {
+        let VarDecl {
+            name,
+            storage,
+            ty,
+            align,
+            init,
+        } = decl;
+        let ty = types::unqualified(ty);
+        let object = if *storage == Some(StorageClass::Extern) {
+            let internal = self.statics.get(*name).is_some_and(|prev| prev.internal);
+            Static {
+                symbol: (*name).to_owned(),
+                ty,
+                internal,
+            }
+        } else {
+            // Objects without linkage in different blocks can share a name, so each is given a
+            // symbol of its own. The `.` keeps it from clashing with C identifiers.
+            self.labels += 1;
+            let symbol = format!("{name}.{}", self.labels);
+            self.static_order.push(symbol.clone());
+            let definition = StaticDefinition {
+                ty: ty.clone(),
+                internal: true,
+                defined: true,
+                align: *align,
+                init: None,
+            };
+            self.statics.insert(symbol.clone(), definition);
+            Static {
+                symbol,
+                ty,
+                internal: true,
+            }
+        };
+        let (symbol, ty) = (object.symbol.clone(), object.ty.clone());
+        let scope = self.scopes.last_mut().unwrap();
+        scope.insert(name, Binding::Static(object));
+        self.define_static(&symbol, &ty, init.as_ref());
+    }
+    /// Lays out the initial contents of the object `symbol` from its initializer, unless an
+    /// earlier declaration already did. The object is already in scope, as its initializer can
+    /// take its address.
+    fn define_static(&mut self, symbol: &str, ty: &Type, init: Option<&Initializer<'s>>) {
+        let Some(init) = init else {
+            return;
+        };
+        let data = self.static_data(ty, init);
+        let definition = self.statics.get_mut(symbol).unwrap();
+        definition.init.get_or_insert(data);
+    }
+    /// Adds the array of a string literal to the program, returning its symbol. Each literal
+    /// gets an array of its own.
+    pub(crate) fn string_literal(&mut self, string: &StringLiteral) -> String {
+        self.labels += 1;
+        let symbol = format!(".Lstr{}", self.labels);
+        let unit_size = string.encoding.unit_size() as u64;
+        let size = Size::from_bytes(unit_size);
+        let mut init: Vec<_> = string
+            .units()
+            .into_iter()
+            .map(|unit| Data::Int(size, unit.into()))
+            .collect();
+        init.push(Data::Int(size, 0));
+        self.strings.push(StaticObject {
+            name: symbol.clone(),
+            global: false,
+            read_only: true,
+            align: unit_size,
+            init,
+        });
+        symbol
+    }
+    /// Adds the unnamed object of a compound literal outside of any function to the program,
+    /// returning its symbol.
+    pub(crate) fn static_compound_literal(&mut self, literal: &CompoundLiteral<'s>) -> String {
+        self.labels += 1;
+        let symbol = format!(".Lcompound{}", self.labels);
+        let ty = types::unqualified(&literal.ty);
+        let init = self.static_data(&ty, &literal.init);
+        self.static_order.push(symbol.clone());
+        let definition = StaticDefinition {
+            ty,
+            internal: true,
+            defined: true,
+            align: None,
+            init: Some(init),
+        };
+        self.statics.insert(symbol.clone(), definition);
+        symbol
+    }
+    /// The symbol the object or function `name` is emitted as.
+    pub(crate) fn symbol(&self, name: &str) -> String {
+        match self.lookup(name) {
+            Some(Binding::Static(object)) => object.symbol.clone(),
+            _ => name.to_owned(),
+        }
+    }
+    /// Builds the objects defined by the program, in the order they were first declared.
+    pub(crate) fn static_objects(&self) -> Vec<StaticObject> {
+        let mut objects = Vec::new();
+        for symbol in &self.static_order {
+            let definition = &self.statics[symbol];
+            if !definition.defined {
+                continue;
+            }
+            // A tentative definition of an array of unknown length gives it one element
+            // (C17 6.9.2)
+            let ty = match &definition.ty {
+                Type::Array(elem, None) => Type::Array(elem.clone(), Some(1)),
+                ty => ty.clone(),
+            };
+            let init = match &definition.init {
+                Some(init) => init.clone(),
+                None => vec![Data::Zero(self.size_of(&ty))],
+            };
+            objects.push(StaticObject {
+                name: symbol.clone(),
+                global: !definition.internal,
+                read_only: false,
+                align: self.align_of(&ty).max(definition.align.unwrap_or(1)),
+                init,
+            });
+        }
+        objects
+    }
+}
+
+impl<'s> ConstContext<'s> for Codegen<'s> {
+    fn enum_constant(&self, name: &str) -> Option<i64> {
+        match self.lookup(name)? {
+            &Binding::Constant(value) => Some(value),
+            _ => None,
+        }
+    }
+    fn static_object(&self, name: &str) -> Option<Type> {
+        match self.lookup(name) {
+            Some(Binding::Static(object)) => Some(object.ty.clone()),
+            Some(_) => None,
+            None => Some(Type::Function(Box::new(self.functions.get(name)?.clone()))),
+        }
+    }
+    fn layouts(&self) -> &Layouts<'s> {
+        &self.records
+    }
+    /// Constants are only evaluated for the initializers of objects with static storage
+    /// duration, where the parser only accepts the addresses of static compound literals.
+    fn static_compound_literals(&self) -> bool {
+        true
+    }
+    fn type_of(&mut self, expr: &SExpr<'s>) -> Option<Type> {
+        Some(self.type_of_operand(expr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::tests::{compile, run};
+
+    #[test]
+    fn static_data() {
+        let asm = compile(
+            "int zeros[100]; static long l = 3;
+            int main(void) { static short h = -2; return zeros[99] + l + h; }",
+        )
+        .unwrap();
+        assert!(asm.contains("\t.globl zeros\n\t.bss\n\t.balign 4\nzeros:\n\t.zero 400\n"));
+        // Internal linkage keeps `l` out of the symbols visible to the linker
+        assert!(asm.contains("\t.data\n\t.balign 8\nl:\n\t.quad 3\n"));
+        assert!(!asm.contains(".globl l\n"));
+        assert!(asm.contains("\t.data\n\t.balign 2\nh.1:\n\t.short -2\n"));
+    }
+
+    #[test]
+    fn static_initializers() {
+        // Members and elements without an initializer are zero-filled, as is the padding
+        let asm = compile(
+            "static struct { char c; long l; } s = { 1 };
+            int a[4] = { [2] = 7, 8 };",
+        )
+        .unwrap();
+        assert!(asm.contains("\t.data\n\t.balign 8\ns:\n\t.byte 1\n\t.zero 15\n"));
+        assert!(
+            asm.contains("a:\n\t.zero 8\n\t.long 7\n\t.long 8\n"),
+            "{asm}"
+        );
+    }
+
+    #[test]
+    fn static_compound_literals() {
+        let asm = compile("int *p = (int[]){5, 6};").unwrap();
+        assert!(asm.contains("p:\n\t.quad .Lcompound1+0\n"), "{asm}");
+        assert!(
+            asm.contains(".Lcompound1:\n\t.long 5\n\t.long 6\n"),
+            "{asm}"
+        );
+        let text = "struct p { int x, y; };
+        int *cp = (int[]){5, 6};
+        struct p *gp = &(struct p){8, 9};
+        int *nested[] = { (int[]){1}, &(int){2} };
+        int main(void) { return cp[1] + gp->y + *nested[0] + *nested[1] + (cp != nested[0]); }";
+        assert_eq!(run("static_compound_literals", text), 19);
+    }
+
+    #[test]
+    fn string_literals() {
+        let asm = compile("int main(void) { return sizeof(u\"hi\") + *\"a\"; }").unwrap();
+        assert!(asm.contains("\t.section .rodata\n\t.balign 1\n.Lstr1:\n\t.byte 97\n\t.byte 0\n"));
+        let text = "int puts(const char *);
+        int main(void) {
+            char *s = \"hi\\\\n\";
+            unsigned short *u = u\"\\xffff\";
+            return s[0] + s[2] - '\\\\' + (u[0] == 65535) + puts(s) * 0;
+        }";
+        assert_eq!(run("string_literals", text), 105);
+    }
+
+    #[test]
+    fn string_address_constants() {
+        let asm = compile("const char *g = \"hi\" + 1;").unwrap();
+        assert!(asm.contains("g:\n\t.quad .Lstr1+1\n"), "{asm}");
+        let text = "const char *g = \"hi\", *h = &\"abc\"[2];
+        char (*a)[3] = &\"ab\";
+        int main(void) {
+            static const char *s[] = { \"x\", \"yz\" };
+            return g[1] + *h + (*a)[1] - s[0][0] - s[1][1] + (g != s[0]);
+        }";
+        assert_eq!(run("string_address_constants", text), 61);
+    }
+}
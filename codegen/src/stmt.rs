@@ -0,0 +1,329 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use asm::{Cond, Instruction, Operand, Register, Size};
+use ast::{Block, BlockItem, For, ForInit, SExpr, SStmt, Spanned, Stmt, Type, VarDecl};
+use utils::VarInt;
+
+use crate::{Codegen, FLOATING, Switch, stack_slot, types};
+
+impl<'s> Codegen<'s> {
+    pub(crate) fn codegen_block(&mut self, block: &Block<'s>) {
+        self.scopes.push(Default::default());
+        for item in &block.0 {
+            match item {
+                BlockItem::Decl(decls) => self.codegen_declaration(decls),
+                BlockItem::Type(decl) => self.declare_type(&decl.inner),
+                BlockItem::Stmt(stmt) => self.codegen_statement(stmt),
+            }
+        }
+        self.scopes.pop();
+    }
+    fn codegen_declaration(&mut self, decls: &[Spanned<VarDecl<'s>>]) {
+        for Spanned { inner: decl, span } in decls {
+            self.span = span.clone();
+            let VarDecl {
+                name,
+                storage,
+                ty,
+                align,
+                init,
+            } = decl;
+            let ty = types::unqualified(ty);
+            if let Type::Function(function) = ty {
+                self.declare_function(name, *function);
+                continue;
+            }
+            if storage.is_some() {
+                self.declare_block_static(decl);
+                continue;
+            }
+            // The variable is in scope in its own initializer
+            let offset = self.declare_local(name, ty.clone(), *align);
+            if let Some(init) = init {
+                self.initialize_local(&ty, offset, init);
+            }
+        }
+    }
+    /// Jumps to `label` if the value in `%rax` is zero.
+    pub(crate) fn jump_if_zero(&mut self, label: &str) {
+        self.emit(Instruction::Cmp {
+            size: Size::Quad,
+            rhs: Operand::Imm(VarInt::Pos(0)),
+            lhs: Operand::Reg(Register::AX),
+        });
+        self.emit(Instruction::JmpCC {
+            cond: Cond::E,
+            label: label.to_owned(),
+        });
+    }
+    fn codegen_condition(&mut self, cond: &SExpr<'s>, false_label: &str) {
+        let ty = self.codegen_expr(cond);
+        if types::is_floating(&ty) {
+            self.unsupported(FLOATING);
+        }
+        self.jump_if_zero(false_label);
+    }
+    /// Generates a loop body, with `break` and `continue` jumping to the given labels.
+    fn codegen_loop_body(&mut self, body: &SStmt<'s>, break_label: &str, continue_label: &str) {
+        self.break_labels.push(break_label.to_owned());
+        self.continue_labels.push(continue_label.to_owned());
+        self.codegen_statement(body);
+        self.break_labels.pop();
+        self.continue_labels.pop();
+    }
+    fn codegen_for(&mut self, for_loop: &For<'s>) {
+        let For {
+            init,
+            cond,
+            step,
+            body,
+        } = for_loop;
+        self.scopes.push(Default::default());
+        match init {
+            Some(ForInit::Decl(decls)) => self.codegen_declaration(decls),
+            Some(ForInit::Expr(init)) => _ = self.codegen_expr(init),
+            None => (),
+        }
+        let (start, next, end) = (self.new_label(), self.new_label(), self.new_label());
+        self.emit(Instruction::Label(start.clone()));
+        if let Some(cond) = cond {
+            self.codegen_condition(cond, &end);
+        }
+        self.codegen_loop_body(body, &end, &next);
+        self.emit(Instruction::Label(next));
+        if let Some(step) = step {
+            self.codegen_expr(step);
+        }
+        self.emit(Instruction::Jmp(start));
+        self.emit(Instruction::Label(end));
+        self.scopes.pop();
+    }
+    /// Collects the labels of the cases belonging to a switch statement, skipping nested switches.
+    fn collect_cases(&mut self, stmt: &SStmt<'s>, switch: &mut Switch) {
+        match &stmt.inner {
+            Stmt::Case { body, .. } => {
+                switch.cases.push(self.new_label());
+                self.collect_cases(body, switch);
+            }
+            Stmt::Default(body) => {
+                switch.default = Some(self.new_label());
+                self.collect_cases(body, switch);
+            }
+            Stmt::Block(block) => {
+                for item in &block.0 {
+                    if let BlockItem::Stmt(stmt) = item {
+                        self.collect_cases(stmt, switch);
+                    }
+                }
+            }
+            Stmt::If {
+                then_body,
+                else_body,
+                ..
+            } => {
+                self.collect_cases(then_body, switch);
+                if let Some(else_body) = else_body {
+                    self.collect_cases(else_body, switch);
+                }
+            }
+            Stmt::While { body, .. } | Stmt::DoWhile { body, .. } | Stmt::Labeled { body, .. } => {
+                self.collect_cases(body, switch)
+            }
+            Stmt::For(for_loop) => self.collect_cases(&for_loop.body, switch),
+            _ => (),
+        }
+    }
+    /// Finds the value of every case label in `stmt`, in the same order as [Self::collect_cases].
+    fn case_values<'a>(stmt: &'a SStmt<'s>, values: &mut Vec<&'a SExpr<'s>>) {
+        match &stmt.inner {
+            Stmt::Case { value, body } => {
+                values.push(value);
+                Self::case_values(body, values);
+            }
+            Stmt::Default(body)
+            | Stmt::While { body, .. }
+            | Stmt::DoWhile { body, .. }
+            | Stmt::Labeled { body, .. } => Self::case_values(body, values),
+            Stmt::For(for_loop) => Self::case_values(&for_loop.body, values),
+            Stmt::Block(block) => {
+                for item in &block.0 {
+                    if let BlockItem::Stmt(stmt) = item {
+                        Self::case_values(stmt, values);
+                    }
+                }
+            }
+            Stmt::If {
+                then_body,
+                else_body,
+                ..
+            } => {
+                Self::case_values(then_body, values);
+                if let Some(else_body) = else_body {
+                    Self::case_values(else_body, values);
+                }
+            }
+            _ => (),
+        }
+    }
+    fn codegen_switch(&mut self, value: &SExpr<'s>, body: &SStmt<'s>) {
+        let value_ty = self.codegen_expr(value);
+        let ty = types::promote(&value_ty);
+        self.convert(&value_ty, &ty);
+        // The controlling value is kept in an unnamed local while the cases are compared
+        let slot = stack_slot(self.allocate(&types::LONG));
+        self.store(&types::LONG, slot.clone());
+        let mut switch = Switch::default();
+        self.collect_cases(body, &mut switch);
+        let mut values = Vec::new();
+        Self::case_values(body, &mut values);
+        for (value, label) in values.into_iter().zip(&switch.cases) {
+            let case_ty = self.codegen_expr(value);
+            self.convert(&case_ty, &ty);
+            self.emit(Instruction::Cmp {
+                size: Size::Quad,
+                rhs: slot.clone(),
+                lhs: Operand::Reg(Register::AX),
+            });
+            self.emit(Instruction::JmpCC {
+                cond: Cond::E,
+                label: label.clone(),
+            });
+        }
+        let end = self.new_label();
+        self.emit(Instruction::Jmp(
+            switch.default.clone().unwrap_or_else(|| end.clone()),
+        ));
+        // Cases are popped off the front as they're reached
+        switch.cases.reverse();
+        self.switches.push(switch);
+        self.break_labels.push(end.clone());
+        self.codegen_statement(body);
+        self.break_labels.pop();
+        self.switches.pop();
+        self.emit(Instruction::Label(end));
+    }
+    pub fn codegen_statement(&mut self, stmt: &SStmt<'s>) {
+        self.span = stmt.span.clone();
+        match &stmt.inner {
+            Stmt::Expr(expr) => _ = self.codegen_expr(expr),
+            Stmt::Return(val) => {
+                if let Some(val) = val {
+                    let ty = self.codegen_expr(val);
+                    let ret = self.functions[self.function].ret.clone();
+                    if types::is_record(&ret) {
+                        self.return_record(&ret);
+                    } else {
+                        self.convert(&ty, &ret);
+                    }
+                }
+                self.emit_return();
+            }
+            Stmt::Block(block) => self.codegen_block(block),
+            Stmt::If {
+                cond,
+                then_body,
+                else_body,
+            } => {
+                let (else_label, end) = (self.new_label(), self.new_label());
+                self.codegen_condition(cond, &else_label);
+                self.codegen_statement(then_body);
+                self.emit(Instruction::Jmp(end.clone()));
+                self.emit(Instruction::Label(else_label));
+                if let Some(else_body) = else_body {
+                    self.codegen_statement(else_body);
+                }
+                self.emit(Instruction::Label(end));
+            }
+            Stmt::While { cond, body } => {
+                let (start, end) = (self.new_label(), self.new_label());
+                self.emit(Instruction::Label(start.clone()));
+                self.codegen_condition(cond, &end);
+                self.codegen_loop_body(body, &end, &start);
+                self.emit(Instruction::Jmp(start));
+                self.emit(Instruction::Label(end));
+            }
+            Stmt::DoWhile { body, cond } => {
+                let (start, next, end) = (self.new_label(), self.new_label(), self.new_label());
+                self.emit(Instruction::Label(start.clone()));
+                self.codegen_loop_body(body, &end, &next);
+                self.emit(Instruction::Label(next));
+                self.codegen_condition(cond, &end);
+                self.emit(Instruction::Jmp(start));
+                self.emit(Instruction::Label(end));
+            }
+            Stmt::For(for_loop) => self.codegen_for(for_loop),
+            Stmt::Break => {
+                let label = self.break_labels.last().expect("`break` outside of a loop");
+                self.emit(Instruction::Jmp(label.clone()));
+            }
+            Stmt::Continue => {
+                let label = self
+                    .continue_labels
+                    .last()
+                    .expect("`continue` outside of a loop");
+                self.emit(Instruction::Jmp(label.clone()));
+            }
+            Stmt::Goto(label) => {
+                let label = format!(".L{}.{label}", self.function);
+                self.emit(Instruction::Jmp(label));
+            }
+            Stmt::Labeled { label, body } => {
+                let label = format!(".L{}.{label}", self.function);
+                self.emit(Instruction::Label(label));
+                self.codegen_statement(body);
+            }
+            Stmt::Switch { value, body } => self.codegen_switch(value, body),
+            Stmt::Case { body, .. } => {
+                let switch = self
+                    .switches
+                    .last_mut()
+                    .expect("`case` outside of a switch");
+                let label = switch.cases.pop().unwrap();
+                self.emit(Instruction::Label(label));
+                self.codegen_statement(body);
+            }
+            Stmt::Default(body) => {
+                let switch = self
+                    .switches
+                    .last_mut()
+                    .expect("`default` outside of a switch");
+                let label = switch.default.clone().unwrap();
+                self.emit(Instruction::Label(label));
+                self.codegen_statement(body);
+            }
+            Stmt::Null => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::tests::run;
+
+    #[test]
+    fn shadowed_locals() {
+        let text = "int main(void) {
+            int x = 1, r;
+            { int x = 2; x += 10; r = x; }
+            { char x = 3; { long x = 100; r += x / 100; } r += x; }
+            return r * 10 + x;
+        }";
+        assert_eq!(run("shadowed_locals", text), 161);
+    }
+}
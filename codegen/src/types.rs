@@ -0,0 +1,95 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! The implicit conversions of C17 6.3 for the System V x86-64 ABI.
+
+use ast::{FunctionType, ScalarType, TagKind, Type};
+
+pub(crate) const INT: Type = Type::Scalar(ScalarType::Int);
+pub(crate) const ULONG: Type = Type::Scalar(ScalarType::ULong);
+pub(crate) const LONG: Type = Type::Scalar(ScalarType::Long);
+
+/// Removes the qualifiers from a type and every type it's derived from, as they don't affect
+/// the generated code. Enums are replaced by `int`, the type of their constants, and
+/// `nullptr_t` by `void *`, which has the same representation.
+pub(crate) fn unqualified(ty: &Type) -> Type {
+    match ty {
+        Type::Scalar(ScalarType::NullPtr) => {
+            Type::Pointer(Box::new(Type::Scalar(ScalarType::Void)))
+        }
+        Type::Scalar(_) => ty.clone(),
+        Type::Tagged(tag) if tag.kind == TagKind::Enum => INT,
+        Type::Tagged(_) => ty.clone(),
+        Type::Pointer(pointee) => Type::Pointer(Box::new(unqualified(pointee))),
+        Type::Array(elem, len) => Type::Array(Box::new(unqualified(elem)), *len),
+        Type::Function(function) => Type::Function(Box::new(unqualified_function(function))),
+        Type::Qualified(_, ty) => unqualified(ty),
+    }
+}
+
+pub(crate) fn unqualified_function(function: &FunctionType) -> FunctionType {
+    FunctionType {
+        ret: unqualified(&function.ret),
+        params: function
+            .params
+            .as_ref()
+            .map(|params| params.iter().map(unqualified).collect()),
+        variadic: function.variadic,
+    }
+}
+
+pub(crate) fn is_integer(ty: &Type) -> bool {
+    matches!(ty, Type::Scalar(s) if s.is_integer())
+}
+
+pub(crate) fn is_floating(ty: &Type) -> bool {
+    use ScalarType::*;
+    matches!(ty, Type::Scalar(Float | Double | LongDouble))
+}
+
+/// Whether values of this type are sign-extended when widened.
+pub(crate) fn is_signed(ty: &Type) -> bool {
+    matches!(ty, Type::Scalar(s) if s.is_signed())
+}
+
+/// Whether values of this type are structs or unions, which are copied rather than loaded.
+pub(crate) fn is_record(ty: &Type) -> bool {
+    matches!(ty, Type::Tagged(tag) if tag.kind != TagKind::Enum)
+}
+
+pub(crate) fn pointee(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Pointer(pointee) => Some(pointee),
+        _ => None,
+    }
+}
+
+/// Applies the integer promotions to an integer type, leaving other types unchanged.
+pub(crate) fn promote(ty: &Type) -> Type {
+    match ty {
+        Type::Scalar(scalar) => Type::Scalar(scalar.promoted()),
+        _ => ty.clone(),
+    }
+}
+
+/// The common type of two promoted integer operands, as given by the usual arithmetic
+/// conversions.
+pub(crate) fn common_type(a: &Type, b: &Type) -> Type {
+    let (&Type::Scalar(a), &Type::Scalar(b)) = (a, b) else {
+        unreachable!("only arithmetic types have a common type");
+    };
+    Type::Scalar(a.common(b))
+}
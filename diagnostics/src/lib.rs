@@ -78,6 +78,8 @@ impl Default for AggregateError {
 pub enum ErrorLevel {
     Error,
     Warning,
+    /// Extra information about the error or warning before it
+    Note,
 }
 
 #[derive(Debug, Clone)]
@@ -102,6 +104,14 @@ impl ErrorComponent {
             highlight_message: None,
         }
     }
+    #[must_use]
+    pub const fn source(&self) -> &SourceFile {
+        &self.source
+    }
+    #[must_use]
+    pub const fn highlight(&self) -> &Span {
+        &self.highlight
+    }
     pub fn set_highlight_message(&mut self, message: String) -> &mut Self {
         self.highlight_message = Some(message);
         self
@@ -134,6 +134,7 @@ impl RenderableError for ErrorComponent {
             highlight_message,
         } = self;
         let [start, end] = source.span_to_pos(highlight);
+        let location = source.line_location(start.line_0idx());
         let lines = {
             source
                 .lines()
@@ -145,6 +146,7 @@ impl RenderableError for ErrorComponent {
             crate::ErrorLevel::Warning => {
                 owo_colors::DynColors::Ansi(owo_colors::AnsiColors::Yellow)
             }
+            crate::ErrorLevel::Note => owo_colors::DynColors::Ansi(owo_colors::AnsiColors::Cyan),
         };
         writeln!(
             writer,
@@ -156,31 +158,51 @@ impl RenderableError for ErrorComponent {
             writer,
             "   {arrow} {path}:{line}:{col}",
             arrow = "-->".blue().bold(),
-            path = source.path(),
-            line = start.line().blue(),
+            path = location.map_or(source.path(), |l| l.path),
+            line = location.map_or(start.line(), |l| l.line).blue(),
             col = start.col().blue()
         )?;
+        for include in source.included_from(start.line_0idx()) {
+            write!(
+                writer,
+                "\n   {arrow} included from {path}:{line}",
+                arrow = ":::".blue().bold(),
+                path = include.path,
+                line = include.line.blue(),
+            )?;
+        }
         let mut prev_line_had_overlap = false;
-        for line in lines {
+        for mut line in lines {
+            // Only show the lines coming from the same file as the start of the highlight
+            match (source.line_location(line.line), location) {
+                (Some(line_location), Some(location)) if line_location.path == location.path => {
+                    line.line = line_location.line - 1;
+                }
+                (_, None) => {}
+                _ => continue,
+            }
             let overlap = highlight.start.max(line.span.start)..highlight.end.min(line.span.end);
-            let overlap = overlap
-                .start
-                .saturating_sub(line.span.start)
-                .min(line.text.len())
-                ..overlap
-                    .end
-                    .saturating_sub(line.span.start)
-                    .min(line.text.len());
+            // A highlight that continues onto the next line covers the newline, which isn't
+            // rendered
+            let len = line.text.strip_suffix('\n').unwrap_or(line.text).len();
+            let overlap = overlap.start.saturating_sub(line.span.start).min(len)
+                ..overlap.end.saturating_sub(line.span.start).min(len);
             if let Some(highlight_message) = highlight_message
                 && overlap.is_empty()
                 && prev_line_had_overlap
             {
-                write!(writer, "{}", highlight_message.red().bold())?;
+                write!(writer, " {}", highlight_message.red().bold())?;
             }
             writeln!(writer)?;
             ctx.render_line(writer, line, overlap.clone(), color)?;
             prev_line_had_overlap = !overlap.is_empty();
         }
+        // The highlighted line may be the last one in the file
+        if let Some(highlight_message) = highlight_message
+            && prev_line_had_overlap
+        {
+            write!(writer, " {}", highlight_message.red().bold())?;
+        }
         writeln!(writer)?;
         if self.long_message.is_empty() {
             return Ok(());
@@ -192,3 +214,79 @@ impl RenderableError for ErrorComponent {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use source::SourceFile;
+
+    use super::{RenderContext, RenderableError};
+    use crate::ErrorComponent;
+
+    /// Renders an error, without the escape sequences that color it.
+    fn render(text: &str, span: source::Span, highlight_message: Option<&str>) -> String {
+        let source = SourceFile::new("a.c".to_owned(), text.to_owned());
+        let mut error = ErrorComponent::new(source, "Bad thing".to_owned(), span);
+        if let Some(message) = highlight_message {
+            error.set_highlight_message(message.to_owned());
+        }
+        let rendered = error.display(RenderContext::default()).to_string();
+        let mut plain = String::new();
+        let mut chars = rendered.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    #[test]
+    fn highlight_messages_follow_the_carets() {
+        assert_eq!(
+            render("int x;\nint @y;\nint z;\n", 11..12, Some("here")),
+            "Error: Bad thing
+   --> a.c:2:5
+1   |int x;
+2   |int @y;
+    |    ^ here
+3   |int z;
+"
+        );
+        // The highlighted line is the last one
+        assert_eq!(
+            render("int x;\nint @y;", 11..12, Some("here")),
+            "Error: Bad thing
+   --> a.c:2:5
+1   |int x;
+2   |int @y;
+    |    ^ here
+"
+        );
+        assert_eq!(
+            render("int x;\nint @y;", 11..12, None),
+            "Error: Bad thing
+   --> a.c:2:5
+1   |int x;
+2   |int @y;
+    |    ^
+"
+        );
+    }
+
+    #[test]
+    fn highlights_across_lines() {
+        assert_eq!(
+            render("int x = a\n + 1;\nint z;\n", 8..14, Some("here")),
+            "Error: Bad thing
+   --> a.c:1:9
+1   |int x = a
+    |        ^
+2   | + 1;
+    |^^^^ here
+3   |int z;
+"
+        );
+    }
+}
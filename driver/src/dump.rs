@@ -0,0 +1,180 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! Printing tokens for `--lex`.
+
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+};
+
+use lexer::SToken;
+use source::SourceFile;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `<line>:<col>-<line>:<col> <kind> <text>`, with a `# <path>` line whenever the tokens
+    /// start coming from another file
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+fn write_json_string(out: &mut impl Write, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\t' => out.write_str("\\t")?,
+            '\r' => out.write_str("\\r")?,
+            c if c.is_control() => write!(out, "\\u{:04x}", u32::from(c))?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+/// The text of a token as written in the source.
+///
+/// Concatenated string literals are the only tokens spanning several lines, and are joined onto
+/// one with the line markers between their parts left out.
+fn source_text<'a>(source: &'a SourceFile, token: &SToken<'_>) -> Cow<'a, str> {
+    let text = &source.text()[token.span.clone()];
+    if !text.contains('\n') {
+        return Cow::Borrowed(text);
+    }
+    let lines: Vec<_> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    Cow::Owned(lines.join(" "))
+}
+
+/// Writes one line per token, with positions mapped back to the original files.
+pub fn dump_tokens<'s>(
+    out: &mut impl Write,
+    source: &SourceFile,
+    tokens: impl Iterator<Item = SToken<'s>>,
+    format: Format,
+) -> fmt::Result {
+    let mut current_path = source.path().to_owned();
+    for token in tokens {
+        let [start, end] = source.span_to_pos(&token.span);
+        let location = source.line_location(start.line_0idx());
+        let path = location.map_or(source.path(), |l| l.path);
+        // Only concatenated strings span multiple lines
+        let start_line = location.map_or(start.line(), |l| l.line);
+        let end_line = start_line + (end.line() - start.line());
+        let (start_col, end_col) = (start.col(), end.col());
+        let text = source_text(source, &token);
+        let kind = token.inner.kind();
+        match format {
+            Format::Text => {
+                if path != current_path {
+                    writeln!(out, "# {path}")?;
+                    path.clone_into(&mut current_path);
+                }
+                writeln!(
+                    out,
+                    "{start_line}:{start_col}-{end_line}:{end_col} {kind} {text}"
+                )?;
+            }
+            Format::Json => {
+                write!(out, "{{\"kind\":\"{kind}\",\"text\":")?;
+                write_json_string(out, &text)?;
+                write!(out, ",\"file\":")?;
+                write_json_string(out, path)?;
+                writeln!(
+                    out,
+                    ",\"start\":{{\"line\":{start_line},\"col\":{start_col}}},\
+                     \"end\":{{\"line\":{end_line},\"col\":{end_col}}}}}"
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use lexer::{ConcatStrings, Logos, SToken, Standard, Token};
+    use pretty_assertions::assert_eq;
+    use source::SourceFile;
+
+    use super::{Format, dump_tokens};
+
+    fn dump(text: &str, format: Format) -> String {
+        let source = SourceFile::new("a.c".to_owned(), text.to_owned());
+        let lexer = Token::lexer_with_extras(source.text(), Standard::C17).spanned();
+        let tokens =
+            ConcatStrings::new(lexer).map(|(token, span)| SToken::new(token.unwrap(), span));
+        let mut out = String::new();
+        dump_tokens(&mut out, &source, tokens, format).unwrap();
+        out
+    }
+
+    #[test]
+    fn text_format() {
+        assert_eq!(
+            dump("x = 0x1F + 017 * 1e3;\nc = '\\a';", Format::Text),
+            "1:1-1:1 Ident x
+1:3-1:3 Eq =
+1:5-1:8 IntLit 0x1F
+1:10-1:10 Plus +
+1:12-1:14 IntLit 017
+1:16-1:16 Star *
+1:18-1:20 FloatLit 1e3
+1:21-1:21 Semicolon ;
+2:1-2:1 Ident c
+2:3-2:3 Eq =
+2:5-2:8 CharLit '\\a'
+2:9-2:9 Semicolon ;
+"
+        );
+        // Tokens from other files are preceded by their path
+        assert_eq!(
+            dump(
+                "# 1 \"b.h\"\nf(\"a\"\n  \"b\");\n# 7 \"a.c\"\n}",
+                Format::Text
+            ),
+            "# b.h
+1:1-1:1 Ident f
+1:2-1:2 LParen (
+1:3-2:5 StringLit \"a\" \"b\"
+2:6-2:6 RParen )
+2:7-2:7 Semicolon ;
+# a.c
+7:1-7:1 RBrace }
+"
+        );
+    }
+
+    #[test]
+    fn json_format() {
+        assert_eq!(
+            dump("s = \"\\t\";", Format::Json),
+            r#"{"kind":"Ident","text":"s","file":"a.c","start":{"line":1,"col":1},"end":{"line":1,"col":1}}
+{"kind":"Eq","text":"=","file":"a.c","start":{"line":1,"col":3},"end":{"line":1,"col":3}}
+{"kind":"StringLit","text":"\"\\t\"","file":"a.c","start":{"line":1,"col":5},"end":{"line":1,"col":8}}
+{"kind":"Semicolon","text":";","file":"a.c","start":{"line":1,"col":9},"end":{"line":1,"col":9}}
+"#
+        );
+    }
+}
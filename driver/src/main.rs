@@ -21,17 +21,22 @@ use diagnostics::{
     AggregateError, ErrorComponent,
     render::{RenderContext, RenderableError},
 };
-use lexer::{Logos, SToken};
+use dump::{Format, dump_tokens};
+use lexer::{ConcatStrings, Logos, SToken, Standard};
+use preprocessor::Preprocessor;
+use syntax::SyntaxKind;
 
 use std::{
     ffi::{OsStr, OsString},
     fs::File,
-    io::{self, stdout, Write},
+    io::{self, Write, stdout},
     path::PathBuf,
     process::Command,
 };
 use std::{fmt, path::Path};
 
+mod dump;
+
 struct FmtToIoWrite<W: io::Write>(pub W);
 impl<W: io::Write> fmt::Write for FmtToIoWrite<W> {
     fn write_str(&mut self, s: &str) -> fmt::Result {
@@ -39,8 +44,15 @@ impl<W: io::Write> fmt::Write for FmtToIoWrite<W> {
     }
 }
 
+const SYSTEM_INCLUDE_PATHS: &[&str] = &[
+    "/usr/local/include",
+    "/usr/include/x86_64-linux-gnu",
+    "/usr/include",
+];
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
 enum Stage {
+    Preprocess,
     Lex,
     Parse,
     Codegen,
@@ -52,13 +64,73 @@ enum Stage {
 #[derive(Debug, Default, Clone)]
 struct Config {
     stop_at_stage: Stage,
+    standard: Standard,
+    /// The format of `--lex` output
+    format: Format,
+    /// Whether the printed tree includes the span of every node
+    spans: bool,
+    /// Whether the lossless syntax tree is printed instead of the AST
+    syntax: bool,
+    /// Whether the program is printed back as C source instead of the AST
+    unparse: bool,
+    include_paths: Vec<PathBuf>,
+    /// `-D` and `-U` flags in order, with [None] for `-U`
+    macros: Vec<(String, Option<String>)>,
 }
 
 impl Config {
     fn from_flags<S: AsRef<OsStr>>(args: impl Iterator<Item = S>) -> Self {
         let mut config = Config::default();
         for flag in args {
-            config.stop_at_stage = match &flag.as_ref().as_encoded_bytes()[1..] {
+            let flag = &flag.as_ref().as_encoded_bytes()[1..];
+            let value = || String::from_utf8_lossy(&flag[1..]).into_owned();
+            match flag.first() {
+                Some(b'I') => {
+                    config.include_paths.push(PathBuf::from(value()));
+                    continue;
+                }
+                Some(b'D') => {
+                    let value = value();
+                    let (name, value) = value.split_once('=').unwrap_or((&value, "1"));
+                    config
+                        .macros
+                        .push((name.to_owned(), Some(value.to_owned())));
+                    continue;
+                }
+                Some(b'U') => {
+                    config.macros.push((value(), None));
+                    continue;
+                }
+                _ => (),
+            }
+            if let Some(format) = flag.strip_prefix(b"-format=") {
+                config.format = match format {
+                    b"json" => Format::Json,
+                    _ => Format::Text,
+                };
+                continue;
+            }
+            if flag == b"-spans" {
+                config.spans = true;
+                continue;
+            }
+            if flag == b"-syntax" {
+                config.syntax = true;
+                continue;
+            }
+            if flag == b"-unparse" {
+                config.unparse = true;
+                continue;
+            }
+            if let Some(std) = flag.strip_prefix(b"std=") {
+                config.standard = match std {
+                    b"c23" | b"c2x" | b"gnu23" | b"gnu2x" => Standard::C23,
+                    _ => Standard::C17,
+                };
+                continue;
+            }
+            config.stop_at_stage = match flag {
+                b"E" => Stage::Preprocess,
                 b"-lex" => Stage::Lex,
                 b"-parse" => Stage::Parse,
                 b"-codegen" => Stage::Codegen,
@@ -78,52 +150,94 @@ impl Driver {
     pub fn new(config: Config) -> Self {
         Self { config }
     }
+    fn preprocessor(&self) -> Preprocessor {
+        let mut preprocessor = Preprocessor::new(self.config.standard);
+        for path in &self.config.include_paths {
+            preprocessor.add_include_path(path);
+        }
+        for path in SYSTEM_INCLUDE_PATHS {
+            preprocessor.add_system_include_path(path);
+        }
+        for (name, value) in &self.config.macros {
+            match value {
+                Some(value) => preprocessor.define(name, value),
+                None => preprocessor.undefine(name),
+            }
+        }
+        preprocessor
+    }
     fn run(&mut self, paths: Vec<OsString>) -> AggregateError {
         let mut errors = AggregateError::new();
         for path in paths {
             let input_path = PathBuf::from(path);
             // let input_file = File::open(&path).expect("the input file should be accessible");
-            let preprocessed_path = input_path.with_added_extension("i");
-            Command::new("gcc")
-                .args(["-E", "-P"])
-                .arg(&input_path)
-                .arg("-o")
-                .arg(&preprocessed_path)
-                .spawn()
-                .unwrap()
-                .wait()
-                .unwrap();
-
-            let source = source::SourceFile::new(
-                input_path.to_string_lossy().to_string(),
-                std::fs::read_to_string(&preprocessed_path).unwrap(),
-            );
-            let lexer = lexer::Token::lexer(source.text())
-                .spanned()
-                .map(|r| match r {
-                    (Ok(token), span) => Ok(SToken::new(token, span)),
-                    (Err(()), span) => Err(ErrorComponent::new(
-                        source.clone(),
-                        String::from("Failed to lex token"),
-                        span,
-                    )),
-                });
+            let (preprocessed, mut preprocessor_errors) =
+                self.preprocessor().preprocess(&input_path);
+            let source = preprocessed.source().clone();
+            let failed = preprocessor_errors.has_error();
+            errors.append(&mut preprocessor_errors);
+            if failed {
+                continue;
+            }
+            if self.config.stop_at_stage == Stage::Preprocess {
+                print!("{}", source.text());
+                continue;
+            }
+            let lexer = ConcatStrings::new(preprocessed.tokens()).map(|r| match r {
+                (Ok(token), span) => Ok(SToken::new(token, span)),
+                (Err(err), span) => Err(err.to_component(&source, span)),
+            });
             if self.config.stop_at_stage == Stage::Lex {
-                for error in lexer.filter_map(Result::err) {
-                    errors.add_error(error);
+                let mut tokens = Vec::new();
+                let mut lexer_errors = AggregateError::new();
+                for token in lexer {
+                    match token {
+                        Ok(token) => tokens.push(token),
+                        Err(error) => _ = lexer_errors.add_error(error),
+                    }
+                }
+                preprocessed.explain(&mut lexer_errors);
+                errors.append(&mut lexer_errors);
+                let mut writer = FmtToIoWrite(stdout().lock());
+                // The reader may have gone away, as with `| head`, leaving nothing more to do
+                if dump_tokens(&mut writer, &source, tokens.into_iter(), self.config.format)
+                    .is_err()
+                {
+                    break;
                 }
                 continue;
             }
             let mut parser = parser::Parser::new(source.clone(), lexer);
             let (program, mut parser_errors) = parser.parse();
+            preprocessed.explain(&mut parser_errors);
             errors.append(&mut parser_errors);
 
             let writer = stdout();
             let mut writer = FmtToIoWrite(writer);
-            let mut ctx = TreeCtx::new();
-            program.fmt_tree(&mut ctx, &mut writer).unwrap();
+            if self.config.syntax {
+                // The tree is of the file as it was written, directives and all
+                let tokens = preprocessed.spelled_tokens().map(|(token, span)| {
+                    let kind = token.map_or(SyntaxKind::Error, |token| SyntaxKind::from(&token));
+                    (kind, span)
+                });
+                let tree = syntax::build_original(
+                    preprocessed.file().clone(),
+                    tokens,
+                    &program.syntax,
+                    |span| preprocessed.original_span(span),
+                );
+                print!("{tree:?}");
+            } else if self.config.unparse {
+                print!("{}", program.to_c());
+            } else {
+                let mut ctx = TreeCtx::new();
+                if self.config.spans {
+                    ctx = ctx.with_spans(source.clone());
+                }
+                program.fmt_tree(&mut ctx, &mut writer).unwrap();
+            }
 
-            if !errors.is_empty() {
+            if errors.has_error() {
                 continue;
             }
 
@@ -131,8 +245,13 @@ impl Driver {
                 continue;
             }
 
-            let codegen = Codegen::new();
-            let asm = codegen.codegen_program(&program);
+            let mut codegen = Codegen::new(source.clone());
+            let (asm, mut codegen_errors) = codegen.codegen_program(&program);
+            preprocessed.explain(&mut codegen_errors);
+            errors.append(&mut codegen_errors);
+            if errors.has_error() {
+                continue;
+            }
             dbg!(&asm);
 
             if self.config.stop_at_stage == Stage::Codegen {
@@ -174,8 +293,11 @@ fn main() {
     let mut driver = Driver::new(config);
     let errors = driver.run(args);
     if !errors.is_empty() {
+        let failed = errors.has_error();
         let render_context = RenderContext::default();
         eprint!("{}", errors.display(render_context));
-        std::process::exit(1);
+        if failed {
+            std::process::exit(1);
+        }
     }
 }
@@ -0,0 +1,75 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use std::iter::FusedIterator;
+
+use logos::Span;
+
+use crate::{LexError, Token};
+
+type Item<'s> = (Result<Token<'s>, LexError>, Span);
+
+/// Concatenates adjacent string literal tokens, translation phase 6 in C17 5.1.1.2.
+///
+/// The span of a concatenated literal covers all of its parts.
+#[derive(Debug, Clone)]
+pub struct ConcatStrings<'s, I> {
+    tokens: I,
+    pending: Option<Item<'s>>,
+}
+
+impl<'s, I: Iterator<Item = Item<'s>>> ConcatStrings<'s, I> {
+    pub const fn new(tokens: I) -> Self {
+        Self {
+            tokens,
+            pending: None,
+        }
+    }
+}
+
+impl<'s, I: Iterator<Item = Item<'s>>> Iterator for ConcatStrings<'s, I> {
+    type Item = Item<'s>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (token, mut span) = self.pending.take().or_else(|| self.tokens.next())?;
+        let Ok(Token::StringLit(mut literal)) = token else {
+            return Some((token, span));
+        };
+        let mut error = None;
+        loop {
+            match self.tokens.next() {
+                Some((Ok(Token::StringLit(next)), next_span)) => {
+                    if !literal.concat(next) {
+                        error.get_or_insert(LexError::MixedEncodings {
+                            span: span.start..next_span.end,
+                        });
+                    }
+                    span.end = next_span.end;
+                }
+                next => {
+                    self.pending = next;
+                    break;
+                }
+            }
+        }
+        match error {
+            Some(e) => Some((Err(e), span)),
+            None => Some((Ok(Token::StringLit(literal)), span)),
+        }
+    }
+}
+
+impl<'s, I: FusedIterator<Item = Item<'s>>> FusedIterator for ConcatStrings<'s, I> {}
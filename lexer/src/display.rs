@@ -20,46 +20,242 @@ use std::fmt::Display;
 impl Display for Token<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use Token::*;
-        let lit = match self {
-            // Else => "else",
-            // For => "for",
-            // If => "if",
-            Return => "return",
-            // While => "while",
-            // Break => "break",
-            // Continue => "continue",
+        match self {
+            StringLit(s) => s.fmt(f),
+            CharLit(c) => c.fmt(f),
+            IntLit(v) => v.fmt(f),
+            FloatLit(v) => v.fmt(f),
+            Ident(i) => f.write_str(i),
+            Invalid => f.write_str("<invalid>"),
+            _ => f.write_str(self.spelling().unwrap()),
+        }
+    }
+}
 
+impl Token<'_> {
+    /// The text of a keyword or punctuator, which is the same for every occurrence, or [None]
+    /// for identifiers and literals.
+    #[must_use]
+    pub const fn spelling(&self) -> Option<&'static str> {
+        use Token::*;
+        Some(match self {
+            Break => "break",
+            Case => "case",
+            Continue => "continue",
+            Default => "default",
+            Do => "do",
+            Else => "else",
+            For => "for",
+            Goto => "goto",
+            If => "if",
+            Return => "return",
+            Switch => "switch",
+            While => "while",
             Void => "void",
+            Char => "char",
+            Short => "short",
             Int => "int",
+            Long => "long",
+            Float => "float",
+            Double => "double",
+            Signed => "signed",
+            Unsigned => "unsigned",
+            Bool => "_Bool",
+            Complex => "_Complex",
+            Imaginary => "_Imaginary",
+            Struct => "struct",
+            Union => "union",
+            Enum => "enum",
+            Auto => "auto",
+            Extern => "extern",
+            Register => "register",
+            Static => "static",
+            Typedef => "typedef",
+            ThreadLocal => "_Thread_local",
+            Const => "const",
+            Restrict => "restrict",
+            Volatile => "volatile",
+            Atomic => "_Atomic",
+            Inline => "inline",
+            Noreturn => "_Noreturn",
+            Sizeof => "sizeof",
+            Alignas => "_Alignas",
+            Alignof => "_Alignof",
+            Generic => "_Generic",
+            StaticAssert => "_Static_assert",
+            True => "true",
+            False => "false",
+            Nullptr => "nullptr",
+            Constexpr => "constexpr",
+            Typeof => "typeof",
+            TypeofUnqual => "typeof_unqual",
 
-            // BoolLit(true) => "true",
-            // BoolLit(false) => "false",
-            // StringLit(s) => return write!(f, "{s:?}"),
-            // CharLiteral(c) => return c.fmt(f),
-            IntLit(v) => return v.fmt(f),
-            // FloatLit(v) => return v.fmt(f),
-            Ident(i) => i,
+            StringLit(_) | CharLit(_) | IntLit(_) | FloatLit(_) | Ident(_) | Invalid => {
+                return None;
+            }
 
-            // EqEq => "==",
-            // Ne => "!=",
-            // Le => "<=",
-            // Ge => ">=",
-            // Plus => "+",
-            // Minus => "-",
-            // Star => "*",
-            // Slash => "/",
+            LBracket => "[",
+            RBracket => "]",
             LParen => "(",
             RParen => ")",
             LBrace => "{",
             RBrace => "}",
+            Dot => ".",
+            Arrow => "->",
+            PlusPlus => "++",
+            MinusMinus => "--",
+            Ampersand => "&",
+            Star => "*",
+            Plus => "+",
+            Minus => "-",
+            Tilde => "~",
+            Not => "!",
+            Slash => "/",
+            Percent => "%",
+            Shl => "<<",
+            Shr => ">>",
+            Lt => "<",
+            Gt => ">",
+            Le => "<=",
+            Ge => ">=",
+            EqEq => "==",
+            Ne => "!=",
+            BitXor => "^",
+            BitOr => "|",
+            And => "&&",
+            Or => "||",
+            Question => "?",
+            Colon => ":",
             Semicolon => ";",
-            // // Comma => ",",
-            // Dot => ".",
-            // Eq => "=",
-            // Not => "!",
-            // Gt => ">",
-            // Lt => "<",
-        };
-        f.write_str(lit)
+            Ellipsis => "...",
+            Eq => "=",
+            StarEq => "*=",
+            SlashEq => "/=",
+            PercentEq => "%=",
+            PlusEq => "+=",
+            MinusEq => "-=",
+            ShlEq => "<<=",
+            ShrEq => ">>=",
+            AmpersandEq => "&=",
+            BitXorEq => "^=",
+            BitOrEq => "|=",
+            Comma => ",",
+            Hash => "#",
+            HashHash => "##",
+        })
+    }
+}
+
+impl Token<'_> {
+    /// The name of the token's variant, for dumping tokens.
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        use Token::*;
+        match self {
+            Break => "Break",
+            Case => "Case",
+            Continue => "Continue",
+            Default => "Default",
+            Do => "Do",
+            Else => "Else",
+            For => "For",
+            Goto => "Goto",
+            If => "If",
+            Return => "Return",
+            Switch => "Switch",
+            While => "While",
+            Void => "Void",
+            Char => "Char",
+            Short => "Short",
+            Int => "Int",
+            Long => "Long",
+            Float => "Float",
+            Double => "Double",
+            Signed => "Signed",
+            Unsigned => "Unsigned",
+            Bool => "Bool",
+            Complex => "Complex",
+            Imaginary => "Imaginary",
+            Struct => "Struct",
+            Union => "Union",
+            Enum => "Enum",
+            Auto => "Auto",
+            Extern => "Extern",
+            Register => "Register",
+            Static => "Static",
+            Typedef => "Typedef",
+            ThreadLocal => "ThreadLocal",
+            Const => "Const",
+            Restrict => "Restrict",
+            Volatile => "Volatile",
+            Atomic => "Atomic",
+            Inline => "Inline",
+            Noreturn => "Noreturn",
+            Sizeof => "Sizeof",
+            Alignas => "Alignas",
+            Alignof => "Alignof",
+            Generic => "Generic",
+            StaticAssert => "StaticAssert",
+            True => "True",
+            False => "False",
+            Nullptr => "Nullptr",
+            Constexpr => "Constexpr",
+            Typeof => "Typeof",
+            TypeofUnqual => "TypeofUnqual",
+            StringLit(_) => "StringLit",
+            CharLit(_) => "CharLit",
+            IntLit(_) => "IntLit",
+            FloatLit(_) => "FloatLit",
+            Ident(_) => "Ident",
+            Invalid => "Invalid",
+            LBracket => "LBracket",
+            RBracket => "RBracket",
+            LParen => "LParen",
+            RParen => "RParen",
+            LBrace => "LBrace",
+            RBrace => "RBrace",
+            Dot => "Dot",
+            Arrow => "Arrow",
+            PlusPlus => "PlusPlus",
+            MinusMinus => "MinusMinus",
+            Ampersand => "Ampersand",
+            Star => "Star",
+            Plus => "Plus",
+            Minus => "Minus",
+            Tilde => "Tilde",
+            Not => "Not",
+            Slash => "Slash",
+            Percent => "Percent",
+            Shl => "Shl",
+            Shr => "Shr",
+            Lt => "Lt",
+            Gt => "Gt",
+            Le => "Le",
+            Ge => "Ge",
+            EqEq => "EqEq",
+            Ne => "Ne",
+            BitXor => "BitXor",
+            BitOr => "BitOr",
+            And => "And",
+            Or => "Or",
+            Question => "Question",
+            Colon => "Colon",
+            Semicolon => "Semicolon",
+            Ellipsis => "Ellipsis",
+            Eq => "Eq",
+            StarEq => "StarEq",
+            SlashEq => "SlashEq",
+            PercentEq => "PercentEq",
+            PlusEq => "PlusEq",
+            MinusEq => "MinusEq",
+            ShlEq => "ShlEq",
+            ShrEq => "ShrEq",
+            AmpersandEq => "AmpersandEq",
+            BitXorEq => "BitXorEq",
+            BitOrEq => "BitOrEq",
+            Comma => "Comma",
+            Hash => "Hash",
+            HashHash => "HashHash",
+        }
     }
 }
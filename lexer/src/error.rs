@@ -0,0 +1,232 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use std::{fmt::Display, ops::Range};
+
+use diagnostics::ErrorComponent;
+use logos::Lexer;
+use source::{SourceFile, Span};
+use utils::{Encoding, FloatKind};
+
+use crate::Token;
+
+/// Errors produced while lexing a single token.
+///
+/// Lexing continues after an error, so a single run reports every problem in a file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexError {
+    /// A character that does not start any token, e.g. `@`
+    StrayCharacter(char),
+    /// A pp-number that is neither a valid integer nor floating constant, e.g. `123abc`
+    InvalidSuffix { suffix: String },
+    /// A digit that is not valid for the base of the constant, e.g. `09` or `0b2`
+    InvalidDigit { digit: char, base: u32 },
+    /// A prefix such as `0x` or `0b` without any digits following it
+    MissingDigits { base: u32 },
+    /// A constant that does not fit into any type permitted for it
+    IntOutOfRange { signed_only: bool },
+    /// An `e` or `p` exponent marker without any digits following it
+    MissingExponentDigits,
+    /// A hexadecimal floating constant without the mandatory `p` exponent
+    MissingBinaryExponent,
+    /// A floating constant too large to be represented by its type
+    FloatOutOfRange { kind: FloatKind },
+    /// A character constant or string literal without a closing quote on the same line
+    UnterminatedLiteral { quote: char },
+    /// `''`
+    EmptyCharLiteral,
+    /// A character constant with a prefix that contains more than one code unit
+    CharTooLong { encoding: Encoding },
+    /// A backslash followed by a character that doesn't start an escape sequence
+    InvalidEscape { escape: String, span: Range<usize> },
+    /// `\x` without any hex digits following it
+    MissingEscapeDigits { span: Range<usize> },
+    /// An octal or hex escape sequence that does not fit into a code unit
+    EscapeOutOfRange { span: Range<usize> },
+    /// A universal character name with fewer than 4 or 8 hex digits
+    IncompleteUcn { span: Range<usize>, expected: usize },
+    /// A universal character name designating a character it may not designate
+    InvalidUcn { value: u32, span: Range<usize> },
+    /// Adjacent string literals with incompatible encoding prefixes
+    MixedEncodings { span: Range<usize> },
+}
+
+impl Default for LexError {
+    fn default() -> Self {
+        Self::StrayCharacter(char::REPLACEMENT_CHARACTER)
+    }
+}
+
+impl LexError {
+    pub(crate) fn from_lexer<'s>(lex: &mut Lexer<'s, Token<'s>>) -> Self {
+        let c = lex.slice().chars().next();
+        Self::StrayCharacter(c.unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+    /// The span of the error, if it is more precise than the span of the token it occurred in.
+    #[must_use]
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            Self::InvalidEscape { span, .. }
+            | Self::MissingEscapeDigits { span }
+            | Self::EscapeOutOfRange { span }
+            | Self::IncompleteUcn { span, .. }
+            | Self::InvalidUcn { span, .. }
+            | Self::MixedEncodings { span } => Some(span.clone()),
+            _ => None,
+        }
+    }
+    /// A short message describing the highlighted part of the source.
+    #[must_use]
+    pub fn label(&self) -> String {
+        match self {
+            Self::StrayCharacter(_) => "not part of any token".into(),
+            Self::InvalidSuffix { .. } => "invalid suffix".into(),
+            Self::InvalidDigit { base, .. } => format!("not a valid {} digit", base_name(*base)),
+            Self::MissingDigits { .. } => "expected digits after the prefix".into(),
+            Self::IntOutOfRange { signed_only: true } => "does not fit in `long long`".into(),
+            Self::IntOutOfRange { signed_only: false } => {
+                "does not fit in `unsigned long long`".into()
+            }
+            Self::MissingExponentDigits => "expected exponent digits".into(),
+            Self::MissingBinaryExponent => "missing `p` exponent".into(),
+            Self::FloatOutOfRange { .. } => "too large".into(),
+            Self::UnterminatedLiteral { quote } => format!("missing closing `{quote}`"),
+            Self::EmptyCharLiteral => "expected a character".into(),
+            Self::CharTooLong { .. } => "too many characters".into(),
+            Self::InvalidEscape { escape, .. } if escape.is_empty() => {
+                "expected an escaped character".into()
+            }
+            Self::InvalidEscape { .. } => "unknown escape sequence".into(),
+            Self::MissingEscapeDigits { .. } => "expected hex digits".into(),
+            Self::EscapeOutOfRange { .. } => "value does not fit in a code unit".into(),
+            Self::IncompleteUcn { expected, .. } => format!("expected {expected} hex digits"),
+            Self::InvalidUcn { .. } => "not allowed in a universal character name".into(),
+            Self::MixedEncodings { .. } => "incompatible encoding prefixes".into(),
+        }
+    }
+    /// Additional information on how to fix the error, if any.
+    #[must_use]
+    pub const fn help(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::InvalidSuffix { .. } => {
+                "Integer constants may have a `u` suffix and an `l` or `ll` suffix.\nFloating constants may have an `f` or `l` suffix."
+            }
+            Self::IntOutOfRange { signed_only: true } => {
+                "Add a `u` suffix to make the constant unsigned."
+            }
+            Self::MissingBinaryExponent => {
+                "Hexadecimal floating constants require a binary exponent, e.g. `0x1.8p0`."
+            }
+            Self::InvalidEscape { escape, .. } if !escape.is_empty() => {
+                "Valid escape sequences are \\' \\\" \\? \\\\ \\a \\b \\f \\n \\r \\t \\v,\noctal escapes like \\0, hex escapes like \\x7f and universal character names like \\u00e9."
+            }
+            Self::InvalidUcn { .. } => {
+                "Universal character names may not designate surrogates or characters of the basic character set."
+            }
+            _ => return None,
+        })
+    }
+    /// Builds a diagnostic for this error, given the span of the token it occurred in.
+    #[must_use]
+    pub fn to_component(&self, source: &SourceFile, token_span: Span) -> ErrorComponent {
+        let span = self.span().unwrap_or(token_span);
+        let mut component = ErrorComponent::new(source.clone(), self.to_string(), span);
+        component.set_highlight_message(self.label());
+        if let Some(help) = self.help() {
+            component.set_long_message(help.to_string());
+        }
+        component
+    }
+}
+
+const fn base_name(base: u32) -> &'static str {
+    match base {
+        2 => "binary",
+        8 => "octal",
+        16 => "hexadecimal",
+        _ => "decimal",
+    }
+}
+
+impl Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StrayCharacter(c) if c.is_ascii_graphic() => write!(f, "Stray `{c}` in program"),
+            Self::StrayCharacter(c) => {
+                write!(f, "Stray `U+{:04X}` in program", u32::from(*c))
+            }
+            Self::InvalidSuffix { suffix } => {
+                write!(f, "Invalid suffix `{suffix}` on numeric constant")
+            }
+            Self::InvalidDigit { digit, base } => {
+                write!(
+                    f,
+                    "Invalid digit `{digit}` in {} constant",
+                    base_name(*base)
+                )
+            }
+            Self::MissingDigits { base } => {
+                write!(f, "Missing digits after {} prefix", base_name(*base))
+            }
+            Self::IntOutOfRange { signed_only: true } => {
+                f.write_str("Integer constant is too large for any signed type")
+            }
+            Self::IntOutOfRange { signed_only: false } => {
+                f.write_str("Integer constant is too large for any integer type")
+            }
+            Self::MissingExponentDigits => f.write_str("Exponent has no digits"),
+            Self::MissingBinaryExponent => {
+                f.write_str("Hexadecimal floating constant requires a `p` exponent")
+            }
+            Self::FloatOutOfRange { kind } => {
+                let ty = match kind {
+                    FloatKind::Float => "float",
+                    FloatKind::Double => "double",
+                    FloatKind::LongDouble => "long double",
+                };
+                write!(f, "Floating constant exceeds the range of `{ty}`")
+            }
+            Self::UnterminatedLiteral { quote: '"' } => f.write_str("Unterminated string literal"),
+            Self::UnterminatedLiteral { .. } => f.write_str("Unterminated character constant"),
+            Self::EmptyCharLiteral => f.write_str("Empty character constant"),
+            Self::CharTooLong { encoding } => write!(
+                f,
+                "Character constant with the `{}` prefix must contain a single code unit",
+                encoding.prefix()
+            ),
+            Self::InvalidEscape { escape, .. } if escape.is_empty() => {
+                f.write_str("Unterminated escape sequence")
+            }
+            Self::InvalidEscape { escape, .. } => write!(f, "Unknown escape sequence `{escape}`"),
+            Self::MissingEscapeDigits { .. } => {
+                f.write_str("`\\x` used with no following hex digits")
+            }
+            Self::EscapeOutOfRange { .. } => {
+                f.write_str("Escape sequence out of range for its character type")
+            }
+            Self::IncompleteUcn { expected, .. } => write!(
+                f,
+                "Incomplete universal character name, expected {expected} hex digits"
+            ),
+            Self::InvalidUcn { value, .. } => {
+                write!(f, "`U+{value:04X}` is not a valid universal character name")
+            }
+            Self::MixedEncodings { .. } => {
+                f.write_str("Concatenation of string literals with different encoding prefixes")
+            }
+        }
+    }
+}
@@ -15,55 +15,204 @@
 // LICENSE NOTICE END
 
 pub use logos::Lexer;
-use memchr::memchr2_iter;
-use std::borrow::Cow;
+use memchr::memchr3;
+use utils::{CharLiteral, Encoding, StrChar, StringLiteral};
 
-use crate::Token;
+use crate::{LexError, Token};
 
+/// Interprets a simple escape sequence, the character following the `\`.
 pub const fn unescape(c: char) -> Option<char> {
     Some(match c {
         'n' => '\n',
         'r' => '\r',
         't' => '\t',
-        '0' => '\0',
+        'a' => '\x07',
+        'b' => '\x08',
+        'f' => '\x0C',
+        'v' => '\x0B',
         '\'' => '\'',
-        '\"' => '\"',
+        '"' => '"',
+        '?' => '?',
+        '\\' => '\\',
         _ => return None,
     })
 }
 
-pub fn unescape_string<'s>(lex: &mut Lexer<'s, Token<'s>>) -> Option<Cow<'s, str>> {
-    let remainder = lex.remainder();
-    let mut important_iter = memchr2_iter(b'\\', b'"', remainder.as_bytes());
-    let first_important = important_iter.next()?;
-    // Early return on string without escapes
-    if remainder.as_bytes()[first_important] == b'"' {
-        lex.bump(first_important + 1);
-        return Some(Cow::Borrowed(&remainder[..first_important]));
+fn parse_prefix(prefix: &str) -> Encoding {
+    match prefix {
+        "u8" => Encoding::Utf8,
+        "u" => Encoding::Utf16,
+        "U" => Encoding::Utf32,
+        "L" => Encoding::Wide,
+        _ => Encoding::Plain,
     }
-    let mut out = String::from(&remainder[..first_important]);
-    let mut start = first_important + 1;
-    let escaped = remainder[start..].chars().next()?;
-    if escaped == '"' {
-        // We know the next important character has been escaped.
-        _ = important_iter.next();
+}
+
+/// Parses the escape sequence at the start of `text`, which must start with a `\`.
+///
+/// Returns the escaped character and the length of the escape sequence.
+/// `offset` is the position of `text` in the source, used for error spans.
+fn parse_escape(
+    text: &str,
+    offset: usize,
+    encoding: Encoding,
+) -> Result<(StrChar, usize), LexError> {
+    let mut chars = text[1..].chars();
+    let Some(c) = chars.next() else {
+        return Err(LexError::InvalidEscape {
+            escape: String::new(),
+            span: offset..offset + 1,
+        });
+    };
+    if let Some(c) = unescape(c) {
+        return Ok((StrChar::Char(c), 2));
     }
-    out.push(unescape(escaped)?);
-    start += escaped.len_utf8();
-    loop {
-        let important = important_iter.next()?;
-        // Capture string component before escape/quote
-        out.push_str(&remainder[start..important]);
-        if remainder.as_bytes()[important] == b'"' {
-            lex.bump(important + 1);
-            return Some(Cow::Owned(out));
+    let digits_len = |base: u32, max: usize| {
+        text[1..]
+            .bytes()
+            .skip(usize::from(base == 16))
+            .take(max)
+            .take_while(|b| char::from(*b).is_digit(base))
+            .count()
+    };
+    match c {
+        '0'..='7' => {
+            let len = digits_len(8, 3);
+            let span = offset..offset + 1 + len;
+            let value = u32::from_str_radix(&text[1..=len], 8).unwrap();
+            if value > encoding.max_unit() {
+                return Err(LexError::EscapeOutOfRange { span });
+            }
+            Ok((StrChar::Unit(value), 1 + len))
+        }
+        'x' => {
+            let len = digits_len(16, usize::MAX);
+            let span = offset..offset + 2 + len;
+            if len == 0 {
+                return Err(LexError::MissingEscapeDigits { span });
+            }
+            let value = u32::from_str_radix(&text[2..2 + len], 16)
+                .ok()
+                .filter(|&v| v <= encoding.max_unit());
+            let Some(value) = value else {
+                return Err(LexError::EscapeOutOfRange { span });
+            };
+            Ok((StrChar::Unit(value), 2 + len))
         }
-        let escaped = remainder[important + 1..].chars().next()?;
-        if escaped == '"' {
-            // We know the next important character has been escaped.
-            _ = important_iter.next();
+        'u' | 'U' => {
+            let expected = if c == 'u' { 4 } else { 8 };
+            let len = digits_len(16, expected);
+            let span = offset..offset + 2 + len;
+            if len != expected {
+                return Err(LexError::IncompleteUcn { span, expected });
+            }
+            let value = u32::from_str_radix(&text[2..2 + len], 16).unwrap();
+            // C17 6.4.3p2: UCNs may not designate characters in the basic character set, other
+            // than `$`, `@` and `` ` ``, nor surrogates.
+            let basic = value < 0xA0 && !matches!(value, 0x24 | 0x40 | 0x60);
+            match char::from_u32(value) {
+                Some(c) if !basic => Ok((StrChar::Char(c), 2 + len)),
+                _ => Err(LexError::InvalidUcn { value, span }),
+            }
+        }
+        c => {
+            let len = 1 + c.len_utf8();
+            Err(LexError::InvalidEscape {
+                escape: text[..len].to_string(),
+                span: offset..offset + len,
+            })
+        }
+    }
+}
+
+/// Scans the contents of a character constant or string literal, up to and including the
+/// closing quote, bumping the lexer past it.
+///
+/// On an invalid escape sequence the rest of the literal is still consumed so that lexing
+/// can continue after it, and the first error is returned.
+fn scan_quoted<'s>(
+    lex: &mut Lexer<'s, Token<'s>>,
+    quote: u8,
+    encoding: Encoding,
+) -> Result<Vec<StrChar>, LexError> {
+    let remainder = lex.remainder();
+    let offset = lex.span().end;
+    let bytes = remainder.as_bytes();
+    let mut chars = Vec::new();
+    let mut error = None;
+    let mut start = 0;
+    loop {
+        let Some(important) = memchr3(b'\\', quote, b'\n', &bytes[start..]).map(|i| i + start)
+        else {
+            lex.bump(remainder.len());
+            return Err(LexError::UnterminatedLiteral {
+                quote: quote.into(),
+            });
+        };
+        chars.extend(remainder[start..important].chars().map(StrChar::Char));
+        match bytes[important] {
+            b'\n' => {
+                lex.bump(important);
+                return Err(LexError::UnterminatedLiteral {
+                    quote: quote.into(),
+                });
+            }
+            b'\\' => match parse_escape(&remainder[important..], offset + important, encoding) {
+                Ok((c, len)) => {
+                    chars.push(c);
+                    start = important + len;
+                }
+                Err(e) => {
+                    error.get_or_insert(e);
+                    // Skip the backslash and the character following it, if any
+                    start = important + 1;
+                    start += remainder[start..]
+                        .chars()
+                        .next()
+                        .filter(|&c| c != '\n')
+                        .map_or(0, char::len_utf8);
+                }
+            },
+            _ => {
+                lex.bump(important + 1);
+                return error.map_or(Ok(chars), Err);
+            }
         }
-        out.push(unescape(escaped)?);
-        start = important + 1 + escaped.len_utf8();
     }
 }
+
+/// Lexes a string literal, the lexer must be positioned after the opening quote.
+pub fn lex_string<'s>(lex: &mut Lexer<'s, Token<'s>>) -> Result<StringLiteral, LexError> {
+    let slice = lex.slice();
+    let encoding = parse_prefix(&slice[..slice.len() - 1]);
+    let chars = scan_quoted(lex, b'"', encoding)?;
+    Ok(StringLiteral { encoding, chars })
+}
+
+/// Lexes a character constant, the lexer must be positioned after the opening quote.
+pub fn lex_char<'s>(lex: &mut Lexer<'s, Token<'s>>) -> Result<CharLiteral, LexError> {
+    let slice = lex.slice();
+    let encoding = parse_prefix(&slice[..slice.len() - 1]);
+    let chars = scan_quoted(lex, b'\'', encoding)?;
+    let literal = StringLiteral { encoding, chars };
+    let units = literal.units();
+    let value = match (encoding, units.as_slice()) {
+        (_, []) => return Err(LexError::EmptyCharLiteral),
+        // `char` is signed on x86-64
+        #[allow(clippy::cast_possible_truncation)]
+        (Encoding::Plain, &[unit]) => i64::from(unit as u8 as i8),
+        // Multi-character constants have an implementation-defined value, we match GCC
+        #[allow(clippy::cast_possible_wrap)]
+        (Encoding::Plain, units) => {
+            i64::from(units.iter().fold(0u32, |acc, &u| (acc << 8) | u) as i32)
+        }
+        // `wchar_t` is a signed 32-bit integer
+        #[allow(clippy::cast_possible_wrap)]
+        (Encoding::Wide, &[unit]) => i64::from(unit as i32),
+        (_, &[unit]) => i64::from(unit),
+        (_, _) => {
+            return Err(LexError::CharTooLong { encoding });
+        }
+    };
+    Ok(CharLiteral { encoding, value })
+}
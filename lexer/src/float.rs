@@ -0,0 +1,196 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use utils::{FloatKind, FloatLiteral};
+
+use crate::LexError;
+
+/// The parameters of an IEEE 754 binary format needed for rounding.
+struct Format {
+    /// Number of significand bits, including the implicit bit
+    precision: u32,
+    /// Exponent of the least significant bit of the smallest subnormal
+    min_lsb_exp: i64,
+    max: f64,
+}
+
+const DOUBLE: Format = Format {
+    precision: f64::MANTISSA_DIGITS,
+    min_lsb_exp: -1074,
+    max: f64::MAX,
+};
+
+const SINGLE: Format = Format {
+    precision: f32::MANTISSA_DIGITS,
+    min_lsb_exp: -149,
+    max: f32::MAX as f64,
+};
+
+/// Computes `mantissa * 2^exp`, which must be exactly representable as an `f64`.
+fn scale(mantissa: u64, mut exp: i64) -> f64 {
+    #[allow(clippy::cast_precision_loss)]
+    let mut value = mantissa as f64;
+    // Scale in steps that stay within the normal range, every intermediate value lies between
+    // the mantissa and the final value, so no rounding can occur.
+    while exp > 0 {
+        let step = exp.min(1000);
+        value *= f64::from_bits(((1023 + step) as u64) << 52);
+        exp -= step;
+    }
+    while exp < 0 {
+        let step = (-exp).min(1000);
+        value /= f64::from_bits(((1023 + step) as u64) << 52);
+        exp += step;
+    }
+    value
+}
+
+/// Rounds `mantissa * 2^exp` to the nearest value of the given format, ties to even.
+///
+/// `sticky` signifies that nonzero bits below the mantissa were discarded.
+fn round_binary(mantissa: u128, exp: i64, sticky: bool, format: &Format) -> f64 {
+    if mantissa == 0 {
+        return 0.0;
+    }
+    let bits = i64::from(128 - mantissa.leading_zeros());
+    let lead_exp = exp + bits - 1;
+    if lead_exp > 2 * 1024 {
+        return f64::INFINITY;
+    }
+    let lsb_exp = (lead_exp - i64::from(format.precision) + 1).max(format.min_lsb_exp);
+    let shift = lsb_exp - exp;
+    let rounded = if shift <= 0 {
+        // Exact, the mantissa already fits
+        return scale(u64::try_from(mantissa).unwrap(), exp);
+    } else if shift > 128 {
+        0
+    } else {
+        let shift = u32::try_from(shift).unwrap();
+        let truncated = mantissa.checked_shr(shift).unwrap_or(0);
+        let remainder = mantissa & (u128::MAX >> (128 - shift));
+        let half = 1u128 << (shift - 1);
+        let round_up = remainder > half || (remainder == half && (sticky || truncated & 1 == 1));
+        truncated + u128::from(round_up)
+    };
+    scale(u64::try_from(rounded).unwrap(), lsb_exp)
+}
+
+/// Converts the digits of a hexadecimal floating constant.
+///
+/// `digits` must only consist of hex digits and at most one `.`.
+fn hex_float(digits: &str, exp: i64, format: &Format) -> f64 {
+    let mut mantissa: u128 = 0;
+    let mut exp = exp;
+    let mut sticky = false;
+    let mut after_dot = false;
+    for c in digits.chars() {
+        if c == '.' {
+            after_dot = true;
+            continue;
+        }
+        let digit = c.to_digit(16).unwrap();
+        if mantissa >> 120 == 0 {
+            mantissa = (mantissa << 4) | u128::from(digit);
+            if after_dot {
+                exp -= 4;
+            }
+        } else {
+            // Out of space, only keep track of whether any of the discarded bits were set
+            sticky |= digit != 0;
+            if !after_dot {
+                exp += 4;
+            }
+        }
+    }
+    round_binary(mantissa, exp, sticky, format)
+}
+
+fn scan_digits(text: &str, base: u32, allow_dot: bool) -> usize {
+    let mut seen_dot = false;
+    text.find(|c: char| {
+        if allow_dot && c == '.' && !seen_dot {
+            seen_dot = true;
+            return false;
+        }
+        !c.is_digit(base)
+    })
+    .unwrap_or(text.len())
+}
+
+/// Parses a decimal or hexadecimal floating constant, including its suffix.
+///
+/// The value is rounded exactly once, to the precision of the type given by the suffix.
+pub fn parse_float(text: &str) -> Result<FloatLiteral, LexError> {
+    let (base, body) = match text.as_bytes() {
+        [b'0', b'x' | b'X', ..] => (16, &text[2..]),
+        _ => (10, text),
+    };
+    let mantissa_end = scan_digits(body, base, true);
+    let (mantissa, rest) = body.split_at(mantissa_end);
+    if !mantissa.chars().any(|c| c.is_digit(base)) {
+        return Err(LexError::MissingDigits { base });
+    }
+    let exp_marker: &[char] = if base == 16 { &['p', 'P'] } else { &['e', 'E'] };
+    let (exponent, suffix) = if let Some(exp) = rest.strip_prefix(exp_marker) {
+        let sign_len = usize::from(exp.starts_with(['+', '-']));
+        let digits_end = sign_len + scan_digits(&exp[sign_len..], 10, false);
+        if digits_end == sign_len {
+            return Err(LexError::MissingExponentDigits);
+        }
+        let (exponent, suffix) = exp.split_at(digits_end);
+        (Some(exponent), suffix)
+    } else if base == 16 {
+        return Err(LexError::MissingBinaryExponent);
+    } else {
+        (None, rest)
+    };
+    let kind = match suffix {
+        "" => FloatKind::Double,
+        "f" | "F" => FloatKind::Float,
+        "l" | "L" => FloatKind::LongDouble,
+        _ => {
+            return Err(LexError::InvalidSuffix {
+                suffix: suffix.to_string(),
+            });
+        }
+    };
+    let format = match kind {
+        FloatKind::Float => &SINGLE,
+        FloatKind::Double | FloatKind::LongDouble => &DOUBLE,
+    };
+    let value = if base == 16 {
+        // Saturate huge exponents, they over- or underflow either way
+        let exp = exponent.unwrap().parse::<i64>().unwrap_or_else(|_| {
+            if exponent.unwrap().starts_with('-') {
+                i64::MIN / 2
+            } else {
+                i64::MAX / 2
+            }
+        });
+        hex_float(mantissa, exp, format)
+    } else {
+        // The standard library parses decimal floats with correct rounding
+        let decimal = &text[..text.len() - suffix.len()];
+        match kind {
+            FloatKind::Float => f64::from(decimal.parse::<f32>().unwrap()),
+            FloatKind::Double | FloatKind::LongDouble => decimal.parse::<f64>().unwrap(),
+        }
+    };
+    if value.is_infinite() || value > format.max {
+        return Err(LexError::FloatOutOfRange { kind });
+    }
+    Ok(FloatLiteral { value, kind })
+}
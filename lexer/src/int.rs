@@ -14,22 +14,89 @@
 // If not, see <https://www.gnu.org/licenses/>.
 // LICENSE NOTICE END
 
-use std::borrow::Cow;
+use utils::{IntKind, IntLiteral};
 
-use utils::VarInt;
+use crate::LexError;
 
+/// Splits an integer pp-number into its base, digits and suffix.
+fn split_prefix(text: &str) -> (u32, &str) {
+    let bytes = text.as_bytes();
+    match bytes {
+        [b'0', b'x' | b'X', ..] => (16, &text[2..]),
+        [b'0', b'b' | b'B', ..] => (2, &text[2..]),
+        [b'0', ..] => (8, text),
+        _ => (10, text),
+    }
+}
+
+fn parse_suffix(suffix: &str) -> Option<(bool, u8)> {
+    // (unsigned, number of `l`s)
+    Some(match suffix {
+        "" => (false, 0),
+        "u" | "U" => (true, 0),
+        "l" | "L" => (false, 1),
+        "ll" | "LL" => (false, 2),
+        "ul" | "uL" | "Ul" | "UL" | "lu" | "lU" | "Lu" | "LU" => (true, 1),
+        "ull" | "uLL" | "Ull" | "ULL" | "llu" | "llU" | "LLu" | "LLU" => (true, 2),
+        _ => return None,
+    })
+}
 
-fn remove_underscores(s: &str) -> Cow<'_, str> {
-    s.split('_')
-        .map(Cow::from)
-        .reduce(|a, b| (a.into_owned() + &b).into())
-        .unwrap_or_default()
+/// The list of candidate types for an integer constant, in the order given by C17 6.4.4.1p5.
+fn candidate_kinds(decimal: bool, unsigned: bool, longs: u8) -> &'static [IntKind] {
+    use IntKind::*;
+    match (decimal, unsigned, longs) {
+        (true, false, 0) => &[Int, Long, LongLong],
+        (false, false, 0) => &[Int, UInt, Long, ULong, LongLong, ULongLong],
+        (_, true, 0) => &[UInt, ULong, ULongLong],
+        (true, false, 1) => &[Long, LongLong],
+        (false, false, 1) => &[Long, ULong, LongLong, ULongLong],
+        (_, true, 1) => &[ULong, ULongLong],
+        (true, false, _) => &[LongLong],
+        (false, false, _) => &[LongLong, ULongLong],
+        (_, true, _) => &[ULongLong],
+    }
 }
 
-pub fn parse_int(base: u32, text: &str) -> Option<VarInt> {
-    let text = remove_underscores(text);
-    u64::from_str_radix(&text, base)
-        .map(VarInt::Pos)
-        .ok()
-        .or_else(|| i64::from_str_radix(&text, base).map(VarInt::Neg).ok())
+/// Parses a C integer constant, including its suffix.
+///
+/// Decimal, octal (leading `0`), hexadecimal (`0x`) and binary (`0b`) constants are supported.
+pub fn parse_int(text: &str) -> Result<IntLiteral, LexError> {
+    let (base, rest) = split_prefix(text);
+    // Octal constants are lexed as decimal digits so that `09` produces a useful error
+    let digit_base = if base == 8 { 10 } else { base };
+    let digits_end = rest
+        .find(|c: char| !c.is_digit(digit_base))
+        .unwrap_or(rest.len());
+    let (digits, suffix) = rest.split_at(digits_end);
+    if digits.is_empty() {
+        if let Some(digit) = suffix.chars().next().filter(char::is_ascii_digit) {
+            return Err(LexError::InvalidDigit { digit, base });
+        }
+        return Err(LexError::MissingDigits { base });
+    }
+    if let Some(digit) = digits.chars().find(|c| !c.is_digit(base)) {
+        return Err(LexError::InvalidDigit { digit, base });
+    }
+    let Some((unsigned, longs)) = parse_suffix(suffix) else {
+        // Report e.g. `0b12` as an invalid digit rather than an invalid suffix
+        if let Some(digit) = suffix.chars().next().filter(char::is_ascii_digit) {
+            return Err(LexError::InvalidDigit { digit, base });
+        }
+        return Err(LexError::InvalidSuffix {
+            suffix: suffix.to_string(),
+        });
+    };
+    let value = u64::from_str_radix(digits, base);
+    let candidates = candidate_kinds(base == 10, unsigned, longs);
+    let out_of_range = || LexError::IntOutOfRange {
+        signed_only: candidates.iter().all(|k| k.is_signed()),
+    };
+    let value = value.map_err(|_| out_of_range())?;
+    let kind = candidates
+        .iter()
+        .copied()
+        .find(|k| value <= k.max_value())
+        .ok_or_else(out_of_range)?;
+    Ok(IntLiteral { value, kind })
 }
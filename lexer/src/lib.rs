@@ -15,122 +15,247 @@
 // If not, see <https://www.gnu.org/licenses/>.
 // LICENSE NOTICE END
 
-use crate::escapes::{unescape, unescape_string};
+use crate::escapes::{lex_char, lex_string};
+use crate::float::parse_float;
 use crate::int::parse_int;
+pub use concat::ConcatStrings;
+pub use error::LexError;
 pub use logos::{Lexer, Logos};
 use std::borrow::Cow;
 use std::fmt::Display;
-use utils::{Spanned, VarInt};
+use utils::{CharLiteral, FloatLiteral, IntLiteral, Spanned, StringLiteral};
 
+mod concat;
 mod display;
+mod error;
 mod escapes;
+mod float;
 mod int;
 
 pub type SToken<'s> = Spanned<Token<'s>>;
 
+/// The revision of the C standard to lex for.
+///
+/// Only C23 changes the set of tokens, the earlier revisions all lex the same as C17.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Standard {
+    #[default]
+    C17,
+    C23,
+}
+
+/// Lexes a keyword introduced in C23, which is an ordinary identifier in earlier revisions.
+fn c23_keyword<'s>(lex: &Lexer<'s, Token<'s>>, keyword: Token<'s>) -> Token<'s> {
+    if lex.extras >= Standard::C23 {
+        keyword
+    } else {
+        Token::Ident(lex.slice())
+    }
+}
 
 #[derive(Debug, Clone, Logos, PartialEq)]
+#[logos(error(LexError, LexError::from_lexer))]
+#[logos(extras = Standard)]
 #[logos(skip "[ \r\n\t]+")]
-// Comments should be stripped by the preprocessor so we'll ignore them for now
+// Line markers left by the preprocessor, `# <line> "<file>" <flags>`. These are mapped back to the
+// original files by `source::SourceFile`.
+#[logos(skip r#"#[ \t]*[0-9]+[ \t]+"([^"\\\n]|\\[^\n])*"[ \t0-9]*"#)]
+// Comments are stripped by the preprocessor, but are skipped here too so that text that wasn't
+// preprocessed can be lexed losslessly by `syntax`
+#[logos(skip(r"//[^\n]*", allow_greedy = true))]
+#[logos(skip r"/\*([^*]|\*+[^*/])*\*+/")]
 pub enum Token<'s> {
-    // // keywords
-    // #[token("else")]
-    // /// else
-    // Else,
-    // #[token("for")]
-    // /// for
-    // For,
-    // #[token("if")]
-    // /// if
-    // If,
+    // keywords: statements
+    #[token("break")]
+    /// break
+    Break,
+    #[token("case")]
+    /// case
+    Case,
+    #[token("continue")]
+    /// continue
+    Continue,
+    #[token("default")]
+    /// default
+    Default,
+    #[token("do")]
+    /// do
+    Do,
+    #[token("else")]
+    /// else
+    Else,
+    #[token("for")]
+    /// for
+    For,
+    #[token("goto")]
+    /// goto
+    Goto,
+    #[token("if")]
+    /// if
+    If,
     #[token("return")]
     /// return
     Return,
-    // #[token("while")]
-    // /// while
-    // While,
-    // #[token("break")]
-    // /// break
-    // Break,
-    // #[token("continue")]
-    // /// continue
-    // Continue,
-
-    // keyword types
+    #[token("switch")]
+    /// switch
+    Switch,
+    #[token("while")]
+    /// while
+    While,
+    // keywords: type specifiers
     #[token("void")]
     /// void
     Void,
+    #[token("char")]
+    /// char
+    Char,
+    #[token("short")]
+    /// short
+    Short,
     #[token("int")]
     /// int
     Int,
-    // #[token("float")]
-    // /// float
-    // Float,
-    // #[token("double")]
-    // /// double
-    // Double,
-
-    // #[regex("\"", unescape_string)]
-    // StringLit(Cow<'s, str>),
-    // #[regex(r"'[^']'", |lex| {
-    //     let text = lex.slice();
-    //     text[1..].chars().next().unwrap()
-    // })]
-    // #[regex(r"'\\[^']'", (|lex: &mut Lexer<'s, Token<'s>>| -> Option<char> {
-    //     let text = lex.slice();
-    //     let text = &text[2..text.len() - 1];
-    //     let mut chars = text.chars();
-    //     let c = chars.next().unwrap();
-    //     unescape(c)
-    // }))]
-    // CharLiteral(char),
-    #[regex(r"0x[0-9a-fA-F][0-9a-fA-F_]*", |lex| parse_int(16, &lex.slice()[2..]))]
-    #[regex(r"0o[0-9a-fA-F][0-9a-fA-F_]*", |lex| parse_int(8, &lex.slice()[2..]))]
-    #[regex(r"0p[0-9a-fA-F][0-9a-fA-F_]*", |lex| parse_int(2, &lex.slice()[2..]))]
-    #[regex(r"[0-9][0-9a-fA-F_]*", |lex| parse_int(10, lex.slice()))]
-    IntLit(VarInt),
-    // #[regex(r"\.\d+", |lex| lex.slice().parse().ok())]
-    // #[regex(r"\d+\.\d+", |lex| lex.slice().parse().ok())]
-    // FloatLit(f64),
+    #[token("long")]
+    /// long
+    Long,
+    #[token("float")]
+    /// float
+    Float,
+    #[token("double")]
+    /// double
+    Double,
+    #[token("signed")]
+    /// signed
+    Signed,
+    #[token("unsigned")]
+    /// unsigned
+    Unsigned,
+    #[token("_Bool")]
+    #[token("bool", |lex| c23_keyword(lex, Token::Bool))]
+    /// _Bool, or bool since C23
+    Bool,
+    #[token("_Complex")]
+    /// _Complex
+    Complex,
+    #[token("_Imaginary")]
+    /// _Imaginary
+    Imaginary,
+    #[token("struct")]
+    /// struct
+    Struct,
+    #[token("union")]
+    /// union
+    Union,
+    #[token("enum")]
+    /// enum
+    Enum,
+    // keywords: storage classes, qualifiers and function specifiers
+    #[token("auto")]
+    /// auto
+    Auto,
+    #[token("extern")]
+    /// extern
+    Extern,
+    #[token("register")]
+    /// register
+    Register,
+    #[token("static")]
+    /// static
+    Static,
+    #[token("typedef")]
+    /// typedef
+    Typedef,
+    #[token("_Thread_local")]
+    #[token("thread_local", |lex| c23_keyword(lex, Token::ThreadLocal))]
+    /// _Thread_local, or thread_local since C23
+    ThreadLocal,
+    #[token("const")]
+    /// const
+    Const,
+    #[token("restrict")]
+    /// restrict
+    Restrict,
+    #[token("volatile")]
+    /// volatile
+    Volatile,
+    #[token("_Atomic")]
+    /// _Atomic
+    Atomic,
+    #[token("inline")]
+    /// inline
+    Inline,
+    #[token("_Noreturn")]
+    /// _Noreturn
+    Noreturn,
+    // keywords: other
+    #[token("sizeof")]
+    /// sizeof
+    Sizeof,
+    #[token("_Alignas")]
+    #[token("alignas", |lex| c23_keyword(lex, Token::Alignas))]
+    /// _Alignas, or alignas since C23
+    Alignas,
+    #[token("_Alignof")]
+    #[token("alignof", |lex| c23_keyword(lex, Token::Alignof))]
+    /// _Alignof, or alignof since C23
+    Alignof,
+    #[token("_Generic")]
+    /// _Generic
+    Generic,
+    #[token("_Static_assert")]
+    #[token("static_assert", |lex| c23_keyword(lex, Token::StaticAssert))]
+    /// _Static_assert, or static_assert since C23
+    StaticAssert,
+    // keywords: C23 only
+    #[token("true", |lex| c23_keyword(lex, Token::True))]
+    /// true
+    True,
+    #[token("false", |lex| c23_keyword(lex, Token::False))]
+    /// false
+    False,
+    #[token("nullptr", |lex| c23_keyword(lex, Token::Nullptr))]
+    /// nullptr
+    Nullptr,
+    #[token("constexpr", |lex| c23_keyword(lex, Token::Constexpr))]
+    /// constexpr
+    Constexpr,
+    #[token("typeof", |lex| c23_keyword(lex, Token::Typeof))]
+    /// typeof
+    Typeof,
+    #[token("typeof_unqual", |lex| c23_keyword(lex, Token::TypeofUnqual))]
+    /// typeof_unqual
+    TypeofUnqual,
+
+    #[regex(r#"(u8|u|U|L)?""#, lex_string)]
+    StringLit(StringLiteral),
+    #[regex(r#"(u8|u|U|L)?'"#, lex_char)]
+    CharLit(CharLiteral),
+    // Matches every pp-number that could be an integer so that malformed ones like `123abc`
+    // produce a diagnostic instead of being split into multiple tokens
+    #[regex(r"[0-9][0-9a-zA-Z_]*", |lex| parse_int(lex.slice()))]
+    IntLit(IntLiteral),
+    // Any pp-number containing a `.`, a signed exponent or an exponent following only decimal
+    // digits is a floating constant. These take priority over the integer catch-all above.
+    #[regex(r"\.[0-9]([0-9a-zA-Z_.]|[eEpP][+-])*", |lex| parse_float(lex.slice()))]
+    #[regex(r"[0-9][0-9a-zA-Z_]*\.([0-9a-zA-Z_.]|[eEpP][+-])*", |lex| parse_float(lex.slice()))]
+    #[regex(r"[0-9][0-9a-zA-Z_]*[eEpP][+-]([0-9a-zA-Z_.]|[eEpP][+-])*", |lex| parse_float(lex.slice()))]
+    #[regex(r"[0-9]+[eE][0-9a-zA-Z_]*", |lex| parse_float(lex.slice()))]
+    #[regex(r"0[xX][0-9a-fA-F]*[pP][0-9a-zA-Z_]*", |lex| parse_float(lex.slice()))]
+    FloatLit(FloatLiteral),
     #[regex(r"[A-Za-z_][A-Za-z0-9_]*")]
     Ident(&'s str),
+    /// Stands in for a token that failed to lex once the error has been reported, so that
+    /// parsing can go on as if it were an operand. Never produced by the lexer itself.
+    Invalid,
 
-    // #[token("==")]
-    // /// ==
-    // EqEq,
-    // #[token("!=")]
-    // /// !=
-    // Ne,
-    // #[token("<=")]
-    // /// <=
-    // Le,
-    // #[token(">=")]
-    // /// =>
-    // Ge,
-    // #[token("<")]
-    // /// <
-    // Lt,
-    // #[token(">")]
-    // /// >
-    // Gt,
-    // #[token("+")]
-    // /// +
-    // Plus,
-    // #[token("-")]
-    // /// -
-    // Minus,
-    // #[token("*")]
-    // /// *
-    // Star,
-    // #[token("=")]
-    // /// =
-    // Eq,
-    // #[token("!")]
-    // /// !
-    // Not,
-    // #[token("/")]
-    /// /
-    // Slash,
+    #[token("[")]
+    #[token("<:")]
+    /// [ or <:
+    LBracket,
+    #[token("]")]
+    #[token(":>")]
+    /// ] or :>
+    RBracket,
     #[token("(")]
     /// (
     LParen,
@@ -138,51 +263,1099 @@ pub enum Token<'s> {
     /// )
     RParen,
     #[token("{")]
-    /// {
+    #[token("<%")]
+    /// { or <%
     LBrace,
     #[token("}")]
-    /// }
+    #[token("%>")]
+    /// } or %>
     RBrace,
-    // #[token(",")]
-    // /// ,
-    // Comma,
+    #[token(".")]
+    /// .
+    Dot,
+    #[token("->")]
+    /// ->
+    Arrow,
+    #[token("++")]
+    /// ++
+    PlusPlus,
+    #[token("--")]
+    /// --
+    MinusMinus,
+    #[token("&")]
+    /// &
+    Ampersand,
+    #[token("*")]
+    /// *
+    Star,
+    #[token("+")]
+    /// +
+    Plus,
+    #[token("-")]
+    /// -
+    Minus,
+    #[token("~")]
+    /// ~
+    Tilde,
+    #[token("!")]
+    /// !
+    Not,
+    #[token("/")]
+    /// /
+    Slash,
+    #[token("%")]
+    /// %
+    Percent,
+    #[token("<<")]
+    /// <<
+    Shl,
+    #[token(">>")]
+    /// >>
+    Shr,
+    #[token("<")]
+    /// <
+    Lt,
+    #[token(">")]
+    /// >
+    Gt,
+    #[token("<=")]
+    /// <=
+    Le,
+    #[token(">=")]
+    /// >=
+    Ge,
+    #[token("==")]
+    /// ==
+    EqEq,
+    #[token("!=")]
+    /// !=
+    Ne,
+    #[token("^")]
+    /// ^
+    BitXor,
+    #[token("|")]
+    /// |
+    BitOr,
+    #[token("&&")]
+    /// &&
+    And,
+    #[token("||")]
+    /// ||
+    Or,
+    #[token("?")]
+    /// ?
+    Question,
+    #[token(":")]
+    /// :
+    Colon,
     #[token(";")]
     /// ;
     Semicolon,
-    // #[token(".")]
-    // /// .
-    // Dot,
+    #[token("...")]
+    /// ...
+    Ellipsis,
+    #[token("=")]
+    /// =
+    Eq,
+    #[token("*=")]
+    /// *=
+    StarEq,
+    #[token("/=")]
+    /// /=
+    SlashEq,
+    #[token("%=")]
+    /// %=
+    PercentEq,
+    #[token("+=")]
+    /// +=
+    PlusEq,
+    #[token("-=")]
+    /// -=
+    MinusEq,
+    #[token("<<=")]
+    /// <<=
+    ShlEq,
+    #[token(">>=")]
+    /// >>=
+    ShrEq,
+    #[token("&=")]
+    /// &=
+    AmpersandEq,
+    #[token("^=")]
+    /// ^=
+    BitXorEq,
+    #[token("|=")]
+    /// |=
+    BitOrEq,
+    #[token(",")]
+    /// ,
+    Comma,
+    #[token("#")]
+    #[token("%:")]
+    /// # or %:
+    Hash,
+    #[token("##")]
+    #[token("%:%:")]
+    /// ## or %:%:
+    HashHash,
 }
 
 #[cfg(test)]
 mod tests {
     use logos::Logos;
     use pretty_assertions::assert_eq;
-    use std::borrow::Cow;
-
-    use crate::Token;
-
-    // fn string<'s>(text: impl Into<Cow<'s, str>>) -> Token<'s> {
-    //     Token::StringLit(text.into())
-    // }
-
-    // #[test]
-    // fn lex_example() {
-    //     use Token::*;
-    //     let example = "";
-    //     let tokens: Vec<_> = Token::lexer(example).collect::<Result<_, _>>().unwrap();
-    //     assert_eq!(
-    //         &tokens,
-    //         [
-    //             // Your first Lox program!
-    //             // print "\"Hello, world\"!";
-    //             Ident("print"),
-    //             LParen,
-    //             string("\"Hello, world\"!"),
-    //             RParen,
-    //             Semicolon
-    //         ]
-    //         .as_slice()
-    //     );
-    // }
+    use std::ops::Range;
+
+    use crate::{ConcatStrings, LexError, Standard, Token};
+    use utils::{
+        CharLiteral, Encoding, FloatKind, FloatLiteral, IntKind, IntLiteral, StrChar, StringLiteral,
+    };
+
+    fn lex(text: &str) -> Vec<Token<'_>> {
+        Token::lexer(text).collect::<Result<_, _>>().unwrap()
+    }
+
+    fn lex_c23(text: &str) -> Vec<Token<'_>> {
+        Token::lexer_with_extras(text, Standard::C23)
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    const KEYWORDS: &[Token<'static>] = {
+        use Token::*;
+        &[
+            Break,
+            Case,
+            Continue,
+            Default,
+            Do,
+            Else,
+            For,
+            Goto,
+            If,
+            Return,
+            Switch,
+            While,
+            Void,
+            Char,
+            Short,
+            Int,
+            Long,
+            Float,
+            Double,
+            Signed,
+            Unsigned,
+            Bool,
+            Complex,
+            Imaginary,
+            Struct,
+            Union,
+            Enum,
+            Auto,
+            Extern,
+            Register,
+            Static,
+            Typedef,
+            ThreadLocal,
+            Const,
+            Restrict,
+            Volatile,
+            Atomic,
+            Inline,
+            Noreturn,
+            Sizeof,
+            Alignas,
+            Alignof,
+            Generic,
+            StaticAssert,
+        ]
+    };
+
+    const C23_KEYWORDS: &[(&str, Token<'static>)] = {
+        use Token::*;
+        &[
+            ("bool", Bool),
+            ("alignas", Alignas),
+            ("alignof", Alignof),
+            ("static_assert", StaticAssert),
+            ("thread_local", ThreadLocal),
+            ("true", True),
+            ("false", False),
+            ("nullptr", Nullptr),
+            ("constexpr", Constexpr),
+            ("typeof", Typeof),
+            ("typeof_unqual", TypeofUnqual),
+        ]
+    };
+
+    const PUNCTUATORS: &[Token<'static>] = {
+        use Token::*;
+        &[
+            LBracket,
+            RBracket,
+            LParen,
+            RParen,
+            LBrace,
+            RBrace,
+            Dot,
+            Arrow,
+            PlusPlus,
+            MinusMinus,
+            Ampersand,
+            Star,
+            Plus,
+            Minus,
+            Tilde,
+            Not,
+            Slash,
+            Percent,
+            Shl,
+            Shr,
+            Lt,
+            Gt,
+            Le,
+            Ge,
+            EqEq,
+            Ne,
+            BitXor,
+            BitOr,
+            And,
+            Or,
+            Question,
+            Colon,
+            Semicolon,
+            Ellipsis,
+            Eq,
+            StarEq,
+            SlashEq,
+            PercentEq,
+            PlusEq,
+            MinusEq,
+            ShlEq,
+            ShrEq,
+            AmpersandEq,
+            BitXorEq,
+            BitOrEq,
+            Comma,
+            Hash,
+            HashHash,
+        ]
+    };
+
+    #[test]
+    fn punctuators_roundtrip_through_display() {
+        for punct in PUNCTUATORS {
+            let text = punct.to_string();
+            assert_eq!(lex(&text), std::slice::from_ref(punct), "lexing {text:?}");
+        }
+    }
+
+    #[test]
+    fn punctuators_separated_by_whitespace() {
+        let text: Vec<String> = PUNCTUATORS.iter().map(Token::to_string).collect();
+        assert_eq!(lex(&text.join(" ")), PUNCTUATORS);
+    }
+
+    #[test]
+    fn punctuators_maximal_munch() {
+        use Token::*;
+        assert_eq!(
+            lex("a+++++b"),
+            [Ident("a"), PlusPlus, PlusPlus, Plus, Ident("b")]
+        );
+        assert_eq!(
+            lex("x<<=y>>z"),
+            [Ident("x"), ShlEq, Ident("y"), Shr, Ident("z")]
+        );
+        assert_eq!(
+            lex("p->q-->r"),
+            [Ident("p"), Arrow, Ident("q"), MinusMinus, Gt, Ident("r")]
+        );
+        assert_eq!(
+            lex("f(a,...)"),
+            [Ident("f"), LParen, Ident("a"), Comma, Ellipsis, RParen]
+        );
+        assert_eq!(
+            lex("a&&b&c||d|e"),
+            [
+                Ident("a"),
+                And,
+                Ident("b"),
+                Ampersand,
+                Ident("c"),
+                Or,
+                Ident("d"),
+                BitOr,
+                Ident("e")
+            ]
+        );
+        assert_eq!(lex("#x##y"), [Hash, Ident("x"), HashHash, Ident("y")]);
+        assert_eq!(lex("a..b"), [Ident("a"), Dot, Dot, Ident("b")]);
+    }
+
+    #[test]
+    fn keywords_roundtrip_through_display() {
+        for keyword in KEYWORDS {
+            let text = keyword.to_string();
+            assert_eq!(lex(&text), std::slice::from_ref(keyword), "lexing {text:?}");
+            assert_eq!(
+                lex_c23(&text),
+                std::slice::from_ref(keyword),
+                "lexing {text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn token_kinds() {
+        use Token::*;
+        let kinds: Vec<_> = lex("int x = 'a' + 1.0;").iter().map(Token::kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                "Int",
+                "Ident",
+                "Eq",
+                "CharLit",
+                "Plus",
+                "FloatLit",
+                "Semicolon"
+            ]
+        );
+        assert_eq!(Sizeof.kind(), "Sizeof");
+    }
+
+    #[test]
+    fn keyword_prefixes_are_identifiers() {
+        use Token::*;
+        assert_eq!(
+            lex("iff int_ _Boolean returns Int"),
+            [
+                Ident("iff"),
+                Ident("int_"),
+                Ident("_Boolean"),
+                Ident("returns"),
+                Ident("Int")
+            ]
+        );
+        assert_eq!(lex_c23("bool_ typeofx"), [Ident("bool_"), Ident("typeofx")]);
+    }
+
+    #[test]
+    fn c23_keywords_depend_on_standard() {
+        for (text, keyword) in C23_KEYWORDS {
+            assert_eq!(lex(text), [Token::Ident(text)], "lexing {text:?} as C17");
+            assert_eq!(
+                lex_c23(text),
+                std::slice::from_ref(keyword),
+                "lexing {text:?} as C23"
+            );
+        }
+        assert_eq!(Token::True.to_string(), "true");
+        assert_eq!(Token::TypeofUnqual.to_string(), "typeof_unqual");
+    }
+
+    #[test]
+    fn line_markers_are_skipped() {
+        use Token::*;
+        let text = "# 1 \"a.c\"\n# 1 \"dir/b \\\"x\\\".h\" 1 3 4\nint x;\n# 2 \"a.c\" 2\n#";
+        assert_eq!(lex(text), [Int, Ident("x"), Semicolon, Hash]);
+        assert_eq!(
+            lex("# 12 x"),
+            [
+                Hash,
+                IntLit(IntLiteral {
+                    value: 12,
+                    kind: IntKind::Int
+                }),
+                Ident("x")
+            ]
+        );
+    }
+
+    #[test]
+    fn comments_are_skipped() {
+        use Token::*;
+        let text = "a // b /* c\n/* d\n * e **/ / f /**/ */";
+        assert_eq!(lex(text), [Ident("a"), Slash, Ident("f"), Star, Slash]);
+    }
+
+    #[test]
+    fn digraphs() {
+        use Token::*;
+        assert_eq!(
+            lex("<: :> <% %> %: %:%:"),
+            [LBracket, RBracket, LBrace, RBrace, Hash, HashHash]
+        );
+    }
+
+    fn int(text: &str) -> Result<IntLiteral, LexError> {
+        let mut lexer = Token::lexer(text);
+        let tok = lexer.next().unwrap();
+        assert_eq!(lexer.next(), None, "{text:?} should be a single token");
+        tok.map(|t| match t {
+            Token::IntLit(i) => i,
+            t => panic!("{text:?} lexed as {t:?}"),
+        })
+    }
+
+    fn int_ok(text: &str, value: u64, kind: IntKind) {
+        assert_eq!(int(text), Ok(IntLiteral { value, kind }), "lexing {text:?}");
+    }
+
+    #[test]
+    fn int_bases() {
+        use IntKind::*;
+        int_ok("0", 0, Int);
+        int_ok("42", 42, Int);
+        int_ok("017", 0o17, Int);
+        int_ok("0x1F", 0x1f, Int);
+        int_ok("0XaBc", 0xabc, Int);
+        int_ok("0b101", 0b101, Int);
+        int_ok("0B1", 1, Int);
+    }
+
+    #[test]
+    fn int_suffixes() {
+        use IntKind::*;
+        for suffix in ["u", "U"] {
+            int_ok(&format!("1{suffix}"), 1, UInt);
+        }
+        for suffix in ["l", "L"] {
+            int_ok(&format!("1{suffix}"), 1, Long);
+        }
+        for suffix in ["ll", "LL"] {
+            int_ok(&format!("1{suffix}"), 1, LongLong);
+        }
+        for suffix in ["ul", "uL", "Ul", "UL", "lu", "lU", "Lu", "LU"] {
+            int_ok(&format!("1{suffix}"), 1, ULong);
+        }
+        for suffix in ["ull", "uLL", "Ull", "ULL", "llu", "llU", "LLu", "LLU"] {
+            int_ok(&format!("1{suffix}"), 1, ULongLong);
+        }
+        for suffix in ["lL", "Ll", "uu", "lul", "lll", "f", "abc", "_"] {
+            assert_eq!(
+                int(&format!("1{suffix}")),
+                Err(LexError::InvalidSuffix {
+                    suffix: suffix.to_string()
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn int_type_selection() {
+        use IntKind::*;
+        // Decimal constants without a `u` suffix only ever get signed types
+        int_ok("2147483647", 2_147_483_647, Int);
+        int_ok("2147483648", 2_147_483_648, Long);
+        int_ok("9223372036854775807", i64::MAX as u64, Long);
+        int_ok("9223372036854775807ll", i64::MAX as u64, LongLong);
+        // Octal, hex and binary constants may become unsigned
+        int_ok("0x7FFFFFFF", 0x7fff_ffff, Int);
+        int_ok("0xFFFFFFFF", 0xffff_ffff, UInt);
+        int_ok("037777777777", 0xffff_ffff, UInt);
+        int_ok("0x100000000", 0x1_0000_0000, Long);
+        int_ok("0xFFFFFFFFFFFFFFFF", u64::MAX, ULong);
+        int_ok("0xFFFFFFFFFFFFFFFFll", u64::MAX, ULongLong);
+        int_ok("0xFFFFFFFFl", 0xffff_ffff, Long);
+        int_ok("4294967295u", 0xffff_ffff, UInt);
+        int_ok("4294967296u", 0x1_0000_0000, ULong);
+        int_ok("18446744073709551615u", u64::MAX, ULong);
+        assert_eq!(
+            int("9223372036854775808"),
+            Err(LexError::IntOutOfRange { signed_only: true })
+        );
+        assert_eq!(
+            int("18446744073709551616u"),
+            Err(LexError::IntOutOfRange { signed_only: false })
+        );
+        assert_eq!(
+            int("0x1FFFFFFFFFFFFFFFF"),
+            Err(LexError::IntOutOfRange { signed_only: false })
+        );
+    }
+
+    #[test]
+    fn int_malformed() {
+        assert_eq!(
+            int("123abc"),
+            Err(LexError::InvalidSuffix {
+                suffix: "abc".to_string()
+            })
+        );
+        assert_eq!(
+            int("09"),
+            Err(LexError::InvalidDigit {
+                digit: '9',
+                base: 8
+            })
+        );
+        assert_eq!(
+            int("0b102"),
+            Err(LexError::InvalidDigit {
+                digit: '2',
+                base: 2
+            })
+        );
+        assert_eq!(
+            int("0b2"),
+            Err(LexError::InvalidDigit {
+                digit: '2',
+                base: 2
+            })
+        );
+        assert_eq!(int("0x"), Err(LexError::MissingDigits { base: 16 }));
+        assert_eq!(int("0xg"), Err(LexError::MissingDigits { base: 16 }));
+        // Non-C forms that used to be accepted
+        assert!(int("0o17").is_err());
+        assert!(int("0p101").is_err());
+        assert!(int("1_000").is_err());
+    }
+
+    fn float(text: &str) -> Result<FloatLiteral, LexError> {
+        let mut lexer = Token::lexer(text);
+        let tok = lexer.next().unwrap();
+        assert_eq!(lexer.next(), None, "{text:?} should be a single token");
+        tok.map(|t| match t {
+            Token::FloatLit(f) => f,
+            t => panic!("{text:?} lexed as {t:?}"),
+        })
+    }
+
+    fn double_bits(text: &str, bits: u64) {
+        let lit = float(text).unwrap();
+        assert_eq!(lit.kind, FloatKind::Double, "lexing {text:?}");
+        assert_eq!(
+            lit.value.to_bits(),
+            bits,
+            "lexing {text:?}: {:#018x} != {bits:#018x}",
+            lit.value.to_bits()
+        );
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn float_bits(text: &str, bits: u32) {
+        let lit = float(text).unwrap();
+        assert_eq!(lit.kind, FloatKind::Float, "lexing {text:?}");
+        let single = lit.value as f32;
+        assert_eq!(
+            f64::from(single),
+            lit.value,
+            "{text:?} is not exactly a float"
+        );
+        assert_eq!(
+            single.to_bits(),
+            bits,
+            "lexing {text:?}: {:#010x} != {bits:#010x}",
+            single.to_bits()
+        );
+    }
+
+    #[test]
+    fn float_decimal_forms() {
+        double_bits("1.", 0x3FF0_0000_0000_0000);
+        double_bits(".5", 0x3FE0_0000_0000_0000);
+        double_bits("1.5", 0x3FF8_0000_0000_0000);
+        double_bits("0.1", 0x3FB9_9999_9999_999A);
+        double_bits("1e10", 0x4202_A05F_2000_0000);
+        double_bits("1E+2", 0x4059_0000_0000_0000);
+        double_bits("25e-1", 0x4004_0000_0000_0000);
+        double_bits("1.e1", 0x4024_0000_0000_0000);
+        double_bits("08e1", 0x4054_0000_0000_0000);
+        double_bits("1.7976931348623157e308", 0x7FEF_FFFF_FFFF_FFFF);
+        // Largest subnormal and smallest subnormal
+        double_bits("2.2250738585072009e-308", 0x000F_FFFF_FFFF_FFFF);
+        double_bits("4.9406564584124654e-324", 0x0000_0000_0000_0001);
+        double_bits("1e-400", 0);
+    }
+
+    #[test]
+    fn float_hex_forms() {
+        double_bits("0x1.8p3", 0x4028_0000_0000_0000);
+        double_bits("0x1p3", 0x4020_0000_0000_0000);
+        double_bits("0X.8P1", 0x3FF0_0000_0000_0000);
+        double_bits("0xA.p0", 0x4024_0000_0000_0000);
+        double_bits("0x1p-1074", 0x0000_0000_0000_0001);
+        double_bits("0x1p-1022", 0x0010_0000_0000_0000);
+        double_bits("0x1.fffffffffffffp1023", 0x7FEF_FFFF_FFFF_FFFF);
+        double_bits("0x0.0000000000001p-1022", 0x0000_0000_0000_0001);
+        double_bits("0x1p-1076", 0);
+    }
+
+    #[test]
+    fn float_hex_rounding() {
+        // Exactly halfway between two doubles, ties to even
+        double_bits("0x1.00000000000008p0", 0x3FF0_0000_0000_0000);
+        double_bits("0x1.00000000000018p0", 0x3FF0_0000_0000_0002);
+        // Just above halfway, including bits far past the precision of the mantissa
+        double_bits("0x1.000000000000080001p0", 0x3FF0_0000_0000_0001);
+        double_bits(
+            "0x1.0000000000000800000000000000000000000001p0",
+            0x3FF0_0000_0000_0001,
+        );
+        // Rounding up may carry into the exponent
+        double_bits("0x1.fffffffffffff8p0", 0x4000_0000_0000_0000);
+        // Halfway in the subnormal range
+        double_bits("0x1.8p-1074", 0x0000_0000_0000_0002);
+        double_bits("0x1p-1075", 0);
+        double_bits("0x1.000001p-1075", 0x0000_0000_0000_0001);
+        // Long mantissas of leading integer digits
+        double_bits(
+            "0x10000000000000000000000000000000000p-136",
+            0x3FF0_0000_0000_0000,
+        );
+    }
+
+    #[test]
+    fn float_suffixes() {
+        float_bits("0.1f", 0x3DCC_CCCD);
+        float_bits("1.F", 0x3F80_0000);
+        float_bits("1e10f", 0x5015_02F9);
+        float_bits("3.4028234663852886e38f", 0x7F7F_FFFF);
+        float_bits("0x1.fffffep127f", 0x7F7F_FFFF);
+        float_bits("0x1p-149f", 0x0000_0001);
+        // Halfway between two floats, must not be rounded to double first
+        float_bits("0x1.000001p0f", 0x3F80_0000);
+        float_bits("0x1.0000010000000001p0f", 0x3F80_0001);
+        float_bits("1.00000005960464477539062500001f", 0x3F80_0001);
+        let long = float("1.5L").unwrap();
+        assert_eq!(long.kind, FloatKind::LongDouble);
+        assert_eq!(long.value, 1.5);
+        assert_eq!(float("2e3l").unwrap().kind, FloatKind::LongDouble);
+    }
+
+    #[test]
+    fn float_display_roundtrips() {
+        for text in ["1.5", "0.1f", "1e100", "2.5l", "0x1p-1074", "0x1.8p3f"] {
+            let lit = float(text).unwrap();
+            assert_eq!(
+                float(&lit.to_string()),
+                Ok(lit),
+                "{text:?} displayed as {lit}"
+            );
+        }
+    }
+
+    #[test]
+    fn float_malformed() {
+        assert_eq!(float("1e"), Err(LexError::MissingExponentDigits));
+        assert_eq!(float("1e+"), Err(LexError::MissingExponentDigits));
+        assert_eq!(float("0x1.8"), Err(LexError::MissingBinaryExponent));
+        assert_eq!(float("0x1.8e+3"), Err(LexError::MissingBinaryExponent));
+        assert_eq!(float("0x.p1"), Err(LexError::MissingDigits { base: 16 }));
+        assert_eq!(
+            float("1.5q"),
+            Err(LexError::InvalidSuffix {
+                suffix: "q".to_string()
+            })
+        );
+        assert_eq!(
+            float("1.0ff"),
+            Err(LexError::InvalidSuffix {
+                suffix: "ff".to_string()
+            })
+        );
+        assert_eq!(
+            float("1..2"),
+            Err(LexError::InvalidSuffix {
+                suffix: ".2".to_string()
+            })
+        );
+        assert_eq!(
+            float("1e400"),
+            Err(LexError::FloatOutOfRange {
+                kind: FloatKind::Double
+            })
+        );
+        assert_eq!(
+            float("0x1p1024"),
+            Err(LexError::FloatOutOfRange {
+                kind: FloatKind::Double
+            })
+        );
+        assert_eq!(
+            float("1e39f"),
+            Err(LexError::FloatOutOfRange {
+                kind: FloatKind::Float
+            })
+        );
+        assert_eq!(
+            float("0x1p99999999999999999999"),
+            Err(LexError::FloatOutOfRange {
+                kind: FloatKind::Double
+            })
+        );
+    }
+
+    #[test]
+    fn numbers_next_to_other_tokens() {
+        use Token::*;
+        let tokens = lex("a[1].b+1.5e3-0x1p2");
+        assert_eq!(
+            tokens[..5],
+            [Ident("a"), LBracket, lex("1")[0].clone(), RBracket, Dot]
+        );
+        assert!(matches!(tokens[7], FloatLit(_)));
+        assert_eq!(tokens[8], Minus);
+        assert!(matches!(tokens[9], FloatLit(_)));
+        assert_eq!(tokens.len(), 10);
+    }
+
+    fn lex_concat(text: &str) -> Vec<(Result<Token<'_>, LexError>, Range<usize>)> {
+        ConcatStrings::new(Token::lexer(text).spanned()).collect()
+    }
+
+    fn string(text: &str) -> Result<StringLiteral, LexError> {
+        let mut tokens = lex_concat(text).into_iter();
+        let (tok, _) = tokens.next().unwrap();
+        assert_eq!(tokens.next(), None, "{text:?} should be a single token");
+        tok.map(|t| match t {
+            Token::StringLit(s) => s,
+            t => panic!("{text:?} lexed as {t:?}"),
+        })
+    }
+
+    fn string_units(text: &str) -> (Encoding, Vec<u32>) {
+        let s = string(text).unwrap();
+        (s.encoding, s.units())
+    }
+
+    fn char_lit(text: &str) -> Result<CharLiteral, LexError> {
+        let mut lexer = Token::lexer(text);
+        let tok = lexer.next().unwrap();
+        assert_eq!(lexer.next(), None, "{text:?} should be a single token");
+        tok.map(|t| match t {
+            Token::CharLit(c) => c,
+            t => panic!("{text:?} lexed as {t:?}"),
+        })
+    }
+
+    fn char_value(text: &str) -> (Encoding, i64) {
+        let c = char_lit(text).unwrap();
+        (c.encoding, c.value)
+    }
+
+    fn bytes(s: &str) -> Vec<u32> {
+        s.bytes().map(u32::from).collect()
+    }
+
+    #[test]
+    fn simple_escapes() {
+        assert_eq!(
+            string_units(r#""\n\r\t\a\b\f\v\'\"\?\\""#),
+            (Encoding::Plain, bytes("\n\r\t\x07\x08\x0C\x0B'\"?\\"))
+        );
+        assert_eq!(string_units(r#""'""#), (Encoding::Plain, bytes("'")));
+        assert_eq!(char_value(r"'\n'"), (Encoding::Plain, 10));
+        assert_eq!(char_value("'\"'"), (Encoding::Plain, 34));
+        assert_eq!(char_value(r"'\''"), (Encoding::Plain, 39));
+    }
+
+    #[test]
+    fn numeric_escapes() {
+        assert_eq!(
+            string_units(r#""\0\7\101\1234\x41\x0000041g""#),
+            (
+                Encoding::Plain,
+                vec![
+                    0,
+                    7,
+                    0o101,
+                    0o123,
+                    u32::from(b'4'),
+                    0x41,
+                    0x41,
+                    u32::from(b'g')
+                ]
+            )
+        );
+        // `char` is signed, so escapes above 0x7F are negative
+        assert_eq!(char_value(r"'\xff'"), (Encoding::Plain, -1));
+        assert_eq!(char_value(r"'\200'"), (Encoding::Plain, -128));
+        assert_eq!(char_value(r"u8'\xff'"), (Encoding::Utf8, 255));
+        assert_eq!(char_value(r"u'\xffff'"), (Encoding::Utf16, 0xffff));
+        assert_eq!(char_value(r"U'\xffffffff'"), (Encoding::Utf32, 0xffff_ffff));
+        assert_eq!(char_value(r"L'\xffffffff'"), (Encoding::Wide, -1));
+        assert_eq!(string_units(r#"L"\777""#), (Encoding::Wide, vec![0o777]));
+    }
+
+    #[test]
+    fn universal_character_names() {
+        assert_eq!(string_units(r#""é""#), (Encoding::Plain, bytes("é")));
+        assert_eq!(
+            string_units(r#"u8"\U0001F600""#),
+            (Encoding::Utf8, bytes("😀"))
+        );
+        assert_eq!(
+            string_units(r#"u"\U0001F600é""#),
+            (Encoding::Utf16, vec![0xD83D, 0xDE00, 0xE9])
+        );
+        assert_eq!(
+            string_units(r#"U"\U0001F600""#),
+            (Encoding::Utf32, vec![0x1F600])
+        );
+        assert_eq!(string_units(r#"L"é""#), (Encoding::Wide, vec![0xE9]));
+        assert_eq!(string_units(r#""$""#), (Encoding::Plain, bytes("$")));
+        assert_eq!(char_value(r"U'é'"), (Encoding::Utf32, 0xE9));
+    }
+
+    #[test]
+    fn char_constants() {
+        assert_eq!(char_value("'a'"), (Encoding::Plain, 97));
+        assert_eq!(char_value("'ab'"), (Encoding::Plain, 0x6162));
+        assert_eq!(char_value("'abcd'"), (Encoding::Plain, 0x6162_6364));
+        assert_eq!(char_value("u'é'"), (Encoding::Utf16, 0xE9));
+        assert_eq!(char_lit("''"), Err(LexError::EmptyCharLiteral));
+        assert_eq!(
+            char_lit("u'😀'"),
+            Err(LexError::CharTooLong {
+                encoding: Encoding::Utf16
+            })
+        );
+        assert_eq!(
+            char_lit("U'ab'"),
+            Err(LexError::CharTooLong {
+                encoding: Encoding::Utf32
+            })
+        );
+        assert_eq!(
+            char_lit("u8'é'"),
+            Err(LexError::CharTooLong {
+                encoding: Encoding::Utf8
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_escapes_report_exact_span() {
+        assert_eq!(
+            string(r#""ab\qcd""#),
+            Err(LexError::InvalidEscape {
+                escape: r"\q".to_string(),
+                span: 3..5
+            })
+        );
+        assert_eq!(
+            string(r#""\x""#),
+            Err(LexError::MissingEscapeDigits { span: 1..3 })
+        );
+        assert_eq!(
+            string(r#""a\x100""#),
+            Err(LexError::EscapeOutOfRange { span: 2..7 })
+        );
+        assert_eq!(
+            string(r#""\400""#),
+            Err(LexError::EscapeOutOfRange { span: 1..5 })
+        );
+        assert_eq!(
+            string(r#"u"\x10000""#),
+            Err(LexError::EscapeOutOfRange { span: 2..9 })
+        );
+        assert_eq!(
+            string(r#""\u12x""#),
+            Err(LexError::IncompleteUcn {
+                span: 1..5,
+                expected: 4
+            })
+        );
+        assert_eq!(
+            string(r#""\UD800""#),
+            Err(LexError::IncompleteUcn {
+                span: 1..7,
+                expected: 8
+            })
+        );
+        assert_eq!(
+            string(r#""\uD800""#),
+            Err(LexError::InvalidUcn {
+                value: 0xD800,
+                span: 1..7
+            })
+        );
+        assert_eq!(
+            string(r#""\u0041""#),
+            Err(LexError::InvalidUcn {
+                value: 0x41,
+                span: 1..7
+            })
+        );
+        assert_eq!(
+            char_lit(r"'\e'"),
+            Err(LexError::InvalidEscape {
+                escape: r"\e".to_string(),
+                span: 1..3
+            })
+        );
+        // Only the first error is reported, and lexing continues after the literal
+        let tokens = lex_concat(r#"x = "\q\w"; y"#);
+        assert_eq!(
+            tokens[2],
+            (
+                Err(LexError::InvalidEscape {
+                    escape: r"\q".to_string(),
+                    span: 5..7
+                }),
+                4..10
+            )
+        );
+        assert_eq!(tokens[3], (Ok(Token::Semicolon), 10..11));
+        assert_eq!(tokens[4], (Ok(Token::Ident("y")), 12..13));
+    }
+
+    #[test]
+    fn unterminated_literals() {
+        let tokens = lex_concat("\"abc\nint x;");
+        assert_eq!(
+            tokens[0],
+            (Err(LexError::UnterminatedLiteral { quote: '"' }), 0..4)
+        );
+        assert_eq!(tokens[1], (Ok(Token::Int), 5..8));
+        let tokens = lex_concat(r#""abc\"#);
+        assert_eq!(
+            tokens[0],
+            (Err(LexError::UnterminatedLiteral { quote: '"' }), 0..5)
+        );
+        assert_eq!(
+            char_lit("'a"),
+            Err(LexError::UnterminatedLiteral { quote: '\'' })
+        );
+    }
+
+    #[test]
+    fn adjacent_strings_concatenate() {
+        let tokens = lex_concat(r#"f("ab" "cd"  "e", "x")"#);
+        assert_eq!(tokens[2].1, 2..16);
+        assert_eq!(string(r#""ab" "cd"  "e""#).unwrap().units(), bytes("abcde"));
+        assert_eq!(tokens.len(), 6);
+        // An unprefixed literal takes on the prefix of the other
+        assert_eq!(string_units(r#""a" L"b""#), (Encoding::Wide, vec![97, 98]));
+        assert_eq!(
+            string_units(r#"u"a" "é""#),
+            (Encoding::Utf16, vec![97, 0xE9])
+        );
+        assert_eq!(string_units(r#""é" u8"a""#), (Encoding::Utf8, bytes("éa")));
+        // Escapes stay single code units
+        assert_eq!(
+            string_units(r#""\xff" U"a""#),
+            (Encoding::Utf32, vec![0xFF, 97])
+        );
+        assert_eq!(
+            string(r#"u"a" U"b" "c""#),
+            Err(LexError::MixedEncodings { span: 0..9 })
+        );
+    }
+
+    #[test]
+    fn literal_display_roundtrips() {
+        for text in [
+            r#""hello, world\n""#,
+            r#""\xff\0001\"\\""#,
+            r#"u8"é\t""#,
+            r#"u"\U0001F600""#,
+            r#"L"\x12345678g""#,
+            r#""\a\b\f\v\x1b[0m\x7f""#,
+            r#"u"\a\x100" "0""#,
+            r#"L"\x100" "f""#,
+        ] {
+            let lit = string(text).unwrap();
+            let display = lit.to_string();
+            // Each literal is displayed as a single token, not as several concatenated ones
+            assert_eq!(
+                Token::lexer(&display).count(),
+                1,
+                "{text} displayed as {lit}"
+            );
+            assert_eq!(
+                string(&display),
+                Ok(lit.clone()),
+                "{text} displayed as {lit}"
+            );
+        }
+        // C1 control characters are written as escapes of their code units
+        for (text, display) in [
+            ("\"\u{85}\u{9f}\"", r#""\302\205\302\237""#),
+            ("u\"\u{85}\"", r#"u"\205""#),
+        ] {
+            let lit = string(text).unwrap();
+            assert_eq!(lit.to_string(), display);
+            assert_eq!(string(display).unwrap().units(), lit.units());
+        }
+        assert_eq!(
+            string(r#""\a\b\f\v\x1b\x85" "\u00e9""#)
+                .unwrap()
+                .to_string(),
+            r#""\a\b\f\v\033\205é""#
+        );
+        for text in [
+            r"'a'",
+            r"'\''",
+            r"'\xff'",
+            r"'ab'",
+            r"L'\xffffffff'",
+            r"u'\xd800'",
+            r"'\a'",
+            r"'\x1f'",
+            r"'\xff\x01'",
+            r"L'\x85'",
+        ] {
+            let lit = char_lit(text).unwrap();
+            assert_eq!(
+                char_lit(&lit.to_string()),
+                Ok(lit),
+                "{text} displayed as {lit}"
+            );
+        }
+        assert_eq!(
+            string(r#""aé""#).unwrap().chars,
+            [StrChar::Char('a'), StrChar::Char('é')]
+        );
+    }
+
+    #[test]
+    fn errors_do_not_stop_lexing() {
+        let text = "int @x = 0x; $ y = 1.5e;\n`";
+        let errors: Vec<_> = Token::lexer(text)
+            .spanned()
+            .filter_map(|(t, span)| t.err().map(|e| (e, span)))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (LexError::StrayCharacter('@'), 4..5),
+                (LexError::MissingDigits { base: 16 }, 9..11),
+                (LexError::StrayCharacter('$'), 13..14),
+                (LexError::MissingExponentDigits, 19..23),
+                (LexError::StrayCharacter('`'), 25..26),
+            ]
+        );
+        let tokens: Vec<_> = Token::lexer(text).filter_map(Result::ok).collect();
+        assert_eq!(
+            tokens,
+            [
+                Token::Int,
+                Token::Ident("x"),
+                Token::Eq,
+                Token::Semicolon,
+                Token::Ident("y"),
+                Token::Eq,
+                Token::Semicolon
+            ]
+        );
+    }
+
+    #[test]
+    fn error_components_use_precise_spans() {
+        let source = source::SourceFile::new("test.c".into(), r#""a\zb""#.into());
+        let mut lexer = Token::lexer(source.text()).spanned();
+        let (Err(err), span) = lexer.next().unwrap() else {
+            panic!("expected an error");
+        };
+        assert_eq!(span, 0..6);
+        let component = err.to_component(&source, span);
+        assert_eq!(component.short_message, r"Unknown escape sequence `\z`");
+        assert!(component.long_message.contains(r"\x7f"));
+        assert_eq!(err.span(), Some(2..4));
+        assert_eq!(err.label(), "unknown escape sequence");
+    }
 }
@@ -16,53 +16,90 @@
 // LICENSE NOTICE END
 
 use std::{
-    collections::VecDeque,
-    fmt::{Debug, Display},
+    collections::{HashSet, VecDeque},
+    fmt::{Debug, Display, Write},
 };
 
-use crate::Parser;
-use diagnostics::{AggregateError, ErrorComponent};
+use crate::{
+    Expected, Parser,
+    scope::{FunctionScope, Scopes},
+};
+use diagnostics::{AggregateError, ErrorComponent, ErrorLevel};
 use lexer::{SToken, Token};
 use source::{SourceFile, Span};
+use syntax::{Builder, SyntaxKind};
 use utils::Spanned;
 
 impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s, Tokens> {
     #[must_use]
-    pub const fn new(source: SourceFile, tokens: Tokens) -> Self {
+    pub fn new(source: SourceFile, tokens: Tokens) -> Self {
         Self {
             tokens,
             peeked: VecDeque::new(),
             errors: AggregateError::new(),
+            syntax: Builder::new(source.clone()),
             lexer_errors: AggregateError::new(),
+            invalid: HashSet::new(),
+            dropped: None,
             source,
+            scopes: Scopes::new(),
+            function: FunctionScope::default(),
+            type_decls: Vec::new(),
+            expected: Vec::new(),
+            prev_span: None,
         }
     }
     pub(crate) fn end_span(&self) -> Span {
         let len = self.source.text().len();
         len..len
     }
-    pub(crate) fn add_lexer_error(&mut self, err: ErrorComponent) {
+    /// The span from `start` up to the end of the last token consumed.
+    pub(crate) fn span_from(&self, start: usize) -> Span {
+        let end = self.prev_span.as_ref().map_or(start, |prev| prev.end);
+        start..end.max(start)
+    }
+    /// Reports a token that failed to lex, returning the [Token::Invalid] that takes its place.
+    pub(crate) fn add_lexer_error(&mut self, err: ErrorComponent) -> SToken<'s> {
+        let span = err.highlight().clone();
+        self.invalid.insert(span.start);
         self.lexer_errors.add_error(err);
+        SToken::new(Token::Invalid, span)
     }
     pub(crate) fn new_parse_error(&mut self, span: Span, message: String) -> &mut ErrorComponent {
-        self.errors
-            .add_error(ErrorComponent::new(self.source.clone(), message, span))
+        let error = ErrorComponent::new(self.source.clone(), message, span.clone());
+        if self.invalid.contains(&span.start) {
+            return self.dropped.insert(error);
+        }
+        self.errors.add_error(error)
+    }
+    /// Spans the item parsed since `start`, adding it to the syntax tree as a node of `kind`.
+    pub(crate) fn node_from(&mut self, kind: SyntaxKind, start: usize) -> Span {
+        let span = self.span_from(start);
+        self.syntax.node(kind, &span);
+        span
+    }
+    /// Reads the next token of the input, adding it to the syntax tree.
+    fn read(&mut self) -> Option<SToken<'s>> {
+        let token = match self.tokens.next()? {
+            Ok(t) => t,
+            Err(e) => self.add_lexer_error(e),
+        };
+        self.syntax
+            .token(SyntaxKind::from(&token.inner), token.as_span());
+        Some(token)
     }
     #[inline(always)]
     pub(crate) fn advance(&mut self) -> Option<SToken<'s>> {
-        if let t @ Some(_) = self.peeked.pop_front() {
-            return t;
-        }
-        loop {
-            match self.tokens.next()? {
-                Ok(t) => break Some(t),
-                Err(e) => {
-                    self.add_lexer_error(e);
-                }
-            }
-        }
+        let token = match self.peeked.pop_front() {
+            t @ Some(_) => t,
+            None => self.read(),
+        };
+        self.expected.clear();
+        self.prev_span = token.as_ref().map(Spanned::as_span);
+        token
     }
     pub(crate) fn put_back(&mut self, tok: SToken<'s>) {
+        self.expected.clear();
         self.peeked.push_front(tok);
     }
     pub fn is_empty(&mut self) -> bool {
@@ -75,14 +112,8 @@ impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s,
     #[inline(always)]
     pub(crate) fn peek(&mut self, idx: usize) -> Option<&SToken<'s>> {
         while self.peeked.len() <= idx {
-            match self.tokens.next()? {
-                Ok(t) => {
-                    self.peeked.push_back(t);
-                }
-                Err(e) => {
-                    self.add_lexer_error(e);
-                }
-            }
+            let token = self.read()?;
+            self.peeked.push_back(token);
         }
         self.peeked.get(idx)
     }
@@ -102,10 +133,31 @@ impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s,
         let next = next.map(|n| &n.inner);
         (next, span)
     }
+    /// Tests the next token without recording what would have been accepted, for lookahead
+    /// that doesn't decide between alternatives of the grammar.
     #[inline(always)]
-    fn check(&mut self, predicate: impl FnOnce(&Token) -> bool) -> bool {
+    pub(crate) fn check(&mut self, predicate: impl FnOnce(&Token) -> bool) -> bool {
         self.peek_next().is_some_and(|t| predicate(&t.inner))
     }
+    /// Tests whether the next token is `tok`, recording it for [Self::expect] if it isn't.
+    pub(crate) fn check_eq(&mut self, tok: &Token<'_>) -> bool {
+        let found = self.check(|next| next == tok);
+        if !found {
+            self.add_expected(tok);
+        }
+        found
+    }
+    /// Tests whether the next token is one of `tokens`, recording them for [Self::expect] if it
+    /// isn't.
+    pub(crate) fn check_any(&mut self, tokens: &[Token<'_>]) -> bool {
+        let found = self.check(|next| tokens.contains(next));
+        if !found {
+            for tok in tokens {
+                self.add_expected(tok);
+            }
+        }
+        found
+    }
     pub(crate) fn try_map<T>(
         &mut self,
         predicate: impl FnOnce(Token) -> Result<T, Token>,
@@ -120,21 +172,45 @@ impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s,
             }
         }
     }
+    /// Records that `tok` would have been accepted as the next token, for the message of
+    /// [Self::expect].
+    pub(crate) fn add_expected(&mut self, tok: &Token<'_>) {
+        if let Some(spelling) = tok.spelling() {
+            self.add_expected_kind(Expected::Token(spelling));
+        }
+    }
+    /// Records that an operator continuing the expression before the next token would have
+    /// been accepted.
+    pub(crate) fn add_expected_operator(&mut self) {
+        self.add_expected_kind(Expected::Operator);
+    }
+    fn add_expected_kind(&mut self, expected: Expected) {
+        if !self.expected.contains(&expected) {
+            self.expected.push(expected);
+        }
+    }
     pub(crate) fn advance_if_eq(&mut self, tok: &Token<'_>) -> Option<SToken<'s>> {
-        let advance = self.check(|next| next == tok);
-        if advance {
+        if self.check_eq(tok) {
             return self.advance();
         }
         None
     }
     #[inline(always)]
     pub(crate) fn consume_if_eq(&mut self, tok: &Token<'_>) -> bool {
-        let advance = self.check(|next| next == tok);
+        let advance = self.check_eq(tok);
         if advance {
             self.advance();
         }
         advance
     }
+    /// Advances if the next token is one of `tokens`, recording them for [Self::expect]
+    /// otherwise.
+    pub(crate) fn advance_if_any(&mut self, tokens: &[Token<'_>]) -> Option<SToken<'s>> {
+        if self.check_any(tokens) {
+            return self.advance();
+        }
+        None
+    }
     #[inline]
     pub(crate) fn advance_if(
         &mut self,
@@ -158,19 +234,58 @@ impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s,
         self.advance_if(predicate).is_some()
     }
     /// Advance if the token matches, log an error otherwise.
+    ///
+    /// The error lists every other token that would have been accepted at this point, as
+    /// recorded by the `check`, `consume` and `advance` methods that take the tokens they
+    /// accept.
     pub(crate) fn expect(&mut self, expected: &Token<'s>, ctx: impl Display) -> Option<SToken<'s>> {
         let tok = self.advance_if(|t| t == expected);
-        if tok.is_none() {
-            let (found, span) = self.peek_next_split();
-            let msg = format!("Expected {expected}{ctx}, found {found:?}");
-            self.new_parse_error(span, msg);
+        if tok.is_some() {
+            return tok;
+        }
+        let mut accepted = vec![format!("`{expected}`")];
+        let others = self.expected.iter().filter(|&&t| match t {
+            Expected::Token(spelling) => Some(spelling) != expected.spelling(),
+            Expected::Operator => false,
+        });
+        accepted.extend(others.map(ToString::to_string));
+        // Any operator could follow, so they're summarized rather than listed
+        if self.expected.contains(&Expected::Operator) {
+            accepted.push(Expected::Operator.to_string());
+        }
+        let mut list = if accepted.len() > 2 {
+            "one of ".to_owned()
+        } else {
+            String::new()
+        };
+        let last = accepted.pop().expect("the expected token is accepted");
+        if !accepted.is_empty() {
+            write!(list, "{} or ", accepted.join(", ")).unwrap();
+        }
+        list += &last;
+        let (found, span) = self.peek_next_split();
+        let msg = format!("Expected {list}{ctx}, found {}", Found(found));
+        self.new_parse_error(span, msg);
+        None
+    }
+    /// Expects the `;` that ends a statement or declaration. A missing one is easier to spot
+    /// where it should have been, at the end of the line before the next token, so a note
+    /// points there.
+    pub(crate) fn expect_semicolon(&mut self, ctx: impl Display) -> Option<SToken<'s>> {
+        let tok = self.expect(&Token::Semicolon, ctx);
+        if tok.is_none()
+            && let Some(prev) = self.prev_span.clone()
+        {
+            let span = prev.end.saturating_sub(1)..prev.end;
+            let msg = "`;` should go after this".to_owned();
+            self.new_parse_error(span, msg).set_level(ErrorLevel::Note);
         }
         tok
     }
     pub(crate) fn expect_ident(&mut self, ctx: impl Display) -> Option<&'s str> {
         let (t, span) = self.peek_next_split();
         let Some(&Token::Ident(val)) = t else {
-            let msg = format!("Expected an identifier{ctx} found {t:?}");
+            let msg = format!("Expected an identifier{ctx}, found {}", Found(t));
             self.new_parse_error(span, msg);
             return None;
         };
@@ -178,6 +293,34 @@ impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s,
         Some(val)
     }
 }
+impl Display for Expected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Token(spelling) => write!(f, "`{spelling}`"),
+            Self::Operator => f.write_str("an operator"),
+        }
+    }
+}
+
+/// The token found where another was expected, in error messages. Tokens that carry a value are
+/// described by their kind along with their spelling.
+pub(crate) struct Found<'a, 's>(pub(crate) Option<&'a Token<'s>>);
+
+impl Display for Found<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            None => f.write_str("end of file"),
+            Some(Token::Ident(name)) => write!(f, "identifier `{name}`"),
+            Some(t @ Token::IntLit(_)) => write!(f, "integer constant `{t}`"),
+            Some(t @ Token::FloatLit(_)) => write!(f, "floating constant `{t}`"),
+            Some(t @ Token::CharLit(_)) => write!(f, "character constant `{t}`"),
+            Some(t @ Token::StringLit(_)) => write!(f, "string literal `{t}`"),
+            // Keywords and punctuators are what they're spelled as
+            Some(t) => write!(f, "`{t}`"),
+        }
+    }
+}
+
 impl<T: Iterator> Debug for Parser<'_, T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         fn format_error_warning_count(components: &[ErrorComponent]) -> String {
@@ -187,6 +330,7 @@ impl<T: Iterator> Debug for Parser<'_, T> {
                 match component.level {
                     diagnostics::ErrorLevel::Error => errors += 1,
                     diagnostics::ErrorLevel::Warning => warnings += 1,
+                    diagnostics::ErrorLevel::Note => (),
                 }
             }
             match (errors, warnings) {
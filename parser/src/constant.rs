@@ -0,0 +1,456 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use ast::{
+    BinaryOperator, Expr, LiteralExpression, SExpr, ScalarType, TagKind, Type, UnaryOperator,
+    constant::{self, ConstContext, ConstError, Constant},
+    layout::Layouts,
+};
+use diagnostics::ErrorComponent;
+
+use crate::{Parser, scope::Symbol};
+
+use super::SToken;
+
+const INT: Type = Type::Scalar(ScalarType::Int);
+
+/// The type of the object `ty` points to, or of the elements of the array `ty`.
+pub(crate) fn pointee(ty: &Type) -> Option<Type> {
+    match ty.unqualified() {
+        Type::Pointer(pointee) | Type::Array(pointee, _) => Some((**pointee).clone()),
+        _ => None,
+    }
+}
+
+/// The type of the value of an operand of type `ty`, with arrays and functions converted to
+/// pointers (C17 6.3.2.1).
+pub(crate) fn decayed(ty: Type) -> Type {
+    match ty.unqualified() {
+        Type::Array(elem, _) => Type::Pointer(elem.clone()),
+        Type::Function(_) => Type::Pointer(Box::new(ty)),
+        ty => ty.clone(),
+    }
+}
+
+/// The promoted type of an arithmetic operand, with enums having the type of their constants.
+pub(crate) fn arithmetic(ty: &Type) -> Option<ScalarType> {
+    match ty.unqualified() {
+        Type::Scalar(ScalarType::Void | ScalarType::NullPtr) => None,
+        Type::Scalar(scalar) => Some(scalar.promoted()),
+        Type::Tagged(tag) if tag.kind == TagKind::Enum => Some(ScalarType::Int),
+        _ => None,
+    }
+}
+
+/// Whether a value of type `ty` is a scalar, which can be compared to zero: an arithmetic value
+/// or a pointer, which arrays and functions are converted to.
+pub(crate) fn is_scalar(ty: &Type) -> bool {
+    arithmetic(ty).is_some()
+        || matches!(
+            decayed(ty.clone()),
+            Type::Pointer(_) | Type::Scalar(ScalarType::NullPtr)
+        )
+}
+
+/// Whether a value of type `ty` is an integer, including characters, `_Bool` and enums.
+pub(crate) fn is_integer(ty: &Type) -> bool {
+    arithmetic(ty).is_some_and(ScalarType::is_integer)
+}
+
+/// The common type of two promoted arithmetic types, which is the wider floating type if
+/// either is one (C17 6.3.1.8).
+fn common(a: ScalarType, b: ScalarType) -> ScalarType {
+    match (a.is_integer(), b.is_integer()) {
+        (true, true) => a.common(b),
+        (false, true) => a,
+        (true, false) => b,
+        (false, false) => {
+            if a.size() >= b.size() {
+                a
+            } else {
+                b
+            }
+        }
+    }
+}
+
+impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> ConstContext<'s>
+    for Parser<'s, Tokens>
+{
+    fn enum_constant(&self, name: &str) -> Option<i64> {
+        match self.scopes.lookup(name)? {
+            Symbol::Constant(value) => Some(value),
+            _ => None,
+        }
+    }
+    fn static_object(&self, name: &str) -> Option<Type> {
+        match self.scopes.lookup(name)? {
+            Symbol::Static(ty) => Some(ty),
+            Symbol::Function { .. } => Some(self.scopes.lookup_global(name)?.ty.clone()),
+            _ => None,
+        }
+    }
+    fn layouts(&self) -> &Layouts<'s> {
+        self.scopes.layouts()
+    }
+    fn static_compound_literals(&self) -> bool {
+        self.scopes.is_file_scope()
+    }
+    fn type_of(&mut self, expr: &SExpr<'s>) -> Option<Type> {
+        self.expr_type(expr)
+    }
+}
+
+impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s, Tokens> {
+    /// Finds the type of an expression, without converting arrays and functions to pointers,
+    /// or [None] if it has an error that has already been reported.
+    pub(crate) fn expr_type(&self, expr: &SExpr<'s>) -> Option<Type> {
+        use BinaryOperator as B;
+        let operand = |expr| Some(decayed(self.expr_type(expr)?));
+        Some(match &expr.inner {
+            Expr::Lit(LiteralExpression::Invalid) => return None,
+            Expr::Lit(lit) => lit.ty(),
+            Expr::Ident(name) => match self.scopes.lookup(name)? {
+                Symbol::Object(ty) | Symbol::Static(ty) => ty,
+                Symbol::Function { .. } => self.scopes.lookup_global(name)?.ty.clone(),
+                Symbol::Constant(_) => INT,
+                Symbol::Typedef(_) => return None,
+            },
+            Expr::Binary(binary) => {
+                let (lhs, rhs) = (operand(&binary.lhs)?, operand(&binary.rhs)?);
+                match (binary.op, &lhs, &rhs) {
+                    (B::Lt | B::Le | B::Gt | B::Ge | B::Eq | B::Ne, ..) => INT,
+                    // The result of a shift has the type of its promoted left operand
+                    (B::Shl | B::Shr, ..) => Type::Scalar(arithmetic(&lhs)?),
+                    (B::Sub, Type::Pointer(_), Type::Pointer(_)) => Type::Scalar(ScalarType::Long),
+                    (B::Add | B::Sub, Type::Pointer(_), _) => lhs,
+                    (B::Add, _, Type::Pointer(_)) => rhs,
+                    _ => Type::Scalar(common(arithmetic(&lhs)?, arithmetic(&rhs)?)),
+                }
+            }
+            Expr::Logical(_) => INT,
+            Expr::Unary(unary) => match unary.op {
+                UnaryOperator::Neg | UnaryOperator::Plus | UnaryOperator::BitNot => {
+                    Type::Scalar(arithmetic(&operand(&unary.val)?)?)
+                }
+                UnaryOperator::Not => INT,
+                UnaryOperator::Deref => pointee(&operand(&unary.val)?)?,
+                UnaryOperator::AddrOf => Type::Pointer(Box::new(self.expr_type(&unary.val)?)),
+                UnaryOperator::PreInc
+                | UnaryOperator::PreDec
+                | UnaryOperator::PostInc
+                | UnaryOperator::PostDec => operand(&unary.val)?,
+            },
+            Expr::Assignment(assignment) => operand(&assignment.target)?,
+            Expr::Ternary(ternary) => {
+                let then_ty = operand(&ternary.then_val)?;
+                let else_ty = operand(&ternary.else_val)?;
+                match (arithmetic(&then_ty), arithmetic(&else_ty)) {
+                    (Some(a), Some(b)) => Type::Scalar(common(a, b)),
+                    // A null pointer constant takes the type of the other pointer
+                    _ if matches!(then_ty, Type::Pointer(_)) => then_ty,
+                    _ if matches!(else_ty, Type::Pointer(_)) => else_ty,
+                    _ => then_ty,
+                }
+            }
+            Expr::Comma(comma) => operand(&comma.rhs)?,
+            Expr::Call(call) => {
+                let callee = operand(&call.callee)?;
+                match pointee(&callee)? {
+                    Type::Function(function) => function.ret,
+                    _ => return None,
+                }
+            }
+            // Either operand of a subscript can be the pointer
+            Expr::Index(index) => {
+                pointee(&operand(&index.target)?).or_else(|| pointee(&operand(&index.index)?))?
+            }
+            Expr::MemberAccess(access) => {
+                let object = self.expr_type(&access.object)?;
+                let object = if access.arrow {
+                    pointee(&decayed(object))?
+                } else {
+                    object
+                };
+                let Type::Tagged(tag) = object.unqualified() else {
+                    return None;
+                };
+                // A member has the qualifiers of the struct or union it's a member of
+                let member = self.scopes.layouts().find_member(tag, access.member)?.0;
+                match object {
+                    Type::Qualified(qualifiers, _) => member.qualified(qualifiers),
+                    _ => member,
+                }
+            }
+            Expr::Cast(cast) => cast.ty.clone(),
+            Expr::CompoundLiteral(literal) => literal.ty.clone(),
+            Expr::SizeOf(_) | Expr::AlignOf(_) => Type::Scalar(ScalarType::ULong),
+        })
+    }
+    /// Evaluates an integer constant expression. If it can't be evaluated, reports either
+    /// `message` when it isn't a constant expression or why its value is undefined.
+    pub(crate) fn eval_integer(
+        &mut self,
+        expr: &SExpr<'s>,
+        message: impl FnOnce() -> String,
+    ) -> Option<i128> {
+        let result = constant::evaluate_integer(expr, self);
+        self.report_const_error(expr, result, message)
+    }
+    /// Evaluates a constant expression that may be an address constant, like the initializers
+    /// of objects with static storage duration, reporting errors as [Self::eval_integer] does.
+    pub(crate) fn eval_constant(
+        &mut self,
+        expr: &SExpr<'s>,
+        message: impl FnOnce() -> String,
+    ) -> Option<Constant<'s>> {
+        let result = constant::evaluate(expr, self);
+        self.report_const_error(expr, result, message)
+    }
+    fn report_const_error<T>(
+        &mut self,
+        expr: &SExpr<'s>,
+        result: Result<T, ConstError>,
+        message: impl FnOnce() -> String,
+    ) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(ConstError::NotConstant) => {
+                self.new_parse_error(expr.as_span(), message());
+                None
+            }
+            Err(ConstError::Invalid { span, message }) => {
+                self.new_parse_error(span, message);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::tests::{parse_scoped, parse_with};
+
+    const FLOATING: &str = "Floating-point types are not supported";
+
+    /// Evaluates `text` as an integer constant expression.
+    fn eval(text: &str) -> Result<i128, Vec<String>> {
+        parse_with(text, |p| {
+            let expr = p.parse_expression()?;
+            p.eval_integer(&expr, || "Not a constant".to_owned())
+        })
+    }
+
+    #[test]
+    fn typed_arithmetic() {
+        assert_eq!(eval("1 + 2 * 3 - 10 / 3 % 2"), Ok(6));
+        assert_eq!(eval("-7 / 2"), Ok(-3));
+        assert_eq!(eval("0u - 1"), Ok(4_294_967_295));
+        assert_eq!(eval("0ul - 1 > 0"), Ok(1));
+        assert_eq!(eval("-1 < 0u"), Ok(0));
+        assert_eq!(eval("-1 < 0l"), Ok(1));
+        assert_eq!(eval("(unsigned char)-1 + (signed char)255"), Ok(254));
+        assert_eq!(eval("(_Bool)256 + (short)65537"), Ok(2));
+        assert_eq!(eval("4294967295u * 4294967295u"), Ok(1));
+        assert_eq!(eval("1u << 31 >> 31"), Ok(1));
+        assert_eq!(eval("-8 >> 1"), Ok(-4));
+        assert_eq!(eval("~0u"), Ok(4_294_967_295));
+        assert_eq!(eval("U'a' - 98 > 0"), Ok(1));
+        assert_eq!(eval("(int)2.9 + (long)1.5"), Ok(3));
+        assert_eq!(eval("1 ? 2u : -1"), Ok(2));
+        assert_eq!(eval("0 ? 1u : -1"), Ok(4_294_967_295));
+        assert_eq!(eval("!0 + !5 + (2 && 3) + (0 || 0)"), Ok(2));
+    }
+
+    #[test]
+    fn sizeof_and_alignof() {
+        assert_eq!(
+            eval("sizeof(int) + sizeof(long double) + _Alignof(short)"),
+            Ok(22)
+        );
+        assert_eq!(
+            eval("sizeof \"abc\" + sizeof u\"ab\" + sizeof 'a' + sizeof u'a'"),
+            Ok(16)
+        );
+        assert_eq!(
+            eval("sizeof(char[3][4]) + sizeof(1 + 1l) + sizeof((short)1)"),
+            Ok(22)
+        );
+        // The operand of `sizeof` isn't evaluated, so it doesn't have to be constant
+        assert_eq!(eval("sizeof(x = 2) + sizeof x++"), Ok(8));
+        assert_eq!(eval("0 - sizeof(int) > 0"), Ok(1));
+        let eval_in = |text| {
+            parse_scoped(text, |p| {
+                p.parse_decl()?;
+                let expr = p.parse_expression()?;
+                p.eval_integer(&expr, || "Not a constant".to_owned())
+            })
+        };
+        assert_eq!(
+            eval_in("struct s { char c; _Alignas(8) short h; } s[3]; sizeof s / sizeof *s"),
+            Ok(3)
+        );
+        assert_eq!(
+            eval_in("struct s { char c; _Alignas(8) short h; } s; _Alignof(struct s) + sizeof s.h"),
+            Ok(10)
+        );
+        assert_eq!(
+            eval_in("int a[5]; sizeof a + sizeof &a + sizeof(a + 1) + sizeof a[0]"),
+            Ok(40)
+        );
+    }
+
+    #[test]
+    fn unmeasurable_types() {
+        assert_eq!(
+            eval("sizeof(void) + _Alignof(int (void))"),
+            Err(vec![
+                "Cannot apply `sizeof` to incomplete type `void`".to_owned(),
+                "Cannot apply `_Alignof` to function type `int (void)`".to_owned(),
+            ])
+        );
+        // Only the operand is reported, not the expression containing it
+        assert_eq!(
+            parse_scoped(
+                "struct s; int f(void); int a[sizeof(struct s) + sizeof f];",
+                |p| {
+                    p.parse_decl()?;
+                    p.parse_decl()?;
+                    p.parse_decl()
+                }
+            )
+            .map(|_| ()),
+            Err(vec![
+                "Cannot apply `sizeof` to incomplete type `struct s`".to_owned(),
+                "Cannot apply `sizeof` to function type `int (void)`".to_owned(),
+            ])
+        );
+    }
+
+    #[test]
+    fn undefined_behavior() {
+        assert_eq!(
+            eval("2147483647 + 1"),
+            Err(vec![
+                "Integer overflow in constant expression of type `int`".to_owned()
+            ])
+        );
+        assert_eq!(
+            eval("-(-9223372036854775807l - 1)"),
+            Err(vec![
+                "Integer overflow in constant expression of type `long`".to_owned()
+            ])
+        );
+        assert_eq!(
+            eval("(-2147483647 - 1) % -1"),
+            Err(vec![
+                "Integer overflow in constant expression of type `int`".to_owned()
+            ])
+        );
+        assert_eq!(
+            eval("1 + 1 / (2 - 2)"),
+            Err(vec!["Division by zero in constant expression".to_owned()])
+        );
+        assert_eq!(
+            eval("1 << -1"),
+            Err(vec!["Shift count -1 is out of range for `int`".to_owned()])
+        );
+        assert_eq!(
+            eval("(char)1e3"),
+            Err(vec!["`1000.0` is out of range for `char`".to_owned()])
+        );
+        // Operands that aren't evaluated can't overflow
+        assert_eq!(eval("0 && 1 / 0"), Ok(0));
+        assert_eq!(eval("1 || 2147483647 + 1"), Ok(1));
+        assert_eq!(eval("1 ? 2 : 1 << 99"), Ok(2));
+        // But they still have to be constant
+        assert_eq!(eval("0 && x"), Err(vec!["Not a constant".to_owned()]));
+        assert_eq!(eval("(1, 2)"), Err(vec!["Not a constant".to_owned()]));
+        assert_eq!(eval("1.5 + 1"), Err(vec!["Not a constant".to_owned()]));
+    }
+
+    /// Parses the declarations in `text`, returning the errors in them.
+    fn check(text: &str) -> Result<(), Vec<String>> {
+        parse_scoped(text, |p| {
+            while !p.is_empty() {
+                p.parse_decl()?;
+            }
+            Some(())
+        })
+    }
+
+    #[test]
+    fn floating_constants() {
+        // Floating constants are allowed in arithmetic constant expressions, but not supported
+        assert_eq!(
+            check(
+                "double d = 3; int y = 1 < 2.0, q = 1.0 && 2, i = (int)2.5, c = (int)(double)1; \
+                 float f = (int)1.5;"
+            ),
+            Err(vec![
+                FLOATING.to_owned(),
+                FLOATING.to_owned(),
+                FLOATING.to_owned(),
+                FLOATING.to_owned(),
+                FLOATING.to_owned(),
+            ])
+        );
+        // In integer constant expressions they're only allowed as the operands of casts
+        assert_eq!(
+            check("int a[1 < 2.0], b[(int)(1.5 + 1)], c[(int)2.5];"),
+            Err(vec![
+                "Array length must be an integer constant".to_owned(),
+                "Array length must be an integer constant".to_owned(),
+            ])
+        );
+    }
+
+    #[test]
+    fn address_constants() {
+        assert_eq!(
+            check(
+                "struct s { int a; struct { char c[4]; } in; } s, *sp = &s, arr[2]; int f(void); \
+                 int *a = &s.a + 1, (*fp)(void) = f, (*fp2)(void) = &f; char *c = s.in.c + 2; \
+                 char *d = &arr[1].in.c[3], *e = (char *)&arr + 4; long l = (long)&s; \
+                 int cmp = &arr[0] < &arr[1], diff = &arr[1] - arr, null = (int *)0 == 0; \
+                 int lengths[&arr[1].in.c[0] - &arr[0].in.c[0]]; \
+                 const char *str = \"hi\", *str_end = \"hi\" + 2, *in_str = &\"abc\"[1]; \
+                 char (*str_array)[3] = &\"ab\"; \
+                 void g(void) { static struct s *p = arr + 1; static char *q = \"q\"; }"
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            check(
+                "int x, y; int *a = &x + y; char b = (char)&x; int c = &x == &y; \
+                 int d = x; int e[(long)&x]; void g(void) { int l; static int *p = &l; } \
+                 int same = \"a\" == \"a\"; long length = \"ab\" - \"a\";"
+            ),
+            Err(vec![
+                "The initializer of `a` must be a constant expression".to_owned(),
+                "The initializer of `b` must be a constant expression".to_owned(),
+                "The initializer of `c` must be a constant expression".to_owned(),
+                "The initializer of `d` must be a constant expression".to_owned(),
+                "Array length must be an integer constant".to_owned(),
+                "The initializer of `p` must be a constant expression".to_owned(),
+                "The initializer of `same` must be a constant expression".to_owned(),
+                "The initializer of `length` must be a constant expression".to_owned(),
+            ])
+        );
+    }
+}
@@ -0,0 +1,1225 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use ast::{
+    Expr, Function, FunctionType, Initializer, LiteralExpression, Param, Qualifiers, ScalarType,
+    Type, TypeDecl, VarDecl,
+};
+use diagnostics::ErrorComponent;
+use lexer::Token;
+use source::Span;
+use syntax::SyntaxKind;
+use utils::Spanned;
+
+use crate::{
+    Parser,
+    basic_ops::Found,
+    recovery::SyncSet,
+    scope::{Global, Linkage, Symbol},
+    types::{self, StorageClass},
+};
+
+use super::SToken;
+
+/// Whether a declarator declares a name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DeclaratorKind {
+    Named,
+    /// A declarator without a name, as in type names
+    Abstract,
+    /// A declarator that may or may not have a name, as for parameters
+    Either,
+}
+
+/// A step in deriving the type of a declarator from the type it was derived from.
+#[derive(Debug)]
+enum Derivation<'s> {
+    Pointer(Qualifiers),
+    /// An array of the given length. The qualifiers in its brackets, if it has any or `static`,
+    /// apply to the pointer that a parameter of this type is adjusted to (C17 6.7.6.3p7).
+    Array(Option<u64>, Option<Qualifiers>),
+    Function(Option<Vec<Spanned<Param<'s>>>>, bool),
+}
+
+/// The name declared by a declarator and its span, if it has one.
+type Name<'s> = Option<(&'s str, Span)>;
+
+/// The parameters of a function declarator, and whether it's variadic.
+pub(crate) type Params<'s> = (Option<Vec<Spanned<Param<'s>>>>, bool);
+
+#[derive(Debug)]
+pub(crate) struct Declarator<'s> {
+    pub(crate) name: Name<'s>,
+    pub(crate) ty: Type,
+    /// The parameters of the function declarator that was applied last, if any. A function
+    /// definition uses them to declare the parameters in its body.
+    pub(crate) params: Option<Params<'s>>,
+    /// The qualifiers in the brackets of the outermost array declarator of a parameter, which
+    /// apply to the pointer it's adjusted to
+    pub(crate) array_qualifiers: Option<Qualifiers>,
+}
+
+impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s, Tokens> {
+    /// Whether the `(` at the start of a direct declarator opens a nested declarator rather than
+    /// a parameter list.
+    fn starts_nested_declarator(&mut self, kind: DeclaratorKind) -> bool {
+        match self.peek(1).map(|t| &t.inner) {
+            Some(Token::Star | Token::LParen | Token::LBracket) => true,
+            Some(Token::Ident(_)) => kind != DeclaratorKind::Abstract,
+            _ => kind == DeclaratorKind::Named,
+        }
+    }
+    /// Parses an array declarator after the opening bracket.
+    fn parse_array(&mut self) -> Option<Derivation<'s>> {
+        let start = self.peek_next_split().1.start;
+        // `static` can come before or after the qualifiers
+        let mut is_static = self.consume_if_eq(&Token::Static);
+        let qualifiers = self.parse_qualifiers();
+        is_static = is_static || self.consume_if_eq(&Token::Static);
+        let qualifiers = (is_static || !qualifiers.is_empty()).then_some(qualifiers);
+        if is_static && self.check_eq(&Token::RBracket) {
+            let msg = "An array declarator with `static` must have a length".to_string();
+            self.new_parse_error(self.span_from(start), msg);
+        }
+        Some(Derivation::Array(self.parse_array_length()?, qualifiers))
+    }
+    /// Parses the length of an array declarator, after the opening bracket and any qualifiers.
+    fn parse_array_length(&mut self) -> Option<Option<u64>> {
+        if self.consume_if_eq(&Token::RBracket) {
+            return Some(None);
+        }
+        let len = self.parse_assignment_expression()?;
+        self.expect(&Token::RBracket, " to close array declarator")?;
+        let message = || "Array length must be an integer constant".to_string();
+        // An invalid length is taken to be 1 rather than left out, which would give errors
+        // about the array type being incomplete
+        let Some(value) = self.eval_integer(&len, message) else {
+            return Some(Some(1));
+        };
+        if value <= 0 {
+            let msg = "Array length must be greater than zero".to_string();
+            self.new_parse_error(len.as_span(), msg);
+        }
+        Some(u64::try_from(value).ok())
+    }
+    /// Parses the derivations of a declarator in the order they apply to the base type: the
+    /// pointers, then the array and function suffixes from right to left, then those of a
+    /// nested declarator.
+    fn parse_derivations(
+        &mut self,
+        kind: DeclaratorKind,
+    ) -> Option<(Name<'s>, Vec<Derivation<'s>>)> {
+        let mut derivations = Vec::new();
+        while self.consume_if_eq(&Token::Star) {
+            derivations.push(Derivation::Pointer(self.parse_qualifiers()));
+        }
+        let (next, span) = self.peek_next_split();
+        let (name, nested) = match next.cloned() {
+            Some(Token::Ident(name)) if kind != DeclaratorKind::Abstract => {
+                _ = self.advance();
+                (Some((name, span)), Vec::new())
+            }
+            Some(Token::LParen) if self.starts_nested_declarator(kind) => {
+                _ = self.advance();
+                let nested = self.parse_derivations(kind)?;
+                self.expect(&Token::RParen, " to close declarator")?;
+                nested
+            }
+            _ if kind == DeclaratorKind::Named => {
+                // There is no identifier here, so this reports the error
+                self.expect_ident(" in declaration")?;
+                unreachable!()
+            }
+            _ => (None, Vec::new()),
+        };
+        let mut suffixes = Vec::new();
+        loop {
+            if self.consume_if_eq(&Token::LBracket) {
+                suffixes.push(self.parse_array()?);
+            } else if self.consume_if_eq(&Token::LParen) {
+                // The parameters are in a scope of their own, the function prototype scope
+                let (params, variadic) = self.in_scope(Self::parse_parameters)?;
+                suffixes.push(Derivation::Function(params, variadic));
+            } else {
+                break;
+            }
+        }
+        derivations.extend(suffixes.into_iter().rev());
+        derivations.extend(nested);
+        Some((name, derivations))
+    }
+    /// Parses a declarator, deriving its type from `base`.
+    pub(crate) fn parse_declarator(
+        &mut self,
+        base: &Type,
+        kind: DeclaratorKind,
+    ) -> Option<Declarator<'s>> {
+        let span = self.peek_next_split().1;
+        let (name, derivations) = self.parse_derivations(kind)?;
+        let mut ty = base.clone();
+        let mut params = None;
+        let mut array_qualifiers = None;
+        let outermost = derivations.len().saturating_sub(1);
+        for (i, derivation) in derivations.into_iter().enumerate() {
+            params = None;
+            ty = match derivation {
+                Derivation::Pointer(qualifiers) => {
+                    Type::Pointer(Box::new(ty)).qualified(qualifiers)
+                }
+                Derivation::Array(len, qualifiers) => {
+                    if matches!(ty.unqualified(), Type::Function(_)) {
+                        let msg = "Arrays of functions are not allowed".to_string();
+                        self.new_parse_error(span.clone(), msg);
+                    }
+                    if qualifiers.is_some() && (kind != DeclaratorKind::Either || i != outermost) {
+                        let msg = "Type qualifiers and `static` are only allowed in the \
+                                   outermost array declarator of a parameter"
+                            .to_string();
+                        self.new_parse_error(span.clone(), msg);
+                    }
+                    array_qualifiers = qualifiers;
+                    Type::Array(Box::new(ty), len)
+                }
+                Derivation::Function(function_params, variadic) => {
+                    let returned = match ty.unqualified() {
+                        Type::Array(..) => Some("arrays"),
+                        Type::Function(_) => Some("functions"),
+                        _ => None,
+                    };
+                    if let Some(returned) = returned {
+                        let msg = format!("Functions cannot return {returned}");
+                        self.new_parse_error(span.clone(), msg);
+                    }
+                    let function = FunctionType {
+                        ret: ty,
+                        params: function_params
+                            .as_ref()
+                            .map(|params| params.iter().map(|p| p.inner.ty.clone()).collect()),
+                        variadic,
+                    };
+                    params = Some((function_params, variadic));
+                    Type::Function(Box::new(function))
+                }
+            };
+        }
+        Some(Declarator {
+            name,
+            ty,
+            params,
+            array_qualifiers,
+        })
+    }
+    /// Parses a declaration of one or more variables or functions, including the terminating
+    /// semicolon.
+    pub(crate) fn parse_declaration(&mut self) -> Option<Vec<Spanned<VarDecl<'s>>>> {
+        let span = self.peek_next_split().1;
+        let (storage, function, align, base) = self.parse_declaration_specifiers()?;
+        let mut declarators = Vec::new();
+        if self.consume_if_eq(&Token::Semicolon) {
+            self.check_function_specifier(function.as_ref(), None);
+            self.check_declares_tag(&base, span);
+            return Some(declarators);
+        }
+        if storage == Some(StorageClass::Typedef) {
+            self.check_function_specifier(function.as_ref(), None);
+            self.check_typedef_alignment(align.as_ref());
+            self.parse_typedefs(&base)?;
+            return Some(declarators);
+        }
+        // The first declarator's span includes the specifiers
+        let mut start = span.start;
+        loop {
+            let mut declarator = self.parse_declarator(&base, DeclaratorKind::Named)?;
+            self.check_function_specifier(function.as_ref(), Some(&declarator.ty));
+            let init = self.declare_with_initializer(storage, &mut declarator)?;
+            let align = self.declarator_alignment(align.as_ref(), &declarator);
+            let (name, _) = declarator.name.expect("named declarators have a name");
+            let decl = VarDecl {
+                name,
+                storage: StorageClass::to_ast(storage),
+                ty: declarator.ty,
+                align,
+                init,
+            };
+            let span = self.node_from(SyntaxKind::Variable, start);
+            declarators.push(Spanned::new(decl, span));
+            if !self.consume_if_eq(&Token::Comma) {
+                break;
+            }
+            start = self.peek_next_split().1.start;
+        }
+        self.expect_semicolon(" after declaration")?;
+        Some(declarators)
+    }
+    /// Checks the alignment given by `_Alignas` to a member or object of type `ty`, returning
+    /// it unless it's less strict than the alignment of the type.
+    pub(crate) fn check_alignment(
+        &mut self,
+        align: Option<&Spanned<u64>>,
+        ty: &Type,
+    ) -> Option<u64> {
+        let align = align?;
+        // Incomplete types have already been reported
+        let natural = self.scopes.layouts().align_of(ty)?;
+        if align.inner < natural {
+            let msg = format!(
+                "Alignment {} is less strict than the alignment {natural} of `{ty}`",
+                align.inner
+            );
+            self.new_parse_error(align.as_span(), msg);
+            return None;
+        }
+        Some(align.inner)
+    }
+    /// Checks the alignment given by `_Alignas` to the object or function declared by
+    /// `declarator`, after its type has been completed by its initializer.
+    pub(crate) fn declarator_alignment(
+        &mut self,
+        align: Option<&Spanned<u64>>,
+        declarator: &Declarator<'s>,
+    ) -> Option<u64> {
+        let (name, _) = declarator
+            .name
+            .clone()
+            .expect("named declarators have a name");
+        let ty = &declarator.ty;
+        if let Some(align) = align
+            && matches!(ty.unqualified(), Type::Function(_))
+        {
+            let msg = format!("`_Alignas` cannot be applied to function `{name}`");
+            self.new_parse_error(align.as_span(), msg);
+            return None;
+        }
+        self.check_alignment(align, ty)
+    }
+    /// Reports a function specifier in a declaration of something other than a function, whose
+    /// type is `ty` if it has a declarator (C17 6.7.4p2).
+    pub(crate) fn check_function_specifier(
+        &mut self,
+        function: Option<&Spanned<&str>>,
+        ty: Option<&Type>,
+    ) {
+        if let Some(function) = function
+            && !ty.is_some_and(|ty| matches!(ty.unqualified(), Type::Function(_)))
+        {
+            let msg = format!("`{function}` can only be applied to functions");
+            self.new_parse_error(function.as_span(), msg);
+        }
+    }
+    /// Reports the alignment specifiers of a typedef declaration, which can't have any.
+    pub(crate) fn check_typedef_alignment(&mut self, align: Option<&Spanned<u64>>) {
+        if let Some(align) = align {
+            let msg = "`_Alignas` cannot be applied to a typedef name".to_string();
+            self.new_parse_error(align.as_span(), msg);
+        }
+    }
+    /// Parses a static assertion after `_Static_assert`, which starts at `start`, including the
+    /// terminating semicolon. A static assertion whose condition is zero is reported along with
+    /// its message, which can be left out since C23.
+    pub(crate) fn parse_static_assert(&mut self, start: usize) -> Option<()> {
+        self.expect(&Token::LParen, " after `_Static_assert`")?;
+        let cond = self.parse_assignment_expression()?;
+        let mut message = None;
+        if self.consume_if_eq(&Token::Comma) {
+            let (next, span) = self.peek_next_split();
+            match next.cloned() {
+                Some(Token::StringLit(string)) => {
+                    _ = self.advance();
+                    message = Some(string);
+                }
+                next => {
+                    let msg = format!(
+                        "Expected a string literal as the message of `_Static_assert`, found {}",
+                        Found(next.as_ref())
+                    );
+                    self.new_parse_error(span, msg);
+                    return None;
+                }
+            }
+        }
+        self.expect(&Token::RParen, " to close `_Static_assert`")?;
+        self.expect_semicolon(" after static assertion")?;
+        let not_constant =
+            || "The condition of `_Static_assert` must be an integer constant".to_string();
+        if self.eval_integer(&cond, not_constant) == Some(0) {
+            let msg = match message {
+                Some(message) => format!("Static assertion failed: {message}"),
+                None => "Static assertion failed".to_string(),
+            };
+            self.new_parse_error(self.span_from(start), msg);
+        }
+        Some(())
+    }
+    /// Declares the name of a declarator in the current scope and parses its initializer, if
+    /// any. The length of an array declared without one is completed by the initializer.
+    pub(crate) fn declare_with_initializer(
+        &mut self,
+        storage: Option<StorageClass>,
+        declarator: &mut Declarator<'s>,
+    ) -> Option<Option<Initializer<'s>>> {
+        let (name, span) = declarator
+            .name
+            .clone()
+            .expect("named declarators have a name");
+        let ty = &declarator.ty;
+        let is_function = matches!(ty.unqualified(), Type::Function(_));
+        let file_scope = self.scopes.is_file_scope();
+        let has_init = self.check_eq(&Token::Eq);
+        // The scope of an identifier starts right after its declarator, so it's visible in
+        // its own initializer
+        if is_function {
+            if storage == Some(StorageClass::Static) && !file_scope {
+                let msg = format!("Function `{name}` declared in a block cannot be `static`");
+                self.new_parse_error(span.clone(), msg);
+            }
+            self.declare_linked(name, ty, storage, false, span.clone());
+        } else {
+            if storage != Some(StorageClass::Extern) {
+                self.check_object_type(name, ty, span.clone());
+            }
+            if file_scope || storage == Some(StorageClass::Extern) {
+                self.declare_linked(name, ty, storage, has_init, span.clone());
+            } else if storage == Some(StorageClass::Static) {
+                self.declare_object(name, Symbol::Static(ty.clone()), span.clone());
+            } else {
+                self.declare_object(name, Symbol::Object(ty.clone()), span.clone());
+            }
+        }
+        if !self.consume_if_eq(&Token::Eq) {
+            return Some(None);
+        }
+        let init = self.parse_initializer()?;
+        let is_static = file_scope || storage == Some(StorageClass::Static);
+        if is_function {
+            let msg = format!("Function `{name}` cannot be initialized");
+            self.new_parse_error(span, msg);
+        } else if storage == Some(StorageClass::Extern) && !file_scope {
+            let msg = format!("`extern` variable `{name}` cannot be initialized in a block");
+            self.new_parse_error(span, msg);
+        } else {
+            // The object has the composite type of every declaration of it so far
+            let ty = match self.scopes.lookup_global(name) {
+                Some(global) if file_scope => global.ty.clone(),
+                _ => declarator.ty.clone(),
+            };
+            let what = format!("`{name}`");
+            if let Some(ty) = self.check_initializer(what, &ty, &init, is_static)
+                && matches!(declarator.ty.unqualified(), Type::Array(_, None))
+            {
+                self.complete_object(name, &ty, is_static);
+                declarator.ty = ty;
+            }
+        }
+        Some(Some(init))
+    }
+    /// Parses the declarators of a typedef declaration and the terminating semicolon, declaring
+    /// each name as a typedef name in the current scope.
+    pub(crate) fn parse_typedefs(&mut self, base: &Type) -> Option<()> {
+        loop {
+            let start = self.peek_next_split().1.start;
+            let declarator = self.parse_declarator(base, DeclaratorKind::Named)?;
+            let (name, span) = declarator.name.expect("named declarators have a name");
+            let ty = declarator.ty;
+            // A typedef name can be declared again in the same scope, but only as the same type
+            match self.scopes.declare(name, Symbol::Typedef(ty.clone())) {
+                None => (),
+                Some(Symbol::Typedef(prev)) if prev == ty => (),
+                Some(Symbol::Typedef(_)) => {
+                    self.new_parse_error(span, format!("Conflicting types for `{name}`"));
+                }
+                Some(_) => {
+                    let msg = format!("Redefinition of `{name}` as a different kind of symbol");
+                    self.new_parse_error(span, msg);
+                }
+            }
+            let decl = TypeDecl::Typedef { name, ty };
+            let span = self.node_from(SyntaxKind::TypeDeclaration, start);
+            self.type_decls.push(Spanned::new(decl, span));
+            if !self.consume_if_eq(&Token::Comma) {
+                break;
+            }
+        }
+        self.expect_semicolon(" after typedef declaration")?;
+        Some(())
+    }
+    /// Reports a declaration without declarators that doesn't declare a tag either.
+    pub(crate) fn check_declares_tag(&mut self, base: &Type, span: Span) {
+        if !matches!(base.unqualified(), Type::Tagged(_)) {
+            let msg = "Declaration does not declare anything".to_string();
+            self.new_parse_error(span, msg);
+        }
+    }
+    /// Reports an object whose elements have an incomplete type, so that it can't be stored.
+    fn check_object_type(&mut self, name: &str, ty: &Type, span: Span) {
+        let mut elem = ty.unqualified();
+        while let Type::Array(inner, _) = elem {
+            elem = inner.unqualified();
+        }
+        // Arrays of functions have already been reported
+        if !matches!(elem, Type::Function(_)) && !self.is_complete(elem) {
+            let msg = format!("Variable `{name}` has incomplete type `{ty}`");
+            self.new_parse_error(span, msg);
+        }
+    }
+    /// Gives the object `name` the type completed by its initializer.
+    fn complete_object(&mut self, name: &'s str, ty: &Type, is_static: bool) {
+        if self.scopes.is_file_scope()
+            && let Some(global) = self.scopes.lookup_global(name)
+        {
+            let global = Global {
+                ty: ty.clone(),
+                ..global.clone()
+            };
+            self.scopes.set_global(name, global);
+        }
+        let symbol = if is_static {
+            Symbol::Static(ty.clone())
+        } else {
+            Symbol::Object(ty.clone())
+        };
+        self.scopes.declare(name, symbol);
+    }
+    /// Declares a parameter or an object without linkage in the innermost scope, reporting a
+    /// redefinition.
+    fn declare_object(&mut self, name: &'s str, symbol: Symbol, span: Span) {
+        if self.scopes.declare(name, symbol).is_some() && !self.scopes.is_file_scope() {
+            let msg = format!("Redefinition of `{name}`");
+            self.new_parse_error(span, msg);
+        }
+    }
+    /// Parses a single parameter declaration, whose name is optional.
+    fn parse_parameter(&mut self) -> Option<Param<'s>> {
+        let span = self.peek_next_split().1;
+        if !self.starts_declaration() {
+            if !self.unknown_type_name() {
+                let t = self.peek_next().map(|t| &t.inner);
+                let msg = format!("Expected a parameter declaration, found {}", Found(t));
+                self.new_parse_error(span, msg);
+            }
+            return None;
+        }
+        let base = self.parse_specifiers()?;
+        let declarator = self.parse_declarator(&base, DeclaratorKind::Either)?;
+        // Parameters of array and function types are adjusted to pointers (C17 6.7.6.3)
+        let qualifiers = declarator.array_qualifiers.unwrap_or_default();
+        let ty = match declarator.ty {
+            Type::Array(elem, _) => Type::Pointer(elem).qualified(qualifiers),
+            ty @ Type::Function(_) => Type::Pointer(Box::new(ty)),
+            ty => ty,
+        };
+        if *ty.unqualified() == Type::Scalar(ScalarType::Void) {
+            let msg = "`void` must be the only parameter".to_string();
+            self.new_parse_error(span, msg);
+        }
+        let name = declarator.name.map(|(name, span)| {
+            self.declare_object(name, Symbol::Object(ty.clone()), span);
+            name
+        });
+        Some(Param { name, ty })
+    }
+    /// Parses the parameter list of a function after the opening parenthesis, returning
+    /// whether it ends with `...`.
+    ///
+    /// The parameters are declared in the current scope.
+    fn parse_parameters(&mut self) -> Option<Params<'s>> {
+        // An empty list leaves the parameters unspecified, while `(void)` specifies that there
+        // are none
+        if self.consume_if_eq(&Token::RParen) {
+            return Some((None, false));
+        }
+        if self.check(|t| *t == Token::Void)
+            && self.peek(1).is_some_and(|t| t.inner == Token::RParen)
+        {
+            _ = self.advance();
+            _ = self.advance();
+            return Some((Some(Vec::new()), false));
+        }
+        let mut params = Vec::new();
+        let mut variadic = false;
+        loop {
+            if self.consume_if_eq(&Token::Ellipsis) {
+                variadic = true;
+                break;
+            }
+            let start = self.peek_next_split().1.start;
+            let param = self
+                .parse_parameter()
+                .map(|param| Spanned::new(param, self.node_from(SyntaxKind::Parameter, start)));
+            let ended = self.check_any(&[Token::Comma, Token::RParen]);
+            if param.is_some() && !ended {
+                // Reports the missing `,` or `)`, then carries on with the next parameter
+                _ = self.expect(&Token::RParen, " to end function parameter list");
+            }
+            params.extend(param);
+            if !ended {
+                self.recover(SyncSet::Parameter, start);
+            }
+            if !self.consume_if_eq(&Token::Comma) {
+                break;
+            }
+        }
+        self.expect(&Token::RParen, " to end function parameter list")?;
+        Some((Some(params), variadic))
+    }
+    /// Declares an identifier with linkage in the current scope, reporting declarations that
+    /// conflict with a previous one of the same entity (C17 6.2.2, 6.7).
+    pub(crate) fn declare_linked(
+        &mut self,
+        name: &'s str,
+        ty: &Type,
+        storage: Option<StorageClass>,
+        defined: bool,
+        span: Span,
+    ) {
+        let is_function = matches!(ty.unqualified(), Type::Function(_));
+        let prev = self.scopes.lookup_global(name).cloned();
+        // `extern` and functions without a storage class take the linkage of a previous
+        // declaration
+        let linkage = match storage {
+            Some(StorageClass::Static) if self.scopes.is_file_scope() => Linkage::Internal,
+            Some(StorageClass::Extern) => prev.as_ref().map_or(Linkage::External, |p| p.linkage),
+            None if is_function => prev.as_ref().map_or(Linkage::External, |p| p.linkage),
+            _ => Linkage::External,
+        };
+        let global = match prev {
+            None => Global {
+                linkage,
+                ty: ty.clone(),
+                defined,
+            },
+            Some(prev) => {
+                if matches!(prev.ty.unqualified(), Type::Function(_)) != is_function {
+                    let msg = format!("Redefinition of `{name}` as a different kind of symbol");
+                    self.new_parse_error(span, msg);
+                    return;
+                }
+                let composite = types::composite(&prev.ty, ty);
+                let msg = if linkage == Linkage::Internal && prev.linkage == Linkage::External {
+                    Some(format!(
+                        "`static` declaration of `{name}` follows a non-static declaration"
+                    ))
+                } else if linkage == Linkage::External && prev.linkage == Linkage::Internal {
+                    Some(format!(
+                        "Non-static declaration of `{name}` follows a `static` declaration"
+                    ))
+                } else if prev.defined && defined {
+                    Some(format!("Redefinition of `{name}`"))
+                } else if composite.is_none() {
+                    Some(format!("Conflicting types for `{name}`"))
+                } else {
+                    None
+                };
+                if let Some(msg) = msg {
+                    self.new_parse_error(span.clone(), msg);
+                }
+                Global {
+                    linkage: prev.linkage,
+                    ty: composite.unwrap_or_else(|| ty.clone()),
+                    defined: prev.defined || defined,
+                }
+            }
+        };
+        let symbol = match global.ty.unqualified() {
+            Type::Function(function) => Symbol::Function {
+                params: function.params.as_ref().map(Vec::len),
+                variadic: function.variadic,
+            },
+            _ => Symbol::Static(global.ty.clone()),
+        };
+        match self.scopes.declare(name, symbol) {
+            Some(Symbol::Typedef(_) | Symbol::Constant(_)) => {
+                let msg = format!("Redefinition of `{name}` as a different kind of symbol");
+                self.new_parse_error(span, msg);
+            }
+            Some(Symbol::Object(_) | Symbol::Static(_)) if is_function => {
+                let msg = format!("Redefinition of `{name}` as a different kind of symbol");
+                self.new_parse_error(span, msg);
+            }
+            _ => self.scopes.set_global(name, global),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diagnostics::render::{RenderContext, RenderableError};
+    use pretty_assertions::assert_eq;
+
+    use crate::tests::{parse_decls, parse_scoped};
+
+    #[test]
+    fn functions() {
+        assert_eq!(
+            parse_decls("int putchar(int); long *f(char c, ...) { return putchar(c); }").as_deref(),
+            Ok(r#"Function Declaration
+├──Name
+│  └──"putchar"
+├──Return
+│  └──Type: int
+└──Parameters
+   └──Parameter
+      └──Type
+         └──Type: int
+Function Declaration
+├──Name
+│  └──"f"
+├──Return
+│  └──Type: long *
+├──Parameters
+│  └──Parameter
+│     ├──Name
+│     │  └──"c"
+│     └──Type
+│        └──Type: char
+├──Variadic
+└──Body
+   └──Block
+      └──Return
+         └──Call
+            ├──Callee
+            │  └──Ident
+            │     └──"putchar"
+            └──Args
+               └──Ident
+                  └──"c"
+"#)
+        );
+    }
+
+    #[test]
+    fn function_errors() {
+        assert_eq!(
+            parse_decls("int f(int a, int b); int f(int a); int f(void) {} int f(void) {}"),
+            Err(vec![
+                "Conflicting types for `f`".to_owned(),
+                "Conflicting types for `f`".to_owned(),
+                "Redefinition of `f`".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse_decls("int f(int a, void, int a) { int b = a; int a; }"),
+            Err(vec![
+                "`void` must be the only parameter".to_owned(),
+                "Redefinition of `a`".to_owned(),
+                "Redefinition of `a`".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse_decls("int f(int a, ...); int g(void) { return f() + g(1) + f(1, 2); }"),
+            Err(vec![
+                "`f` takes at least 1 argument, found 0".to_owned(),
+                "`g` takes 0 arguments, found 1".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse_decls("int f(void) { int a[3], *p = a; a = 0; a++; f = 0; p = a; }"),
+            Err(vec![
+                "The operand of `=` has type `int [3]`, which is not assignable".to_owned(),
+                "The operand of `++` has type `int [3]`, which is not assignable".to_owned(),
+                "The operand of `=` has type `int (void)`, which is not assignable".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse_decls(
+                "struct C { struct { const int a; } in; int b; }; \
+                 int f(const struct C *p, struct C c, int *const q) { \
+                 const int x = 1; x = 2; ++p->b; c = *p; c.b = 1; q[0] = 1; q = 0; }"
+            ),
+            Err(vec![
+                "The operand of `=` has type `const int`, which is not assignable".to_owned(),
+                "The operand of `++` has type `const int`, which is not assignable".to_owned(),
+                "The operand of `=` has type `struct C`, which is not assignable".to_owned(),
+                "The operand of `=` has type `int *const`, which is not assignable".to_owned(),
+            ])
+        );
+    }
+
+    #[test]
+    fn function_specifiers() {
+        assert_eq!(
+            parse_decls(
+                "typedef unsigned short u16; \
+                 static inline u16 swap(u16 x) { return x >> 8 | x << 8; } \
+                 _Noreturn void fail(void); inline inline int f(void), g(int); \
+                 int h(void) { _Noreturn void stop(void); return 0; }"
+            )
+            .map(|_| ()),
+            Ok(())
+        );
+        assert_eq!(
+            parse_decls(
+                "inline int x; _Noreturn int f(void), y; inline typedef int T; inline struct s { int a; }; \
+                 int g(inline int a) { return (inline int)a; }"
+            ),
+            Err(vec![
+                "`inline` can only be applied to functions".to_owned(),
+                "`_Noreturn` can only be applied to functions".to_owned(),
+                "`inline` can only be applied to functions".to_owned(),
+                "`inline` can only be applied to functions".to_owned(),
+                "`inline` is not allowed here".to_owned(),
+                "`inline` is not allowed here".to_owned(),
+            ])
+        );
+        // A declaration without a type specifier doesn't have a type at all
+        assert_eq!(
+            parse_decls("static inline f(void);"),
+            Err(vec![
+                "Expected a type specifier, found identifier `f`".to_owned()
+            ])
+        );
+        assert_eq!(
+            parse_decls("const;"),
+            Err(vec!["Expected a type specifier, found `;`".to_owned()])
+        );
+    }
+
+    #[test]
+    fn globals() {
+        assert_eq!(
+            parse_decls("static int x = 1 + 2; extern int *p;").as_deref(),
+            Ok(r#"Variable
+├──Name
+│  └──"x"
+├──Storage: static
+├──Type
+│  └──Type: int
+└──Init
+   └──+
+      ├──Literal
+      │  └──Int
+      │     └──1
+      └──Literal
+         └──Int
+            └──2
+Variable
+├──Name
+│  └──"p"
+├──Storage: extern
+└──Type
+   └──Type: int *
+"#)
+        );
+        // Tentative definitions, and later declarations keeping the linkage of earlier ones
+        assert_eq!(
+            parse_decls(
+                "int a[]; int a[3]; static int s; extern int s; static int f(void); \
+                 int f(void) { extern int s; static int n = 2; return n + s + a[0]; }"
+            )
+            .map(|_| ()),
+            Ok(())
+        );
+        assert_eq!(
+            parse_decls("int a[2]; int a[3];"),
+            Err(vec!["Conflicting types for `a`".to_owned()])
+        );
+    }
+
+    #[test]
+    fn linkage_errors() {
+        assert_eq!(
+            parse_decls("int x; static int x; static int y; int y; int z = 1; int z = 2;"),
+            Err(vec![
+                "`static` declaration of `x` follows a non-static declaration".to_owned(),
+                "Non-static declaration of `y` follows a `static` declaration".to_owned(),
+                "Redefinition of `z`".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse_decls("int f; int f(void); typedef int T; extern int T; register int r;"),
+            Err(vec![
+                "Redefinition of `f` as a different kind of symbol".to_owned(),
+                "Redefinition of `T` as a different kind of symbol".to_owned(),
+                "`register` is not allowed at file scope".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse_decls(
+                "int g = 1; int n = g; \
+                 int f(void) { static int s = f(); extern int e = 1; static int h(void); }"
+            ),
+            Err(vec![
+                "The initializer of `n` must be a constant expression".to_owned(),
+                "The initializer of `s` must be a constant expression".to_owned(),
+                "`extern` variable `e` cannot be initialized in a block".to_owned(),
+                "Function `h` declared in a block cannot be `static`".to_owned(),
+            ])
+        );
+    }
+
+    #[test]
+    fn initializers() {
+        assert_eq!(
+            types("int a[] = {1, [4] = 2}, b[][2] = {1, 2, 3}, c[2] = {0};").unwrap(),
+            ["int [5]", "int [2][2]", "int [2]"]
+        );
+        assert_eq!(
+            types(r#"char s[] = "ab", t[] = {"xyz"}, u[2][3] = {"ab", {'c'}};"#).unwrap(),
+            ["char [3]", "char [4]", "char [2][3]"]
+        );
+        assert_eq!(
+            parse_decls(
+                "struct p { int x, y; struct { int z; } in; }; \
+                 struct p a = {.y = 1, 2}, b[] = {[2].in.z = 1, {3, .in = {4}}}; \
+                 union { int i; char c; } u = {.c = 'a'};"
+            )
+            .map(|_| ()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn initializer_errors() {
+        assert_eq!(
+            parse_decls(
+                "struct p { int x, y; }; int a[2] = {1, 2, 3}; struct p q = {.z = 1}; \
+                 int b[3] = {[3] = 1}; char s[2] = \"abc\"; int c = {1, 2}; int d[2] = 5; \
+                 int f[] = {}; int h = {.x = 1}; int g; int i[2] = {[g] = 1}; int e[] = {g};"
+            ),
+            Err(vec![
+                "Excess elements in initializer for `int [2]`".to_owned(),
+                "`struct p` has no member `z`".to_owned(),
+                "Array designator index 3 is out of bounds for `int [3]`".to_owned(),
+                "String literal is too long for `char [2]`".to_owned(),
+                "Excess elements in initializer for `int`".to_owned(),
+                "`int [2]` must be initialized by a brace-enclosed list".to_owned(),
+                "Array length must be greater than zero".to_owned(),
+                "Member designator `.x` used for non-struct type `int`".to_owned(),
+                "Array designator must be an integer constant".to_owned(),
+                "The initializer of `e` must be a constant expression".to_owned(),
+            ])
+        ); // Compound literals only have static storage duration outside of functions
+        assert_eq!(
+            parse_decls(
+                "struct p { int x, y; }; int *a = (int[]){1, 2}; struct p *q = &(struct p){3}; \
+                 int f(void) { static int *b = (int[]){1}; return 0; }"
+            )
+            .map(|_| ()),
+            Err(vec![
+                "The initializer of `b` must be a constant expression".to_owned()
+            ])
+        );
+    }
+
+    /// Parses a declaration, returning the type of each declarator.
+    fn types(text: &str) -> Result<Vec<String>, Vec<String>> {
+        let decls = parse_scoped(text, |p| p.in_scope(|p| p.parse_declaration()))?;
+        Ok(decls.iter().map(|decl| decl.inner.ty.to_string()).collect())
+    }
+
+    #[test]
+    fn declarators() {
+        assert_eq!(
+            types("int a, *b, **c, d[3], *e[3], (*f)[3], g[2][3];").unwrap(),
+            [
+                "int",
+                "int *",
+                "int **",
+                "int [3]",
+                "int *[3]",
+                "int (*)[3]",
+                "int [2][3]"
+            ]
+        );
+        assert_eq!(
+            types("int f(int, char *), (*fp)(int), (*(*x)(void))[4], h();").unwrap(),
+            [
+                "int (int, char *)",
+                "int (*)(int)",
+                "int (*(*)(void))[4]",
+                "int ()"
+            ]
+        );
+        assert_eq!(
+            types("const char *const volatile s, * restrict r;").unwrap(),
+            ["const char *const volatile", "const char *restrict"]
+        );
+        // Parameters of array and function types are adjusted to pointers
+        assert_eq!(
+            types("int f(int a[], int g(void), ...);").unwrap(),
+            ["int (int *, int (*)(void), ...)"]
+        );
+        // Along with the qualifiers in the brackets of the array
+        assert_eq!(
+            types(
+                "int f(int a[static 3], int b[const], int c[restrict static 2][4], int [volatile]);"
+            )
+            .unwrap(),
+            ["int (int *, int *const, int (*restrict)[4], int *volatile)"]
+        );
+    }
+
+    #[test]
+    fn declarator_errors() {
+        assert_eq!(
+            types("int f(void)[3], g(void)(void), h[2](void);"),
+            Err(vec![
+                "Functions cannot return arrays".to_owned(),
+                "Functions cannot return functions".to_owned(),
+                "Arrays of functions are not allowed".to_owned(),
+            ])
+        );
+        assert_eq!(
+            types("int a[0], b[2 - 3], c[1.0], d[1 << 40];"),
+            Err(vec![
+                "Array length must be greater than zero".to_owned(),
+                "Array length must be greater than zero".to_owned(),
+                "Array length must be an integer constant".to_owned(),
+                "Shift count 40 is out of range for `int`".to_owned(),
+            ])
+        );
+        assert_eq!(
+            types("int a[const 3], f(int b[2][static 3]), g(int (*c)[const 2]), h(int d[static]);"),
+            Err(vec![
+                "Type qualifiers and `static` are only allowed in the outermost array declarator \
+                 of a parameter"
+                    .to_owned(),
+                "Type qualifiers and `static` are only allowed in the outermost array declarator \
+                 of a parameter"
+                    .to_owned(),
+                "Type qualifiers and `static` are only allowed in the outermost array declarator \
+                 of a parameter"
+                    .to_owned(),
+                "An array declarator with `static` must have a length".to_owned(),
+            ])
+        );
+        assert_eq!(
+            types("int (*)(void);"),
+            Err(vec![
+                "Expected an identifier in declaration, found `)`".to_owned()
+            ])
+        );
+    }
+
+    /// Parses declarations, returning the type of each declarator of the last one.
+    fn last_types(text: &str) -> Result<Vec<String>, Vec<String>> {
+        let decls = parse_scoped(text, |p| {
+            p.in_scope(|p| {
+                let mut decls = Vec::new();
+                while !p.is_empty() {
+                    decls = p.parse_declaration()?;
+                }
+                Some(decls)
+            })
+        })?;
+        Ok(decls.iter().map(|decl| decl.inner.ty.to_string()).collect())
+    }
+
+    #[test]
+    fn typedefs() {
+        assert_eq!(
+            last_types("typedef int A[3], F(int); typedef char *P; const A a; F *f, g; const P p;")
+                .unwrap(),
+            ["char *const"]
+        );
+        assert_eq!(
+            last_types("typedef int A[3], F(int); const A a; F *f, g;").unwrap(),
+            ["int (*)(int)", "int (int)"]
+        );
+        assert_eq!(
+            last_types("typedef int A[3]; const A a;").unwrap(),
+            ["const int [3]"]
+        );
+        // `T` is only a type where it isn't hidden by a variable
+        assert_eq!(
+            parse_decls("typedef int T; int f(int x) { T * y; { int T = 1; return T * x; } }")
+                .map(|_| ()),
+            Ok(())
+        );
+        assert_eq!(
+            parse_decls("int f(int T) { T * y; }"),
+            Err(vec!["Use of undeclared identifier `y`".to_owned()])
+        );
+    }
+
+    #[test]
+    fn typedef_errors() {
+        assert_eq!(
+            parse_decls("typedef int T; typedef int T; typedef long T; int g(void) { return T; }"),
+            Err(vec![
+                "Conflicting types for `T`".to_owned(),
+                "Expected an expression, found type name `T`".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse_decls("typedef int T; T int x;"),
+            Err(vec![
+                "Cannot combine `int` with the previous type specifier".to_owned()
+            ])
+        );
+        for text in [
+            "typedef size_t n;",
+            "const size_t n;",
+            "size_t n;",
+            "int f(size_t n);",
+            "struct s { size_t n; };",
+        ] {
+            assert_eq!(
+                parse_decls(text),
+                Err(vec!["Unknown type name `size_t`".to_owned()]),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn alignment() {
+        assert_eq!(
+            parse_decls("_Alignas(16) static char c; _Alignas(0) int _Alignas(long) x;").as_deref(),
+            Ok(r#"Variable
+├──Name
+│  └──"c"
+├──Storage: static
+├──Alignment: 16
+└──Type
+   └──Type: char
+Variable
+├──Name
+│  └──"x"
+├──Alignment: 8
+└──Type
+   └──Type: int
+"#)
+        );
+    }
+
+    #[test]
+    fn alignment_errors() {
+        assert_eq!(
+            parse_decls("_Alignas(3) int a; _Alignas(2) int b; _Alignas(1 << 30) char c;"),
+            Err(vec![
+                "Alignment 3 is not a power of two".to_owned(),
+                "Alignment 2 is less strict than the alignment 4 of `int`".to_owned(),
+                "Alignment 1073741824 is too large".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse_decls(
+                "_Alignas(8) typedef int T; _Alignas(8) int f(void) { _Alignas(32) int x; }"
+            ),
+            Err(vec![
+                "`_Alignas` cannot be applied to a typedef name".to_owned(),
+                "`_Alignas` cannot be applied to function `f`".to_owned(),
+            ])
+        );
+        assert!(
+            parse_decls("void f(void) { _Alignas(64) int x; static _Alignas(64) int y; }").is_ok()
+        );
+    }
+
+    #[test]
+    fn spans() {
+        use ast::tree::{TreeCtx, TreeDisplay};
+        use source::SourceFile;
+
+        let text = "struct s { int a, *b; };\nenum e { A, B = 2 };\nint f(int x,\n      long);";
+        let decls = parse_scoped(text, |p| {
+            let mut decls = Vec::new();
+            while !p.is_empty() {
+                decls.extend(p.parse_decl()?);
+            }
+            Some(decls)
+        })
+        .unwrap();
+        let source = SourceFile::new("test.c".to_owned(), text.to_owned());
+        let mut tree = String::new();
+        for decl in decls {
+            let mut ctx = TreeCtx::new().with_spans(source.clone());
+            decl.fmt_tree(&mut ctx, &mut tree).unwrap();
+        }
+        assert_eq!(
+            tree,
+            r#"Type Declaration struct s @ 1:1-1:23
+├──Member @ 1:12-1:16
+│  ├──Name
+│  │  └──"a"
+│  └──Type
+│     └──Type: int
+└──Member @ 1:19-1:20
+   ├──Name
+   │  └──"b"
+   └──Type
+      └──Type: int *
+Type Declaration enum e @ 2:1-2:19
+├──Enumerator @ 2:10-2:10
+│  ├──Name
+│  │  └──"A"
+│  └──Value
+│     └──0
+└──Enumerator @ 2:13-2:17
+   ├──Name
+   │  └──"B"
+   ├──Value
+   │  └──2
+   └──Init
+      └──Literal @ 2:17-2:17
+         └──Int
+            └──2
+Function Declaration @ 3:1-4:11
+├──Name
+│  └──"f"
+├──Return
+│  └──Type: int
+└──Parameters
+   ├──Parameter @ 3:7-3:11
+   │  ├──Name
+   │  │  └──"x"
+   │  └──Type
+   │     └──Type: int
+   └──Parameter @ 4:7-4:10
+      └──Type
+         └──Type: long
+"#
+        );
+    }
+
+    #[test]
+    fn static_assertions() {
+        assert_eq!(
+            parse_decls(
+                "_Static_assert(sizeof(long) == 8, \"LP64\"); \
+                 int f(void) { _Static_assert(_Alignof(int) == 4); return 0; }"
+            )
+            .map(|_| ()),
+            Ok(())
+        );
+        assert_eq!(
+            parse_decls(
+                "_Static_assert(sizeof(int) == 2, \"16-bit int\"); \
+                 int f(int x) { _Static_assert(0); _Static_assert(x, \"x\"); }"
+            ),
+            Err(vec![
+                "Static assertion failed: \"16-bit int\"".to_owned(),
+                "Static assertion failed".to_owned(),
+                "The condition of `_Static_assert` must be an integer constant".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse_decls("_Static_assert(1, 2);"),
+            Err(vec![
+                "Expected a string literal as the message of `_Static_assert`, found integer constant `2`"
+                    .to_owned()
+            ])
+        );
+        // The highlight covers the whole assertion, which may be written over several lines
+        let errors = parse_scoped("_Static_assert(1 == 2,\n \"msg\");", |p| Some(p.parse().1));
+        let rendered = errors
+            .unwrap()
+            .display(RenderContext::default())
+            .to_string();
+        assert!(rendered.contains("Static assertion failed: \"msg\""));
+        assert!(rendered.contains(" \"msg\");"));
+    }
+}
@@ -16,21 +16,27 @@
 
 use std::{fmt::Display, iter::FusedIterator};
 
-use ast::{Expr, LiteralExpression};
+use ast::{
+    Assignment, BinaryExpr, BinaryOperator, Call, Cast, Comma, Expr, Index, LiteralExpression,
+    LogicalExpr, LogicalOperator, MemberAccess, SExpr, SizeOf, TagKind, Ternary, Type, UnaryExpr,
+    UnaryOperator,
+};
 use diagnostics::ErrorComponent;
 use lexer::Token;
+use source::Span;
+use syntax::SyntaxKind;
 
-use crate::Parser;
+use crate::{
+    Parser,
+    basic_ops::Found,
+    constant::{decayed, is_integer, is_scalar, pointee},
+    recovery::SyncSet,
+    scope::Symbol,
+};
 
 use super::SToken;
 
 impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s, Tokens> {
-    // /// Should only be called after a leading ( has been consumed
-    // fn parse_group(&mut self) -> Option<Expr<'s>> {
-    //     let expr = self.parse_expr(BindingPower::Lowest)?;
-    //     self.expect(&Token::RParen)?;
-    //     Some(ast::Expr::Group(Box::new(expr)))
-    // }
     pub(crate) fn delimited_list_with_terminator<'p, T, P: FnMut(&'_ mut Self) -> Option<T>>(
         &'p mut self,
         mut parser: P,
@@ -39,339 +45,1153 @@ impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s,
     ) -> impl FusedIterator<Item = T> + use<'p, 's, T, P, Tokens> {
         let mut done = false;
         core::iter::from_fn(move || {
-            if self.consume_if(|t| t == terminator) {
+            if self.consume_if_eq(terminator) {
                 return None;
             }
             if done {
                 return None;
             }
             let expr = parser(self)?;
-            if !self.consume_if(|t| t == delimiter) {
+            if !self.consume_if_eq(delimiter) {
                 done = true;
             }
             Some(expr)
         })
         .fuse()
     }
-    // pub(crate) fn parse_type(&mut self) -> Option<Type<'s>> {
-    //     let (next, span) = self.advance_split();
-    //     let Some(next) = next else {
-    //         self.new_parse_error(span, "Expected a type, found EOF.");
-    //         return None;
-    //     };
-    //     Some(match next {
-    //         Token::Ident(name) => Type::Named(name),
-    //         Token::Void => Type::Void,
-    //         Token::Bool => Type::Bool,
-    //         Token::Char => Type::Char,
-    //         Token::Int => Type::Int,
-    //         Token::UInt => Type::UInt,
-    //         Token::Float => Type::Float,
-    //         Token::Double => Type::Double,
-    //         Token::Str => Type::Str,
-    //         Token::Star => {
-    //             let pointee = self.parse_type()?;
-    //             let pointee = Box::new(pointee);
-    //             Type::Pointer { pointee }
-    //         }
-    //         Token::LBrace => {
-    //             let ty = self.parse_type()?;
-    //             self.expect(&Token::Semicolon)?;
-    //             let size = self.parse_expr(BindingPower::Lowest)?;
-    //             self.expect(&Token::RBracket)?;
-    //             Type::Array(Box::new(ty), size)
-    //         }
-    //         t => {
-    //             let msg = format!("Cannot parse {t} as a type.");
-    //             self.new_parse_error(span, "Invalid type")
-    //                 .set_long_message(msg);
-    //             return None;
-    //         }
-    //     })
-    // }
-    // /// Should only be called after a leading [ has been consumed
-    // fn parse_array(&mut self) -> Option<Expr<'s>> {
-    //     let elements = self
-    //         .delimited_list_with_terminator(
-    //             |p| p.parse_expr(BindingPower::Lowest),
-    //             &Token::Comma,
-    //             &Token::RBracket,
-    //         )
-    //         .collect();
-    //     Some(ast::Expr::Array(elements))
-    // }
-    // fn parse_unary(&mut self) -> Option<Expr<'s>> {
-    //     let (t, span) = self.advance_if_split(|t| token_to_uop(t).is_some());
-    //     let op = t.as_ref().map(|t| token_to_uop(t).unwrap());
-    //     let Some(op) = op else {
-    //         let msg = format!("Expected a unary operator, found {t:?}");
-    //         self.new_parse_error(span, "Unary operator error")
-    //             .set_long_message(msg);
-    //         return None;
-    //     };
-    //     let val = self.parse_expr(BindingPower::Unary)?;
-    //     Some(Expr::UnaryOp(Box::new(ast::UnaryOp { val, op })))
-    // }
-    // /// Used for the left-hand side, to be later extended by [Self::left_denotation].
-    // pub(crate) fn null_denotation(&mut self) -> Option<Expr<'s>> {
-    //     let (t, span) = self.advance_split();
-    //     Some(match t? {
-    //         Token::LParen => self.parse_group()?,
-    //         Token::LBracket => self.parse_array()?,
-    //         Token::Star => Expr::Intrinsic(Box::new(Intrinsic::Deref {
-    //             addr: self.parse_expr(BindingPower::Unary)?,
-    //         })),
-    //         Token::Ampersand => Expr::Intrinsic(Box::new(Intrinsic::Addr {
-    //             of: self.parse_expr(BindingPower::None)?,
-    //         })),
-    //         Token::Free => {
-    //             self.expect(&Token::LParen)?;
-    //             let ptr = {
-    //                 let start = self
-    //                     .peek_next_span()
-    //                     .unwrap_or_else(|| self.end_span())
-    //                     .start;
-    //                 let mut args = self.delimited_list_with_terminator(
-    //                     |p| p.parse_expr(BindingPower::Lowest),
-    //                     &Token::Comma,
-    //                     &Token::RParen,
-    //                 );
-    //                 let [Some(ptr), None] = core::array::from_fn(|_| args.next()) else {
-    //                     core::mem::drop(args);
-    //                     let end = self.peek_next_span().unwrap_or_else(|| self.end_span()).end;
-    //                     self.new_parse_error(start..end, "Invalid number of arguments to Free")
-    //                         .set_long_message("Free expects a single argument.");
-    //                     return None;
-    //                 };
-    //                 ptr
-    //             };
-    //             Expr::Intrinsic(Box::new(Intrinsic::Free { ptr }))
-    //         }
-    //         Token::Alloc => {
-    //             self.expect(&Token::LParen)?;
-    //             let size = {
-    //                 let start = self
-    //                     .peek_next_span()
-    //                     .unwrap_or_else(|| self.end_span())
-    //                     .start;
-    //                 let mut args = self.delimited_list_with_terminator(
-    //                     |p| p.parse_expr(BindingPower::Lowest),
-    //                     &Token::Comma,
-    //                     &Token::RParen,
-    //                 );
-    //                 let [Some(size), None] = core::array::from_fn(|_| args.next()) else {
-    //                     core::mem::drop(args);
-    //                     let end = self.peek_next_span().unwrap_or_else(|| self.end_span()).end;
-    //                     self.new_parse_error(start..end, "Invalid number of arguments to Alloc")
-    //                         .set_long_message("Alloc expects a single argument.");
-    //                     return None;
-    //                 };
-    //                 size
-    //             };
-    //             Expr::Intrinsic(Box::new(Intrinsic::Alloc { size }))
-    //         }
-    //         Token::Cast => {
-    //             self.expect(&Token::Lt)?;
-    //             let ty = self.parse_type()?;
-    //             self.expect(&Token::Gt)?;
-    //             self.expect(&Token::LParen)?;
-    //             let val = self.parse_expr(BindingPower::None)?;
-    //             self.expect(&Token::RParen)?;
-    //             Expr::Intrinsic(Box::new(Intrinsic::Cast { ty, val }))
-    //         }
-    //         Token::Sizeof => {
-    //             self.expect(&Token::Lt)?;
-    //             let before = self.errors.components.len();
-    //             let sizeof = if let Some(ty) = self.parse_type() {
-    //                 SizeOf::Type(ty)
-    //             } else {
-    //                 let expr = self.parse_expr(BindingPower::None)?;
-    //                 SizeOf::Val(expr)
-    //             };
-    //             self.errors.components.truncate(before);
-    //             self.expect(&Token::Gt)?;
-    //             Expr::Intrinsic(Box::new(Intrinsic::SizeOf(sizeof)))
-    //         }
-    //         Token::IntLit(i) => Expr::Lit(LiteralExpression::Int(i)),
-    //         Token::FloatLit(i) => Expr::Lit(LiteralExpression::Float(i)),
-    //         Token::CharLiteral(c) => Expr::Lit(LiteralExpression::Char(c)),
-    //         Token::BoolLit(b) => Expr::Lit(LiteralExpression::Bool(b)),
-    //         Token::StringLit(s) => Expr::Lit(LiteralExpression::Str(s.clone())),
-    //         Token::Ident(i) => Expr::Ident(i),
-    //         t @ (Token::Minus | Token::Plus | Token::Not | Token::PlusPlus | Token::MinusMinus) => {
-    //             self.put_back(utils::Spanned::new(t, span));
-    //             self.parse_unary()?
-    //         }
-    //         t => {
-    //             let msg = format!("Parsing {t} as a null denotation is not yet supported");
-    //             self.new_parse_error(span, "TODO").set_long_message(msg);
-    //             return None;
-    //         }
-    //     })
-    // }
-    // pub(crate) fn postfix_expr(&mut self, lhs: Expr<'s>) -> Option<Expr<'s>> {
-    //     let (next, _) = self.advance_split();
-    //     let next = next.unwrap();
-    //
-    //     Some(match next {
-    //         Token::Dot => {
-    //             let member = self.expect_ident(" after . member access")?;
-    //             Expr::MemberAccess(Box::new(ast::MemberAccess {
-    //                 object: lhs,
-    //                 member,
-    //             }))
-    //         }
-    //         Token::ColonColon => {
-    //             let member = self.expect_ident(" after :: namespace access")?;
-    //             Expr::NamespaceAccess(Box::new(ast::NamespaceAccess {
-    //                 object: lhs,
-    //                 member,
-    //             }))
-    //         }
-    //         t @ (Token::PlusPlus | Token::MinusMinus) => {
-    //             let op = if t == Token::PlusPlus {
-    //                 UnaryOpKind::PostInc
-    //             } else {
-    //                 UnaryOpKind::PostDec
-    //             };
-    //             Expr::UnaryOp(Box::new(ast::UnaryOp { val: lhs, op }))
-    //         }
-    //         Token::LBracket => {
-    //             let index = self.parse_expr(BindingPower::Lowest)?;
-    //             self.expect(&Token::RBracket)?;
-    //             Expr::Intrinsic(Box::new(Intrinsic::Index { target: lhs, index }))
-    //         }
-    //         _ => {
-    //             unreachable!()
-    //         }
-    //     })
-    // }
-    // /// Applies operations to the given left-hand side, if they have a lower binding power than the
-    // /// context.
-    // /// Should only be called when more tokens are available
-    // pub(crate) fn left_denotation(&mut self, lhs: Expr<'s>) -> Option<Expr<'s>> {
-    //     let (next, span) = self.peek_next_split();
-    //     let next = next.unwrap();
-    //     Some(match next {
-    //         Token::LParen => {
-    //             _ = self.advance();
-    //             let args: Vec<Expr<'s>> = self
-    //                 .delimited_list_with_terminator(
-    //                     |p| p.parse_expr(BindingPower::Lowest),
-    //                     &Token::Comma,
-    //                     &Token::RParen,
-    //                 )
-    //                 .collect();
-    //             Expr::Call(ast::Call {
-    //                 callee: Box::new(lhs),
-    //                 args,
-    //             })
-    //         }
-    //         Token::Eq => {
-    //             _ = self.advance();
-    //             let val = self.parse_expr(BindingPower::Assign)?;
-    //             Expr::Assignment(Box::new(ast::Assignment { target: lhs, val }))
-    //         }
-    //         Token::Dot
-    //         | Token::ColonColon
-    //         | Token::PlusPlus
-    //         | Token::MinusMinus
-    //         | Token::LBracket => self.postfix_expr(lhs)?,
-    //         t if token_to_bop(t).is_some() => {
-    //             let op = token_to_bop(t).unwrap();
-    //             let bp = BindingPower::from_token(t);
-    //             _ = self.advance();
-    //             let rhs = self.parse_expr(bp)?;
-    //             Expr::BinOp(Box::new(ast::BinOp { lhs, rhs, op }))
-    //         }
-    //         t => {
-    //             let msg = format!("Unexpected token for binary operation: {t}");
-    //             self.new_parse_error(span, "Internal error")
-    //                 .set_long_message(msg);
-    //             return None;
-    //         }
-    //     })
-    // }
-    // pub fn parse_expr(&mut self, outer_bp: BindingPower) -> Option<Expr<'s>> {
-    //     let mut left = self.null_denotation()?;
-    //     loop {
-    //         let next = self.peek_next().map(|s| &s.inner);
-    //         let new_bp = next.map(BindingPower::from_token).unwrap_or_default();
-    //         if new_bp <= outer_bp {
-    //             break;
-    //         }
-    //         left = self.left_denotation(left)?;
-    //     }
-    //     Some(left)
-    // }
+    /// Parses a full expression, including the comma operator.
+    pub fn parse_expression(&mut self) -> Option<SExpr<'s>> {
+        self.parse_expr(BindingPower::None)
+    }
+    /// Parses an assignment-expression, which excludes the comma operator. Used for function
+    /// arguments and initializers.
+    pub fn parse_assignment_expression(&mut self) -> Option<SExpr<'s>> {
+        self.parse_expr(BindingPower::Comma)
+    }
+    /// Should only be called after a leading ( has been consumed
+    fn parse_group(&mut self) -> Option<SExpr<'s>> {
+        let expr = self.parse_expression()?;
+        self.expect(&Token::RParen, " to close parenthesized expression")?;
+        Some(expr)
+    }
+    /// Parses the operand of a prefix operator or cast.
+    fn parse_operand(&mut self, op: impl Display, span: Span) -> Option<SExpr<'s>> {
+        if self.peek_next().is_none() {
+            let msg = format!("Expected an operand for `{op}`, found end of file");
+            self.new_parse_error(span, msg);
+            return None;
+        }
+        self.parse_expr(BindingPower::Unary)
+    }
+    /// Reports an error if `target` can never designate an object.
+    fn check_assignable(&mut self, target: &SExpr<'s>, op: impl Display, span: Span) {
+        let assignable = match &target.inner {
+            Expr::Ident(name) => !matches!(self.scopes.lookup(name), Some(Symbol::Constant(_))),
+            Expr::Index(_) | Expr::MemberAccess(_) => true,
+            Expr::Unary(unary) => unary.op == UnaryOperator::Deref,
+            _ => false,
+        };
+        if !assignable {
+            let msg = format!("The operand of `{op}` is not assignable");
+            self.new_parse_error(span, msg).set_long_message(
+                "Only variables, dereferenced pointers, array elements and members can be modified"
+                    .to_string(),
+            );
+            return;
+        }
+        // Arrays, functions and const objects are lvalues, but not modifiable ones
+        // (C17 6.3.2.1p1)
+        let Some(ty) = self.expr_type(target) else {
+            return;
+        };
+        let reason = if matches!(ty.unqualified(), Type::Array(..) | Type::Function(_)) {
+            "Arrays and functions can't be modified as a whole, only array elements can"
+        } else if self.is_read_only(&ty) {
+            "Objects of const-qualified types, and structs and unions with const members, can't \
+             be modified"
+        } else {
+            return;
+        };
+        let msg = format!("The operand of `{op}` has type `{ty}`, which is not assignable");
+        self.new_parse_error(span, msg)
+            .set_long_message(reason.to_string());
+    }
+    /// Whether `ty` is const-qualified, or is a struct or union with a member that is, even in
+    /// a nested struct, union or array.
+    fn is_read_only(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Qualified(qualifiers, _) if qualifiers.is_const => true,
+            Type::Qualified(_, ty) | Type::Array(ty, _) => self.is_read_only(ty),
+            Type::Tagged(tag) if tag.kind != TagKind::Enum => {
+                let members = self.scopes.layouts().members(tag).unwrap_or_default();
+                members.iter().any(|member| self.is_read_only(&member.ty))
+            }
+            _ => false,
+        }
+    }
+    /// Reports the operand of `op`, which is `sizeof`, `_Alignof` or `_Alignas`, if it doesn't
+    /// have a complete object type, whose size and alignment are known.
+    pub(crate) fn check_measurable(&mut self, ty: &Type, op: &Token<'_>, span: Span) {
+        if matches!(ty.unqualified(), Type::Function(_)) {
+            let msg = format!("Cannot apply `{op}` to function type `{ty}`");
+            self.new_parse_error(span, msg);
+        } else if !self.is_complete(ty) {
+            let msg = format!("Cannot apply `{op}` to incomplete type `{ty}`");
+            self.new_parse_error(span, msg);
+        }
+    }
+    /// Reports `operand`, described by `what`, if it isn't a scalar. Only scalars can be
+    /// compared to zero or used in arithmetic.
+    pub(crate) fn check_scalar(&mut self, operand: &SExpr<'s>, what: impl Display) {
+        if let Some(ty) = self.expr_type(operand)
+            && !is_scalar(&ty)
+        {
+            let msg = format!("{what} has type `{ty}`, which is not a scalar type");
+            self.new_parse_error(operand.as_span(), msg);
+        }
+    }
+    /// Reports `value` if it can't be converted to `ty` as if by assignment: a scalar can only
+    /// be converted to a scalar, and a struct or union only to its own type.
+    pub(crate) fn check_conversion(&mut self, value: &SExpr<'s>, ty: &Type) {
+        let Some(value_ty) = self.expr_type(value) else {
+            return;
+        };
+        let converts = match (is_scalar(ty), is_scalar(&value_ty)) {
+            (true, true) => true,
+            (false, false) => value_ty.unqualified() == ty.unqualified(),
+            _ => false,
+        };
+        if !converts {
+            let msg = format!("Cannot convert a value of type `{value_ty}` to `{ty}`");
+            self.new_parse_error(value.as_span(), msg);
+        }
+    }
+    /// Reports a dereferenced operand that isn't a pointer, which arrays and functions are
+    /// converted to.
+    fn check_pointer(&mut self, operand: &SExpr<'s>) {
+        if let Some(ty) = self.expr_type(operand)
+            && pointee(&decayed(ty.clone())).is_none()
+        {
+            let msg = format!("The operand of `*` has type `{ty}`, which is not a pointer");
+            self.new_parse_error(operand.as_span(), msg);
+        }
+    }
+    /// Reports a subscript unless one of its operands points to objects and the other is an
+    /// integer. Either one can be the pointer, as `a[i]` means `*(a + i)`.
+    fn check_subscript(&mut self, target: &SExpr<'s>, index: &SExpr<'s>) {
+        let (Some(target_ty), Some(index_ty)) = (self.expr_type(target), self.expr_type(index))
+        else {
+            return;
+        };
+        let points_to_object = |ty: &Type| {
+            pointee(&decayed(ty.clone())).is_some_and(|ty| !matches!(ty, Type::Function(_)))
+        };
+        let (integer, integer_ty) = if points_to_object(&target_ty) {
+            (index, index_ty)
+        } else if points_to_object(&index_ty) {
+            (target, target_ty)
+        } else {
+            let msg = format!(
+                "The subscripted value has type `{target_ty}`, which is not an array or pointer"
+            );
+            self.new_parse_error(target.as_span(), msg);
+            return;
+        };
+        if !is_integer(&integer_ty) {
+            let msg =
+                format!("The subscript has type `{integer_ty}`, which is not an integer type");
+            self.new_parse_error(integer.as_span(), msg);
+        }
+    }
+    /// Reports a member access whose operand isn't a struct or union, or a pointer to one with
+    /// `->`, or whose struct or union is incomplete or has no such member.
+    fn check_member(&mut self, object: &SExpr<'s>, member: &str, arrow: bool, span: Span) {
+        let Some(ty) = self.expr_type(object) else {
+            return;
+        };
+        let record = if arrow {
+            pointee(&decayed(ty.clone()))
+        } else {
+            Some(ty.clone())
+        };
+        let tag = match record.as_ref().map(Type::unqualified) {
+            Some(Type::Tagged(tag)) if tag.kind != TagKind::Enum => tag.clone(),
+            _ => {
+                let (op, expected) = if arrow {
+                    ("->", "a pointer to a struct or union")
+                } else {
+                    (".", "a struct or union")
+                };
+                let msg = format!("The operand of `{op}` has type `{ty}`, which is not {expected}");
+                self.new_parse_error(object.as_span(), msg);
+                return;
+            }
+        };
+        if !self.scopes.is_complete(&tag) {
+            let msg = format!("Member access into incomplete type `{tag}`");
+            self.new_parse_error(span, msg);
+        } else if self.scopes.layouts().find_member(&tag, member).is_none() {
+            let msg = format!("No member named `{member}` in `{tag}`");
+            self.new_parse_error(span, msg);
+        }
+    }
+    /// Used for the left-hand side, to be later extended by [Self::left_denotation].
+    pub(crate) fn null_denotation(&mut self) -> Option<SExpr<'s>> {
+        let (t, span) = self.advance_split();
+        let start = span.start;
+        let Some(t) = t else {
+            self.new_parse_error(
+                span,
+                "Expected an expression, found end of file".to_string(),
+            );
+            return None;
+        };
+        let expr = match t {
+            Token::IntLit(int) => Expr::Lit(LiteralExpression::Int(int)),
+            Token::FloatLit(float) => Expr::Lit(LiteralExpression::Float(float)),
+            Token::CharLit(c) => Expr::Lit(LiteralExpression::Char(c)),
+            Token::StringLit(s) => Expr::Lit(LiteralExpression::Str(s)),
+            Token::True => Expr::Lit(LiteralExpression::Bool(true)),
+            Token::False => Expr::Lit(LiteralExpression::Bool(false)),
+            Token::Nullptr => Expr::Lit(LiteralExpression::Nullptr),
+            Token::Invalid => Expr::Lit(LiteralExpression::Invalid),
+            Token::Ident(name) => {
+                match self.scopes.lookup(name) {
+                    None => {
+                        let msg = format!("Use of undeclared identifier `{name}`");
+                        self.new_parse_error(span, msg);
+                    }
+                    Some(Symbol::Typedef(_)) => {
+                        let msg = format!("Expected an expression, found type name `{name}`");
+                        self.new_parse_error(span, msg);
+                    }
+                    Some(_) => (),
+                }
+                Expr::Ident(name)
+            }
+            Token::LParen if self.starts_type_name(0) => {
+                let ty = self.parse_type_name()?;
+                self.expect(&Token::RParen, " to close cast")?;
+                if self.check(|t| *t == Token::LBrace) {
+                    self.parse_compound_literal(ty)?
+                } else {
+                    let val = self.parse_operand("cast", span)?;
+                    Expr::Cast(Box::new(Cast { ty, val }))
+                }
+            }
+            // The span of a parenthesized expression includes the parentheses
+            Token::LParen => self.parse_group()?.inner,
+            Token::Sizeof
+                if self.peek(0).is_some_and(|t| t.inner == Token::LParen)
+                    && self.starts_type_name(1) =>
+            {
+                let start = self.advance().unwrap().span.start;
+                let ty = self.parse_type_name()?;
+                self.expect(&Token::RParen, " to close `sizeof`")?;
+                if self.check(|t| *t == Token::LBrace) {
+                    // The operand is a compound literal, which postfix operators apply to
+                    let literal = self.parse_compound_literal(ty)?;
+                    let span = self.node_from(SyntaxKind::Expression, start);
+                    let mut val = SExpr::new(literal, span);
+                    while self.check(|t| BindingPower::from_token(t) == BindingPower::Postfix) {
+                        val = self.postfix_expr(val)?;
+                    }
+                    if let Some(ty) = self.expr_type(&val) {
+                        self.check_measurable(&ty, &Token::Sizeof, val.as_span());
+                    }
+                    Expr::SizeOf(Box::new(SizeOf::Val(val)))
+                } else {
+                    self.check_measurable(&ty, &Token::Sizeof, self.span_from(start));
+                    Expr::SizeOf(Box::new(SizeOf::Type(ty)))
+                }
+            }
+            Token::Sizeof => {
+                let val = self.parse_operand(Token::Sizeof, span)?;
+                if let Some(ty) = self.expr_type(&val) {
+                    self.check_measurable(&ty, &Token::Sizeof, val.as_span());
+                }
+                Expr::SizeOf(Box::new(SizeOf::Val(val)))
+            }
+            // Unlike `sizeof`, `_Alignof` only applies to type names
+            Token::Alignof => {
+                self.expect(&Token::LParen, " after `_Alignof`")?;
+                let start = self.peek_next_split().1.start;
+                if !self.starts_type_name(0) {
+                    let t = self.peek_next().map(|t| &t.inner);
+                    let msg = format!("Expected a type name in `_Alignof`, found {}", Found(t));
+                    let span = self.peek_next_split().1;
+                    self.new_parse_error(span, msg);
+                    return None;
+                }
+                let ty = self.parse_type_name()?;
+                self.check_measurable(&ty, &Token::Alignof, self.span_from(start));
+                self.expect(&Token::RParen, " to close `_Alignof`")?;
+                Expr::AlignOf(ty)
+            }
+            ref t if token_to_uop(t).is_some() => {
+                let op = token_to_uop(t).unwrap();
+                let val = self.parse_operand(t, span.clone())?;
+                if matches!(op, UnaryOperator::PreInc | UnaryOperator::PreDec) {
+                    self.check_assignable(&val, t, span);
+                }
+                match op {
+                    UnaryOperator::Deref => self.check_pointer(&val),
+                    UnaryOperator::AddrOf => (),
+                    _ => self.check_scalar(&val, format_args!("The operand of `{t}`")),
+                }
+                Expr::Unary(Box::new(UnaryExpr { op, val }))
+            }
+            t => {
+                let msg = format!("Expected an expression, found {}", Found(Some(&t)));
+                self.new_parse_error(span.clone(), msg);
+                // The token may be where parsing resumes, such as the `;` ending a statement
+                self.put_back(SToken::new(t, span));
+                return None;
+            }
+        };
+        Some(SExpr::new(
+            expr,
+            self.node_from(SyntaxKind::Expression, start),
+        ))
+    }
+    /// Applies postfix operators to the given left-hand side.
+    /// Reports a call of something that isn't a function or a pointer to one, or that passes
+    /// the wrong number of arguments to a function with a prototype. The number of arguments
+    /// is [None] if one of them couldn't be parsed.
+    fn check_call(&mut self, callee: &SExpr<'s>, count: Option<usize>, span: Span) {
+        if let Some(ty) = self.expr_type(callee)
+            && !matches!(pointee(&decayed(ty.clone())), Some(Type::Function(_)))
+        {
+            let msg = format!("The called value has type `{ty}`, which is not a function");
+            self.new_parse_error(callee.as_span(), msg);
+            return;
+        }
+        let (&Expr::Ident(name), Some(count)) = (&callee.inner, count) else {
+            return;
+        };
+        let Some(Symbol::Function {
+            params: Some(params),
+            variadic,
+            ..
+        }) = self.scopes.lookup(name)
+        else {
+            return;
+        };
+        if count == params || (variadic && count > params) {
+            return;
+        }
+        let at_least = if variadic { "at least " } else { "" };
+        let plural = if params == 1 { "" } else { "s" };
+        let msg = format!("`{name}` takes {at_least}{params} argument{plural}, found {count}");
+        self.new_parse_error(span, msg);
+    }
+    fn postfix_expr(&mut self, lhs: SExpr<'s>) -> Option<SExpr<'s>> {
+        let start = lhs.span.start;
+        let (t, span) = self.advance_split();
+        let expr = match t.unwrap() {
+            Token::LParen => {
+                let mut args = Vec::new();
+                // The number of arguments isn't known if one of them couldn't be parsed
+                let mut failed = false;
+                let close = match self.advance_if_eq(&Token::RParen) {
+                    Some(close) => close,
+                    None => loop {
+                        let start = self.peek_next_split().1.start;
+                        let arg = self.parse_assignment_expression();
+                        let ended = self.check_any(&[Token::Comma, Token::RParen]);
+                        if arg.is_some() && !ended {
+                            // Reports the missing `,` or `)`, then carries on with the next
+                            // argument
+                            _ = self.expect(&Token::RParen, " to close argument list");
+                        }
+                        failed |= arg.is_none();
+                        args.extend(arg);
+                        if !ended {
+                            self.recover(SyncSet::Expression, start);
+                        }
+                        if !self.consume_if_eq(&Token::Comma) {
+                            break self.expect(&Token::RParen, " to close argument list")?;
+                        }
+                    },
+                };
+                let count = (!failed).then_some(args.len());
+                self.check_call(&lhs, count, span.start..close.span.end);
+                Expr::Call(Box::new(Call { callee: lhs, args }))
+            }
+            Token::LBracket => {
+                let index = self.parse_expression()?;
+                self.expect(&Token::RBracket, " to close subscript")?;
+                self.check_subscript(&lhs, &index);
+                Expr::Index(Box::new(Index { target: lhs, index }))
+            }
+            t @ (Token::Dot | Token::Arrow) => {
+                let member = self.expect_ident(format_args!(" after `{t}`"))?;
+                self.check_member(&lhs, member, t == Token::Arrow, self.span_from(start));
+                Expr::MemberAccess(Box::new(MemberAccess {
+                    object: lhs,
+                    member,
+                    arrow: t == Token::Arrow,
+                }))
+            }
+            t @ (Token::PlusPlus | Token::MinusMinus) => {
+                self.check_assignable(&lhs, &t, span);
+                self.check_scalar(&lhs, format_args!("The operand of `{t}`"));
+                let op = if t == Token::PlusPlus {
+                    UnaryOperator::PostInc
+                } else {
+                    UnaryOperator::PostDec
+                };
+                Expr::Unary(Box::new(UnaryExpr { op, val: lhs }))
+            }
+            _ => unreachable!(),
+        };
+        Some(SExpr::new(
+            expr,
+            self.node_from(SyntaxKind::Expression, start),
+        ))
+    }
+    /// Applies operations to the given left-hand side, if they have a lower binding power than the
+    /// context.
+    /// Should only be called when more tokens are available
+    pub(crate) fn left_denotation(&mut self, lhs: SExpr<'s>) -> Option<SExpr<'s>> {
+        let start = lhs.span.start;
+        let (next, span) = self.peek_next_split();
+        let next = next.unwrap();
+        let bp = BindingPower::from_token(next);
+        let expr = match next {
+            Token::LParen
+            | Token::LBracket
+            | Token::Dot
+            | Token::Arrow
+            | Token::PlusPlus
+            | Token::MinusMinus => return self.postfix_expr(lhs),
+            Token::Question => {
+                _ = self.advance();
+                let then_val = self.parse_expression()?;
+                self.expect(&Token::Colon, " to separate the branches of `?`")?;
+                // Conditional expressions are right-associative
+                let else_val = self.parse_expr(BindingPower::Assign)?;
+                self.check_scalar(&lhs, "The condition of `?`");
+                Expr::Ternary(Box::new(Ternary {
+                    cond: lhs,
+                    then_val,
+                    else_val,
+                }))
+            }
+            Token::Comma => {
+                _ = self.advance();
+                let rhs = self.parse_expr(bp)?;
+                Expr::Comma(Box::new(Comma { lhs, rhs }))
+            }
+            t if bp == BindingPower::Assign => {
+                let op = token_to_assignment_op(t);
+                let token = self.advance().unwrap().inner;
+                self.check_assignable(&lhs, &token, span);
+                // Assignments are right-associative
+                let val = self.parse_expr(BindingPower::Comma)?;
+                if op.is_some() {
+                    self.check_scalar(&lhs, format_args!("The left operand of `{token}`"));
+                    self.check_scalar(&val, format_args!("The right operand of `{token}`"));
+                } else if let Some(ty) = self.expr_type(&lhs) {
+                    self.check_conversion(&val, &ty);
+                }
+                Expr::Assignment(Box::new(Assignment {
+                    target: lhs,
+                    op,
+                    val,
+                }))
+            }
+            Token::And | Token::Or => {
+                let op = if *next == Token::And {
+                    LogicalOperator::And
+                } else {
+                    LogicalOperator::Or
+                };
+                _ = self.advance();
+                let rhs = self.parse_expr(bp)?;
+                self.check_scalar(&lhs, format_args!("The left operand of `{op}`"));
+                self.check_scalar(&rhs, format_args!("The right operand of `{op}`"));
+                Expr::Logical(Box::new(LogicalExpr { lhs, op, rhs }))
+            }
+            t => {
+                let op = token_to_bop(t).unwrap();
+                _ = self.advance();
+                let rhs = self.parse_expr(bp)?;
+                self.check_scalar(&lhs, format_args!("The left operand of `{op}`"));
+                self.check_scalar(&rhs, format_args!("The right operand of `{op}`"));
+                Expr::Binary(Box::new(BinaryExpr { lhs, op, rhs }))
+            }
+        };
+        Some(SExpr::new(
+            expr,
+            self.node_from(SyntaxKind::Expression, start),
+        ))
+    }
+    /// Parses an expression containing only operators that bind tighter than `outer_bp`.
+    pub(crate) fn parse_expr(&mut self, outer_bp: BindingPower) -> Option<SExpr<'s>> {
+        let mut left = self.null_denotation()?;
+        loop {
+            let next = self.peek_next().map(|s| &s.inner);
+            let new_bp = next.map(BindingPower::from_token).unwrap_or_default();
+            if new_bp <= outer_bp {
+                self.add_expected_operator();
+                break;
+            }
+            left = self.left_denotation(left)?;
+        }
+        Some(left)
+    }
 }
 
-// #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, PartialOrd, Ord)]
-// pub enum BindingPower {
-//     /// No binding power
-//     #[default]
-//     None = 0,
-//     /// Lowest binding power
-//     Lowest,
-//     /// Assignment operators (=, +=, etc.)
-//     Assign,
-//     /// Ternary conditional operator (? :)
-//     Ternary,
-//     /// Logical OR operator (||)
-//     LogicalOr,
-//     /// Logical AND operator (&&)
-//     LogicalAnd,
-//     /// Bitwise OR operator (|)
-//     BitOr,
-//     /// Bitwise XOR operator (^)
-//     BitXor,
-//     /// Bitwise AND operator (&)
-//     BitAnd,
-//     /// Equality operators (==, !=)
-//     Equality,
-//     /// Relational operators (<, >, <=, >=)
-//     Relational,
-//     /// Range operations (..)
-//     Range,
-//     /// Shift operators (<<, >>)
-//     Shift,
-//     /// Addition and subtraction (+, -)
-//     Sum,
-//     /// Multiplication, division, modulo (*, /, %)
-//     Product,
-//     /// Exponentiation operator (**)
-//     Exponent,
-//     /// Unary operators (!, ~, +, -, prefix ++/--)
-//     Unary,
-//     /// Postfix operators (++/-- postfix)
-//     Postfix,
-//     /// Function call or indexing
-//     Call,
-//     /// Primary expressions (literals, variables)
-//     Primary,
-// }
-//
-// impl BindingPower {
-//     pub fn from_token(t: &Token<'_>) -> Self {
-//         use BindingPower as BP;
-//         use Token::*;
-//         match t {
-//             Eq => BP::Assign,
-//             Question => BP::Ternary,
-//             Or => BP::LogicalOr,
-//             And => BP::LogicalOr,
-//             BitOr => BP::BitOr,
-//             BitXor => BP::BitXor,
-//             Ampersand => BP::BitAnd,
-//             EqEq | Ne => BP::Equality,
-//             Lt | Le | Gt | Ge => BP::Relational,
-//             Plus | Minus => BP::Sum,
-//             Star | Slash | Percent => BP::Product,
-//             PlusPlus | MinusMinus => BP::Postfix,
-//             LParen | LBracket | Dot | ColonColon => BP::Call,
-//             Range => BP::Range,
-//             _ => BP::None,
-//         }
-//     }
-// }
-//
+fn token_to_uop(t: &Token<'_>) -> Option<UnaryOperator> {
+    use UnaryOperator as U;
+    Some(match t {
+        Token::Minus => U::Neg,
+        Token::Plus => U::Plus,
+        Token::Not => U::Not,
+        Token::Tilde => U::BitNot,
+        Token::Star => U::Deref,
+        Token::Ampersand => U::AddrOf,
+        Token::PlusPlus => U::PreInc,
+        Token::MinusMinus => U::PreDec,
+        _ => return None,
+    })
+}
+
+fn token_to_bop(t: &Token<'_>) -> Option<BinaryOperator> {
+    use BinaryOperator as B;
+    Some(match t {
+        Token::Star => B::Mul,
+        Token::Slash => B::Div,
+        Token::Percent => B::Rem,
+        Token::Plus => B::Add,
+        Token::Minus => B::Sub,
+        Token::Shl => B::Shl,
+        Token::Shr => B::Shr,
+        Token::Lt => B::Lt,
+        Token::Le => B::Le,
+        Token::Gt => B::Gt,
+        Token::Ge => B::Ge,
+        Token::EqEq => B::Eq,
+        Token::Ne => B::Ne,
+        Token::Ampersand => B::BitAnd,
+        Token::BitXor => B::BitXor,
+        Token::BitOr => B::BitOr,
+        _ => return None,
+    })
+}
+
+/// The operator of a compound assignment, or [None] for `=`.
+fn token_to_assignment_op(t: &Token<'_>) -> Option<BinaryOperator> {
+    use BinaryOperator as B;
+    match t {
+        Token::StarEq => Some(B::Mul),
+        Token::SlashEq => Some(B::Div),
+        Token::PercentEq => Some(B::Rem),
+        Token::PlusEq => Some(B::Add),
+        Token::MinusEq => Some(B::Sub),
+        Token::ShlEq => Some(B::Shl),
+        Token::ShrEq => Some(B::Shr),
+        Token::AmpersandEq => Some(B::BitAnd),
+        Token::BitXorEq => Some(B::BitXor),
+        Token::BitOrEq => Some(B::BitOr),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, PartialOrd, Ord)]
+pub enum BindingPower {
+    /// No binding power
+    #[default]
+    None = 0,
+    /// Comma operator (,)
+    Comma,
+    /// Assignment operators (=, +=, etc.)
+    Assign,
+    /// Ternary conditional operator (? :)
+    Ternary,
+    /// Logical OR operator (||)
+    LogicalOr,
+    /// Logical AND operator (&&)
+    LogicalAnd,
+    /// Bitwise OR operator (|)
+    BitOr,
+    /// Bitwise XOR operator (^)
+    BitXor,
+    /// Bitwise AND operator (&)
+    BitAnd,
+    /// Equality operators (==, !=)
+    Equality,
+    /// Relational operators (<, >, <=, >=)
+    Relational,
+    /// Shift operators (<<, >>)
+    Shift,
+    /// Addition and subtraction (+, -)
+    Sum,
+    /// Multiplication, division, modulo (*, /, %)
+    Product,
+    /// Unary operators (!, ~, +, -, *, &, prefix ++/--, casts, sizeof and _Alignof)
+    Unary,
+    /// Postfix operators (postfix ++/--, calls, indexing and member access)
+    Postfix,
+}
+
+impl BindingPower {
+    pub fn from_token(t: &Token<'_>) -> Self {
+        use BindingPower as BP;
+        use Token::*;
+        match t {
+            Comma => BP::Comma,
+            Eq | StarEq | SlashEq | PercentEq | PlusEq | MinusEq | ShlEq | ShrEq | AmpersandEq
+            | BitXorEq | BitOrEq => BP::Assign,
+            Question => BP::Ternary,
+            Or => BP::LogicalOr,
+            And => BP::LogicalAnd,
+            BitOr => BP::BitOr,
+            BitXor => BP::BitXor,
+            Ampersand => BP::BitAnd,
+            EqEq | Ne => BP::Equality,
+            Lt | Le | Gt | Ge => BP::Relational,
+            Shl | Shr => BP::Shift,
+            Plus | Minus => BP::Sum,
+            Star | Slash | Percent => BP::Product,
+            PlusPlus | MinusMinus | LParen | LBracket | Dot | Arrow => BP::Postfix,
+            _ => BP::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use lexer::Standard;
+
+    use crate::tests::{parse_scoped, parse_standard};
+
+    /// The declarations of the identifiers used by the tests.
+    const DECLS: &str = "int a, b, c, d, e, x, y, *p, f(), g(int, int), (*(*fns[2])(int))(int);";
+
+    /// Parses `text` as a single expression after the file scope declarations `decls`,
+    /// returning its S-expression form or the error messages.
+    fn parse_after(decls: &str, text: &str) -> Result<String, Vec<String>> {
+        parse_scoped(&format!("{decls} {text}"), |p| {
+            while p.starts_declaration() {
+                p.parse_declaration()?;
+            }
+            p.parse_expression()
+        })
+        .map(|e| e.to_string())
+    }
+
+    /// Parses `text` as a single expression using the identifiers in [DECLS].
+    fn parse(text: &str) -> Result<String, Vec<String>> {
+        parse_after(DECLS, text)
+    }
+
+    #[track_caller]
+    fn check(text: &str, expected: &str) {
+        assert_eq!(parse(text).as_deref(), Ok(expected), "parsing {text:?}");
+    }
+
+    #[test]
+    fn binary_precedence() {
+        check("1 + 2 * 3", "(+ 1 (* 2 3))");
+        check("1 * 2 + 3", "(+ (* 1 2) 3)");
+        check("a << 1 + b", "(<< a (+ 1 b))");
+        check("a < b == c > d", "(== (< a b) (> c d))");
+        check("a & b ^ c | d", "(| (^ (& a b) c) d)");
+        check("a | b && c || d && e", "(|| (&& (| a b) c) (&& d e))");
+        check("(1 + 2) * 3", "(* (+ 1 2) 3)");
+    }
+
+    #[test]
+    fn associativity() {
+        check("a - b - c", "(- (- a b) c)");
+        check("a / b % c", "(% (/ a b) c)");
+        check("a = b = c", "(= a (= b c))");
+        check("a += b -= c", "(+= a (-= b c))");
+        check("a ? b : c ? d : e", "(? a b (? c d e))");
+        check("a, b, c", "(, (, a b) c)");
+    }
+
+    #[test]
+    fn ternary_and_assignment() {
+        check("a ? b, c : d", "(? a (, b c) d)");
+        check("a = b ? c : d", "(= a (? b c d))");
+        check("a || b ? c : d", "(? (|| a b) c d)");
+        check("a = 1, b = 2", "(, (= a 1) (= b 2))");
+        check("x <<= y >>= 2", "(<<= x (>>= y 2))");
+    }
+
+    #[test]
+    fn unary_and_postfix() {
+        check("-a * b", "(* (- a) b)");
+        check("!~a", "(! (~ a))");
+        check("*p++", "(* (post++ p))");
+        check("--*p", "(-- (* p))");
+        check("&p[1]", "(& ([] p 1))");
+        check("- -a", "(- (- a))");
+        check("a++ + ++b", "(+ (post++ a) (++ b))");
+    }
+
+    #[test]
+    fn pointer_operands() {
+        check("1[p] + *f", "(+ ([] 1 p) (* f))");
+        let error = |text| parse(text).unwrap_err();
+        assert_eq!(
+            error("*a"),
+            ["The operand of `*` has type `int`, which is not a pointer"]
+        );
+        assert_eq!(
+            error("a[1]"),
+            ["The subscripted value has type `int`, which is not an array or pointer"]
+        );
+        assert_eq!(
+            error("p[p]"),
+            ["The subscript has type `int *`, which is not an integer type"]
+        );
+    }
+
+    #[test]
+    fn member_access() {
+        let decls = "struct S { int a[3], b, value; struct S *next; } s, *p; struct T *t; int i;";
+        let parse = |text| parse_after(decls, text);
+        assert_eq!(
+            parse("p->next->value").as_deref(),
+            Ok("(-> (-> p next) value)")
+        );
+        assert_eq!(
+            parse("s.a[2] + s.b").as_deref(),
+            Ok("(+ ([] (. s a) 2) (. s b))")
+        );
+        let error = |text| parse(text).unwrap_err();
+        assert_eq!(error("s.c"), ["No member named `c` in `struct S`"]);
+        assert_eq!(
+            error("t->a"),
+            ["Member access into incomplete type `struct T`"]
+        );
+        assert_eq!(
+            error("i.a"),
+            ["The operand of `.` has type `int`, which is not a struct or union"]
+        );
+        assert_eq!(
+            error("s->a"),
+            [
+                "The operand of `->` has type `struct S`, which is not a pointer to a struct or union"
+            ]
+        );
+    }
+
+    #[test]
+    fn scalar_operands() {
+        let parse = |text| {
+            let text = format!("struct S {{ int a; }} s; int i; void f(void) {{ {text} }}");
+            parse_scoped(&text, |p| {
+                while !p.is_empty() {
+                    p.parse_decl()?;
+                }
+                Some(())
+            })
+        };
+        assert_eq!(parse("int x = i + 1, *y = &s.a;"), Ok(()));
+        let error = |text| parse(text).unwrap_err();
+        assert_eq!(
+            error("int x = s + 1;"),
+            ["The left operand of `+` has type `struct S`, which is not a scalar type"]
+        );
+        assert_eq!(
+            error("int x = !s;"),
+            ["The operand of `!` has type `struct S`, which is not a scalar type"]
+        );
+        assert_eq!(
+            error("int x = 1 && s;"),
+            ["The right operand of `&&` has type `struct S`, which is not a scalar type"]
+        );
+        assert_eq!(
+            error("int x = s ? 1 : 2;"),
+            ["The condition of `?` has type `struct S`, which is not a scalar type"]
+        );
+        assert_eq!(
+            error("if (s); while (s); for (; s;);"),
+            [
+                "The condition of `if` has type `struct S`, which is not a scalar type",
+                "The condition of `while` has type `struct S`, which is not a scalar type",
+                "The condition of `for` has type `struct S`, which is not a scalar type",
+            ]
+        );
+        assert_eq!(
+            error("int x = s; x = s; struct S t = 1;"),
+            [
+                "Cannot convert a value of type `struct S` to `int`",
+                "Cannot convert a value of type `struct S` to `int`",
+                "Cannot convert a value of type `int` to `struct S`",
+            ]
+        );
+    }
+
+    #[test]
+    fn calls() {
+        check("f()", "(call f [])");
+        check("f(1, g(2, 3))", "(call f [1, (call g [2, 3])])");
+        check("f(a = 1, (b, c))", "(call f [(= a 1), (, b c)])");
+        check("fns[0](x)(y)", "(call (call ([] fns 0) [x]) [y])");
+        check("(*fns[1])(x)", "(call (* ([] fns 1)) [x])");
+        let error = |text| parse(text).unwrap_err();
+        assert_eq!(
+            error("a()"),
+            ["The called value has type `int`, which is not a function"]
+        );
+        assert_eq!(
+            error("f(1)(2)"),
+            ["The called value has type `int`, which is not a function"]
+        );
+        assert_eq!(error("g(1)"), ["`g` takes 2 arguments, found 1"]);
+    }
+
+    #[test]
+    fn casts_and_sizeof() {
+        check("(int)x", "(cast int x)");
+        check("(unsigned long)-x * 2", "(* (cast unsigned long (- x)) 2)");
+        check("(char **)p[1]", "(cast char ** ([] p 1))");
+        check("(long long int)(short)c", "(cast long long (cast short c))");
+        check("sizeof x + 1", "(+ (sizeof x) 1)");
+        check("sizeof(int) * 2", "(* (sizeof type int) 2)");
+        check("sizeof (x) * 2", "(* (sizeof x) 2)");
+        check("sizeof *p", "(sizeof (* p))");
+        check("sizeof(unsigned char *)", "(sizeof type unsigned char *)");
+        check("_Alignof(long) - 1", "(- (alignof type long) 1)");
+        check(
+            "sizeof p[0] / _Alignof(int *)",
+            "(/ (sizeof ([] p 0)) (alignof type int *))",
+        );
+    }
+
+    #[test]
+    fn compound_literals() {
+        check("(int[]){1, 2}[1]", "([] (compound int [2] {1, 2}) 1)");
+        check("(int){1} + x", "(+ (compound int {1}) x)");
+        check("sizeof (int[3]){0}", "(sizeof (compound int [3] {0}))");
+        check("(char[]){\"ab\"}", "(compound char [3] {\"ab\"})");
+        // Outside of a function a compound literal has static storage duration
+        assert_eq!(
+            parse("(int){x}"),
+            Err(vec![
+                "The initializer of a compound literal must be a constant expression".to_owned()
+            ])
+        );
+    }
+
+    #[test]
+    fn literals() {
+        check(
+            r#"f("hi", 'c', 1.5f, 10ul)"#,
+            r#"(call f ["hi", 'c', 1.5f, 10ul])"#,
+        );
+    }
+
+    #[test]
+    fn c23_constants() {
+        let parse = |text| {
+            parse_standard(text, Standard::C23, |p| {
+                let expr = p.parse_expression()?;
+                let ty = p.expr_type(&expr)?;
+                Some(format!("{expr}: {ty}"))
+            })
+        };
+        assert_eq!(parse("true").as_deref(), Ok("true: _Bool"));
+        assert_eq!(parse("!false").as_deref(), Ok("(! false): int"));
+        assert_eq!(parse("nullptr").as_deref(), Ok("nullptr: nullptr_t"));
+        assert_eq!(
+            parse("(int *)nullptr").as_deref(),
+            Ok("(cast int * nullptr): int *")
+        );
+        // They are constants, so they can initialize objects with static storage duration
+        let text = "int *p = nullptr; bool b = true; _Static_assert(true + !false == 2);";
+        let decls = parse_standard(text, Standard::C23, |p| {
+            while !p.is_empty() {
+                p.parse_decl()?;
+            }
+            Some(())
+        });
+        assert_eq!(decls, Ok(()));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse("1 +"),
+            Err(vec!["Expected an expression, found end of file".to_owned()])
+        );
+        assert_eq!(
+            parse("(1 + 2"),
+            Err(vec![
+                "Expected `)` or an operator to close parenthesized expression, found end of file"
+                    .to_owned()
+            ])
+        );
+        assert_eq!(
+            parse("a * / b"),
+            Err(vec!["Expected an expression, found `/`".to_owned()])
+        );
+        assert_eq!(
+            parse("1 = 2"),
+            Err(vec!["The operand of `=` is not assignable".to_owned()])
+        );
+        assert_eq!(
+            parse("f()++"),
+            Err(vec!["The operand of `++` is not assignable".to_owned()])
+        );
+        assert_eq!(
+            parse("(short long)x"),
+            Err(vec![
+                "Cannot combine `long` with the previous size".to_owned()
+            ])
+        );
+        assert_eq!(
+            parse("(float int)x"),
+            Err(vec![
+                "Cannot combine `int` with the previous type specifier".to_owned()
+            ])
+        );
+        assert_eq!(
+            parse("(unsigned double)x"),
+            Err(vec!["Invalid combination of type specifiers".to_owned()])
+        );
+        assert_eq!(
+            parse("p->"),
+            Err(vec![
+                "Expected an identifier after `->`, found end of file".to_owned()
+            ])
+        );
+        assert_eq!(
+            parse("_Alignof(x)"),
+            Err(vec![
+                "Expected a type name in `_Alignof`, found identifier `x`".to_owned()
+            ])
+        );
+        assert_eq!(
+            parse("(_Alignas(8) int)x"),
+            Err(vec!["`_Alignas` is not allowed here".to_owned()])
+        );
+    }
+
+    /// Parses `text` and prints it back as C.
+    fn unparse(text: &str) -> String {
+        parse_scoped(&format!("{DECLS} {text}"), |p| {
+            p.parse_declaration()?;
+            p.parse_expression()
+        })
+        .unwrap()
+        .inner
+        .to_c()
+    }
+
+    #[test]
+    fn unparse_minimal_parentheses() {
+        assert_eq!(unparse("((a + b)) * c"), "(a + b) * c");
+        assert_eq!(unparse("(a + b) + c"), "a + b + c");
+        assert_eq!(unparse("a + (b + c)"), "a + (b + c)");
+        assert_eq!(unparse("a = (b = c)"), "a = b = c");
+        assert_eq!(
+            unparse("(a ? b : c) ? d : (e ? f : g)"),
+            "(a ? b : c) ? d : e ? f : g"
+        );
+        assert_eq!(unparse("a ? (b, c) : (d = e)"), "a ? b, c : (d = e)");
+        assert_eq!(unparse("f((a, b), c)"), "f((a, b), c)");
+        assert_eq!(unparse("-(-a) - (-b)"), "- -a - -b");
+        assert_eq!(unparse("&(&a)[0]"), "&(&a)[0]");
+        assert_eq!(unparse("(*p)++ + *p++"), "(*p)++ + *p++");
+        assert_eq!(
+            unparse("sizeof((int)a) + sizeof(a)"),
+            "sizeof ((int)a) + sizeof a"
+        );
+        assert_eq!(unparse("(int (*)(int))f"), "(int (*)(int))f");
+        assert_eq!(
+            unparse("(int[2]){1, [1] = 2}[a]"),
+            "(int [2]){ 1, [1] = 2 }[a]"
+        );
+    }
+
+    mod roundtrip {
+        use ast::{
+            Assignment, BinaryExpr, BinaryOperator, Call, Cast, Comma, Expr, FunctionType, Index,
+            LiteralExpression, LogicalExpr, LogicalOperator, MemberAccess, SExpr, ScalarType,
+            SizeOf, Spanned, Ternary, Type, UnaryExpr, UnaryOperator,
+        };
+        use proptest::{prelude::*, sample::select};
+        use utils::{CharLiteral, Encoding, IntKind, IntLiteral, StrChar, StringLiteral};
+
+        fn spanned(expr: Expr<'static>) -> SExpr<'static> {
+            Spanned::new(expr, 0..0)
+        }
+
+        fn leaf() -> impl Strategy<Value = Expr<'static>> {
+            prop_oneof![
+                select(vec!["a", "b", "c"]).prop_map(Expr::Ident),
+                (0..100u64).prop_map(|value| {
+                    let kind = IntKind::Int;
+                    Expr::Lit(LiteralExpression::Int(IntLiteral { value, kind }))
+                }),
+                // Every value of a plain `char`, including the control characters
+                (-128..128i64).prop_map(|value| {
+                    let encoding = Encoding::Plain;
+                    Expr::Lit(LiteralExpression::Char(CharLiteral { encoding, value }))
+                }),
+                prop::collection::vec(str_char(), 0..6).prop_map(|chars| {
+                    let encoding = Encoding::Plain;
+                    Expr::Lit(LiteralExpression::Str(StringLiteral { encoding, chars }))
+                }),
+            ]
+        }
+
+        /// The characters of a string literal, focusing on those that have to be escaped and
+        /// the hexadecimal digits that could run into an escape before them.
+        fn str_char() -> impl Strategy<Value = StrChar> {
+            prop_oneof![
+                select(vec![
+                    '\0', '\x07', '\x08', '\t', '\n', '\x0b', '\x0c', '\r', '\x1b', '\x7f',
+                    '\u{85}', '"', '\'', '\\', '?', 'a', 'F', '0', 'é',
+                ])
+                .prop_map(StrChar::Char),
+                (0..256u32).prop_map(StrChar::Unit),
+            ]
+        }
+
+        fn types() -> impl Strategy<Value = Type> {
+            let int = Type::Scalar(ScalarType::Int);
+            select(vec![
+                int.clone(),
+                Type::Scalar(ScalarType::ULong),
+                Type::Pointer(Box::new(Type::Scalar(ScalarType::Char))),
+                Type::Pointer(Box::new(Type::Function(Box::new(FunctionType {
+                    ret: int.clone(),
+                    params: Some(vec![int.clone()]),
+                    variadic: false,
+                })))),
+                Type::Array(Box::new(int), Some(3)),
+            ])
+        }
+
+        /// Expressions that can be assigned to, built from `inner`.
+        fn place(
+            inner: impl Strategy<Value = Expr<'static>> + Clone,
+        ) -> impl Strategy<Value = Expr<'static>> {
+            prop_oneof![
+                select(vec!["a", "b", "c"]).prop_map(Expr::Ident),
+                (inner.clone(), inner.clone()).prop_map(|(target, index)| {
+                    let (target, index) = (spanned(target), spanned(index));
+                    Expr::Index(Box::new(Index { target, index }))
+                }),
+                inner.clone().prop_map(|val| {
+                    let op = UnaryOperator::Deref;
+                    Expr::Unary(Box::new(UnaryExpr {
+                        op,
+                        val: spanned(val),
+                    }))
+                }),
+                (inner, any::<bool>()).prop_map(|(object, arrow)| {
+                    let object = spanned(object);
+                    let member = "m";
+                    Expr::MemberAccess(Box::new(MemberAccess {
+                        object,
+                        member,
+                        arrow,
+                    }))
+                }),
+            ]
+        }
+
+        fn expr() -> impl Strategy<Value = Expr<'static>> {
+            use BinaryOperator::*;
+            use UnaryOperator::*;
+            leaf().prop_recursive(5, 64, 3, |inner| {
+                let binary = select(vec![
+                    Mul, Div, Rem, Add, Sub, Shl, Shr, Lt, Le, Gt, Ge, Eq, Ne, BitAnd, BitXor,
+                    BitOr,
+                ]);
+                let compound = select(vec![
+                    Mul, Div, Rem, Add, Sub, Shl, Shr, BitAnd, BitXor, BitOr,
+                ]);
+                let logical = select(vec![LogicalOperator::And, LogicalOperator::Or]);
+                let prefix = select(vec![Neg, Plus, Not, BitNot, Deref, AddrOf]);
+                let step = select(vec![PreInc, PreDec, PostInc, PostDec]);
+                prop_oneof![
+                    (inner.clone(), binary, inner.clone()).prop_map(|(lhs, op, rhs)| {
+                        let (lhs, rhs) = (spanned(lhs), spanned(rhs));
+                        Expr::Binary(Box::new(BinaryExpr { lhs, op, rhs }))
+                    }),
+                    (inner.clone(), logical, inner.clone()).prop_map(|(lhs, op, rhs)| {
+                        let (lhs, rhs) = (spanned(lhs), spanned(rhs));
+                        Expr::Logical(Box::new(LogicalExpr { lhs, op, rhs }))
+                    }),
+                    (prefix, inner.clone()).prop_map(|(op, val)| {
+                        Expr::Unary(Box::new(UnaryExpr {
+                            op,
+                            val: spanned(val),
+                        }))
+                    }),
+                    (step, place(inner.clone())).prop_map(|(op, val)| {
+                        Expr::Unary(Box::new(UnaryExpr {
+                            op,
+                            val: spanned(val),
+                        }))
+                    }),
+                    (
+                        place(inner.clone()),
+                        prop::option::of(compound),
+                        inner.clone()
+                    )
+                        .prop_map(|(target, op, val)| {
+                            let (target, val) = (spanned(target), spanned(val));
+                            Expr::Assignment(Box::new(Assignment { target, op, val }))
+                        }),
+                    (inner.clone(), inner.clone(), inner.clone()).prop_map(|(c, t, e)| {
+                        Expr::Ternary(Box::new(Ternary {
+                            cond: spanned(c),
+                            then_val: spanned(t),
+                            else_val: spanned(e),
+                        }))
+                    }),
+                    (inner.clone(), inner.clone()).prop_map(|(lhs, rhs)| {
+                        let (lhs, rhs) = (spanned(lhs), spanned(rhs));
+                        Expr::Comma(Box::new(Comma { lhs, rhs }))
+                    }),
+                    (inner.clone(), prop::collection::vec(inner.clone(), 0..3)).prop_map(
+                        |(callee, args)| {
+                            let callee = spanned(callee);
+                            let args = args.into_iter().map(spanned).collect();
+                            Expr::Call(Box::new(Call { callee, args }))
+                        }
+                    ),
+                    (types(), inner.clone()).prop_map(|(ty, val)| {
+                        Expr::Cast(Box::new(Cast {
+                            ty,
+                            val: spanned(val),
+                        }))
+                    }),
+                    inner.prop_map(|val| Expr::SizeOf(Box::new(SizeOf::Val(spanned(val))))),
+                    types().prop_map(|ty| Expr::SizeOf(Box::new(SizeOf::Type(ty)))),
+                    types().prop_map(Expr::AlignOf),
+                ]
+            })
+        }
+
+        proptest! {
+            /// Printing an expression and parsing it again gives back the same tree.
+            #[test]
+            fn unparse_roundtrips(expr in expr()) {
+                let text = expr.to_c();
+                // The operands are rarely of the right types, like a dereferenced `int`, which
+                // is a constraint violation that is reported without changing the tree
+                let mut parsed = None;
+                _ = crate::tests::parse_with(&text, |p| {
+                    parsed = p.parse_expression();
+                    Some(())
+                });
+                let parsed = parsed.map(|e| e.to_string());
+                prop_assert_eq!(parsed, Some(expr.to_string()), "parsing {:?}", text);
+            }
+        }
+    }
+}
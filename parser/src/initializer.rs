@@ -0,0 +1,147 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+use std::fmt::Display;
+
+use ast::{
+    CompoundLiteral, Designator, Expr, InitItem, Initializer, Member, SExpr, Tag, Type,
+    UnaryOperator,
+    initializer::{self, InitContext, InitError},
+};
+use diagnostics::ErrorComponent;
+use lexer::Token;
+use syntax::SyntaxKind;
+use utils::Spanned;
+
+use crate::{Parser, expr::BindingPower, scope::Symbol};
+
+use super::SToken;
+
+impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s, Tokens> {
+    /// Parses the initializer of a declarator after its `=`, which is either an
+    /// assignment-expression or a brace-enclosed list.
+    pub(crate) fn parse_initializer(&mut self) -> Option<Initializer<'s>> {
+        let start = self.peek_next_split().1.start;
+        if !self.consume_if_eq(&Token::LBrace) {
+            return Some(Initializer::Expr(self.parse_assignment_expression()?));
+        }
+        let mut items = Vec::new();
+        // A trailing comma is allowed, and so is an empty list as in C23
+        while !self.consume_if_eq(&Token::RBrace) {
+            items.push(self.parse_init_item()?);
+            if !self.consume_if_eq(&Token::Comma) {
+                self.expect(&Token::RBrace, " to close initializer list")?;
+                break;
+            }
+        }
+        let span = self.node_from(SyntaxKind::InitializerList, start);
+        Some(Initializer::List(Spanned::new(items, span)))
+    }
+    /// Parses an element of an initializer list along with its designators.
+    fn parse_init_item(&mut self) -> Option<InitItem<'s>> {
+        let mut designators = Vec::new();
+        loop {
+            let start = self.peek_next_split().1.start;
+            let designator = if self.consume_if_eq(&Token::Dot) {
+                Designator::Member(self.expect_ident(" after `.` in designator")?)
+            } else if self.consume_if_eq(&Token::LBracket) {
+                let index = self.parse_expr(BindingPower::Assign)?;
+                self.expect(&Token::RBracket, " to close array designator")?;
+                Designator::Index(index)
+            } else {
+                break;
+            };
+            let span = self.node_from(SyntaxKind::Designator, start);
+            designators.push(Spanned::new(designator, span));
+        }
+        if !designators.is_empty() {
+            self.expect(&Token::Eq, " after designator")?;
+        }
+        let init = self.parse_initializer()?;
+        Some(InitItem { designators, init })
+    }
+    /// Parses the initializer list of a compound literal of type `ty`, after its type name.
+    pub(crate) fn parse_compound_literal(&mut self, ty: Type) -> Option<Expr<'s>> {
+        let init = self.parse_initializer()?;
+        // Compound literals outside of functions have static storage duration
+        let is_static = self.scopes.is_file_scope();
+        let ty = self
+            .check_initializer("a compound literal", &ty, &init, is_static)
+            .unwrap_or(ty);
+        Some(Expr::CompoundLiteral(Box::new(CompoundLiteral {
+            ty,
+            init,
+        })))
+    }
+    /// Checks that `init` matches the object of type `ty` that it initializes, which is
+    /// described by `what` in messages. Returns the type with the length of an array completed
+    /// by the initializer, or [None] if it doesn't match.
+    pub(crate) fn check_initializer(
+        &mut self,
+        what: impl Display,
+        ty: &Type,
+        init: &Initializer<'s>,
+        is_static: bool,
+    ) -> Option<Type> {
+        let resolved = match initializer::resolve(ty, init, self) {
+            Ok(resolved) => resolved,
+            Err(InitError { span, message }) => {
+                self.new_parse_error(span, message);
+                return None;
+            }
+        };
+        if matches!(resolved.ty.unqualified(), Type::Array(_, Some(0))) {
+            let msg = "Array length must be greater than zero".to_string();
+            self.new_parse_error(init.span(), msg);
+        }
+        for leaf in &resolved.leaves {
+            if !matches!(leaf.ty.unqualified(), Type::Array(..)) {
+                self.check_conversion(leaf.value, &leaf.ty);
+            }
+        }
+        // Objects with static storage duration are initialized before the program starts. An
+        // array in a leaf is initialized by a string literal.
+        if is_static {
+            let message = || format!("The initializer of {what} must be a constant expression");
+            for leaf in &resolved.leaves {
+                if matches!(leaf.ty.unqualified(), Type::Array(..)) {
+                    continue;
+                }
+                if let Type::Scalar(scalar) = leaf.ty.unqualified()
+                    && scalar.is_floating()
+                {
+                    let msg = "Floating-point types are not supported".to_string();
+                    self.new_parse_error(leaf.value.as_span(), msg);
+                    continue;
+                }
+                // An address can only be stored in a pointer or an integer as wide as one
+                if let Some(value) = self.eval_constant(leaf.value, message)
+                    && value.convert(&leaf.ty).is_none()
+                {
+                    self.new_parse_error(leaf.value.as_span(), message());
+                }
+            }
+        }
+        Some(resolved.ty)
+    }
+}
+
+impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> InitContext<'s>
+    for Parser<'s, Tokens>
+{
+    fn members(&mut self, tag: &Tag) -> Option<Vec<Member<'s>>> {
+        self.scopes.layouts().members(tag)
+    }
+}
@@ -15,16 +15,42 @@
 // If not, see <https://www.gnu.org/licenses/>.
 // LICENSE NOTICE END
 
-use ast::{Block, Decl, Expr, Function, LiteralExpression, Program, Stmt};
+use ast::{Decl, Function, FunctionType, Param, Program, Type, TypeDecl, VarDecl};
+use basic_ops::Found;
+use decl::{Declarator, DeclaratorKind};
 use diagnostics::{AggregateError, ErrorComponent};
 use lexer::{SToken, Token};
-use source::SourceFile;
-use std::{collections::VecDeque, rc::Rc};
-
-// use crate::expr::BindingPower;
+use recovery::SyncSet;
+use scope::{FunctionScope, Scopes, Symbol};
+use source::{SourceFile, Span};
+use std::{
+    collections::{HashSet, VecDeque},
+    rc::Rc,
+};
+use syntax::{Builder, SyntaxKind};
+use types::StorageClass;
+use utils::Spanned;
 
 mod basic_ops;
+mod constant;
+mod decl;
 mod expr;
+mod initializer;
+mod recovery;
+mod scope;
+mod stmt;
+mod tag;
+mod types;
+
+/// Something that would have been accepted instead of the next token, listed in the errors of
+/// [Parser::expect].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expected {
+    /// A keyword or punctuator, by its spelling
+    Token(&'static str),
+    /// Any operator that can follow an operand
+    Operator,
+}
 
 pub struct Parser<'s, Tokens: Iterator> {
     tokens: Tokens,
@@ -32,61 +58,201 @@ pub struct Parser<'s, Tokens: Iterator> {
     peeked: VecDeque<SToken<'s>>,
     lexer_errors: AggregateError,
     errors: AggregateError,
+    /// The starts of the [Token::Invalid]s put in place of tokens that failed to lex
+    invalid: HashSet<usize>,
+    /// Where errors about an invalid token go, since they follow from its lexer error
+    dropped: Option<ErrorComponent>,
+    scopes: Scopes<'s>,
+    function: FunctionScope<'s>,
+    /// The tags declared by the item being parsed, which come before it in the tree
+    type_decls: Vec<Spanned<TypeDecl<'s>>>,
+    /// The tokens that were tested for and would have been accepted instead of the next one
+    expected: Vec<Expected>,
+    /// The span of the last token consumed
+    prev_span: Option<Span>,
+    /// The syntax tree of the tokens read so far
+    syntax: Builder,
 }
 
 // If a parsing function returns None, an error occurred and we must synchronize to try to
-// continue parsing
+// continue parsing, with [Parser::recover]
 impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s, Tokens> {
-    // TODO: The thing
-    pub fn post_error_sync(&mut self) {
-        let Some(mut prev) = self.advance() else {
-            return;
+    /// Builds the function declared by a declarator of function type, without a body.
+    fn function_of(storage: Option<StorageClass>, declarator: Declarator<'s>) -> Function<'s> {
+        let (name, span) = declarator.name.expect("named declarators have a name");
+        let Type::Function(ty) = declarator.ty else {
+            unreachable!("the declarator should have a function type")
         };
-        while let Some(tok) = self.advance() {
-            use Token::*;
-            if matches!(prev.inner, Token::Semicolon | Int | Return) {
-                self.put_back(tok);
-                return;
-            }
-            prev = tok;
+        let FunctionType {
+            ret,
+            params,
+            variadic,
+        } = *ty;
+        let (params, variadic) = declarator.params.unwrap_or_else(|| {
+            // A function declared with a typedef name for its type has unnamed parameters, which
+            // aren't written anywhere, so they're given an empty span at its name
+            let params = params.map(|params| {
+                let params = params
+                    .into_iter()
+                    .map(|ty| Spanned::new(Param { name: None, ty }, span.start..span.start));
+                params.collect()
+            });
+            (params, variadic)
+        });
+        Function {
+            name,
+            storage: StorageClass::to_ast(storage),
+            ret,
+            params,
+            variadic,
+            body: None,
         }
     }
-    pub fn parse_stmt(&mut self) -> Option<Stmt<'s>> {
-        self.expect(&Token::Return, " as the sole statement")?;
-        let (Some(Token::IntLit(int)), _) = self.advance_split() else {
-            todo!()
+    /// Parses the body of a function definition, after its declarator.
+    fn parse_function(
+        &mut self,
+        storage: Option<StorageClass>,
+        declarator: Declarator<'s>,
+    ) -> Option<Function<'s>> {
+        let (name, span) = declarator
+            .name
+            .clone()
+            .expect("named declarators have a name");
+        // Declared before the body is parsed, so it can be called recursively
+        self.declare_linked(name, &declarator.ty, storage, true, span);
+        let mut function = Self::function_of(storage, declarator);
+        self.function = FunctionScope {
+            ret: Some(function.ret.clone()),
+            ..FunctionScope::default()
         };
-        self.expect(&Token::Semicolon, " to terminate return")?;
-        Some(Stmt::Return(Some(Expr::Lit(LiteralExpression::Int(int)))))
-    }
-    pub fn parse_block(&mut self) -> Option<Block<'s>> {
-        self.expect(&Token::LBrace, " to start function body")?;
-        let mut statements = Vec::new();
-        while !self.consume_if_eq(&Token::RBrace) {
-            statements.push(self.parse_stmt()?);
+        // The parameters are in scope for the body, which doesn't open a scope of its own
+        self.scopes.push();
+        for param in function.params.iter().flatten() {
+            if let Some(name) = param.inner.name {
+                self.scopes
+                    .declare(name, Symbol::Object(param.inner.ty.clone()));
+            }
+        }
+        let body = self.parse_block_items(" to start function body");
+        self.scopes.pop();
+        function.body = Some(body?);
+        // Labels are visible in the entire function, so `goto`s can only be checked at the end
+        for (label, span) in std::mem::take(&mut self.function.gotos) {
+            if !self.function.labels.contains_key(label) {
+                self.new_parse_error(span, format!("Use of undeclared label `{label}`"));
+            }
         }
-        Some(Block(statements))
+        Some(function)
     }
-    pub fn parse_function(&mut self) -> Option<Function<'s>> {
-        self.expect(&Token::Int, " to start function declaration")?;
-        let name = self.expect_ident(" after int type for function declaration")?;
-        self.expect(&Token::LParen, " to start function parameter list")?;
-        self.expect(&Token::Void, " in function parameter list")?;
-        self.expect(&Token::RParen, " to end function parameter list")?;
-        let body = self.parse_block()?;
-        Some(Function { name, body })
+    /// Parses the declarators of a file scope declaration after its specifiers, up to and
+    /// including the terminating semicolon or the body of a function definition.
+    fn parse_external_declarators(
+        &mut self,
+        storage: Option<StorageClass>,
+        function: Option<&Spanned<&str>>,
+        align: Option<&Spanned<u64>>,
+        base: &Type,
+        span: Span,
+    ) -> Option<Vec<Spanned<Decl<'s>>>> {
+        if let Some(class @ (StorageClass::Auto | StorageClass::Register)) = storage {
+            let class = if class == StorageClass::Auto {
+                "auto"
+            } else {
+                "register"
+            };
+            let msg = format!("`{class}` is not allowed at file scope");
+            self.new_parse_error(span.clone(), msg);
+        }
+        let mut decls = Vec::new();
+        // The first declarator's span includes the specifiers
+        let mut start = span.start;
+        loop {
+            let mut declarator = self.parse_declarator(base, DeclaratorKind::Named)?;
+            self.check_function_specifier(function, Some(&declarator.ty));
+            let is_function = matches!(declarator.ty.unqualified(), Type::Function(_));
+            // Only the first declarator can start a definition, and only if it is a function
+            // declarator rather than a typedef name (C17 6.9.1)
+            if is_function
+                && decls.is_empty()
+                && declarator.params.is_some()
+                && self.check_eq(&Token::LBrace)
+            {
+                self.declarator_alignment(align, &declarator);
+                let function = self.parse_function(storage, declarator)?;
+                let span = self.node_from(SyntaxKind::Function, start);
+                return Some(vec![Spanned::new(Decl::Fun(function), span)]);
+            }
+            let init = self.declare_with_initializer(storage, &mut declarator)?;
+            let align = self.declarator_alignment(align, &declarator);
+            let decl = if is_function {
+                Decl::Fun(Self::function_of(storage, declarator))
+            } else {
+                let (name, _) = declarator.name.expect("named declarators have a name");
+                Decl::Var(VarDecl {
+                    name,
+                    storage: StorageClass::to_ast(storage),
+                    ty: declarator.ty,
+                    align,
+                    init,
+                })
+            };
+            let kind = if is_function {
+                SyntaxKind::Function
+            } else {
+                SyntaxKind::Variable
+            };
+            decls.push(Spanned::new(decl, self.node_from(kind, start)));
+            if !self.consume_if_eq(&Token::Comma) {
+                break;
+            }
+            start = self.peek_next_split().1.start;
+        }
+        self.expect_semicolon(" after declaration")?;
+        Some(decls)
     }
-    pub fn parse_decl(&mut self) -> Option<Decl<'s>> {
-        Some(Decl::Fun(self.parse_function()?))
+    /// Parses a file scope declaration, returning it preceded by the tags and typedef names it
+    /// declared.
+    pub fn parse_decl(&mut self) -> Option<Vec<Spanned<Decl<'s>>>> {
+        let span = self.peek_next_split().1;
+        if self.consume_if_eq(&Token::StaticAssert) {
+            self.parse_static_assert(span.start)?;
+            return Some(Vec::new());
+        }
+        if !self.starts_declaration() {
+            if !self.unknown_type_name() {
+                let t = self.peek_next().map(|t| &t.inner);
+                let msg = format!("Expected a declaration, found {}", Found(t));
+                self.new_parse_error(span, msg);
+            }
+            return None;
+        }
+        let (storage, function, align, base) = self.parse_declaration_specifiers()?;
+        let mut items = Vec::new();
+        if self.consume_if_eq(&Token::Semicolon) {
+            self.check_function_specifier(function.as_ref(), None);
+            self.check_declares_tag(&base, span);
+        } else if storage == Some(StorageClass::Typedef) {
+            self.check_function_specifier(function.as_ref(), None);
+            self.check_typedef_alignment(align.as_ref());
+            self.parse_typedefs(&base)?;
+        } else {
+            let (function, align) = (function.as_ref(), align.as_ref());
+            items = self.parse_external_declarators(storage, function, align, &base, span)?;
+        }
+        let type_decls = self.type_decls.drain(..);
+        let mut decls: Vec<_> = type_decls.map(|decl| decl.map(Decl::Type)).collect();
+        decls.extend(items);
+        Some(decls)
     }
     pub fn parse(&mut self) -> (Program<'s>, AggregateError) {
         let mut declarations = Vec::new();
-        while self.peek_next().is_some() {
-            let Some(stmt) = self.parse_decl() else {
-                self.post_error_sync();
+        while let Some(start) = self.peek_next_span() {
+            let Some(decls) = self.parse_decl() else {
+                self.type_decls.clear();
+                self.recover(SyncSet::Declaration, start.start);
                 continue;
             };
-            declarations.push(stmt);
+            declarations.extend(decls);
         }
         let components: Vec<ErrorComponent> = self
             .lexer_errors
@@ -94,6 +260,344 @@ impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s,
             .drain(..)
             .chain(self.errors.components.drain(..))
             .collect();
-        (Program { declarations }, AggregateError { components })
+        let syntax = std::mem::replace(&mut self.syntax, Builder::new(self.source.clone()));
+        let syntax = syntax.finish();
+        (
+            Program {
+                declarations,
+                syntax,
+            },
+            AggregateError { components },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ast::{ScalarType, Type};
+    use diagnostics::ErrorLevel;
+    use lexer::{Logos, SToken, Standard, Token};
+    use source::{SourceFile, Span};
+    use syntax::SyntaxKind;
+
+    use crate::{Parser, Symbol};
+
+    const INT: Type = Type::Scalar(ScalarType::Int);
+
+    pub(crate) type TestParser<'s> =
+        Parser<'s, std::vec::IntoIter<Result<SToken<'s>, diagnostics::ErrorComponent>>>;
+
+    /// Runs `parse` over `text`, with every identifier in it declared as an `int` object at
+    /// file scope.
+    pub(crate) fn parse_with<'s, T>(
+        text: &'s str,
+        parse: impl FnOnce(&mut TestParser<'s>) -> Option<T>,
+    ) -> Result<T, Vec<String>> {
+        parse_scoped(text, |p| {
+            for token in p.tokens.as_slice() {
+                if let Ok(SToken {
+                    inner: Token::Ident(name),
+                    ..
+                }) = token
+                {
+                    p.scopes.declare(name, Symbol::Object(INT));
+                }
+            }
+            parse(p)
+        })
+    }
+
+    /// Runs `parse` over `text`, returning its result, or the error messages if any were
+    /// reported or not all of `text` was consumed.
+    pub(crate) fn parse_scoped<'s, T>(
+        text: &'s str,
+        parse: impl FnOnce(&mut TestParser<'s>) -> Option<T>,
+    ) -> Result<T, Vec<String>> {
+        parse_standard(text, Standard::C17, parse)
+    }
+
+    /// Runs `parse` over `text` as [parse_scoped] does, lexing it for the revision `standard`.
+    pub(crate) fn parse_standard<'s, T>(
+        text: &'s str,
+        standard: Standard,
+        parse: impl FnOnce(&mut TestParser<'s>) -> Option<T>,
+    ) -> Result<T, Vec<String>> {
+        let source = SourceFile::new("test.c".to_owned(), text.to_owned());
+        let tokens: Vec<_> = Token::lexer_with_extras(text, standard)
+            .spanned()
+            .map(|(token, span)| match token {
+                Ok(token) => Ok(SToken::new(token, span)),
+                Err(err) => Err(err.to_component(&source, span)),
+            })
+            .collect();
+        let mut parser = Parser::new(source, tokens.into_iter());
+        let result = parse(&mut parser);
+        if let Some(token) = parser.advance()
+            && result.is_some()
+        {
+            let msg = format!("Unexpected {}", token.inner);
+            parser.new_parse_error(token.span, msg);
+        }
+        if !parser.errors.is_empty() {
+            // Notes are only checked by the tests that are about them
+            let errors = parser.errors.components.drain(..);
+            let errors = errors.filter(|e| e.level == ErrorLevel::Error);
+            return Err(errors.map(|e| e.short_message).collect());
+        }
+        Ok(result.unwrap())
+    }
+
+    /// Parses `text` as a sequence of file scope declarations, returning their trees or the error
+    /// messages.
+    pub(crate) fn parse_decls(text: &str) -> Result<String, Vec<String>> {
+        use ast::tree::{TreeCtx, TreeDisplay};
+        let decls = parse_scoped(text, |p| {
+            let mut decls = Vec::new();
+            while !p.is_empty() {
+                decls.extend(p.parse_decl()?);
+            }
+            Some(decls)
+        })?;
+        let mut tree = String::new();
+        for decl in decls {
+            decl.fmt_tree(&mut TreeCtx::new(), &mut tree).unwrap();
+        }
+        Ok(tree)
+    }
+
+    /// Parses `text` as a translation unit, which must have no errors.
+    fn parse_program(text: &str) -> ast::Program<'_> {
+        let source = SourceFile::new("test.c".to_owned(), text.to_owned());
+        let tokens = Token::lexer(text)
+            .spanned()
+            .map(|(token, span)| Ok(SToken::new(token.unwrap(), span)));
+        let (program, errors) = Parser::new(source, tokens).parse();
+        let messages: Vec<_> = errors.components.iter().map(|e| &e.short_message).collect();
+        assert!(!errors.has_error(), "parsing {text:?}: {messages:?}");
+        program
+    }
+
+    fn tree(program: &ast::Program<'_>) -> String {
+        use ast::tree::{TreeCtx, TreeDisplay};
+        let mut tree = String::new();
+        program.fmt_tree(&mut TreeCtx::new(), &mut tree).unwrap();
+        tree
+    }
+
+    #[test]
+    fn unparse_roundtrips() {
+        let text = r#"
+            struct node { int value; struct node *next; } head, *tail;
+            typedef struct { int x, y; } point;
+            enum { RED, GREEN = RED + 2 };
+            static int (*handlers[2])(int, ...);
+            extern const char *const names[];
+            int apply(point *p, int (*f)(int)) {
+                struct { union { int i; char c; } u; int n; } s, *ps = &s;
+                const point origin;
+                for (int i = 0, *q = &i; i < 10; i++, --*q)
+                    if (i) if (p->x) s.n += -(-i); else continue;
+                    else if (i == 1) s.u.c = 'a';
+                    else s.u.i = (int)sizeof (long) * 2;
+                switch (p->y ? f(1) : (p->x = 2)) {
+                case GREEN: return ps->n;
+                default: break;
+                }
+            again:
+                do p->x <<= 1; while (p->x < 100 && !(p->y & 1));
+                if (p->x == 0) goto again;
+                return origin.x, "done"[0];
+            }
+        "#;
+        let program = parse_program(text);
+        let unparsed = program.to_c();
+        assert_eq!(tree(&parse_program(&unparsed)), tree(&program));
+        pretty_assertions::assert_eq!(
+            unparsed,
+            r#"struct node {
+    int value;
+    struct node *next;
+};
+struct node head, *tail;
+typedef struct {
+    int x, y;
+} point;
+enum { RED, GREEN = RED + 2 };
+static int (*handlers[2])(int, ...);
+extern const char *const names[];
+
+int apply(point *p, int (*f)(int)) {
+    struct {
+        union {
+            int i;
+            char c;
+        } u;
+        int n;
+    } s, *ps = &s;
+    const point origin;
+    for (int i = 0, *q = &i; i < 10; i++, --*q)
+        if (i)
+            if (p->x)
+                s.n += - -i;
+            else
+                continue;
+        else if (i == 1)
+            s.u.c = 'a';
+        else
+            s.u.i = (int)sizeof(long) * 2;
+    switch (p->y ? f(1) : (p->x = 2)) {
+    case GREEN:
+        return ps->n;
+    default:
+        break;
+    }
+again:
+    do
+        p->x <<= 1;
+    while (p->x < 100 && !(p->y & 1));
+    if (p->x == 0)
+        goto again;
+    return origin.x, "done"[0];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn errors_spanning_lines_render() {
+        use diagnostics::render::{RenderContext, RenderableError};
+        let cases = [
+            (
+                "int g; int n = g\n + 1;",
+                "The initializer of `n` must be a constant expression",
+            ),
+            (
+                "struct S { int a; } s; int f(void) { return s\n.b; }",
+                "No member named `b` in `struct S`",
+            ),
+            (
+                "enum { A = 1 /\n 0 };",
+                "Division by zero in constant expression",
+            ),
+            ("long\nchar x;", "Invalid combination of type specifiers"),
+        ];
+        for (text, message) in cases {
+            let errors = parse_scoped(text, |p| Some(p.parse().1)).unwrap();
+            let rendered = errors.display(RenderContext::default()).to_string();
+            assert!(rendered.contains(message), "rendering {text:?}: {rendered}");
+        }
+    }
+
+    #[test]
+    fn syntax_tree() {
+        let text = "// header\n# 1 \"a.c\"\nint x = 1 /* one */ + 2;\n\nint main(void) {\n\treturn  (x) ;\n}\n";
+        let program = parse_program(text);
+        let root = &program.syntax;
+        assert_eq!(root.to_string(), text);
+        pretty_assertions::assert_eq!(
+            format!("{root:?}"),
+            r##"Program@0..80
+  Comment@0..9 "// header"
+  Whitespace@9..10 "\n"
+  LineMarker@10..19 "# 1 \"a.c\""
+  Whitespace@19..20 "\n"
+  Variable@20..43
+    Token("Int")@20..23 "int"
+    Whitespace@23..24 " "
+    Token("Ident")@24..25 "x"
+    Whitespace@25..26 " "
+    Token("Eq")@26..27 "="
+    Whitespace@27..28 " "
+    Expression@28..43
+      Expression@28..29
+        Token("IntLit")@28..29 "1"
+      Whitespace@29..30 " "
+      Comment@30..39 "/* one */"
+      Whitespace@39..40 " "
+      Token("Plus")@40..41 "+"
+      Whitespace@41..42 " "
+      Expression@42..43
+        Token("IntLit")@42..43 "2"
+  Token("Semicolon")@43..44 ";"
+  Whitespace@44..46 "\n\n"
+  Function@46..79
+    Token("Int")@46..49 "int"
+    Whitespace@49..50 " "
+    Token("Ident")@50..54 "main"
+    Token("LParen")@54..55 "("
+    Token("Void")@55..59 "void"
+    Token("RParen")@59..60 ")"
+    Whitespace@60..61 " "
+    Token("LBrace")@61..62 "{"
+    Whitespace@62..64 "\n\t"
+    Statement@64..77
+      Token("Return")@64..70 "return"
+      Whitespace@70..72 "  "
+      Expression@72..75
+        Token("LParen")@72..73 "("
+        Expression@73..74
+          Token("Ident")@73..74 "x"
+        Token("RParen")@74..75 ")"
+      Whitespace@75..76 " "
+      Token("Semicolon")@76..77 ";"
+    Whitespace@77..78 "\n"
+    Token("RBrace")@78..79 "}"
+  Whitespace@79..80 "\n"
+"##
+        );
+        // Text the parser skipped over after an error is kept outside of any node
+        let text = "int f(void) { return 1 +; }\nint @ y;\n";
+        let source = SourceFile::new("test.c".to_owned(), text.to_owned());
+        let tokens = Token::lexer(text)
+            .spanned()
+            .map(|(token, span)| match token {
+                Ok(token) => Ok(SToken::new(token, span)),
+                Err(err) => Err(err.to_component(&source, span)),
+            });
+        let (program, errors) = Parser::new(source.clone(), tokens).parse();
+        assert!(errors.has_error());
+        assert_eq!(program.syntax.to_string(), text);
+        let tokens = program.syntax.tokens().into_iter();
+        let errors: Vec<_> = tokens
+            .filter(|t| t.kind() == SyntaxKind::Error)
+            .map(|t| t.text().to_owned())
+            .collect();
+        assert_eq!(errors, ["@"]);
+    }
+
+    #[test]
+    fn typed_view() {
+        let text = "struct s { int a; } v;\nint g(int n) { while (n) n = n - 1; return n; }";
+        let program = parse_program(text);
+        let kinds: Vec<_> = program.syntax.children().map(|node| node.kind()).collect();
+        assert_eq!(kinds, [SyntaxKind::Variable, SyntaxKind::Function]);
+        let view = program.view();
+        let decls: Vec<_> = view.declarations().collect();
+        assert_eq!(decls.len(), 3);
+        // The struct is declared inside the declaration of `v`
+        assert_eq!(decls[0].syntax().kind(), SyntaxKind::TypeDeclaration);
+        assert_eq!(decls[0].syntax().to_string(), "struct s { int a; }");
+        assert_eq!(decls[0].syntax().parent(), Some(decls[1].syntax()));
+        assert_eq!(decls[1].syntax().to_string(), "struct s { int a; } v");
+        let ast::Decl::Fun(function) = decls[2].ast() else {
+            panic!("expected a function");
+        };
+        let param = decls[2]
+            .child(&function.params.as_ref().unwrap()[0])
+            .unwrap();
+        assert_eq!(param.name, Some("n"));
+        assert_eq!(param.syntax().to_string(), "int n");
+        let ast::BlockItem::Stmt(stmt) = &function.body.as_ref().unwrap().0[0] else {
+            panic!("expected a statement");
+        };
+        let stmt = decls[2].child(stmt).unwrap();
+        assert_eq!(stmt.syntax().to_string(), "while (n) n = n - 1;");
+        let ast::Stmt::While { cond, body } = stmt.ast() else {
+            panic!("expected a loop");
+        };
+        assert_eq!(stmt.child(cond).unwrap().syntax().to_string(), "n");
+        let body = stmt.child(body).unwrap();
+        assert_eq!(body.syntax().kind(), SyntaxKind::Statement);
+        assert_eq!(body.syntax().parent(), Some(stmt.syntax()));
     }
 }
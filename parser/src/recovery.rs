@@ -0,0 +1,242 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! Panic-mode error recovery: after a syntax error, tokens are skipped until one where parsing
+//! can resume, so that a single run reports every independent error.
+
+use diagnostics::ErrorComponent;
+use lexer::Token;
+
+use crate::Parser;
+
+use super::SToken;
+
+/// The tokens parsing can resume at after a syntax error, which depend on what was being
+/// parsed. Tokens inside brackets opened while skipping are never stopped at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SyncSet {
+    /// File scope declarations resume after a `;` or the `}` ending a function body, or before
+    /// a keyword that starts a declaration.
+    Declaration,
+    /// Block items resume after a `;` or a nested block, or before the `}` closing the
+    /// enclosing block or a token that starts a statement or declaration.
+    Statement,
+    /// Member declarations resume after a `;`, or before the `}` closing the member list or a
+    /// keyword that starts a declaration.
+    Member,
+    /// Arguments and enumerators resume before the `,` or closing bracket ending them. A `;`
+    /// ends the enclosing statement instead.
+    Expression,
+    /// Parameters resume before the `,` or `)` ending them. A `{` or `;` ends the enclosing
+    /// declarator instead.
+    Parameter,
+    /// Resumes before the `}` closing the enclosing block.
+    BlockClose,
+}
+
+impl SyncSet {
+    /// Whether parsing can resume after this token, once it's skipped.
+    fn resumes_after(self, t: &Token<'_>) -> bool {
+        match self {
+            Self::Declaration | Self::Statement => matches!(t, Token::Semicolon | Token::RBrace),
+            Self::Member => *t == Token::Semicolon,
+            Self::Expression | Self::Parameter | Self::BlockClose => false,
+        }
+    }
+}
+
+impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s, Tokens> {
+    /// Whether parsing can resume before this token, without skipping it.
+    fn resumes_before(set: SyncSet, t: &Token<'_>) -> bool {
+        use Token::*;
+        match set {
+            SyncSet::Declaration => Self::is_specifier_keyword(t) || *t == StaticAssert,
+            SyncSet::Statement => {
+                matches!(
+                    t,
+                    RBrace
+                        | LBrace
+                        | If
+                        | While
+                        | Do
+                        | For
+                        | Switch
+                        | Case
+                        | Default
+                        | Return
+                        | Break
+                        | Continue
+                        | Goto
+                        | StaticAssert
+                ) || Self::is_specifier_keyword(t)
+            }
+            SyncSet::Member => *t == RBrace || Self::is_specifier_keyword(t),
+            SyncSet::Expression => matches!(t, Comma | RParen | RBracket | RBrace | Semicolon),
+            SyncSet::Parameter => matches!(t, Comma | RParen | LBrace | Semicolon),
+            SyncSet::BlockClose => *t == RBrace,
+        }
+    }
+    /// Skips tokens after a syntax error until parsing can resume in the context given by
+    /// `set`.
+    ///
+    /// `start` is the offset of the token the failed construct started at. If it wasn't
+    /// consumed, it's skipped regardless so that parsing makes progress.
+    pub(crate) fn recover(&mut self, set: SyncSet, start: usize) {
+        let mut forced = self
+            .peek_next()
+            .is_some_and(|next| next.span.start == start);
+        // The brackets opened while skipping, whose contents can't be resumed at
+        let mut depth = 0usize;
+        while let Some(t) = self.peek_next().map(|t| t.inner.clone()) {
+            if depth == 0 && Self::resumes_before(set, &t) && !forced {
+                return;
+            }
+            forced = false;
+            _ = self.advance();
+            match t {
+                Token::LParen | Token::LBracket | Token::LBrace => depth += 1,
+                Token::RParen | Token::RBracket | Token::RBrace => {
+                    depth = depth.saturating_sub(1);
+                }
+                _ => (),
+            }
+            if depth == 0 && set.resumes_after(&t) {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diagnostics::ErrorLevel;
+    use pretty_assertions::assert_eq;
+
+    use crate::tests::parse_scoped;
+
+    /// Parses a whole file, returning every error reported.
+    fn errors(text: &str) -> Vec<String> {
+        let errors = parse_scoped(text, |p| Some(p.parse().1)).unwrap();
+        let errors = errors.components.into_iter();
+        let errors = errors.filter(|e| e.level == ErrorLevel::Error);
+        errors.map(|e| e.short_message).collect()
+    }
+
+    #[test]
+    fn statements() {
+        assert_eq!(
+            errors(
+                "int f(int a) { int x = 1 +; if (a b) { x = 2; } while (x) x x; return x }
+                 int g(void) { return f(1); }"
+            ),
+            [
+                "Expected an expression, found `;`",
+                "Expected `)` or an operator to close `if` condition, found identifier `b`",
+                "Expected `;` or an operator after expression, found identifier `x`",
+                "Expected `;` or an operator after `return` statement, found `}`",
+            ]
+        );
+        assert_eq!(
+            errors("int f(int a) { return a; ) } int g(void) { return f(1, , 2 3); }"),
+            [
+                "Expected an expression, found `)`",
+                "Expected an expression, found `,`",
+                "Expected one of `)`, `,` or an operator to close argument list, found integer constant `3`",
+            ]
+        );
+    }
+
+    #[test]
+    fn declarations() {
+        assert_eq!(
+            errors(
+                "int f(int a, int b c, int d) { return a + d; }
+                 struct s { int x y; int z; }; enum e { A = , B; C };
+                 int h = 3 3; ) int k(void) { return f(1, 2, h) + B; }"
+            ),
+            [
+                "Expected one of `)`, `[`, `(` or `,` to end function parameter list, found identifier `c`",
+                "Expected one of `;`, `[`, `(`, `:` or `,` after member declaration, found identifier `y`",
+                "Expected an expression, found `,`",
+                "Expected one of `}`, `=` or `,` to close enumerator list, found `;`",
+                "Expected one of `;`, `,` or an operator after declaration, found integer constant `3`",
+                "Expected a declaration, found `)`",
+            ]
+        );
+        assert_eq!(
+            errors("int f(void) { return 0;"),
+            ["Expected `}` to close block, found end of file"]
+        );
+    }
+
+    #[test]
+    fn expected_tokens() {
+        // Tokens tested for by lookahead are listed along with those tried directly
+        assert_eq!(
+            errors("int x 3; int f(void) { for (;; x 1) return; }"),
+            [
+                "Expected one of `;`, `[`, `(`, `=` or `,` after declaration, found integer constant `3`",
+                "Expected `)` or an operator to close `for` clauses, found integer constant `1`",
+            ]
+        );
+    }
+
+    #[test]
+    fn member_lists() {
+        // Tokens that start statements are skipped rather than resumed at
+        for (text, found) in [
+            ("struct { case", "`case`"),
+            ("struct { if", "`if`"),
+            ("struct { { }", "`{`"),
+        ] {
+            assert_eq!(
+                errors(text),
+                [
+                    format!("Expected a member declaration, found {found}"),
+                    "Expected a member declaration, found end of file".to_owned(),
+                ]
+            );
+        }
+        assert_eq!(
+            errors("struct S { int a; return 0; }; int b = sizeof(struct S);"),
+            ["Expected a member declaration, found `return`"]
+        );
+    }
+
+    #[test]
+    fn lexer_errors() {
+        // Tokens that failed to lex are operands, and only the lexer reports them
+        for (text, error) in [
+            (
+                "int a = 123abc;",
+                "Invalid suffix `abc` on numeric constant",
+            ),
+            ("int b = '\\q';", "Unknown escape sequence `\\q`"),
+            (
+                "int c = 1 + 0x + 2;",
+                "Missing digits after hexadecimal prefix",
+            ),
+            ("char *d = \"abc\n;", "Unterminated string literal"),
+            ("int e[2z];", "Invalid suffix `z` on numeric constant"),
+            (
+                "int f(void) { return f() * 09; }",
+                "Invalid digit `9` in octal constant",
+            ),
+        ] {
+            assert_eq!(errors(text), [error], "{text}");
+        }
+    }
+}
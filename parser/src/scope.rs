@@ -0,0 +1,203 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use ast::{Member, ScalarType, Tag, TagKind, Type, layout::Layouts};
+
+use source::Span;
+use utils::Spanned;
+
+/// What an ordinary identifier refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Symbol {
+    /// An object with automatic storage duration, with its type
+    Object(Type),
+    /// An object with static storage duration, whose address is a constant
+    Static(Type),
+    Function {
+        /// The number of parameters, if the function was declared with a prototype
+        params: Option<usize>,
+        variadic: bool,
+    },
+    /// An enumeration constant with its value
+    Constant(i64),
+    /// A typedef name, which is a type specifier rather than an expression
+    Typedef(Type),
+}
+
+/// The linkage of an identifier, which decides whether its declarations in different scopes
+/// and translation units refer to the same entity (C17 6.2.2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Linkage {
+    Internal,
+    External,
+}
+
+/// What the declarations of an identifier with linkage have established so far.
+#[derive(Debug, Clone)]
+pub(crate) struct Global {
+    pub(crate) linkage: Linkage,
+    /// The composite of the types of every declaration
+    pub(crate) ty: Type,
+    /// Whether a function body or an object initializer has been given
+    pub(crate) defined: bool,
+}
+
+/// The identifiers declared in a single scope. Tags have a name space of their own.
+#[derive(Debug, Default)]
+struct Scope<'s> {
+    ordinary: HashMap<&'s str, Symbol>,
+    tags: HashMap<&'s str, Tag>,
+}
+
+/// The identifiers visible at the current point, innermost scope last.
+#[derive(Debug)]
+pub(crate) struct Scopes<'s> {
+    scopes: Vec<Scope<'s>>,
+    /// The ids of the tags that have been defined, and so are complete
+    defined_tags: HashSet<usize>,
+    /// The layouts of the structs and unions that have been defined
+    layouts: Layouts<'s>,
+    tag_count: usize,
+    /// The identifiers with linkage, which may also have been declared in blocks
+    globals: HashMap<&'s str, Global>,
+}
+
+impl<'s> Scopes<'s> {
+    /// Creates the table with only the file scope.
+    pub(crate) fn new() -> Self {
+        Self {
+            scopes: vec![Scope::default()],
+            defined_tags: HashSet::new(),
+            layouts: Layouts::default(),
+            tag_count: 0,
+            globals: HashMap::new(),
+        }
+    }
+    pub(crate) fn push(&mut self) {
+        self.scopes.push(Scope::default());
+    }
+    pub(crate) fn pop(&mut self) {
+        assert!(
+            self.scopes.len() > 1,
+            "the file scope should never be popped"
+        );
+        self.scopes.pop();
+    }
+    pub(crate) fn is_file_scope(&self) -> bool {
+        self.scopes.len() == 1
+    }
+    /// Declares `name` in the innermost scope, returning the previous declaration in that same
+    /// scope, if any.
+    pub(crate) fn declare(&mut self, name: &'s str, symbol: Symbol) -> Option<Symbol> {
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .ordinary
+            .insert(name, symbol)
+    }
+    pub(crate) fn lookup(&self, name: &str) -> Option<Symbol> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.ordinary.get(name).cloned())
+    }
+    /// Finds the type `name` is a typedef name for, if it isn't hidden by another declaration.
+    pub(crate) fn lookup_typedef(&self, name: &str) -> Option<&Type> {
+        let symbol = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.ordinary.get(name))?;
+        match symbol {
+            Symbol::Typedef(ty) => Some(ty),
+            _ => None,
+        }
+    }
+    pub(crate) fn lookup_global(&self, name: &str) -> Option<&Global> {
+        self.globals.get(name)
+    }
+    pub(crate) fn set_global(&mut self, name: &'s str, global: Global) {
+        self.globals.insert(name, global);
+    }
+    /// Creates a new tag, declaring it in the innermost scope if it has a name.
+    pub(crate) fn new_tag(&mut self, kind: TagKind, name: Option<&'s str>) -> Tag {
+        self.tag_count += 1;
+        let tag = Tag {
+            kind,
+            name: name.map(Rc::from),
+            id: self.tag_count,
+        };
+        if let Some(name) = name {
+            let scope = self.scopes.last_mut().unwrap();
+            scope.tags.insert(name, tag.clone());
+        }
+        tag
+    }
+    /// Finds the tag `name` refers to, only searching the innermost scope if `innermost` is set.
+    pub(crate) fn lookup_tag(&self, name: &str, innermost: bool) -> Option<&Tag> {
+        let scopes = if innermost {
+            &self.scopes[self.scopes.len() - 1..]
+        } else {
+            &self.scopes[..]
+        };
+        scopes.iter().rev().find_map(|scope| scope.tags.get(name))
+    }
+    /// Marks a tag as defined, returning whether it already was.
+    pub(crate) fn define_tag(&mut self, tag: &Tag) -> bool {
+        !self.defined_tags.insert(tag.id)
+    }
+    pub(crate) fn is_complete(&self, tag: &Tag) -> bool {
+        self.defined_tags.contains(&tag.id)
+    }
+    /// Lays out a struct or union from the members of its definition.
+    pub(crate) fn set_members(&mut self, tag: &Tag, members: &[Spanned<Member<'s>>]) {
+        self.layouts.define(tag, members);
+    }
+    pub(crate) fn layouts(&self) -> &Layouts<'s> {
+        &self.layouts
+    }
+}
+
+/// The labels of a `switch` statement found so far, which must all be distinct
+/// (C17 6.8.4.2p3).
+#[derive(Debug, Default)]
+pub(crate) struct Switch {
+    /// The promoted type of the controlling expression, which the `case` values are converted
+    /// to before they're compared, if it's known to be an integer type
+    pub(crate) ty: Option<ScalarType>,
+    pub(crate) cases: HashSet<i128>,
+    pub(crate) default: bool,
+}
+
+/// The state that is local to the body of a function: labels and the statements that can be
+/// targeted by `break`, `continue` and `case`.
+#[derive(Debug, Default)]
+pub(crate) struct FunctionScope<'s> {
+    /// The number of loops enclosing the current statement
+    pub(crate) loops: usize,
+    /// The `switch` statements enclosing the current statement, innermost last
+    pub(crate) switches: Vec<Switch>,
+    pub(crate) labels: HashMap<&'s str, Span>,
+    /// Every `goto` in the function, checked against [Self::labels] once the body is parsed
+    pub(crate) gotos: Vec<(&'s str, Span)>,
+    /// The return type of the function, which the values of `return` statements must convert to
+    pub(crate) ret: Option<Type>,
+}
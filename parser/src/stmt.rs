@@ -0,0 +1,614 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use std::fmt::Display;
+
+use ast::{Block, BlockItem, For, ForInit, SExpr, SStmt, ScalarType, Stmt, Type};
+use diagnostics::ErrorComponent;
+use lexer::Token;
+use source::Span;
+use syntax::SyntaxKind;
+
+use crate::{Parser, constant::arithmetic, recovery::SyncSet, scope::Switch};
+
+use super::SToken;
+
+impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s, Tokens> {
+    /// Parses a parenthesized expression, as used for the condition of `if`, `while` and `switch`.
+    fn parse_condition(&mut self, keyword: &Token<'_>) -> Option<SExpr<'s>> {
+        self.expect(&Token::LParen, format_args!(" after `{keyword}`"))?;
+        let cond = self.parse_expression()?;
+        self.expect(
+            &Token::RParen,
+            format_args!(" to close `{keyword}` condition"),
+        )?;
+        self.check_scalar(&cond, format_args!("The condition of `{keyword}`"));
+        Some(cond)
+    }
+    fn parse_body(&mut self) -> Option<Box<SStmt<'s>>> {
+        self.parse_stmt().map(Box::new)
+    }
+    /// Parses the body of a loop, in which `break` and `continue` are allowed.
+    fn parse_loop_body(&mut self) -> Option<Box<SStmt<'s>>> {
+        self.function.loops += 1;
+        let body = self.parse_body();
+        self.function.loops -= 1;
+        body
+    }
+    /// Reports the value of a `return` statement if it doesn't convert to the return type of
+    /// the function, which can't return a value at all if it returns `void` (C17 6.8.6.4p1).
+    fn check_return(&mut self, value: &SExpr<'s>) {
+        let Some(ret) = self.function.ret.clone() else {
+            return;
+        };
+        if ret.unqualified() == &Type::Scalar(ScalarType::Void) {
+            let msg = "A function returning `void` can't return a value".to_string();
+            self.new_parse_error(value.as_span(), msg);
+        } else {
+            self.check_conversion(value, &ret);
+        }
+    }
+    /// Reports a `break`, `continue`, `case` or `default` that is not inside a statement it
+    /// could belong to.
+    fn check_jump_context(&mut self, keyword: &Token<'_>, span: Span) {
+        let (allowed, context) = match keyword {
+            Token::Break => (
+                self.function.loops > 0 || !self.function.switches.is_empty(),
+                "a loop or `switch`",
+            ),
+            Token::Continue => (self.function.loops > 0, "a loop"),
+            _ => (!self.function.switches.is_empty(), "a `switch`"),
+        };
+        if !allowed {
+            self.new_parse_error(span, format!("`{keyword}` outside of {context}"));
+        }
+    }
+    /// Reports a `case` label whose value, converted to the promoted type of the controlling
+    /// expression, is the same as that of an earlier label in the same `switch`.
+    fn check_case(&mut self, value: &SExpr<'s>, constant: i128) {
+        let Some(switch) = self.function.switches.last_mut() else {
+            return;
+        };
+        let constant = switch.ty.map_or(constant, |ty| ty.widen(ty.wrap(constant)));
+        if !switch.cases.insert(constant) {
+            let msg = format!("Duplicate `case` value {constant}");
+            self.new_parse_error(value.as_span(), msg);
+        }
+    }
+    /// Should only be called after a leading `for` has been consumed
+    fn parse_for(&mut self) -> Option<Stmt<'s>> {
+        self.expect(&Token::LParen, " after `for`")?;
+        let init = if self.starts_declaration() {
+            Some(ForInit::Decl(self.parse_declaration()?))
+        } else if self.consume_if_eq(&Token::Semicolon) {
+            None
+        } else {
+            let init = self.parse_expression()?;
+            self.expect(&Token::Semicolon, " after `for` initializer")?;
+            Some(ForInit::Expr(init))
+        };
+        let cond = if self.check_eq(&Token::Semicolon) {
+            None
+        } else {
+            let cond = self.parse_expression()?;
+            self.check_scalar(&cond, "The condition of `for`");
+            Some(cond)
+        };
+        self.expect(&Token::Semicolon, " after `for` condition")?;
+        let step = if self.check_eq(&Token::RParen) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        self.expect(&Token::RParen, " to close `for` clauses")?;
+        let body = *self.parse_loop_body()?;
+        Some(Stmt::For(Box::new(For {
+            init,
+            cond,
+            step,
+            body,
+        })))
+    }
+    pub fn parse_stmt(&mut self) -> Option<SStmt<'s>> {
+        let start = self.peek_next_split().1.start;
+        let stmt = self.parse_unspanned_stmt()?;
+        Some(SStmt::new(
+            stmt,
+            self.node_from(SyntaxKind::Statement, start),
+        ))
+    }
+    fn parse_unspanned_stmt(&mut self) -> Option<Stmt<'s>> {
+        let (next, span) = self.peek_next_split();
+        let Some(keyword) = next.cloned() else {
+            self.new_parse_error(span, "Expected a statement, found end of file".to_string());
+            return None;
+        };
+        // Labels are the only statements that start with an identifier and a colon
+        if let Token::Ident(label) = keyword
+            && self.peek(1).is_some_and(|t| t.inner == Token::Colon)
+        {
+            _ = self.advance();
+            _ = self.advance();
+            if self.function.labels.insert(label, span.clone()).is_some() {
+                self.new_parse_error(span, format!("Redefinition of label `{label}`"));
+            }
+            let body = self.parse_body()?;
+            return Some(Stmt::Labeled { label, body });
+        }
+        let stmt = match keyword {
+            Token::LBrace => return Some(Stmt::Block(self.parse_block(" to start block")?)),
+            Token::If => {
+                _ = self.advance();
+                let cond = self.parse_condition(&keyword)?;
+                let then_body = self.parse_body()?;
+                let else_body = if self.consume_if_eq(&Token::Else) {
+                    Some(self.parse_body()?)
+                } else {
+                    None
+                };
+                return Some(Stmt::If {
+                    cond,
+                    then_body,
+                    else_body,
+                });
+            }
+            Token::While => {
+                _ = self.advance();
+                let cond = self.parse_condition(&keyword)?;
+                let body = self.parse_loop_body()?;
+                return Some(Stmt::While { cond, body });
+            }
+            Token::Do => {
+                _ = self.advance();
+                let body = self.parse_loop_body()?;
+                self.expect(&Token::While, " after `do` body")?;
+                let cond = self.parse_condition(&Token::While)?;
+                Stmt::DoWhile { body, cond }
+            }
+            Token::For => {
+                _ = self.advance();
+                // The scope of a declaration in the initializer ends with the loop
+                return self.in_scope(Self::parse_for);
+            }
+            Token::Switch => {
+                _ = self.advance();
+                let value = self.parse_condition(&keyword)?;
+                let ty = self
+                    .expr_type(&value)
+                    .as_ref()
+                    .and_then(arithmetic)
+                    .filter(|ty| ty.is_integer());
+                self.function.switches.push(Switch {
+                    ty,
+                    ..Switch::default()
+                });
+                let body = self.parse_body();
+                self.function.switches.pop();
+                return Some(Stmt::Switch { value, body: body? });
+            }
+            Token::Case => {
+                _ = self.advance();
+                self.check_jump_context(&keyword, span);
+                let value = self.parse_expr(crate::expr::BindingPower::Assign)?;
+                let message = || "The value of a `case` label must be an integer constant".into();
+                if let Some(constant) = self.eval_integer(&value, message) {
+                    self.check_case(&value, constant);
+                }
+                self.expect(&Token::Colon, " after `case` value")?;
+                let body = self.parse_body()?;
+                return Some(Stmt::Case { value, body });
+            }
+            Token::Default => {
+                _ = self.advance();
+                self.check_jump_context(&keyword, span.clone());
+                if let Some(switch) = self.function.switches.last_mut()
+                    && std::mem::replace(&mut switch.default, true)
+                {
+                    let msg = "Multiple `default` labels in one `switch`".to_string();
+                    self.new_parse_error(span, msg);
+                }
+                self.expect(&Token::Colon, " after `default`")?;
+                return Some(Stmt::Default(self.parse_body()?));
+            }
+            Token::Return => {
+                _ = self.advance();
+                if self.check_eq(&Token::Semicolon) {
+                    Stmt::Return(None)
+                } else {
+                    let value = self.parse_expression()?;
+                    self.check_return(&value);
+                    Stmt::Return(Some(value))
+                }
+            }
+            Token::Break => {
+                _ = self.advance();
+                self.check_jump_context(&keyword, span);
+                Stmt::Break
+            }
+            Token::Continue => {
+                _ = self.advance();
+                self.check_jump_context(&keyword, span);
+                Stmt::Continue
+            }
+            Token::Goto => {
+                _ = self.advance();
+                let span = self.peek_next_split().1;
+                let label = self.expect_ident(" after `goto`")?;
+                self.function.gotos.push((label, span));
+                Stmt::Goto(label)
+            }
+            Token::Semicolon => Stmt::Null,
+            _ => Stmt::Expr(self.parse_expression()?),
+        };
+        let ctx = match stmt {
+            Stmt::Expr(_) => " after expression".to_string(),
+            Stmt::Null => String::new(),
+            _ => format!(" after `{keyword}` statement"),
+        };
+        self.expect_semicolon(ctx)?;
+        Some(stmt)
+    }
+    /// Runs `parse` in a new block scope.
+    pub(crate) fn in_scope<T>(&mut self, parse: impl FnOnce(&mut Self) -> T) -> T {
+        self.scopes.push();
+        let result = parse(self);
+        self.scopes.pop();
+        result
+    }
+    pub fn parse_block(&mut self, ctx: impl Display) -> Option<Block<'s>> {
+        self.in_scope(|p| p.parse_block_items(ctx))
+    }
+    /// Parses a block in the current scope, as for function bodies, which share the scope of
+    /// the parameters.
+    pub(crate) fn parse_block_items(&mut self, ctx: impl Display) -> Option<Block<'s>> {
+        self.expect(&Token::LBrace, ctx)?;
+        let mut items = Vec::new();
+        while !self.consume_if_eq(&Token::RBrace) {
+            if self.is_empty() {
+                let msg = "Expected `}` to close block, found end of file".to_string();
+                let span = self.end_span();
+                self.new_parse_error(span, msg);
+                return None;
+            }
+            // Tags declared by an item come before it, so they are visible to it
+            let pending = self.type_decls.len();
+            let start = self.peek_next_split().1.start;
+            let Some(item) = self.parse_block_item() else {
+                self.type_decls.truncate(pending);
+                self.recover(SyncSet::Statement, start);
+                continue;
+            };
+            items.extend(self.type_decls.drain(pending..).map(BlockItem::Type));
+            // Declarations of only tags or typedef names, and static assertions, have nothing
+            // left to declare
+            if !matches!(&item, BlockItem::Decl(decls) if decls.is_empty()) {
+                items.push(item);
+            }
+        }
+        Some(Block(items))
+    }
+    fn parse_block_item(&mut self) -> Option<BlockItem<'s>> {
+        let span = self.peek_next_split().1;
+        if self.consume_if_eq(&Token::StaticAssert) {
+            self.parse_static_assert(span.start)?;
+            return Some(BlockItem::Decl(Vec::new()));
+        }
+        // A typedef name followed by a colon is a label
+        let is_label = self.peek(1).is_some_and(|t| t.inner == Token::Colon);
+        if self.starts_declaration() && !is_label {
+            return Some(BlockItem::Decl(self.parse_declaration()?));
+        }
+        Some(BlockItem::Stmt(self.parse_stmt()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ast::tree::{TreeCtx, TreeDisplay};
+    use diagnostics::ErrorLevel;
+    use pretty_assertions::assert_eq;
+    use source::SourceFile;
+
+    use crate::tests::{parse_scoped, parse_with};
+
+    fn parse(text: &str) -> Result<String, Vec<String>> {
+        let stmt = parse_with(text, |p| p.parse_stmt())?;
+        let mut tree = String::new();
+        stmt.fmt_tree(&mut TreeCtx::new(), &mut tree).unwrap();
+        Ok(tree)
+    }
+
+    #[track_caller]
+    fn check(text: &str, expected: &str) {
+        assert_eq!(parse(text).as_deref(), Ok(expected), "parsing {text:?}");
+    }
+
+    #[test]
+    fn dangling_else() {
+        check(
+            "if (a) if (b) x; else y;",
+            r#"If
+├──Cond
+│  └──Ident
+│     └──"a"
+└──Then
+   └──If
+      ├──Cond
+      │  └──Ident
+      │     └──"b"
+      ├──Then
+      │  └──Ident
+      │     └──"x"
+      └──Else
+         └──Ident
+            └──"y"
+"#,
+        );
+    }
+
+    #[test]
+    fn loops() {
+        check(
+            "for (int i = 0, *p; ;) { continue; }",
+            r#"For
+├──Init
+│  └──Declaration
+│     ├──Variable
+│     │  ├──Name
+│     │  │  └──"i"
+│     │  ├──Type
+│     │  │  └──Type: int
+│     │  └──Init
+│     │     └──Literal
+│     │        └──Int
+│     │           └──0
+│     └──Variable
+│        ├──Name
+│        │  └──"p"
+│        └──Type
+│           └──Type: int *
+└──Body
+   └──Block
+      └──Continue
+"#,
+        );
+        check(
+            "do ; while (x);",
+            r#"Do While
+├──Body
+│  └──Null
+└──Cond
+   └──Ident
+      └──"x"
+"#,
+        );
+    }
+
+    #[test]
+    fn switch_and_labels() {
+        check(
+            "switch (x) { case 1: end: break; default: goto end; }",
+            r#"Switch
+├──Value
+│  └──Ident
+│     └──"x"
+└──Body
+   └──Block
+      ├──Case
+      │  ├──Value
+      │  │  └──Literal
+      │  │     └──Int
+      │  │        └──1
+      │  └──Body
+      │     └──Label end
+      │        └──Break
+      └──Default
+         └──Goto
+            └──"end"
+"#,
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse("while x;"),
+            Err(vec![
+                "Expected `(` after `while`, found identifier `x`".to_owned()
+            ])
+        );
+        assert_eq!(
+            parse("{ return 1 }"),
+            Err(vec![
+                "Expected `;` or an operator after `return` statement, found `}`".to_owned()
+            ])
+        );
+        assert_eq!(
+            parse("{ x = 1;"),
+            Err(vec![
+                "Expected `}` to close block, found end of file".to_owned()
+            ])
+        );
+    }
+
+    #[test]
+    fn missing_semicolon_note() {
+        let errors = parse_scoped("int f(int x) { x = 1\n return x; }", |p| Some(p.parse().1));
+        let errors = errors.unwrap().components;
+        let errors: Vec<_> = errors
+            .iter()
+            .map(|e| (e.level, e.short_message.as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (
+                    ErrorLevel::Error,
+                    "Expected `;` or an operator after expression, found `return`"
+                ),
+                (ErrorLevel::Note, "`;` should go after this"),
+            ]
+        );
+        // The note is only given for the ends of statements and declarations
+        let errors = parse_scoped("struct s { int a\n int b; };", |p| Some(p.parse().1));
+        let levels: Vec<_> = errors.unwrap().components.iter().map(|e| e.level).collect();
+        assert_eq!(levels, [ErrorLevel::Error]);
+    }
+
+    #[test]
+    fn block_scope() {
+        let parse = |text| parse_scoped(text, |p| p.parse_stmt()).map(|_| ());
+        assert_eq!(
+            parse("{ int a = 1, b = a; { long a = b; } a = 2; }"),
+            Ok(())
+        );
+        assert_eq!(
+            parse("{ int a; { int b; } a = b; int a; }"),
+            Err(vec![
+                "Use of undeclared identifier `b`".to_owned(),
+                "Redefinition of `a`".to_owned()
+            ])
+        );
+        assert_eq!(
+            parse("{ for (int i = 0; i; i) {} i; }"),
+            Err(vec!["Use of undeclared identifier `i`".to_owned()])
+        );
+    }
+
+    #[test]
+    fn jumps() {
+        let parse = |text| parse_scoped(text, |p| p.parse_decl()).map(|_| ());
+        assert_eq!(
+            parse(
+                "int f(void) { goto end; while (1) { switch (0) { case 0: continue; } } end: ; }"
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            parse("int f(void) { break; continue; case 1: ; default: ; }"),
+            Err(vec![
+                "`break` outside of a loop or `switch`".to_owned(),
+                "`continue` outside of a loop".to_owned(),
+                "`case` outside of a `switch`".to_owned(),
+                "`default` outside of a `switch`".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse("int f(void) { a: a: goto b; }"),
+            Err(vec![
+                "Redefinition of label `a`".to_owned(),
+                "Use of undeclared label `b`".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse("int f(int x) { switch (x) { case 1 << 2: case x: case 1 % 0: ; } }"),
+            Err(vec![
+                "The value of a `case` label must be an integer constant".to_owned(),
+                "Division by zero in constant expression".to_owned(),
+            ])
+        );
+    }
+
+    #[test]
+    fn switch_labels() {
+        let parse = |text| parse_scoped(text, |p| p.parse_decl()).map(|_| ());
+        // Nested `switch` statements have labels of their own
+        assert_eq!(
+            parse(
+                "int f(int x) { switch (x) { case 1: default: switch (x) { case 1: default: ; } } }"
+            ),
+            Ok(())
+        );
+        // Values are compared after conversion to the promoted type of the controlling expression
+        assert_eq!(
+            parse(
+                "int f(int x) { switch (x) { case 'a': case 97: case 1: case 4294967297: \
+                 default: ; default: ; } }"
+            ),
+            Err(vec![
+                "Duplicate `case` value 97".to_owned(),
+                "Duplicate `case` value 1".to_owned(),
+                "Multiple `default` labels in one `switch`".to_owned(),
+            ])
+        );
+    }
+
+    #[test]
+    fn return_values() {
+        let parse = |function: &str| {
+            let decls = "struct S { int a; } s; void g(void); struct S h(void) { return s; }";
+            parse_scoped(&format!("{decls} {function}"), |p| {
+                while !p.is_empty() {
+                    p.parse_decl()?;
+                }
+                Some(())
+            })
+        };
+        assert_eq!(parse("int f(void) { return 'a'; }"), Ok(()));
+        assert_eq!(
+            parse("int f(void) { return g(); }"),
+            Err(vec![
+                "Cannot convert a value of type `void` to `int`".to_owned()
+            ])
+        );
+        assert_eq!(
+            parse("int f(void) { return h(); }"),
+            Err(vec![
+                "Cannot convert a value of type `struct S` to `int`".to_owned()
+            ])
+        );
+        assert_eq!(
+            parse("void f(void) { return 1; }"),
+            Err(vec![
+                "A function returning `void` can't return a value".to_owned()
+            ])
+        );
+    }
+
+    #[test]
+    fn spans() {
+        let text = "while (i < 10)\n    i = i + (1);";
+        let stmt = parse_with(text, |p| p.parse_stmt()).unwrap();
+        let source = SourceFile::new("test.c".to_owned(), text.to_owned());
+        let mut tree = String::new();
+        let mut ctx = TreeCtx::new().with_spans(source);
+        stmt.fmt_tree(&mut ctx, &mut tree).unwrap();
+        assert_eq!(
+            tree,
+            r#"While @ 1:1-2:16
+├──Cond
+│  └──< @ 1:8-1:13
+│     ├──Ident @ 1:8-1:8
+│     │  └──"i"
+│     └──Literal @ 1:12-1:13
+│        └──Int
+│           └──10
+└──Body
+   └──Assignment @ 2:5-2:15
+      ├──Target
+      │  └──Ident @ 2:5-2:5
+      │     └──"i"
+      └──Value
+         └──+ @ 2:9-2:15
+            ├──Ident @ 2:9-2:9
+            │  └──"i"
+            └──Literal @ 2:13-2:15
+               └──Int
+                  └──1
+"#
+        );
+    }
+}
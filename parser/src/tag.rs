@@ -0,0 +1,480 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use ast::{Enumerator, Member, ScalarType, Tag, TagKind, Type, TypeDecl};
+use diagnostics::ErrorComponent;
+use lexer::Token;
+use source::Span;
+use syntax::SyntaxKind;
+use utils::Spanned;
+
+use crate::{
+    Parser, basic_ops::Found, constant::is_integer, decl::DeclaratorKind, expr::BindingPower,
+    recovery::SyncSet, scope::Symbol,
+};
+
+use super::SToken;
+
+impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s, Tokens> {
+    pub(crate) fn tag_kind(t: &Token<'_>) -> Option<TagKind> {
+        match t {
+            Token::Struct => Some(TagKind::Struct),
+            Token::Union => Some(TagKind::Union),
+            Token::Enum => Some(TagKind::Enum),
+            _ => None,
+        }
+    }
+    /// Whether `ty` is a complete object type, whose size is known.
+    pub(crate) fn is_complete(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Scalar(ScalarType::Void) | Type::Function(_) | Type::Array(_, None) => false,
+            Type::Scalar(_) | Type::Pointer(_) => true,
+            Type::Array(elem, Some(_)) => self.is_complete(elem),
+            Type::Tagged(tag) => self.scopes.is_complete(tag),
+            Type::Qualified(_, ty) => self.is_complete(ty),
+        }
+    }
+    /// Parses a struct, union or enum specifier after its keyword, which starts at `start`,
+    /// returning the tag it names.
+    ///
+    /// The definition or declaration of the tag is left in the pending type declarations, to
+    /// be placed before the item that contains it.
+    pub(crate) fn parse_tag_specifier(&mut self, kind: TagKind, start: usize) -> Option<Tag> {
+        let (next, span) = self.peek_next_split();
+        let name = match next.cloned() {
+            Some(Token::Ident(name)) => {
+                _ = self.advance();
+                Some(name)
+            }
+            Some(Token::LBrace) => None,
+            t => {
+                let msg = format!(
+                    "Expected an identifier or `{{` after `{kind}`, found {}",
+                    Found(t.as_ref())
+                );
+                self.new_parse_error(span, msg);
+                return None;
+            }
+        };
+        let defines = self.check_eq(&Token::LBrace);
+        // `struct S;` declares a new tag, hiding any tag `S` from an outer scope
+        let declares = defines || (kind != TagKind::Enum && self.check_eq(&Token::Semicolon));
+        let tag = match name.map(|name| (name, self.scopes.lookup_tag(name, declares).cloned())) {
+            None => self.scopes.new_tag(kind, None),
+            Some((name, Some(tag))) => {
+                if tag.kind != kind {
+                    let msg = format!(
+                        "`{name}` was previously declared as {} {}",
+                        article(tag.kind),
+                        tag.kind
+                    );
+                    self.new_parse_error(span, msg);
+                    return None;
+                }
+                tag.clone()
+            }
+            Some((name, None)) => {
+                if kind == TagKind::Enum && !defines {
+                    self.new_parse_error(span, format!("Use of undefined `enum {name}`"));
+                    return None;
+                }
+                let tag = self.scopes.new_tag(kind, Some(name));
+                if !defines {
+                    let decl = TypeDecl::Record {
+                        tag: tag.clone(),
+                        members: None,
+                    };
+                    let span = self.node_from(SyntaxKind::TypeDeclaration, start);
+                    self.type_decls.push(Spanned::new(decl, span));
+                }
+                tag
+            }
+        };
+        if defines {
+            _ = self.advance();
+            if self.scopes.is_complete(&tag) {
+                self.new_parse_error(span, format!("Redefinition of `{tag}`"));
+            }
+            let decl = if kind == TagKind::Enum {
+                let enumerators = self.parse_enumerators()?;
+                TypeDecl::Enum {
+                    tag: tag.clone(),
+                    enumerators,
+                }
+            } else {
+                let members = self.parse_members()?;
+                self.scopes.set_members(&tag, &members);
+                let members = Some(members);
+                TypeDecl::Record {
+                    tag: tag.clone(),
+                    members,
+                }
+            };
+            // The tag is only complete after its closing brace
+            self.scopes.define_tag(&tag);
+            let span = self.node_from(SyntaxKind::TypeDeclaration, start);
+            self.type_decls.push(Spanned::new(decl, span));
+        }
+        Some(tag)
+    }
+    /// Parses the member declarations of a struct or union, after the opening brace.
+    fn parse_members(&mut self) -> Option<Vec<Spanned<Member<'s>>>> {
+        let mut members: Vec<Spanned<Member<'s>>> = Vec::new();
+        while !self.consume_if_eq(&Token::RBrace) {
+            let start = self.peek_next_split().1.start;
+            if self.parse_member_declaration(&mut members).is_none() {
+                if self.is_empty() {
+                    return None;
+                }
+                self.recover(SyncSet::Member, start);
+            }
+        }
+        Some(members)
+    }
+    /// Parses a single member declaration, which may declare several members.
+    fn parse_member_declaration(&mut self, members: &mut Vec<Spanned<Member<'s>>>) -> Option<()> {
+        let span = self.peek_next_split().1;
+        if !self.starts_declaration() {
+            if !self.unknown_type_name() {
+                let t = self.peek_next().map(|t| &t.inner);
+                let msg = format!("Expected a member declaration, found {}", Found(t));
+                self.new_parse_error(span, msg);
+            }
+            return None;
+        }
+        let (align, base) = self.parse_member_specifiers()?;
+        if self.consume_if_eq(&Token::Semicolon) {
+            // Only anonymous structs and unions can be members without a declarator
+            match base.unqualified() {
+                Type::Tagged(Tag {
+                    kind: TagKind::Struct | TagKind::Union,
+                    name: None,
+                    ..
+                }) => {
+                    let align = self.check_alignment(align.as_ref(), &base);
+                    let member = Member {
+                        name: None,
+                        ty: base,
+                        align,
+                    };
+                    let span = self.node_from(SyntaxKind::Member, span.start);
+                    members.push(Spanned::new(member, span));
+                }
+                _ => {
+                    let msg = "Member declaration does not declare anything".to_string();
+                    self.new_parse_error(span, msg);
+                }
+            }
+            return Some(());
+        }
+        // The first member's span includes the specifiers
+        let mut start = span.start;
+        loop {
+            // A bit-field without a declarator only pads the record
+            let declarator = if self.check(|t| *t == Token::Colon) {
+                None
+            } else {
+                Some(self.parse_declarator(&base, DeclaratorKind::Named)?)
+            };
+            let colon = self.peek_next_split().1;
+            if self.consume_if_eq(&Token::Colon) {
+                let (name, ty) = match declarator {
+                    Some(declarator) => (declarator.name, declarator.ty),
+                    None => (None, base.clone()),
+                };
+                let (name, span) = name.map_or((None, colon), |(name, span)| (Some(name), span));
+                self.parse_bit_field(name, span, &ty, start)?;
+                if !self.consume_if_eq(&Token::Comma) {
+                    break;
+                }
+                start = self.peek_next_split().1.start;
+                continue;
+            }
+            let declarator = declarator.expect("members without a declarator are bit-fields");
+            let (name, span) = declarator.name.expect("named declarators have a name");
+            let ty = declarator.ty;
+            if matches!(ty, Type::Function(_)) {
+                let msg = format!("Member `{name}` cannot have a function type");
+                self.new_parse_error(span, msg);
+            } else if !self.is_complete(&ty) {
+                let msg = format!("Member `{name}` has incomplete type `{ty}`");
+                self.new_parse_error(span, msg);
+            } else if members.iter().any(|member| member.inner.name == Some(name)) {
+                self.new_parse_error(span, format!("Duplicate member `{name}`"));
+            }
+            let align = self.check_alignment(align.as_ref(), &ty);
+            let member = Member {
+                name: Some(name),
+                ty,
+                align,
+            };
+            let span = self.node_from(SyntaxKind::Member, start);
+            members.push(Spanned::new(member, span));
+            if !self.consume_if_eq(&Token::Comma) {
+                break;
+            }
+            start = self.peek_next_split().1.start;
+        }
+        self.expect(&Token::Semicolon, " after member declaration")?;
+        Some(())
+    }
+    /// Parses the width of a bit-field after the colon and checks it against the type of the
+    /// member, which is named at `span` unless it's unnamed (C17 6.7.2.1p4). Bit-fields that are
+    /// valid are reported as unsupported, from `start` to the end of the width.
+    fn parse_bit_field(
+        &mut self,
+        name: Option<&'s str>,
+        span: Span,
+        ty: &Type,
+        start: usize,
+    ) -> Option<()> {
+        let width = self.parse_expr(BindingPower::Assign)?;
+        let bit_field = match name {
+            Some(name) => format!("bit-field `{name}`"),
+            None => "an unnamed bit-field".to_owned(),
+        };
+        let message = || format!("The width of {bit_field} must be an integer constant");
+        let value = self.eval_integer(&width, message);
+        // `_Bool` is the only integer type with padding bits
+        let bits = if !is_integer(ty) {
+            let msg = format!("The type `{ty}` of {bit_field} is not an integer type");
+            self.new_parse_error(span, msg);
+            None
+        } else if ty.unqualified() == &Type::Scalar(ScalarType::Bool) {
+            Some(1)
+        } else {
+            self.scopes
+                .layouts()
+                .size_of(ty)
+                .map(|size| 8 * i128::from(size))
+        };
+        let Some(value) = value else {
+            return Some(());
+        };
+        if value < 0 {
+            let msg = format!("The width of {bit_field} is negative");
+            self.new_parse_error(width.as_span(), msg);
+        } else if let Some(bits) = bits
+            && value > bits
+        {
+            let msg = format!("The width of {bit_field} exceeds the width {bits} of `{ty}`");
+            self.new_parse_error(width.as_span(), msg);
+        } else if value == 0 && name.is_some() {
+            let msg = format!("The width of {bit_field} is zero, which only unnamed ones can be");
+            self.new_parse_error(width.as_span(), msg);
+        } else if bits.is_some() {
+            let msg = "Bit-fields are not supported".to_string();
+            self.new_parse_error(self.span_from(start), msg);
+        }
+        Some(())
+    }
+    /// Parses the enumerators of an enum after the opening brace, declaring them as constants
+    /// in the current scope.
+    fn parse_enumerators(&mut self) -> Option<Vec<Spanned<Enumerator<'s>>>> {
+        let mut enumerators = Vec::new();
+        let mut value = 0i64;
+        loop {
+            let span = self.peek_next_split().1;
+            // A trailing comma is allowed, but not an empty list
+            if !enumerators.is_empty() && self.consume_if_eq(&Token::RBrace) {
+                break;
+            }
+            match self.parse_enumerator(value) {
+                Some(enumerator) => {
+                    value = enumerator.value.wrapping_add(1);
+                    let span = self.node_from(SyntaxKind::Enumerator, span.start);
+                    enumerators.push(Spanned::new(enumerator, span));
+                }
+                None => self.recover(SyncSet::Expression, span.start),
+            }
+            if !self.consume_if_eq(&Token::Comma) {
+                if self
+                    .expect(&Token::RBrace, " to close enumerator list")
+                    .is_none()
+                {
+                    // Skip the rest of the list, so the declaration it's in can still be parsed
+                    let start = self.peek_next_split().1.start;
+                    self.recover(SyncSet::BlockClose, start);
+                    if !self.consume_if_eq(&Token::RBrace) {
+                        return None;
+                    }
+                }
+                break;
+            }
+        }
+        Some(enumerators)
+    }
+    /// Parses a single enumerator, whose value is `value` unless it's given explicitly.
+    fn parse_enumerator(&mut self, mut value: i64) -> Option<Enumerator<'s>> {
+        let span = self.peek_next_split().1;
+        let name = self.expect_ident(" in enumerator list")?;
+        let init = if self.consume_if_eq(&Token::Eq) {
+            let init = self.parse_assignment_expression()?;
+            let message = || format!("The value of `{name}` must be an integer constant");
+            if let Some(init_value) = self.eval_integer(&init, message) {
+                // Enumeration constants have type `int`
+                match i32::try_from(init_value) {
+                    Ok(init_value) => value = init_value.into(),
+                    Err(_) => {
+                        let msg = format!("The value of `{name}` doesn't fit in `int`");
+                        self.new_parse_error(init.as_span(), msg);
+                    }
+                }
+            }
+            Some(init)
+        } else {
+            None
+        };
+        // The constant is in scope right after its enumerator, so later ones can use it
+        if self.scopes.declare(name, Symbol::Constant(value)).is_some() {
+            self.new_parse_error(span, format!("Redefinition of `{name}`"));
+        }
+        Some(Enumerator { name, value, init })
+    }
+}
+
+/// The indefinite article to use before a tag kind in messages.
+fn article(kind: TagKind) -> &'static str {
+    match kind {
+        TagKind::Struct | TagKind::Union => "a",
+        TagKind::Enum => "an",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::tests::parse_decls;
+
+    #[test]
+    fn records_and_enums() {
+        assert_eq!(
+            parse_decls(
+                "struct list; struct list { struct list *next; }; enum e { A, B = A + 4, C, };"
+            )
+            .as_deref(),
+            Ok(r#"Type Declaration struct list
+Type Declaration struct list
+└──Member
+   ├──Name
+   │  └──"next"
+   └──Type
+      └──Type: struct list *
+Type Declaration enum e
+├──Enumerator
+│  ├──Name
+│  │  └──"A"
+│  └──Value
+│     └──0
+├──Enumerator
+│  ├──Name
+│  │  └──"B"
+│  ├──Value
+│  │  └──4
+│  └──Init
+│     └──+
+│        ├──Ident
+│        │  └──"A"
+│        └──Literal
+│           └──Int
+│              └──4
+└──Enumerator
+   ├──Name
+   │  └──"C"
+   └──Value
+      └──5
+"#)
+        );
+        // Anonymous members, and tags declared in a block that hide those at file scope
+        assert_eq!(
+            parse_decls(
+                "struct s { union { int a; char b; }; }; \
+                 int f(void) { struct s; struct s { int c; } x; return x.c; }"
+            )
+            .map(|_| ()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn bit_fields() {
+        // Bit-fields are only reported as unsupported once their width has been checked
+        assert_eq!(
+            parse_decls(
+                "enum e { A }; struct s { unsigned a : 3, : 0; enum e b : 1 + 1; }; \
+                 struct t { int c : 33; char d : -1; double e : 2; int f : 0; _Bool g : 2; };"
+            ),
+            Err(vec![
+                "Bit-fields are not supported".to_owned(),
+                "Bit-fields are not supported".to_owned(),
+                "Bit-fields are not supported".to_owned(),
+                "The width of bit-field `c` exceeds the width 32 of `int`".to_owned(),
+                "The width of bit-field `d` is negative".to_owned(),
+                "The type `double` of bit-field `e` is not an integer type".to_owned(),
+                "The width of bit-field `f` is zero, which only unnamed ones can be".to_owned(),
+                "The width of bit-field `g` exceeds the width 1 of `_Bool`".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse_decls("int n; struct s { int a : n; long : 2.0; };"),
+            Err(vec![
+                "The width of bit-field `a` must be an integer constant".to_owned(),
+                "The width of an unnamed bit-field must be an integer constant".to_owned(),
+            ])
+        );
+    }
+
+    #[test]
+    fn tag_errors() {
+        assert_eq!(
+            parse_decls("struct s { int x; char x; };"),
+            Err(vec!["Duplicate member `x`".to_owned()])
+        );
+        assert_eq!(
+            parse_decls("struct s { int x; }; struct s { int y; }; union s *p(void);"),
+            Err(vec![
+                "Redefinition of `struct s`".to_owned(),
+                "`s` was previously declared as a struct".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse_decls("enum e *f(void);"),
+            Err(vec!["Use of undefined `enum e`".to_owned()])
+        );
+        assert_eq!(
+            parse_decls("struct t; struct u { struct t t; }; int f(void) { struct t x; }"),
+            Err(vec![
+                "Member `t` has incomplete type `struct t`".to_owned(),
+                "Variable `x` has incomplete type `struct t`".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse_decls("enum e { A, B = 1 / 0, A }; int f(void) { A = 1; }"),
+            Err(vec![
+                "Division by zero in constant expression".to_owned(),
+                "Redefinition of `A`".to_owned(),
+                "The operand of `=` is not assignable".to_owned(),
+            ])
+        );
+        assert_eq!(
+            parse_decls("struct m { _Alignas(2) long l; _Alignas(16) struct { char c; }; };"),
+            Err(vec![
+                "Alignment 2 is less strict than the alignment 8 of `long`".to_owned()
+            ])
+        );
+    }
+}
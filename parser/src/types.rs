@@ -0,0 +1,499 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use ast::{FunctionType, Qualifiers, ScalarType, Type};
+use diagnostics::ErrorComponent;
+use lexer::Token;
+use utils::Spanned;
+
+use crate::{Parser, basic_ops::Found, decl::DeclaratorKind};
+
+use super::SToken;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Base {
+    Void,
+    Bool,
+    Char,
+    Int,
+    Float,
+    Double,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Size {
+    Short,
+    Long,
+    LongLong,
+}
+
+/// The type specifiers of a declaration, which may appear in any order (C17 6.7.2).
+#[derive(Debug, Default)]
+struct Specifiers {
+    base: Option<Base>,
+    size: Option<Size>,
+    unsigned: Option<bool>,
+    /// A struct, union or enum specifier or a typedef name, which can't be combined with any
+    /// other
+    named: Option<Type>,
+}
+
+/// The first function specifier of a declaration, `inline` or `_Noreturn`, along with its span.
+/// Neither changes the code generated for the function.
+pub(crate) type FunctionSpecifier = Option<Spanned<&'static str>>;
+
+/// The strictest alignment `_Alignas` can give, which is the same as GCC's.
+const MAX_ALIGN: i128 = 1 << 28;
+
+/// The strictest alignment given by the `_Alignas` specifiers of a declaration, along with the
+/// span of the specifier that gave it.
+pub(crate) type Alignment = Option<Spanned<u64>>;
+
+/// What a sequence of specifiers can contain besides type specifiers and qualifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SpecifierKind {
+    /// A type name or parameter declaration, which can't contain either
+    TypeName,
+    /// A member declaration, which can contain alignment specifiers
+    Member,
+    /// A declaration, which can also contain a storage class and function specifiers
+    Declaration,
+}
+
+/// The storage-class specifiers of C17 6.7.1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StorageClass {
+    Typedef,
+    Extern,
+    Static,
+    Auto,
+    Register,
+}
+
+impl StorageClass {
+    /// The storage class as recorded in the tree, where only those affecting linkage or
+    /// storage duration are kept.
+    pub(crate) const fn to_ast(this: Option<Self>) -> Option<ast::StorageClass> {
+        match this {
+            Some(Self::Static) => Some(ast::StorageClass::Static),
+            Some(Self::Extern) => Some(ast::StorageClass::Extern),
+            _ => None,
+        }
+    }
+}
+
+impl Specifiers {
+    /// Adds a specifier, returning an error message if it can't be combined with the previous
+    /// ones.
+    fn add(&mut self, token: &Token<'_>) -> Result<(), String> {
+        let base = match token {
+            Token::Void => Base::Void,
+            Token::Bool => Base::Bool,
+            Token::Char => Base::Char,
+            Token::Int => Base::Int,
+            Token::Float => Base::Float,
+            Token::Double => Base::Double,
+            Token::Short | Token::Long => {
+                self.size = match (self.size, token) {
+                    (None, Token::Short) => Some(Size::Short),
+                    (None, _) => Some(Size::Long),
+                    (Some(Size::Long), Token::Long) => Some(Size::LongLong),
+                    (Some(Size::LongLong), Token::Long) => {
+                        return Err("`long long long` is too long".to_string());
+                    }
+                    _ => return Err(format!("Cannot combine `{token}` with the previous size")),
+                };
+                return Ok(());
+            }
+            Token::Signed | Token::Unsigned => {
+                let unsigned = *token == Token::Unsigned;
+                match self.unsigned {
+                    None => self.unsigned = Some(unsigned),
+                    Some(prev) if prev == unsigned => return Err(format!("Duplicate `{token}`")),
+                    Some(_) => return Err("Cannot combine `signed` and `unsigned`".to_string()),
+                }
+                return Ok(());
+            }
+            _ => unreachable!(),
+        };
+        if self.base.is_some() || self.named.is_some() {
+            return Err(format!(
+                "Cannot combine `{token}` with the previous type specifier"
+            ));
+        }
+        self.base = Some(base);
+        Ok(())
+    }
+    /// Adds a struct, union or enum specifier or a typedef name, naming the type `ty`.
+    fn add_named(&mut self, token: &Token<'_>, ty: Type) -> Result<(), String> {
+        if self.base.is_some() || self.named.is_some() {
+            return Err(format!(
+                "Cannot combine `{token}` with the previous type specifier"
+            ));
+        }
+        self.named = Some(ty);
+        Ok(())
+    }
+    /// Whether no type specifier has been added yet. Only then can an identifier be a typedef
+    /// name, so that `T T;` declares a variable `T` of the type `T`.
+    fn is_empty(&self) -> bool {
+        self.base.is_none()
+            && self.size.is_none()
+            && self.unsigned.is_none()
+            && self.named.is_none()
+    }
+    fn resolve(&self) -> Option<Type> {
+        use ScalarType as S;
+        let Self {
+            base,
+            size,
+            unsigned,
+            ref named,
+        } = *self;
+        if let Some(ty) = named {
+            return (size.is_none() && unsigned.is_none()).then(|| ty.clone());
+        }
+        Some(Type::Scalar(match (base, size, unsigned) {
+            (Some(Base::Void), None, None) => S::Void,
+            (Some(Base::Bool), None, None) => S::Bool,
+            (Some(Base::Char), None, None) => S::Char,
+            (Some(Base::Char), None, Some(false)) => S::SChar,
+            (Some(Base::Char), None, Some(true)) => S::UChar,
+            (None | Some(Base::Int), Some(Size::Short), Some(true)) => S::UShort,
+            (None | Some(Base::Int), Some(Size::Short), _) => S::Short,
+            (None | Some(Base::Int), Some(Size::Long), Some(true)) => S::ULong,
+            (None | Some(Base::Int), Some(Size::Long), _) => S::Long,
+            (None | Some(Base::Int), Some(Size::LongLong), Some(true)) => S::ULongLong,
+            (None | Some(Base::Int), Some(Size::LongLong), _) => S::LongLong,
+            (None | Some(Base::Int), None, Some(true)) => S::UInt,
+            (Some(Base::Int), None, _) | (None, None, Some(false)) => S::Int,
+            (Some(Base::Float), None, None) => S::Float,
+            (Some(Base::Double), None, None) => S::Double,
+            (Some(Base::Double), Some(Size::Long), None) => S::LongDouble,
+            _ => return None,
+        }))
+    }
+}
+
+impl<'s, Tokens: Iterator<Item = Result<SToken<'s>, ErrorComponent>>> Parser<'s, Tokens> {
+    pub(crate) fn is_type_specifier(t: &Token<'_>) -> bool {
+        use Token::*;
+        matches!(
+            t,
+            Void | Bool
+                | Char
+                | Short
+                | Int
+                | Long
+                | Float
+                | Double
+                | Signed
+                | Unsigned
+                | Struct
+                | Union
+                | Enum
+        )
+    }
+    pub(crate) fn is_type_qualifier(t: &Token<'_>) -> bool {
+        use Token::*;
+        matches!(t, Const | Volatile | Restrict | Atomic)
+    }
+    fn storage_class(t: &Token<'_>) -> Option<StorageClass> {
+        match t {
+            Token::Typedef => Some(StorageClass::Typedef),
+            Token::Extern => Some(StorageClass::Extern),
+            Token::Static => Some(StorageClass::Static),
+            Token::Auto => Some(StorageClass::Auto),
+            Token::Register => Some(StorageClass::Register),
+            _ => None,
+        }
+    }
+    /// The keyword of a function specifier (C17 6.7.4).
+    fn function_specifier(t: &Token<'_>) -> Option<&'static str> {
+        match t {
+            Token::Inline => Some("inline"),
+            Token::Noreturn => Some("_Noreturn"),
+            _ => None,
+        }
+    }
+    /// Whether this token is a keyword that can be a declaration specifier.
+    pub(crate) fn is_specifier_keyword(t: &Token<'_>) -> bool {
+        Self::is_type_specifier(t)
+            || Self::is_type_qualifier(t)
+            || Self::storage_class(t).is_some()
+            || Self::function_specifier(t).is_some()
+            || *t == Token::Alignas
+    }
+    /// Whether this token is a typedef name in the current scope.
+    fn is_typedef_name(&self, t: &Token<'_>) -> bool {
+        matches!(t, Token::Ident(name) if self.scopes.lookup_typedef(name).is_some())
+    }
+    /// Whether a declaration, and so also a type name, starts with this token. Identifiers
+    /// only do if they are typedef names, which is what tells `T * x;` from a multiplication.
+    pub(crate) fn is_declaration_specifier(&self, t: &Token<'_>) -> bool {
+        Self::is_specifier_keyword(t) || self.is_typedef_name(t)
+    }
+    /// Whether the next token starts a declaration.
+    pub(crate) fn starts_declaration(&mut self) -> bool {
+        let next = self.peek_next().map(|t| t.inner.clone());
+        next.is_some_and(|t| self.is_declaration_specifier(&t))
+    }
+    /// Reports the next token as an unknown type name if it is an identifier followed by
+    /// another, which can only be a declaration. Returns whether it was reported.
+    pub(crate) fn unknown_type_name(&mut self) -> bool {
+        let Some((Token::Ident(name), span)) =
+            self.peek(0).map(|t| (t.inner.clone(), t.span.clone()))
+        else {
+            return false;
+        };
+        if !matches!(self.peek(1).map(|t| &t.inner), Some(Token::Ident(_))) {
+            return false;
+        }
+        self.new_parse_error(span, format!("Unknown type name `{name}`"));
+        true
+    }
+    /// Whether the token `idx` tokens ahead starts a type name, as in a cast or `sizeof`.
+    pub(crate) fn starts_type_name(&mut self, idx: usize) -> bool {
+        let token = self.peek(idx).map(|t| t.inner.clone());
+        token.is_some_and(|t| self.is_declaration_specifier(&t))
+    }
+    /// Adds a type qualifier token to `qualifiers`. Repeating a qualifier has no effect.
+    fn add_qualifier(qualifiers: &mut Qualifiers, token: &Token<'_>) {
+        match token {
+            Token::Const => qualifiers.is_const = true,
+            Token::Volatile => qualifiers.is_volatile = true,
+            Token::Restrict => qualifiers.is_restrict = true,
+            Token::Atomic => qualifiers.is_atomic = true,
+            _ => unreachable!(),
+        }
+    }
+    /// Parses a possibly empty sequence of type qualifiers, as found after a `*`.
+    pub(crate) fn parse_qualifiers(&mut self) -> Qualifiers {
+        let mut qualifiers = Qualifiers::default();
+        use Token::{Atomic, Const, Restrict, Volatile};
+        while let Some(token) = self.advance_if_any(&[Const, Volatile, Restrict, Atomic]) {
+            Self::add_qualifier(&mut qualifiers, &token.inner);
+        }
+        qualifiers
+    }
+    /// Parses the operand of `_Alignas` along with its parentheses, returning the alignment it
+    /// gives or 0 if it doesn't give one.
+    fn parse_alignment_specifier(&mut self) -> Option<u64> {
+        self.expect(&Token::LParen, " after `_Alignas`")?;
+        let start = self.peek_next_split().1.start;
+        let align = if self.starts_type_name(0) {
+            let ty = self.parse_type_name()?;
+            let span = self.span_from(start);
+            self.check_measurable(&ty, &Token::Alignas, span);
+            self.scopes.layouts().align_of(&ty).unwrap_or(0)
+        } else {
+            let expr = self.parse_assignment_expression()?;
+            let message = || "The operand of `_Alignas` must be an integer constant".to_string();
+            match self.eval_integer(&expr, message) {
+                Some(0) | None => 0,
+                Some(value) if value > MAX_ALIGN => {
+                    let msg = format!("Alignment {value} is too large");
+                    self.new_parse_error(expr.as_span(), msg);
+                    0
+                }
+                Some(value) if value < 0 || !value.cast_unsigned().is_power_of_two() => {
+                    let msg = format!("Alignment {value} is not a power of two");
+                    self.new_parse_error(expr.as_span(), msg);
+                    0
+                }
+                Some(value) => value.try_into().unwrap(),
+            }
+        };
+        self.expect(&Token::RParen, " to close `_Alignas`")?;
+        Some(align)
+    }
+    /// Parses a sequence of type specifiers and qualifiers into the type they name.
+    pub(crate) fn parse_specifiers(&mut self) -> Option<Type> {
+        Some(self.parse_specifiers_impl(SpecifierKind::TypeName)?.3)
+    }
+    /// Parses the specifiers of a member declaration, which may include alignment specifiers,
+    /// into the alignment they give and the type they name.
+    pub(crate) fn parse_member_specifiers(&mut self) -> Option<(Alignment, Type)> {
+        let (_, _, align, ty) = self.parse_specifiers_impl(SpecifierKind::Member)?;
+        Some((align, ty))
+    }
+    /// Parses the specifiers of a declaration, which may include a storage class, function
+    /// specifiers and alignment specifiers, into the storage class, the first function
+    /// specifier, the alignment and the type they name.
+    pub(crate) fn parse_declaration_specifiers(
+        &mut self,
+    ) -> Option<(Option<StorageClass>, FunctionSpecifier, Alignment, Type)> {
+        self.parse_specifiers_impl(SpecifierKind::Declaration)
+    }
+    fn parse_specifiers_impl(
+        &mut self,
+        kind: SpecifierKind,
+    ) -> Option<(Option<StorageClass>, FunctionSpecifier, Alignment, Type)> {
+        let start = self.peek_next_span().unwrap_or_else(|| self.end_span());
+        let mut end = start.clone();
+        let mut specifiers = Specifiers::default();
+        let mut qualifiers = Qualifiers::default();
+        let mut storage = None;
+        let mut function: FunctionSpecifier = None;
+        let mut align: Alignment = None;
+        let mut failed = false;
+        while let Some(next) = self.peek_next().map(|t| t.inner.clone()) {
+            let typedef = match next {
+                Token::Ident(name) if specifiers.is_empty() => {
+                    self.scopes.lookup_typedef(name).cloned()
+                }
+                _ => None,
+            };
+            if typedef.is_none() && !Self::is_specifier_keyword(&next) {
+                break;
+            }
+            let token = self.advance().unwrap();
+            let result = if let Some(ty) = typedef {
+                specifiers.add_named(&token.inner, ty)
+            } else if let Some(class) = Self::storage_class(&token.inner) {
+                if kind != SpecifierKind::Declaration {
+                    Err(format!("`{}` is not allowed here", token.inner))
+                } else if storage.is_some() {
+                    Err("Cannot combine multiple storage classes".to_string())
+                } else {
+                    storage = Some(class);
+                    Ok(())
+                }
+            } else if let Some(keyword) = Self::function_specifier(&token.inner) {
+                if kind != SpecifierKind::Declaration {
+                    Err(format!("`{keyword}` is not allowed here"))
+                } else {
+                    // Repeating a function specifier has no effect
+                    function.get_or_insert(Spanned::new(keyword, token.span.clone()));
+                    Ok(())
+                }
+            } else if token.inner == Token::Alignas {
+                let value = self.parse_alignment_specifier()?;
+                let span = self.span_from(token.span.start);
+                if kind == SpecifierKind::TypeName {
+                    Err(format!("`{}` is not allowed here", token.inner))
+                } else {
+                    // The strictest of several alignment specifiers applies
+                    if value > align.as_ref().map_or(0, |align| align.inner) {
+                        align = Some(Spanned::new(value, span));
+                    }
+                    Ok(())
+                }
+            } else if Self::is_type_qualifier(&token.inner) {
+                Self::add_qualifier(&mut qualifiers, &token.inner);
+                Ok(())
+            } else if let Some(kind) = Self::tag_kind(&token.inner) {
+                let tag = self.parse_tag_specifier(kind, token.span.start)?;
+                specifiers.add_named(&token.inner, Type::Tagged(tag))
+            } else {
+                specifiers.add(&token.inner)
+            };
+            if let Err(msg) = result {
+                self.new_parse_error(token.span.clone(), msg);
+                failed = true;
+            }
+            end = token.span;
+        }
+        if failed {
+            return None;
+        }
+        let Some(ty) = specifiers.resolve() else {
+            // A typedef name is the only thing an identifier followed by a declarator can be here
+            if specifiers.is_empty()
+                && let Some(SToken {
+                    inner: Token::Ident(name),
+                    span,
+                }) = self.peek_next().cloned()
+                && self
+                    .peek(1)
+                    .is_some_and(|t| matches!(t.inner, Token::Ident(_) | Token::Star))
+            {
+                self.new_parse_error(span, format!("Unknown type name `{name}`"));
+                return None;
+            }
+            if specifiers.is_empty() {
+                let (next, span) = self.peek_next_split();
+                let msg = format!("Expected a type specifier, found {}", Found(next));
+                self.new_parse_error(span, msg);
+                return None;
+            }
+            let msg = "Invalid combination of type specifiers".to_string();
+            self.new_parse_error(start.start..end.end, msg);
+            return None;
+        };
+        Some((storage, function, align, qualify(ty, qualifiers)))
+    }
+    /// Parses a type name, as used in casts and `sizeof`.
+    pub(crate) fn parse_type_name(&mut self) -> Option<Type> {
+        let base = self.parse_specifiers()?;
+        Some(self.parse_declarator(&base, DeclaratorKind::Abstract)?.ty)
+    }
+}
+
+/// Applies qualifiers to a type. Qualifying an array type, which can only be named by a typedef
+/// name, qualifies its elements instead (C17 6.7.3).
+fn qualify(ty: Type, qualifiers: Qualifiers) -> Type {
+    match ty {
+        Type::Array(elem, len) => Type::Array(Box::new(qualify(*elem, qualifiers)), len),
+        ty => ty.qualified(qualifiers),
+    }
+}
+
+/// The composite of two compatible types, or [None] if they aren't compatible (C17 6.2.7). An
+/// array of unknown length is completed by one of known length, and a function without a
+/// prototype by one with.
+pub(crate) fn composite(a: &Type, b: &Type) -> Option<Type> {
+    Some(match (a, b) {
+        (Type::Pointer(a), Type::Pointer(b)) => Type::Pointer(Box::new(composite(a, b)?)),
+        (Type::Array(a, a_len), Type::Array(b, b_len)) => {
+            if a_len.is_some() && b_len.is_some() && a_len != b_len {
+                return None;
+            }
+            Type::Array(Box::new(composite(a, b)?), a_len.or(*b_len))
+        }
+        (Type::Function(a), Type::Function(b)) => {
+            let params = match (&a.params, &b.params) {
+                (Some(a_params), Some(b_params)) => {
+                    if a_params.len() != b_params.len() || a.variadic != b.variadic {
+                        return None;
+                    }
+                    // The qualifiers of parameters don't affect the type of the function
+                    let params = a_params.iter().zip(b_params);
+                    let params = params.map(|(a, b)| composite(a.unqualified(), b.unqualified()));
+                    Some(params.collect::<Option<_>>()?)
+                }
+                (params, None) | (None, params) => params.clone(),
+            };
+            let variadic = if a.params.is_some() {
+                a.variadic
+            } else {
+                b.variadic
+            };
+            Type::Function(Box::new(FunctionType {
+                ret: composite(&a.ret, &b.ret)?,
+                params,
+                variadic,
+            }))
+        }
+        (Type::Qualified(a_qualifiers, a), Type::Qualified(b_qualifiers, b))
+            if a_qualifiers == b_qualifiers =>
+        {
+            Type::Qualified(*a_qualifiers, Box::new(composite(a, b)?))
+        }
+        (a, b) if a == b => a.clone(),
+        _ => return None,
+    })
+}
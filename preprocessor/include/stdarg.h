@@ -0,0 +1,40 @@
+/*
+ * LICENSE NOTICE START
+ * This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+ * Copyright (C) 2026 CordlessCoder
+ *
+ * CCcc is free software: you can redistribute it and/or modify it under the terms
+ * of the GNU General Public License as published by the Free Software Foundation,
+ * either version 3 of the License, or (at your option) any later version.
+ *
+ * CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+ * without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+ * PURPOSE. See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along with CCcc.
+ * If not, see <https://www.gnu.org/licenses/>.
+ * LICENSE NOTICE END
+ */
+
+#ifndef __CCCC_STDARG_H
+#define __CCCC_STDARG_H
+
+/* The x86-64 System V definition, which GCC and Clang build in */
+typedef struct __va_list_tag {
+    unsigned int gp_offset;
+    unsigned int fp_offset;
+    void *overflow_arg_area;
+    void *reg_save_area;
+} __builtin_va_list[1];
+
+typedef __builtin_va_list va_list;
+typedef __builtin_va_list __gnuc_va_list;
+
+#define va_start(ap, ...) __builtin_va_start(ap, 0)
+#define va_arg(ap, type) __builtin_va_arg(ap, type)
+#define va_end(ap) __builtin_va_end(ap)
+#define va_copy(dest, src) __builtin_va_copy(dest, src)
+
+#endif
+
+#undef __need___va_list
@@ -0,0 +1,29 @@
+/*
+ * LICENSE NOTICE START
+ * This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+ * Copyright (C) 2026 CordlessCoder
+ *
+ * CCcc is free software: you can redistribute it and/or modify it under the terms
+ * of the GNU General Public License as published by the Free Software Foundation,
+ * either version 3 of the License, or (at your option) any later version.
+ *
+ * CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+ * without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+ * PURPOSE. See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along with CCcc.
+ * If not, see <https://www.gnu.org/licenses/>.
+ * LICENSE NOTICE END
+ */
+
+#ifndef __CCCC_STDBOOL_H
+#define __CCCC_STDBOOL_H
+
+#if __STDC_VERSION__ < 202311L
+#define bool _Bool
+#define true 1
+#define false 0
+#endif
+#define __bool_true_false_are_defined 1
+
+#endif
@@ -0,0 +1,457 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{MAX_INCLUDE_DEPTH, Preprocessor, expr::Evaluator, headers, token::PpToken};
+
+/// An `#if` group being processed.
+#[derive(Debug)]
+pub(crate) struct Condition {
+    pub directive: &'static str,
+    /// The directive that started the conditional, for diagnostics
+    pub token: PpToken,
+    pub else_seen: bool,
+}
+
+/// Writes tokens back out as text, separated the way they were in the source.
+pub(crate) fn spell(tokens: &[PpToken]) -> String {
+    let mut text = String::new();
+    for (idx, token) in tokens.iter().enumerate() {
+        if idx != 0 && token.space {
+            text.push(' ');
+        }
+        text += &token.text;
+    }
+    text
+}
+
+impl Preprocessor {
+    /// Processes the directive introduced by `hash`.
+    pub(crate) fn directive(&mut self, hash: PpToken) {
+        let mut line = self.rest_of_line();
+        if line.is_empty() {
+            return;
+        }
+        let name = line.remove(0);
+        match &*name.text {
+            "define" => self.define_macro(&name, line),
+            "undef" => self.undefine_macro(&name, &line),
+            "include" => self.include(&name, line, false),
+            "include_next" => self.include(&name, line, true),
+            "if" => {
+                let taken = self.condition(&name, line);
+                self.start_conditional("if", name, taken);
+            }
+            "ifdef" | "ifndef" => {
+                let directive = if name.is("ifdef") { "ifdef" } else { "ifndef" };
+                let taken = self.is_defined(&name, &line) == name.is("ifdef");
+                self.start_conditional(directive, name, taken);
+            }
+            "elif" | "elifdef" | "elifndef" | "else" => {
+                if self.else_directive(&name) {
+                    // The group that just ended was the one taken
+                    self.skip_group(true);
+                }
+            }
+            "endif" => {
+                self.extra_tokens("endif", &line);
+                self.end_conditional(&name);
+            }
+            "line" => self.line_directive(&name, line),
+            // GNU line markers, `# <line> "<file>" <flags>`, as found in preprocessed text
+            _ if name.text.bytes().all(|b| b.is_ascii_digit()) => {
+                line.insert(0, name);
+                self.line_marker(&hash, line);
+            }
+            "error" => {
+                let message = format!("#error {}", spell(&line));
+                self.error(&name, message);
+            }
+            "warning" => {
+                let message = format!("#warning {}", spell(&line));
+                self.warning(&name, message);
+            }
+            "pragma" => self.pragma(&name, line),
+            _ if name.is_ident() => {
+                let message = format!("Invalid preprocessing directive `#{}`", name.text);
+                self.error(&name, message);
+            }
+            _ => {
+                let message = format!("Expected a directive name after `{}`", hash.text);
+                self.error(&name, message);
+            }
+        }
+    }
+
+    pub(crate) fn extra_tokens(&mut self, directive: &str, tokens: &[PpToken]) {
+        if let Some(first) = tokens.first() {
+            self.warning(first, format!("Extra tokens at the end of `#{directive}`"));
+        }
+    }
+
+    fn is_defined(&mut self, directive: &PpToken, line: &[PpToken]) -> bool {
+        let Some(name) = line.first() else {
+            let message = format!("Expected a macro name after `#{}`", directive.text);
+            self.error(directive, message);
+            return false;
+        };
+        if !name.is_ident() {
+            self.error(
+                name,
+                format!("Macro names must be identifiers, found `{}`", name.text),
+            );
+            return false;
+        }
+        self.extra_tokens(&directive.text, &line[1..]);
+        self.macros.contains_key(&name.text)
+    }
+
+    fn start_conditional(&mut self, directive: &'static str, token: PpToken, taken: bool) {
+        let Some(file) = self.stack.last_mut() else {
+            return;
+        };
+        file.conditions.push(Condition {
+            directive,
+            token,
+            else_seen: false,
+        });
+        if !taken {
+            self.skip_group(false);
+        }
+    }
+
+    fn end_conditional(&mut self, directive: &PpToken) {
+        let condition = self.stack.last_mut().and_then(|f| f.conditions.pop());
+        if condition.is_none() {
+            self.error(directive, "`#endif` without `#if`".to_owned());
+        }
+    }
+
+    /// Checks an `#else` or `#elif` against the enclosing conditional, returning whether it may
+    /// start a new group.
+    fn else_directive(&mut self, directive: &PpToken) -> bool {
+        let Some(condition) = self.stack.last_mut().and_then(|f| f.conditions.last_mut()) else {
+            let message = format!("`#{}` without `#if`", directive.text);
+            self.error(directive, message);
+            return false;
+        };
+        if condition.else_seen {
+            let message = format!("`#{}` after `#else`", directive.text);
+            self.error(directive, message);
+            return false;
+        }
+        condition.else_seen = directive.is("else");
+        true
+    }
+
+    /// Skips lines until a group of the current conditional is taken or the conditional ends.
+    ///
+    /// `taken` tells whether an earlier group was already taken, in which case the rest are all
+    /// skipped.
+    fn skip_group(&mut self, mut taken: bool) {
+        let mut depth = 0usize;
+        loop {
+            let Some(file) = self.stack.last_mut() else {
+                return;
+            };
+            // The end of the file is reported as an unterminated conditional once it's popped
+            let Some(token) = file.next(&self.files[file.src]) else {
+                return;
+            };
+            if !(token.line_start && token.is_hash()) {
+                continue;
+            }
+            let mut line = self.rest_of_line();
+            if line.is_empty() {
+                continue;
+            }
+            let name = line.remove(0);
+            match &*name.text {
+                "if" | "ifdef" | "ifndef" => depth += 1,
+                "endif" if depth > 0 => depth -= 1,
+                _ if depth > 0 => (),
+                "endif" => {
+                    self.extra_tokens("endif", &line);
+                    self.end_conditional(&name);
+                    return;
+                }
+                "else" | "elif" | "elifdef" | "elifndef" => {
+                    if !self.else_directive(&name) || taken {
+                        continue;
+                    }
+                    taken = match &*name.text {
+                        "else" => {
+                            self.extra_tokens("else", &line);
+                            true
+                        }
+                        "elif" => self.condition(&name, line),
+                        "elifdef" => self.is_defined(&name, &line),
+                        _ => !self.is_defined(&name, &line),
+                    };
+                    if taken {
+                        return;
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Evaluates the expression of an `#if` or `#elif`.
+    fn condition(&mut self, directive: &PpToken, line: Vec<PpToken>) -> bool {
+        if line.is_empty() {
+            let message = format!("`#{}` with no expression", directive.text);
+            self.error(directive, message);
+            return false;
+        }
+        // `defined` and `__has_include` have to be handled before macro expansion
+        let mut replaced = Vec::new();
+        let mut tokens = line.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            let value = if token.is("defined") {
+                let parenthesized = tokens.next_if(|t| t.is("(")).is_some();
+                let name = tokens.next_if(PpToken::is_ident);
+                if parenthesized && name.is_some() && tokens.next_if(|t| t.is(")")).is_none() {
+                    self.error(&token, "Expected `)` after `defined(name`".to_owned());
+                    return false;
+                }
+                let Some(name) = name else {
+                    self.error(&token, "Expected a macro name after `defined`".to_owned());
+                    return false;
+                };
+                self.macros.contains_key(&name.text)
+            } else if token.is("__has_include") || token.is("__has_include_next") {
+                let mut operand = Vec::new();
+                let mut depth = 0usize;
+                for t in tokens.by_ref() {
+                    depth = match &*t.text {
+                        "(" => depth + 1,
+                        ")" => depth - 1,
+                        _ => depth,
+                    };
+                    operand.push(t);
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                let header = match operand.as_slice() {
+                    [open, inner @ .., close] if open.is("(") && close.is(")") => {
+                        self.header_name(inner.to_vec())
+                    }
+                    _ => None,
+                };
+                let Some((header, angled)) = header else {
+                    let message = format!("Expected a header name in `{}`", token.text);
+                    self.error(&token, message);
+                    return false;
+                };
+                let next = token.is("__has_include_next");
+                self.find_include(&header, angled, next).is_some()
+            } else {
+                replaced.push(token);
+                continue;
+            };
+            replaced.push(token.with_text(if value { "1" } else { "0" }));
+        }
+        let expanded = self.expand_isolated(replaced);
+        match Evaluator::new(&expanded, self.standard).evaluate() {
+            Ok(value) => value.is_true(),
+            Err(err) => {
+                let token = err.token.map_or(directive, |idx| &expanded[idx]).clone();
+                self.error(&token, err.message);
+                false
+            }
+        }
+    }
+
+    /// Parses `"name"` or `<name>`, expanding macros if it is neither. Returns the name and
+    /// whether it was in angle brackets.
+    fn header_name(&mut self, tokens: Vec<PpToken>) -> Option<(String, bool)> {
+        fn parse(tokens: &[PpToken]) -> Option<(String, bool)> {
+            match tokens {
+                [string] if string.text.starts_with('"') && string.is_string() => {
+                    Some((string.text[1..string.text.len() - 1].to_owned(), false))
+                }
+                [open, inner @ .., close] if open.is("<") && close.is(">") => {
+                    Some((spell(inner), true))
+                }
+                _ => None,
+            }
+        }
+        if let Some(header) = parse(&tokens) {
+            return Some(header);
+        }
+        parse(&self.expand_isolated(tokens))
+    }
+
+    /// Searches for an included file, returning its path and the index of the search path it
+    /// was found in.
+    pub(crate) fn find_include(
+        &self,
+        header: &str,
+        angled: bool,
+        next: bool,
+    ) -> Option<(PathBuf, Option<usize>)> {
+        let path = Path::new(header);
+        if path.is_absolute() {
+            return path.is_file().then(|| (path.to_path_buf(), None));
+        }
+        let current = self.stack.last()?;
+        if !angled && !next {
+            let candidate = current.path.parent().unwrap_or(Path::new("")).join(path);
+            if candidate.is_file() {
+                return Some((candidate, None));
+            }
+        }
+        let start = if next {
+            current.search_idx.map_or(0, |idx| idx + 1)
+        } else {
+            0
+        };
+        let builtin = Path::new(headers::BUILTIN_DIR);
+        self.include_paths
+            .iter()
+            .map(PathBuf::as_path)
+            .chain([builtin])
+            .chain(self.system_paths.iter().map(PathBuf::as_path))
+            .enumerate()
+            .skip(start)
+            .map(|(idx, dir)| (dir.join(path), Some(idx)))
+            .find(|(candidate, _)| headers::exists(candidate))
+    }
+
+    fn include(&mut self, directive: &PpToken, line: Vec<PpToken>, next: bool) {
+        let Some((header, angled)) = self.header_name(line) else {
+            let message = format!(
+                "Expected \"FILENAME\" or <FILENAME> after `#{}`",
+                directive.text
+            );
+            self.error(directive, message);
+            return;
+        };
+        let Some((path, search_idx)) = self.find_include(&header, angled, next) else {
+            self.error(directive, format!("Cannot find included file `{header}`"));
+            return;
+        };
+        if self.pragma_once.contains(&headers::canonicalize(&path)) {
+            return;
+        }
+        if self.stack.len() >= MAX_INCLUDE_DEPTH {
+            self.error(directive, "`#include` nested too deeply".to_owned());
+            return;
+        }
+        let text = match headers::read(&path) {
+            Ok(text) => text,
+            Err(err) => {
+                let message = format!("Cannot read `{}`: {err}", path.display());
+                self.error(directive, message);
+                return;
+            }
+        };
+        let emit = self.stack.last().is_some_and(|f| f.emit);
+        let name: Rc<str> = Rc::from(path.to_string_lossy().as_ref());
+        if emit {
+            self.output.marker(1, &name, Some(1));
+        }
+        self.push_file(name, text, path, search_idx, emit);
+        if let Some(file) = self.stack.last_mut() {
+            file.resume_line = directive.pos.line + 1;
+        }
+    }
+
+    fn line_directive(&mut self, directive: &PpToken, line: Vec<PpToken>) {
+        let line = self.expand_isolated(line);
+        let number = line
+            .first()
+            .filter(|t| t.text.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|t| t.text.parse::<usize>().ok());
+        let Some(number) = number else {
+            self.error(directive, "Expected a line number after `#line`".to_owned());
+            return;
+        };
+        let Some(name) = self.line_filename(line.get(1), "`#line`") else {
+            return;
+        };
+        self.extra_tokens("line", line.get(2..).unwrap_or_default());
+        self.set_line(directive, number, name);
+    }
+
+    /// Handles a line marker like `#line`, except that it isn't macro expanded and may end with
+    /// flags, which are only checked since they describe the include stack the line marker was
+    /// written in.
+    fn line_marker(&mut self, hash: &PpToken, line: Vec<PpToken>) {
+        let Ok(number) = line[0].text.parse::<usize>() else {
+            let message = format!("Line number `{}` is too large", line[0].text);
+            self.error(&line[0], message);
+            return;
+        };
+        let Some(name) = self.line_filename(line.get(1), "line marker") else {
+            return;
+        };
+        for flag in line.get(2..).unwrap_or_default() {
+            if !matches!(&*flag.text, "1" | "2" | "3" | "4") {
+                let message = format!("Invalid flag `{}` in line marker", flag.text);
+                self.error(flag, message);
+                return;
+            }
+        }
+        self.set_line(hash, number, name);
+    }
+
+    /// Reads the optional filename of a `#line` directive or line marker, or reports it and
+    /// returns [None] if it isn't a string literal.
+    fn line_filename(&mut self, token: Option<&PpToken>, what: &str) -> Option<Option<Rc<str>>> {
+        match token {
+            Some(t) if t.text.starts_with('"') && t.is_string() => {
+                Some(Some(Rc::from(&t.text[1..t.text.len() - 1])))
+            }
+            Some(t) => {
+                self.error(t, format!("Invalid filename `{}` in {what}", t.text));
+                None
+            }
+            None => Some(None),
+        }
+    }
+
+    /// Makes the line after `directive` line `number` of the current file, renaming the file if
+    /// `name` is given.
+    fn set_line(&mut self, directive: &PpToken, number: usize, name: Option<Rc<str>>) {
+        let Some(file) = self.stack.last_mut() else {
+            return;
+        };
+        let physical = self.files[file.src]
+            .offset_to_pos(directive.span.start)
+            .line()
+            + 1;
+        file.line_delta = number.cast_signed() - physical.cast_signed();
+        if let Some(name) = name {
+            file.name = name;
+        }
+    }
+
+    pub(crate) fn pragma(&mut self, _directive: &PpToken, line: Vec<PpToken>) {
+        // Pragmas we don't know about are ignored
+        if line.first().is_some_and(|t| t.is("once"))
+            && let Some(file) = self.stack.last()
+        {
+            self.pragma_once.insert(headers::canonicalize(&file.path));
+        }
+    }
+}
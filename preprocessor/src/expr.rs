@@ -0,0 +1,283 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! Evaluation of `#if` expressions.
+//!
+//! All arithmetic is done in `intmax_t` or `uintmax_t`, both 64 bits wide here.
+
+use lexer::{Logos, Standard, Token};
+
+use crate::token::PpToken;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Value {
+    bits: u64,
+    unsigned: bool,
+}
+
+impl Value {
+    const fn signed(value: i64) -> Self {
+        Self {
+            bits: value.cast_unsigned(),
+            unsigned: false,
+        }
+    }
+    const fn bool(value: bool) -> Self {
+        Self::signed(value as i64)
+    }
+    pub const fn is_true(self) -> bool {
+        self.bits != 0
+    }
+}
+
+pub(crate) struct ExprError {
+    pub message: String,
+    /// The offending token, [None] if the expression ended early
+    pub token: Option<usize>,
+}
+
+pub(crate) struct Evaluator<'t> {
+    tokens: &'t [PpToken],
+    next: usize,
+    standard: Standard,
+    /// Cleared in operands that aren't evaluated, like the right side of `0 && x`
+    evaluated: bool,
+}
+
+type EvalResult = Result<Value, ExprError>;
+
+const fn binary_precedence(op: &str) -> Option<u8> {
+    Some(match op.as_bytes() {
+        b"," => 1,
+        b"?" => 2,
+        b"||" => 3,
+        b"&&" => 4,
+        b"|" => 5,
+        b"^" => 6,
+        b"&" => 7,
+        b"==" | b"!=" => 8,
+        b"<" | b">" | b"<=" | b">=" => 9,
+        b"<<" | b">>" => 10,
+        b"+" | b"-" => 11,
+        b"*" | b"/" | b"%" => 12,
+        _ => return None,
+    })
+}
+
+impl<'t> Evaluator<'t> {
+    pub const fn new(tokens: &'t [PpToken], standard: Standard) -> Self {
+        Self {
+            tokens,
+            next: 0,
+            standard,
+            evaluated: true,
+        }
+    }
+
+    pub fn evaluate(mut self) -> EvalResult {
+        let value = self.expr(1)?;
+        if let Some(token) = self.tokens.get(self.next) {
+            return Err(self.error(format!("Missing binary operator before `{}`", token.text)));
+        }
+        Ok(value)
+    }
+
+    fn error(&self, message: String) -> ExprError {
+        let token = (self.next < self.tokens.len()).then_some(self.next);
+        ExprError { message, token }
+    }
+
+    fn peek(&self) -> Option<&'t PpToken> {
+        self.tokens.get(self.next)
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), ExprError> {
+        match self.peek() {
+            Some(token) if token.is(text) => {
+                self.next += 1;
+                Ok(())
+            }
+            Some(token) => Err(self.error(format!("Expected `{text}`, found `{}`", token.text))),
+            None => Err(self.error(format!("Expected `{text}` before the end of the line"))),
+        }
+    }
+
+    fn expr(&mut self, min_precedence: u8) -> EvalResult {
+        let mut lhs = self.unary()?;
+        while let Some(op) = self.peek() {
+            let Some(precedence) = binary_precedence(&op.text) else {
+                break;
+            };
+            if precedence < min_precedence {
+                break;
+            }
+            let op_idx = self.next;
+            self.next += 1;
+            let evaluated = self.evaluated;
+            lhs = match &*op.text {
+                "?" => {
+                    self.evaluated = evaluated && lhs.is_true();
+                    let then = self.expr(1)?;
+                    self.expect(":")?;
+                    self.evaluated = evaluated && !lhs.is_true();
+                    let otherwise = self.expr(precedence)?;
+                    let value = if lhs.is_true() { then } else { otherwise };
+                    Value {
+                        unsigned: then.unsigned || otherwise.unsigned,
+                        ..value
+                    }
+                }
+                "&&" | "||" => {
+                    let short_circuits = lhs.is_true() == (&*op.text == "||");
+                    self.evaluated = evaluated && !short_circuits;
+                    let rhs = self.expr(precedence + 1)?;
+                    Value::bool(if short_circuits {
+                        lhs.is_true()
+                    } else {
+                        rhs.is_true()
+                    })
+                }
+                _ => {
+                    let rhs = self.expr(precedence + 1)?;
+                    self.binary(op_idx, lhs, rhs)?
+                }
+            };
+            self.evaluated = evaluated;
+        }
+        Ok(lhs)
+    }
+
+    fn binary(&self, op_idx: usize, lhs: Value, rhs: Value) -> EvalResult {
+        let op = &*self.tokens[op_idx].text;
+        let unsigned = lhs.unsigned || rhs.unsigned;
+        let (l, r) = (lhs.bits, rhs.bits);
+        let (sl, sr) = (l.cast_signed(), r.cast_signed());
+        let bits = match op {
+            "," => return Ok(rhs),
+            "*" => l.wrapping_mul(r),
+            "/" | "%" if r == 0 => {
+                if self.evaluated {
+                    let message = "Division by zero in preprocessor expression".to_owned();
+                    return Err(ExprError {
+                        message,
+                        token: Some(op_idx),
+                    });
+                }
+                0
+            }
+            "/" if unsigned => l / r,
+            "/" => sl.wrapping_div(sr).cast_unsigned(),
+            "%" if unsigned => l % r,
+            "%" => sl.wrapping_rem(sr).cast_unsigned(),
+            "+" => l.wrapping_add(r),
+            "-" => l.wrapping_sub(r),
+            // Shifts take the type of the left operand
+            "<<" => l
+                .checked_shl(u32::try_from(r).unwrap_or(u32::MAX))
+                .unwrap_or(0),
+            ">>" if lhs.unsigned => l
+                .checked_shr(u32::try_from(r).unwrap_or(u32::MAX))
+                .unwrap_or(0),
+            ">>" => {
+                let shift = u32::try_from(r).unwrap_or(u32::MAX).min(63);
+                (sl >> shift).cast_unsigned()
+            }
+            "&" => l & r,
+            "^" => l ^ r,
+            "|" => l | r,
+            _ => {
+                let result = match op {
+                    "==" => l == r,
+                    "!=" => l != r,
+                    "<" if unsigned => l < r,
+                    ">" if unsigned => l > r,
+                    "<=" if unsigned => l <= r,
+                    ">=" if unsigned => l >= r,
+                    "<" => sl < sr,
+                    ">" => sl > sr,
+                    "<=" => sl <= sr,
+                    ">=" => sl >= sr,
+                    _ => unreachable!("{op} is not a binary operator"),
+                };
+                return Ok(Value::bool(result));
+            }
+        };
+        let unsigned = if matches!(op, "<<" | ">>") {
+            lhs.unsigned
+        } else {
+            unsigned
+        };
+        Ok(Value { bits, unsigned })
+    }
+
+    fn unary(&mut self) -> EvalResult {
+        let Some(token) = self.peek() else {
+            return Err(self.error("Expected an expression before the end of the line".to_owned()));
+        };
+        self.next += 1;
+        match &*token.text {
+            "+" => self.unary(),
+            "-" => {
+                let value = self.unary()?;
+                Ok(Value {
+                    bits: value.bits.wrapping_neg(),
+                    ..value
+                })
+            }
+            "~" => {
+                let value = self.unary()?;
+                Ok(Value {
+                    bits: !value.bits,
+                    ..value
+                })
+            }
+            "!" => Ok(Value::bool(!self.unary()?.is_true())),
+            "(" => {
+                let value = self.expr(1)?;
+                self.expect(")")?;
+                Ok(value)
+            }
+            "true" if self.standard >= Standard::C23 => Ok(Value::bool(true)),
+            // Identifiers left after macro expansion evaluate to 0
+            _ if token.is_ident() => Ok(Value::signed(0)),
+            _ => {
+                self.next -= 1;
+                self.constant(token)
+            }
+        }
+    }
+
+    fn constant(&mut self, token: &PpToken) -> EvalResult {
+        let value = match Token::lexer(&token.text).next() {
+            Some(Ok(Token::IntLit(int))) => Value {
+                bits: int.value,
+                unsigned: !int.kind.is_signed(),
+            },
+            Some(Ok(Token::CharLit(c))) => Value::signed(c.value),
+            Some(Ok(Token::FloatLit(_))) => {
+                let message = "Floating constant in preprocessor expression".to_owned();
+                return Err(self.error(message));
+            }
+            Some(Err(err)) => return Err(self.error(err.to_string())),
+            _ => {
+                let message = format!("Unexpected `{}` in preprocessor expression", token.text);
+                return Err(self.error(message));
+            }
+        };
+        self.next += 1;
+        Ok(value)
+    }
+}
@@ -0,0 +1,91 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! The freestanding headers, which come with the compiler rather than the C library.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// The directory the headers appear to be in.
+pub(crate) const BUILTIN_DIR: &str = "<built-in>";
+
+const HEADERS: &[(&str, &str)] = &[
+    ("float.h", include_str!("../include/float.h")),
+    ("iso646.h", include_str!("../include/iso646.h")),
+    ("stdalign.h", include_str!("../include/stdalign.h")),
+    ("stdarg.h", include_str!("../include/stdarg.h")),
+    ("stdbool.h", include_str!("../include/stdbool.h")),
+    ("stddef.h", include_str!("../include/stddef.h")),
+    ("stdnoreturn.h", include_str!("../include/stdnoreturn.h")),
+];
+
+fn builtin(path: &Path) -> Option<&'static str> {
+    let name = path.strip_prefix(BUILTIN_DIR).ok()?;
+    HEADERS
+        .iter()
+        .find(|(header, _)| Path::new(header) == name)
+        .map(|(_, text)| *text)
+}
+
+pub(crate) fn exists(path: &Path) -> bool {
+    builtin(path).is_some() || path.is_file()
+}
+
+pub(crate) fn read(path: &Path) -> io::Result<String> {
+    match builtin(path) {
+        Some(text) => Ok(text.to_owned()),
+        None => std::fs::read_to_string(path),
+    }
+}
+
+/// Resolves symlinks and relative components for `#pragma once`.
+pub(crate) fn canonicalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use lexer::{ConcatStrings, SToken, Standard};
+
+    use super::HEADERS;
+    use crate::Preprocessor;
+
+    #[test]
+    fn headers_parse() {
+        let text: String = HEADERS
+            .iter()
+            .map(|(name, _)| format!("#include <{name}>\n"))
+            .collect();
+        for standard in [Standard::C17, Standard::C23] {
+            let preprocessor = Preprocessor::new(standard);
+            let (preprocessed, errors) =
+                preprocessor.preprocess_text(Path::new("test.c"), text.clone());
+            assert!(errors.is_empty(), "{errors:?}");
+            let source = preprocessed.source();
+            let tokens =
+                ConcatStrings::new(preprocessed.tokens()).map(|(token, span)| match token {
+                    Ok(token) => Ok(SToken::new(token, span)),
+                    Err(err) => Err(err.to_component(source, span)),
+                });
+            let (_, errors) = parser::Parser::new(source.clone(), tokens).parse();
+            assert!(errors.is_empty(), "{standard:?}: {errors:?}");
+        }
+    }
+}
@@ -0,0 +1,764 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! A C17 preprocessor.
+//!
+//! The output is the preprocessed text with line markers, along with every token written to it.
+//! The line markers let [`SourceFile`] map every line back to where it came from, and are what
+//! `-E` prints. Tokens are written at the column they had in their file where the line has room
+//! for it, so diagnostics outside macro expansions point at the original source.
+//!
+//! The tokens of a macro expansion are all placed at the name of the macro, but each keeps the
+//! [`Origin`] it was spelled at in the macro's definition, along with the expansions it came out
+//! of. [`Preprocessed::tokens`] turns the tokens into [`lexer::Token`]s spanned in the output
+//! text, and [`Preprocessed::explain`] uses their origins to show which macros a diagnostic
+//! came out of.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use diagnostics::{AggregateError, ErrorComponent, ErrorLevel};
+use lexer::Standard;
+use source::SourceFile;
+
+pub use crate::preprocessed::{Expansion, Origin, Preprocessed};
+use crate::{
+    directive::Condition,
+    macros::Macro,
+    output::Output,
+    predefined::BUILT_IN,
+    token::{Pos, PpToken, clean, tokenize},
+};
+
+mod directive;
+mod expr;
+mod headers;
+mod macros;
+mod output;
+mod predefined;
+mod preprocessed;
+mod token;
+
+/// Includes nested deeper than this are assumed to be recursive.
+const MAX_INCLUDE_DEPTH: usize = 200;
+
+pub struct Preprocessor {
+    standard: Standard,
+    /// The `-I` directories
+    include_paths: Vec<PathBuf>,
+    /// Searched after [`headers::BUILTIN_DIR`]
+    system_paths: Vec<PathBuf>,
+    /// Definitions from the command line, as `#define` and `#undef` directives
+    command_line: String,
+
+    /// Every file read so far, for diagnostics
+    files: Vec<SourceFile>,
+    stack: Vec<FileState>,
+    /// Tokens to be rescanned before reading on from the file
+    pending: VecDeque<PpToken>,
+    /// Set while expanding a list of tokens on its own, such as a macro argument
+    isolated: bool,
+    macros: HashMap<Rc<str>, Rc<Macro>>,
+    pragma_once: HashSet<PathBuf>,
+    counter: usize,
+    errors: AggregateError,
+    output: Output,
+}
+
+struct FileState {
+    src: usize,
+    tokens: Vec<PpToken>,
+    next: usize,
+    conditions: Vec<Condition>,
+    /// The directory of the file is searched first by `#include "..."`
+    path: PathBuf,
+    /// The index of the search path the file was found in, for `#include_next`
+    search_idx: Option<usize>,
+    /// The name used for `__FILE__` and the output, changed by `#line`
+    name: Rc<str>,
+    /// The difference between the presumed and the physical line numbers
+    line_delta: isize,
+    /// Whether tokens from the file are written to the output
+    emit: bool,
+    /// The presumed line of the including file to continue at once this file ends
+    resume_line: usize,
+}
+
+impl FileState {
+    fn next(&mut self, source: &SourceFile) -> Option<PpToken> {
+        let mut token = self.tokens.get(self.next)?.clone();
+        self.next += 1;
+        let pos = source.offset_to_pos(token.span.start);
+        token.pos = Pos {
+            name: self.name.clone(),
+            line: pos.line().saturating_add_signed(self.line_delta),
+            col: pos.col(),
+        };
+        Some(token)
+    }
+    fn peek(&self) -> Option<&PpToken> {
+        self.tokens.get(self.next)
+    }
+}
+
+impl Preprocessor {
+    #[must_use]
+    pub fn new(standard: Standard) -> Self {
+        Self {
+            standard,
+            include_paths: Vec::new(),
+            system_paths: Vec::new(),
+            command_line: String::new(),
+            files: Vec::new(),
+            stack: Vec::new(),
+            pending: VecDeque::new(),
+            isolated: false,
+            macros: HashMap::new(),
+            pragma_once: HashSet::new(),
+            counter: 0,
+            errors: AggregateError::new(),
+            output: Output::new(),
+        }
+    }
+    /// Adds a directory to search for included files, in the order they are added.
+    pub fn add_include_path(&mut self, path: impl Into<PathBuf>) {
+        self.include_paths.push(path.into());
+    }
+    /// Adds a directory to search for included files after the include paths and the headers
+    /// built into the compiler.
+    pub fn add_system_include_path(&mut self, path: impl Into<PathBuf>) {
+        self.system_paths.push(path.into());
+    }
+    /// Defines a macro as if by `#define name value`.
+    pub fn define(&mut self, name: &str, value: &str) {
+        self.command_line += &format!("#define {name} {value}\n");
+    }
+    /// Removes a macro as if by `#undef name`.
+    pub fn undefine(&mut self, name: &str) {
+        self.command_line += &format!("#undef {name}\n");
+    }
+
+    /// Preprocesses a file.
+    #[must_use]
+    pub fn preprocess(self, path: &Path) -> (Preprocessed, AggregateError) {
+        match std::fs::read_to_string(path) {
+            Ok(text) => self.preprocess_text(path, text),
+            Err(err) => {
+                let name = path.to_string_lossy().into_owned();
+                let message = format!("Cannot read `{name}`: {err}");
+                let mut errors = AggregateError::new();
+                let source = SourceFile::new(name, String::new());
+                errors.add_error(ErrorComponent::new(source, message, 0..0));
+                let output = SourceFile::new(String::new(), String::new());
+                let preprocessed = Preprocessed::new(
+                    output.clone(),
+                    Vec::new(),
+                    output,
+                    Vec::new(),
+                    self.standard,
+                );
+                (preprocessed, errors)
+            }
+        }
+    }
+
+    /// Preprocesses text as if it was read from `path`.
+    #[must_use]
+    pub fn preprocess_text(mut self, path: &Path, text: String) -> (Preprocessed, AggregateError) {
+        let predefined = self.predefined();
+        self.run_text(BUILT_IN, predefined);
+        let command_line = std::mem::take(&mut self.command_line);
+        self.run_text("<command-line>", command_line);
+        let name: Rc<str> = Rc::from(path.to_string_lossy().as_ref());
+        self.output.marker(1, &name, None);
+        self.push_file(name.clone(), text, path.to_path_buf(), None, true);
+        let file = self.files.last().expect("the file was just pushed").clone();
+        let state = self.stack.last().expect("the file was just pushed");
+        let spelled = state
+            .tokens
+            .iter()
+            .map(|token| token.span.clone())
+            .collect();
+        self.run();
+        let (text, tokens) = self.output.finish();
+        let output = SourceFile::new(name.to_string(), text);
+        (
+            Preprocessed::new(output, tokens, file, spelled, self.standard),
+            self.errors,
+        )
+    }
+
+    /// Processes the directives in some text without producing any output.
+    fn run_text(&mut self, name: &str, text: String) {
+        self.push_file(Rc::from(name), text, PathBuf::new(), None, false);
+        self.run();
+    }
+
+    fn push_file(
+        &mut self,
+        name: Rc<str>,
+        text: String,
+        path: PathBuf,
+        search_idx: Option<usize>,
+        emit: bool,
+    ) {
+        let src = self.files.len();
+        let tokens = tokenize(src, &clean(&text));
+        self.files.push(SourceFile::new(name.to_string(), text));
+        self.stack.push(FileState {
+            src,
+            tokens,
+            next: 0,
+            conditions: Vec::new(),
+            path,
+            search_idx,
+            name,
+            line_delta: 0,
+            emit,
+            resume_line: 0,
+        });
+    }
+
+    /// Processes the file on top of the stack along with everything it includes.
+    fn run(&mut self) {
+        let depth = self.stack.len();
+        while self.stack.len() >= depth {
+            while let Some(token) = self.expand_next() {
+                if self.stack.last().is_some_and(|f| f.emit) {
+                    let origin = self.origin(&token);
+                    self.output.emit(&token, origin);
+                }
+            }
+            self.pop_file();
+        }
+    }
+
+    fn pop_file(&mut self) {
+        let Some(file) = self.stack.pop() else {
+            return;
+        };
+        for condition in file.conditions {
+            let message = format!("Unterminated `#{}`", condition.directive);
+            self.error(&condition.token, message);
+        }
+        if !file.emit {
+            return;
+        }
+        if let Some(parent) = self.stack.last() {
+            let name = parent.name.clone();
+            self.output.marker(file.resume_line, &name, Some(2));
+        }
+    }
+
+    /// Returns the next token without expanding it, processing any directives on the way.
+    ///
+    /// Returns [None] at the end of the current file or of the isolated tokens.
+    fn next_raw(&mut self) -> Option<PpToken> {
+        if let Some(token) = self.pending.pop_front() {
+            return Some(token);
+        }
+        if self.isolated {
+            return None;
+        }
+        loop {
+            let file = self.stack.last_mut()?;
+            let token = file.next(&self.files[file.src])?;
+            if token.line_start && token.is_hash() {
+                self.directive(token);
+                continue;
+            }
+            return Some(token);
+        }
+    }
+
+    /// Reads the rest of the current line of the current file.
+    fn rest_of_line(&mut self) -> Vec<PpToken> {
+        let mut tokens = Vec::new();
+        let Some(file) = self.stack.last_mut() else {
+            return tokens;
+        };
+        while file.peek().is_some_and(|t| !t.line_start) {
+            tokens.extend(file.next(&self.files[file.src]));
+        }
+        tokens
+    }
+
+    fn origin(&self, token: &PpToken) -> Origin {
+        Origin {
+            source: self.files[token.src].clone(),
+            span: token.span.clone(),
+            expansion: token.expansion.clone(),
+        }
+    }
+
+    fn error(&mut self, token: &PpToken, message: String) -> &mut ErrorComponent {
+        let source = self.files[token.src].clone();
+        self.errors
+            .add_error(ErrorComponent::new(source, message, token.span.clone()))
+    }
+
+    fn warning(&mut self, token: &PpToken, message: String) -> &mut ErrorComponent {
+        self.error(token, message).set_level(ErrorLevel::Warning)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use diagnostics::{AggregateError, ErrorComponent, ErrorLevel};
+    use lexer::{SToken, Standard};
+    use pretty_assertions::assert_eq;
+    use syntax::SyntaxKind;
+
+    use crate::Preprocessor;
+
+    fn run(text: &str, standard: Standard) -> (String, AggregateError) {
+        let preprocessor = Preprocessor::new(standard);
+        let (output, errors) = preprocessor.preprocess_text(Path::new("test.c"), text.to_owned());
+        (output.source().text().to_owned(), errors)
+    }
+
+    /// Preprocesses text that should have no diagnostics, dropping line markers and blank lines
+    /// from the output. Runs of spaces between tokens, which keep them at their original
+    /// columns, are written as one.
+    fn pp(text: &str) -> String {
+        let (output, errors) = run(text, Standard::C17);
+        assert!(errors.is_empty(), "{errors:?}");
+        output
+            .lines()
+            .filter(|l| !l.starts_with("# ") && !l.trim().is_empty())
+            .map(|line| collapse_spaces(line.trim()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn collapse_spaces(line: &str) -> String {
+        let mut collapsed = String::new();
+        let mut quote = None;
+        let mut escaped = false;
+        for c in line.chars() {
+            match quote {
+                Some(_) if escaped => escaped = false,
+                Some(_) if c == '\\' => escaped = true,
+                Some(q) if c == q => quote = None,
+                Some(_) => (),
+                None if matches!(c, '"' | '\'') => quote = Some(c),
+                None if c == ' ' && collapsed.ends_with(' ') => continue,
+                None => (),
+            }
+            collapsed.push(c);
+        }
+        collapsed
+    }
+
+    fn error_messages(text: &str) -> Vec<String> {
+        let (_, errors) = run(text, Standard::C17);
+        errors
+            .components
+            .into_iter()
+            .map(|c| c.short_message)
+            .collect()
+    }
+
+    #[test]
+    fn object_like_macros() {
+        assert_eq!(pp("#define A 1 + 2\nint x = A;"), "int x = 1 + 2;");
+        assert_eq!(pp("#define A B\n#define B A\nA B"), "A B");
+        assert_eq!(pp("#define A 1\n#undef A\nA"), "A");
+        // Comments are whitespace and splices join lines
+        assert_eq!(pp("#define A /* 1 */ 2 \\\n  + 3 // 4\nA"), "2 + 3");
+    }
+
+    #[test]
+    fn function_like_macros() {
+        assert_eq!(pp("#define f(a, b) b a\nf((1, 2), 3)"), "3 (1, 2)");
+        assert_eq!(pp("#define f(a) [a]\nf()"), "[]");
+        assert_eq!(pp("#define f(a) a\nf\n(1) f + 1"), "1\nf + 1");
+        assert_eq!(pp("#define f (a) a\nf(1)"), "(a) a(1)");
+        // From C17 6.10.3.5
+        let text = "#define f(a) a*g\n#define g(a) f(a)\nf(2)(9)";
+        assert_eq!(pp(text), "2*9*g");
+    }
+
+    #[test]
+    fn stringizing_and_pasting() {
+        assert_eq!(
+            pp("#define s(x) #x\ns( a  \"\\n\"  '\\'' )"),
+            r#""a \"\\n\" '\\''""#
+        );
+        assert_eq!(
+            pp("#define c(a, b) a ## b\nc(x, y) c(1, e3) c(, y) c(x, )"),
+            "xy 1e3 y x"
+        );
+        // From C17 6.10.3.3
+        let text = "#define hash_hash # ## #\n#define mkstr(a) # a\n\
+                    #define in_between(a) mkstr(a)\n#define join(c, d) in_between(c hash_hash d)\n\
+                    join(x, y)";
+        assert_eq!(pp(text), r#""x ## y""#);
+        // Pasted identifiers are expanded again
+        assert_eq!(pp("#define ab 1\n#define c(a, b) a ## b\nc(a, b)"), "1");
+    }
+
+    #[test]
+    fn variadic_macros() {
+        // From C17 6.10.3.5
+        let text = "#define showlist(...) puts(#__VA_ARGS__)\n\
+                    #define report(test, ...) ((test)?puts(#test): printf(__VA_ARGS__))\n\
+                    showlist(The first, second, and third items.);\n\
+                    report(x>y, \"x is %d but y is %d\", x, y);";
+        assert_eq!(
+            pp(text),
+            "puts(\"The first, second, and third items.\") ;\n\
+             ((x>y)?puts(\"x>y\"): printf(\"x is %d but y is %d\", x, y));"
+        );
+        let text = "#define log(fmt, ...) f(fmt, ## __VA_ARGS__)\nlog(a) log(a, b, c)";
+        assert_eq!(pp(text), "f(a) f(a,b, c)");
+        let text = "#define log(fmt, ...) f(fmt __VA_OPT__(,) __VA_ARGS__)\nlog(a) log(a, b)";
+        assert_eq!(pp(text), "f(a) f(a , b)");
+    }
+
+    #[test]
+    fn conditionals() {
+        let text = "#if 1 + 1 == 2\na\n#elif 1\nb\n#else\nc\n#endif";
+        assert_eq!(pp(text), "a");
+        let text = "#define X\n#if 0\n#if 1\na\n#endif\n#elif defined X && !defined(Y)\nb\n#endif";
+        assert_eq!(pp(text), "b");
+        assert_eq!(pp("#ifdef X\na\n#else\nb\n#endif"), "b");
+        assert_eq!(pp("#ifndef X\na\n#endif"), "a");
+        // Skipped groups only need to be valid preprocessing tokens
+        assert_eq!(pp("#if 0\n#bogus\n'\n#endif\nc"), "c");
+    }
+
+    #[test]
+    fn if_expressions() {
+        let truthy = [
+            "-1 < 0",
+            "-1 > 0u",
+            "0xFFFFFFFFFFFFFFFF == -1",
+            "(2 || 1 / 0) == 1",
+            "0 ? 1 / 0 : 1",
+            "'a' == 97",
+            "1 << 62 > 0",
+            "-1 >> 1 == -1",
+            "(1, 2) == 2",
+            "undefined_identifier == 0",
+            "__STDC_VERSION__ == 201710L",
+            "__has_include(<stddef.h>) && !__has_include(\"missing.h\")",
+        ];
+        for expr in truthy {
+            assert_eq!(pp(&format!("#if {expr}\nyes\n#endif")), "yes", "#if {expr}");
+        }
+    }
+
+    #[test]
+    fn predefined_macros() {
+        assert_eq!(pp("__LINE__\n\n__LINE__ __FILE__"), "1\n3 \"test.c\"");
+        assert_eq!(
+            pp("#line 100 \"other.c\"\n__LINE__ __FILE__"),
+            "100 \"other.c\""
+        );
+        // Line markers in preprocessed input work like `#line`
+        assert_eq!(
+            pp("# 10 \"orig.c\" 1 3\n__LINE__ __FILE__\n# 20 \"test.c\" 2\n__LINE__"),
+            "10 \"orig.c\"\n20"
+        );
+        assert_eq!(pp("__COUNTER__ __COUNTER__"), "0 1");
+        let (output, _) = run("__STDC_VERSION__ true", Standard::C23);
+        assert!(
+            collapse_spaces(&output).contains("202311L true"),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn builtin_headers() {
+        assert_eq!(pp("#include <stdbool.h>\ntrue"), "1");
+        assert_eq!(pp("#include <iso646.h>\na and not b"), "a && ! b");
+    }
+
+    #[test]
+    fn output_keeps_lines_and_separates_tokens() {
+        let (output, _) = run("#define neg(x) -x\nint a;\n\n  -neg(-1)", Standard::C17);
+        assert_eq!(output, "# 1 \"test.c\"\n\nint a;\n\n  - - -1\n");
+        let (output, _) = run("a\n\n\n\n\n\n\n\n\n\n\nb", Standard::C17);
+        assert_eq!(output, "# 1 \"test.c\"\na\n# 12 \"test.c\"\nb\n");
+    }
+
+    #[test]
+    fn output_keeps_columns() {
+        // Expansions start at the macro name, and later tokens go back to their own columns
+        let (output, _) = run(
+            "#define A 1
+int  x =  A +	A;",
+            Standard::C17,
+        );
+        assert_eq!(output, "# 1 \"test.c\"\n\nint  x =  1 + 1;\n");
+        let (output, _) = run(
+            "#define f(a) a
+x = f(1) + y;",
+            Standard::C17,
+        );
+        assert_eq!(output, "# 1 \"test.c\"\n\nx = 1    + y;\n");
+        // Unless an expansion made the line longer
+        let (output, _) = run(
+            "#define A 1 + 2 + 3
+x = A + y;",
+            Standard::C17,
+        );
+        assert_eq!(output, "# 1 \"test.c\"\n\nx = 1 + 2 + 3 + y;\n");
+    }
+
+    /// The tokens of the output, each with the line it was spelled on and the names of the
+    /// macros it was expanded from, innermost first.
+    fn origins(text: &str) -> Vec<(String, usize, Vec<String>)> {
+        let preprocessor = Preprocessor::new(Standard::C17);
+        let (output, errors) = preprocessor.preprocess_text(Path::new("test.c"), text.to_owned());
+        assert!(errors.is_empty(), "{errors:?}");
+        output
+            .tokens()
+            .map(|(_, span)| {
+                let origin = output.origin(span.start).unwrap();
+                let line = origin.source.offset_to_pos(origin.span.start).line();
+                let mut names = Vec::new();
+                let mut expansion = origin.expansion.as_ref();
+                while let Some(site) = expansion {
+                    names.push(site.name.to_string());
+                    expansion = site.invocation.expansion.as_ref();
+                }
+                (output.source().text()[span].to_owned(), line, names)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn token_origins() {
+        let expanded = |text: &str, line, names: &[&str]| {
+            let names = names.iter().map(|name| (*name).to_owned()).collect();
+            (text.to_owned(), line, names)
+        };
+        assert_eq!(
+            origins("#define ONE 1\n#define TWO ONE + ONE\nx = TWO;"),
+            [
+                expanded("x", 3, &[]),
+                expanded("=", 3, &[]),
+                expanded("1", 1, &["ONE", "TWO"]),
+                expanded("+", 2, &["TWO"]),
+                expanded("1", 1, &["ONE", "TWO"]),
+                expanded(";", 3, &[]),
+            ]
+        );
+        // Arguments keep their own origin, while pasted tokens come from the macro
+        assert_eq!(
+            origins("#define f(a, b) (a + b ## 1)\nf(x,\n y)"),
+            [
+                expanded("(", 1, &["f"]),
+                expanded("x", 2, &[]),
+                expanded("+", 1, &["f"]),
+                expanded("y1", 1, &["f"]),
+                expanded(")", 1, &["f"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn explain_expansions() {
+        let text = "#define ONE 1\n#define TWO ONE + ONE\nx = TWO;";
+        let preprocessor = Preprocessor::new(Standard::C17);
+        let (output, _) = preprocessor.preprocess_text(Path::new("test.c"), text.to_owned());
+        let source = output.source();
+        let one = source.text().find("= 1").unwrap() + 2;
+        let mut errors = AggregateError::new();
+        errors.add_error(ErrorComponent::new(
+            source.clone(),
+            "A".to_owned(),
+            one..one + 1,
+        ));
+        errors.add_error(ErrorComponent::new(source.clone(), "B".to_owned(), 0..1));
+        output.explain(&mut errors);
+        let components: Vec<_> = errors
+            .components
+            .iter()
+            .map(|c| {
+                let line = c.source().offset_to_pos(c.highlight().start).line();
+                (c.short_message.as_str(), c.level, line)
+            })
+            .collect();
+        assert_eq!(
+            components,
+            [
+                // The line in the output, which starts with a line marker
+                ("A", ErrorLevel::Error, 4),
+                ("Expanded from macro `ONE`", ErrorLevel::Note, 1),
+                ("Expanded from macro `TWO`", ErrorLevel::Note, 2),
+                ("B", ErrorLevel::Error, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn original_syntax() {
+        let dir = std::env::temp_dir().join(format!("cccc-syntax-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("h.h"), "int h(int);\n").unwrap();
+        let text = "#include \"h.h\"\n#define N 100\n#define PAIR 1 + \\\n  2\n#define ADD(a, b) ((a) + (b))\n/* x */ int x = N + 1;\nint y = PAIR;\n#if 0\nint z;\n#endif\nint main(void) {\n\tint w = ADD(x,\n\t  y) * 2;\n\treturn h(w) + y;\n}\n";
+        let preprocessor = Preprocessor::new(Standard::C17);
+        let (output, errors) = preprocessor.preprocess_text(&dir.join("test.c"), text.to_owned());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(errors.is_empty(), "{errors:?}");
+        let tokens = output
+            .tokens()
+            .map(|(token, span)| Ok(SToken::new(token.unwrap(), span)));
+        let (program, errors) = parser::Parser::new(output.source().clone(), tokens).parse();
+        assert!(errors.is_empty(), "{errors:?}");
+        let tokens = output
+            .spelled_tokens()
+            .map(|(token, span)| (SyntaxKind::from(&token.unwrap()), span));
+        let root = syntax::build_original(output.file().clone(), tokens, &program.syntax, |span| {
+            output.original_span(span)
+        });
+        assert_eq!(root.to_string(), text);
+        // The declaration of `h` came from the header
+        let nodes: Vec<_> = root
+            .children()
+            .map(|node| (node.kind(), node.to_string()))
+            .collect();
+        let expected = [
+            (SyntaxKind::Variable, "int x = N + 1"),
+            (SyntaxKind::Variable, "int y = PAIR"),
+            (
+                SyntaxKind::Function,
+                "int main(void) {\n\tint w = ADD(x,\n\t  y) * 2;\n\treturn h(w) + y;\n}",
+            ),
+        ];
+        assert_eq!(nodes, expected.map(|(kind, text)| (kind, text.to_owned())));
+        let x = text.find("N + 1").unwrap();
+        let node = root.find(&(x..x + 5)).unwrap();
+        assert_eq!(node.kind(), SyntaxKind::Expression);
+        let name = root.find(&(x..x + 1)).unwrap();
+        assert_eq!(name.parent(), Some(&node));
+        // Nodes made of tokens from an expansion cover the whole invocation
+        let y = text.find("PAIR;").unwrap();
+        let pair = root.find(&(y..y + 4)).unwrap();
+        assert_eq!(pair.kind(), SyntaxKind::Expression);
+        let add = text.find("ADD(x").unwrap();
+        let call = root.find(&(add..add + 12)).unwrap();
+        assert_eq!(call.kind(), SyntaxKind::Expression);
+        assert_eq!(call.parent().unwrap().to_string(), "ADD(x,\n\t  y) * 2");
+        // While the arguments keep their own
+        let arg = root.find(&(add + 4..add + 5)).unwrap();
+        assert!(arg.ancestors().any(|node| node == call));
+        let directives: Vec<_> = root
+            .tokens()
+            .into_iter()
+            .filter(|t| t.kind() == SyntaxKind::Directive)
+            .map(|t| t.text().to_owned())
+            .collect();
+        assert_eq!(
+            directives,
+            [
+                "#include \"h.h\"",
+                "#define N 100",
+                "#define PAIR 1 + \\\n  2",
+                "#define ADD(a, b) ((a) + (b))",
+                "#if 0",
+                "#endif"
+            ]
+        );
+    }
+
+    #[test]
+    fn includes_and_pragma_once() {
+        let dir = std::env::temp_dir().join(format!("cccc-pp-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("once.h"), "#pragma once\nonce\n").unwrap();
+        std::fs::write(dir.join("sub/inner.h"), "inner\n").unwrap();
+        std::fs::write(
+            dir.join("main.c"),
+            "#include \"once.h\"\n#include \"once.h\"\n#include <inner.h>\nmain\n",
+        )
+        .unwrap();
+        let mut preprocessor = Preprocessor::new(Standard::C17);
+        preprocessor.add_include_path(dir.join("sub"));
+        let (output, errors) = preprocessor.preprocess(&dir.join("main.c"));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(errors.is_empty(), "{errors:?}");
+        let main = dir.join("main.c").display().to_string();
+        let once = dir.join("once.h").display().to_string();
+        let inner = dir.join("sub/inner.h").display().to_string();
+        assert_eq!(
+            output.source().text(),
+            format!(
+                "# 1 \"{main}\"\n# 1 \"{once}\" 1\n\nonce\n# 2 \"{main}\" 2\n\
+                 # 1 \"{inner}\" 1\ninner\n# 4 \"{main}\" 2\nmain\n"
+            )
+        );
+    }
+
+    #[test]
+    fn diagnostics() {
+        assert_eq!(error_messages("#error stop  here"), ["#error stop here"]);
+        assert_eq!(
+            error_messages("#if 1 / 0\n#endif\n#if 0 && 1 / 0\n#endif"),
+            ["Division by zero in preprocessor expression"]
+        );
+        assert_eq!(error_messages("#if 1\n"), ["Unterminated `#if`"]);
+        assert_eq!(error_messages("#endif"), ["`#endif` without `#if`"]);
+        assert_eq!(
+            error_messages("#if 1\n#else\n#else\n#endif"),
+            ["`#else` after `#else`"]
+        );
+        assert_eq!(
+            error_messages("#define f(a, b)\nf(1)"),
+            ["Macro `f` takes 2 arguments, but 1 were given"]
+        );
+        assert_eq!(
+            error_messages("#define f(a) #b"),
+            ["`#` is not followed by a macro parameter"]
+        );
+        assert_eq!(
+            error_messages("#include <missing.h>"),
+            ["Cannot find included file `missing.h`"]
+        );
+        assert_eq!(
+            error_messages("# 1 \"a.c\" 5\n# 2 a.c\n# 99999999999999999999999"),
+            [
+                "Invalid flag `5` in line marker",
+                "Invalid filename `a` in line marker",
+                "Line number `99999999999999999999999` is too large",
+            ]
+        );
+        assert_eq!(
+            error_messages("#define __VA_ARGS__ 1\n#undef __LINE__\n#define __STDC__ 2"),
+            [
+                "`__VA_ARGS__` cannot be used as a macro name",
+                "Predefined macro `__LINE__` cannot be undefined",
+                "Predefined macro `__STDC__` cannot be redefined",
+            ]
+        );
+        assert_eq!(
+            error_messages("#frobnicate"),
+            ["Invalid preprocessing directive `#frobnicate`"]
+        );
+        let (_, errors) = run("#warning careful\n#define A 1\n#define A 2", Standard::C17);
+        assert!(!errors.has_error());
+        let levels: Vec<_> = errors.components.iter().map(|c| c.level).collect();
+        assert_eq!(levels, [ErrorLevel::Warning, ErrorLevel::Warning]);
+    }
+}
@@ -0,0 +1,647 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use std::rc::Rc;
+
+use crate::{
+    Expansion, Preprocessor,
+    predefined::{BUILT_IN, STANDARD_PREDEFINED},
+    token::{PpToken, is_single_token, tokenize},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Builtin {
+    File,
+    Line,
+    Counter,
+    /// Only meaningful in `#if`, exists so that `defined(__has_include)` holds
+    HasInclude,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MacroKind {
+    Object,
+    Function {
+        /// Ends with `__VA_ARGS__` for variadic macros
+        params: Vec<Rc<str>>,
+        variadic: bool,
+    },
+    Builtin(Builtin),
+}
+
+#[derive(Debug)]
+pub(crate) struct Macro {
+    pub kind: MacroKind,
+    pub body: Vec<PpToken>,
+}
+
+impl Macro {
+    pub fn builtin(builtin: Builtin) -> Self {
+        Self {
+            kind: MacroKind::Builtin(builtin),
+            body: Vec::new(),
+        }
+    }
+    /// Whether two definitions are the same as far as redefinition is concerned.
+    fn same_as(&self, other: &Self) -> bool {
+        self.kind == other.kind
+            && self.body.len() == other.body.len()
+            && self
+                .body
+                .iter()
+                .zip(&other.body)
+                .all(|(a, b)| a.text == b.text && a.space == b.space)
+    }
+    fn param_index(&self, token: &PpToken) -> Option<usize> {
+        let MacroKind::Function { params, .. } = &self.kind else {
+            return None;
+        };
+        params.iter().position(|p| *p == token.text)
+    }
+    const fn is_variadic(&self) -> bool {
+        matches!(self.kind, MacroKind::Function { variadic: true, .. })
+    }
+}
+
+fn is_va_args(token: &PpToken) -> bool {
+    token.is("__VA_ARGS__")
+}
+
+/// Tokens with empty text stand in for empty arguments next to `##`.
+fn placemarker(site: &PpToken) -> PpToken {
+    site.with_text("")
+}
+
+/// Finds the `)` matching the `(` at `tokens[open]`.
+fn matching_paren(tokens: &[PpToken], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (idx, token) in tokens.iter().enumerate().skip(open) {
+        if token.is("(") {
+            depth += 1;
+        } else if token.is(")") {
+            depth -= 1;
+            if depth == 0 {
+                return Some(idx);
+            }
+        }
+    }
+    None
+}
+
+/// Implements the `#` operator.
+pub(crate) fn stringify(tokens: &[PpToken], site: &PpToken) -> PpToken {
+    let mut text = String::from("\"");
+    for (idx, token) in tokens.iter().enumerate() {
+        if idx != 0 && token.space {
+            text.push(' ');
+        }
+        if token.is_literal() {
+            for c in token.text.chars() {
+                if matches!(c, '"' | '\\') {
+                    text.push('\\');
+                }
+                text.push(c);
+            }
+        } else {
+            text += &token.text;
+        }
+    }
+    text.push('"');
+    site.with_text(text)
+}
+
+impl Preprocessor {
+    pub(crate) fn define_macro(&mut self, directive: &PpToken, line: Vec<PpToken>) {
+        let Some(name) = line.first() else {
+            self.error(
+                directive,
+                "Expected a macro name after `#define`".to_owned(),
+            );
+            return;
+        };
+        if !self.check_macro_name(directive, name) {
+            return;
+        }
+        let mut body = &line[1..];
+        let kind = match body.first() {
+            Some(open) if open.is("(") && !open.space => {
+                let Some((kind, rest)) = self.parse_params(open, &body[1..]) else {
+                    return;
+                };
+                body = rest;
+                kind
+            }
+            Some(first) if !first.space => {
+                self.warning(first, "Missing whitespace after the macro name".to_owned());
+                MacroKind::Object
+            }
+            _ => MacroKind::Object,
+        };
+        let mut body = body.to_vec();
+        if let Some(first) = body.first_mut() {
+            first.space = false;
+        }
+        let definition = Macro { kind, body };
+        if !self.check_body(&definition) {
+            return;
+        }
+        if let Some(previous) = self.macros.get(&name.text)
+            && !previous.same_as(&definition)
+        {
+            self.warning(name, format!("`{}` redefined", name.text));
+        }
+        self.macros.insert(name.text.clone(), Rc::new(definition));
+    }
+
+    /// Parses the parameter list following the `(` of a function-like macro, returning the
+    /// tokens after the closing `)`.
+    fn parse_params<'t>(
+        &mut self,
+        open: &PpToken,
+        tokens: &'t [PpToken],
+    ) -> Option<(MacroKind, &'t [PpToken])> {
+        let mut params = Vec::new();
+        let mut variadic = false;
+        if tokens.first().is_some_and(|t| t.is(")")) {
+            return Some((MacroKind::Function { params, variadic }, &tokens[1..]));
+        }
+        let mut idx = 0;
+        loop {
+            let Some(token) = tokens.get(idx) else {
+                self.error(open, "Unterminated macro parameter list".to_owned());
+                return None;
+            };
+            if token.is("...") {
+                variadic = true;
+                params.push(Rc::from("__VA_ARGS__"));
+            } else if !token.is_ident() || is_va_args(token) {
+                self.error(
+                    token,
+                    format!("Expected a parameter name, found `{}`", token.text),
+                );
+                return None;
+            } else if params.contains(&token.text) {
+                self.error(token, format!("Duplicate macro parameter `{}`", token.text));
+                return None;
+            } else {
+                params.push(token.text.clone());
+            }
+            idx += 1;
+            match tokens.get(idx) {
+                Some(t) if t.is(")") => {
+                    let kind = MacroKind::Function { params, variadic };
+                    return Some((kind, &tokens[idx + 1..]));
+                }
+                Some(t) if t.is(",") && !variadic => idx += 1,
+                Some(t) => {
+                    let message =
+                        format!("Expected `,` or `)` after a parameter, found `{}`", t.text);
+                    self.error(t, message);
+                    return None;
+                }
+                None => {
+                    self.error(open, "Unterminated macro parameter list".to_owned());
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Checks the placement of `#` and `##` in a macro body.
+    fn check_body(&mut self, definition: &Macro) -> bool {
+        let body = &definition.body;
+        for edge in [body.first(), body.last()].into_iter().flatten() {
+            if edge.is_hash_hash() {
+                self.error(
+                    edge,
+                    "`##` cannot appear at either end of a macro".to_owned(),
+                );
+                return false;
+            }
+        }
+        if matches!(definition.kind, MacroKind::Object) {
+            return true;
+        }
+        for (idx, token) in body.iter().enumerate() {
+            let operand = body.get(idx + 1);
+            let is_param = operand.is_some_and(|t| definition.param_index(t).is_some());
+            if token.is_hash() && !is_param {
+                self.error(token, "`#` is not followed by a macro parameter".to_owned());
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Checks that `name` can be defined or undefined by `directive`, which rules out the
+    /// identifiers with a meaning of their own and the standard predefined macros outside of
+    /// where they are predefined (C17 6.10.8p2).
+    fn check_macro_name(&mut self, directive: &PpToken, name: &PpToken) -> bool {
+        if !name.is_ident() {
+            let message = format!("Macro names must be identifiers, found `{}`", name.text);
+            self.error(name, message);
+            return false;
+        }
+        if ["defined", "__VA_ARGS__", "__VA_OPT__"].contains(&&*name.text) {
+            let message = format!("`{}` cannot be used as a macro name", name.text);
+            self.error(name, message);
+            return false;
+        }
+        let builtin = self
+            .macros
+            .get(&name.text)
+            .is_some_and(|m| matches!(m.kind, MacroKind::Builtin(_)));
+        if (builtin || STANDARD_PREDEFINED.contains(&&*name.text))
+            && self.files[directive.src].path() != BUILT_IN
+        {
+            let action = if directive.is("define") {
+                "redefined"
+            } else {
+                "undefined"
+            };
+            let message = format!("Predefined macro `{}` cannot be {action}", name.text);
+            self.error(name, message);
+            return false;
+        }
+        true
+    }
+
+    pub(crate) fn undefine_macro(&mut self, directive: &PpToken, line: &[PpToken]) {
+        let Some(name) = line.first() else {
+            self.error(directive, "Expected a macro name after `#undef`".to_owned());
+            return;
+        };
+        if !self.check_macro_name(directive, name) {
+            return;
+        }
+        self.extra_tokens("undef", &line[1..]);
+        self.macros.remove(&name.text);
+    }
+
+    /// Returns the next token after macro expansion.
+    ///
+    /// Expansion follows the hide set algorithm: every token remembers which macros it came out
+    /// of and won't be expanded by them again.
+    pub(crate) fn expand_next(&mut self) -> Option<PpToken> {
+        loop {
+            let token = self.next_raw()?;
+            if !token.is_ident() || token.hidden(&token.text) {
+                return Some(token);
+            }
+            let Some(definition) = self.macros.get(&token.text).cloned() else {
+                if token.is("_Pragma") && self.pragma_operator(&token) {
+                    continue;
+                }
+                return Some(token);
+            };
+            let mut hide = token.hide.clone();
+            let invocation = self.origin(&token);
+            let expansion = match &definition.kind {
+                MacroKind::Object => {
+                    hide.push(token.text.clone());
+                    let site = Rc::new(Expansion {
+                        name: token.text.clone(),
+                        end: invocation.clone(),
+                        invocation,
+                    });
+                    self.substitute(&definition, &definition.body, &[], &site)
+                }
+                MacroKind::Function { .. } => {
+                    match self.next_raw() {
+                        Some(open) if open.is("(") => (),
+                        Some(other) => {
+                            self.pending.push_front(other);
+                            return Some(token);
+                        }
+                        None => return Some(token),
+                    }
+                    let Some((args, close)) = self.collect_args(&token, &definition) else {
+                        continue;
+                    };
+                    hide.retain(|name| close.hidden(name));
+                    hide.push(token.text.clone());
+                    let site = Rc::new(Expansion {
+                        name: token.text.clone(),
+                        invocation,
+                        end: self.origin(&close),
+                    });
+                    self.substitute(&definition, &definition.body, &args, &site)
+                }
+                MacroKind::Builtin(builtin) => match self.builtin(*builtin, &token) {
+                    Some(expansion) => vec![expansion],
+                    None => return Some(token),
+                },
+            };
+            for (idx, mut expanded) in expansion.into_iter().enumerate().rev() {
+                if idx == 0 {
+                    expanded.space = token.space;
+                }
+                expanded.line_start = false;
+                expanded.pos = token.pos.clone();
+                for name in &hide {
+                    if !expanded.hidden(name) {
+                        expanded.hide.push(name.clone());
+                    }
+                }
+                self.pending.push_front(expanded);
+            }
+        }
+    }
+
+    /// Fully expands a list of tokens on its own.
+    pub(crate) fn expand_isolated(&mut self, tokens: Vec<PpToken>) -> Vec<PpToken> {
+        let pending = std::mem::replace(&mut self.pending, tokens.into());
+        let isolated = std::mem::replace(&mut self.isolated, true);
+        let mut expanded = Vec::new();
+        while let Some(token) = self.expand_next() {
+            expanded.push(token);
+        }
+        self.pending = pending;
+        self.isolated = isolated;
+        expanded
+    }
+
+    /// Reads the arguments of a function-like macro invocation, following the `(`.
+    ///
+    /// Returns the arguments along with the closing `)`.
+    fn collect_args(
+        &mut self,
+        name: &PpToken,
+        definition: &Macro,
+    ) -> Option<(Vec<Vec<PpToken>>, PpToken)> {
+        let MacroKind::Function { params, variadic } = &definition.kind else {
+            unreachable!("only function-like macros take arguments")
+        };
+        let mut args = vec![Vec::new()];
+        let mut depth = 0usize;
+        let close = loop {
+            let Some(mut token) = self.next_raw() else {
+                let message = format!("Unterminated argument list invoking macro `{}`", name.text);
+                self.error(name, message);
+                return None;
+            };
+            // Newlines in arguments are just whitespace
+            token.space |= token.line_start;
+            token.line_start = false;
+            match &*token.text {
+                "(" => depth += 1,
+                ")" if depth == 0 => break token,
+                ")" => depth -= 1,
+                "," if depth == 0 && !(*variadic && args.len() == params.len()) => {
+                    args.push(Vec::new());
+                    continue;
+                }
+                _ => (),
+            }
+            args.last_mut().unwrap().push(token);
+        };
+        if params.is_empty() && args.len() == 1 && args[0].is_empty() {
+            args.clear();
+        }
+        // The variadic arguments may be left out entirely
+        if *variadic && args.len() + 1 == params.len() {
+            args.push(Vec::new());
+        }
+        if args.len() != params.len() {
+            let expected = params.len() - usize::from(*variadic);
+            let at_least = if *variadic { "at least " } else { "" };
+            let plural = if expected == 1 { "" } else { "s" };
+            let message = format!(
+                "Macro `{}` takes {at_least}{expected} argument{plural}, but {} were given",
+                name.text,
+                args.len()
+            );
+            self.error(name, message);
+            return None;
+        }
+        Some((args, close))
+    }
+
+    /// Replaces the parameters in (part of) a macro body with the arguments.
+    ///
+    /// The tokens of the body are marked as coming out of `site`, while those of the arguments
+    /// keep the origin they already had.
+    fn substitute(
+        &mut self,
+        definition: &Macro,
+        body: &[PpToken],
+        args: &[Vec<PpToken>],
+        site: &Rc<Expansion>,
+    ) -> Vec<PpToken> {
+        let from_body = |token: &PpToken| PpToken {
+            expansion: Some(site.clone()),
+            ..token.clone()
+        };
+        let mut expanded_args: Vec<Option<Vec<PpToken>>> = vec![None; args.len()];
+        let mut out: Vec<PpToken> = Vec::new();
+        let mut idx = 0;
+        while let Some(token) = body.get(idx) {
+            let next = body.get(idx + 1);
+            let pasted_after = body.get(idx + 1).is_some_and(PpToken::is_hash_hash);
+            if token.is_hash()
+                && let Some(param) = next.and_then(|n| definition.param_index(n))
+            {
+                out.push(stringify(&args[param], &from_body(token)));
+                idx += 2;
+                continue;
+            }
+            if token.is_hash_hash()
+                && let Some(operand) = next
+            {
+                let (mut rhs, after) = match definition.param_index(operand) {
+                    Some(param) => (args[param].clone(), idx + 2),
+                    None if definition.is_variadic() && operand.is("__VA_OPT__") => {
+                        self.va_opt(definition, body, idx + 1, args, site)
+                    }
+                    None => (vec![from_body(operand)], idx + 2),
+                };
+                if let Some(first) = rhs.first_mut() {
+                    first.space = false;
+                }
+                // GNU extension: `, ## __VA_ARGS__` drops the comma if there are no arguments and
+                // doesn't paste otherwise
+                if is_va_args(operand) && out.last().is_some_and(|t| t.is(",")) {
+                    if rhs.is_empty() {
+                        out.pop();
+                    }
+                    out.extend(rhs);
+                } else {
+                    self.paste(&mut out, rhs, &from_body(token));
+                }
+                idx = after;
+                continue;
+            }
+            if definition.is_variadic() && token.is("__VA_OPT__") {
+                let (mut tokens, end) = self.va_opt(definition, body, idx, args, site);
+                if let Some(first) = tokens.first_mut() {
+                    first.space = token.space;
+                }
+                if tokens.is_empty() && body.get(end).is_some_and(PpToken::is_hash_hash) {
+                    tokens.push(placemarker(token));
+                }
+                out.extend(tokens);
+                idx = end;
+                continue;
+            }
+            if let Some(param) = definition.param_index(token) {
+                let mut arg = if pasted_after {
+                    args[param].clone()
+                } else {
+                    let expanded = match &expanded_args[param] {
+                        Some(expanded) => expanded.clone(),
+                        None => self.expand_isolated(args[param].clone()),
+                    };
+                    expanded_args[param] = Some(expanded.clone());
+                    expanded
+                };
+                if arg.is_empty() && pasted_after {
+                    arg.push(placemarker(token));
+                }
+                if let Some(first) = arg.first_mut() {
+                    first.space = token.space;
+                }
+                out.extend(arg);
+                idx += 1;
+                continue;
+            }
+            out.push(from_body(token));
+            idx += 1;
+        }
+        out.retain(|t| !t.text.is_empty());
+        out
+    }
+
+    /// Substitutes `__VA_OPT__(...)` starting at `body[start]`, returning the tokens and the
+    /// index following the closing `)`.
+    fn va_opt(
+        &mut self,
+        definition: &Macro,
+        body: &[PpToken],
+        start: usize,
+        args: &[Vec<PpToken>],
+        site: &Rc<Expansion>,
+    ) -> (Vec<PpToken>, usize) {
+        let end = match body.get(start + 1) {
+            Some(open) if open.is("(") => matching_paren(body, start + 1),
+            _ => None,
+        };
+        let Some(end) = end else {
+            self.error(
+                &body[start],
+                "Expected a parenthesized `__VA_OPT__`".to_owned(),
+            );
+            return (Vec::new(), start + 1);
+        };
+        let va_args = args.last().map(Vec::as_slice).unwrap_or_default();
+        if va_args.is_empty() {
+            return (Vec::new(), end + 1);
+        }
+        (
+            self.substitute(definition, &body[start + 2..end], args, site),
+            end + 1,
+        )
+    }
+
+    /// Implements the `##` operator, pasting the last token of `out` to the first of `rhs`.
+    ///
+    /// A pasted token was spelled by neither operand, so its origin is the `##` operator.
+    fn paste(&mut self, out: &mut Vec<PpToken>, rhs: Vec<PpToken>, operator: &PpToken) {
+        let mut rhs = rhs.into_iter();
+        let Some(first) = rhs.next() else {
+            return;
+        };
+        let Some(lhs) = out.pop() else {
+            out.push(first);
+            out.extend(rhs);
+            return;
+        };
+        if lhs.text.is_empty() {
+            out.push(first);
+        } else if first.text.is_empty() {
+            out.push(lhs);
+        } else {
+            let text = format!("{}{}", lhs.text, first.text);
+            if is_single_token(&text) {
+                out.push(PpToken {
+                    src: operator.src,
+                    span: operator.span.clone(),
+                    expansion: operator.expansion.clone(),
+                    ..lhs.with_text(text)
+                });
+            } else {
+                let message = format!(
+                    "Pasting `{}` and `{}` does not give a valid preprocessing token",
+                    lhs.text, first.text
+                );
+                self.error(&lhs, message);
+                out.extend([lhs, first]);
+            }
+        }
+        out.extend(rhs);
+    }
+
+    fn builtin(&mut self, builtin: Builtin, token: &PpToken) -> Option<PpToken> {
+        let text = match builtin {
+            Builtin::File => {
+                let name = token.pos.name.replace('\\', "\\\\").replace('"', "\\\"");
+                format!("\"{name}\"")
+            }
+            Builtin::Line => token.pos.line.to_string(),
+            Builtin::Counter => {
+                self.counter += 1;
+                (self.counter - 1).to_string()
+            }
+            Builtin::HasInclude => return None,
+        };
+        Some(token.with_text(text))
+    }
+
+    /// Handles `_Pragma("...")`, returning whether it was well formed.
+    fn pragma_operator(&mut self, token: &PpToken) -> bool {
+        let mut tokens = Vec::new();
+        for expected in ["(", "string", ")"] {
+            match self.next_raw() {
+                Some(t) if t.is(expected) || (expected == "string" && t.is_string()) => {
+                    tokens.push(t);
+                }
+                other => {
+                    self.pending.extend(other);
+                    for t in tokens.into_iter().rev() {
+                        self.pending.push_front(t);
+                    }
+                    let message = "`_Pragma` takes a parenthesized string literal".to_owned();
+                    self.error(token, message);
+                    return false;
+                }
+            }
+        }
+        let string = &tokens[1];
+        let text = string.text.trim_start_matches(['u', '8', 'U', 'L']);
+        let text = text[1..text.len() - 1]
+            .replace("\\\"", "\"")
+            .replace("\\\\", "\\");
+        let line = tokenize(string.src, &text)
+            .into_iter()
+            .map(|t| PpToken {
+                span: string.span.clone(),
+                pos: string.pos.clone(),
+                ..t
+            })
+            .collect();
+        self.pragma(token, line);
+        true
+    }
+}
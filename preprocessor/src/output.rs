@@ -0,0 +1,106 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use std::rc::Rc;
+
+use source::Span;
+
+use crate::{
+    Origin,
+    token::{PpToken, pastes},
+};
+
+/// Any jump of more lines than this is written as a line marker instead of newlines.
+const MAX_NEWLINES: usize = 8;
+
+/// The preprocessed text, written so that tokens end up on their original lines, and at their
+/// original columns where the line has room for them.
+pub(crate) struct Output {
+    text: String,
+    name: Rc<str>,
+    line: usize,
+    /// The column the next character is written at, starting from 1
+    col: usize,
+    /// The last token written on the current line
+    last: Option<Rc<str>>,
+    /// Every token written, with its span in the text
+    tokens: Vec<(Span, Origin)>,
+}
+
+impl Output {
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+            name: Rc::from(""),
+            line: 0,
+            col: 1,
+            last: None,
+            tokens: Vec::new(),
+        }
+    }
+    /// Writes a line marker stating that the next line is `line` of `name`.
+    pub fn marker(&mut self, line: usize, name: &Rc<str>, flag: Option<u8>) {
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push('\n');
+        }
+        self.text += &format!("# {line} \"");
+        for c in name.chars() {
+            if matches!(c, '"' | '\\') {
+                self.text.push('\\');
+            }
+            self.text.push(c);
+        }
+        self.text.push('"');
+        if let Some(flag) = flag {
+            self.text += &format!(" {flag}");
+        }
+        self.text.push('\n');
+        self.name = name.clone();
+        self.line = line;
+        self.col = 1;
+        self.last = None;
+    }
+    pub fn emit(&mut self, token: &PpToken, origin: Origin) {
+        let pos = &token.pos;
+        if pos.name != self.name || pos.line < self.line || pos.line > self.line + MAX_NEWLINES {
+            self.marker(pos.line, &pos.name.clone(), None);
+        }
+        while self.line < pos.line {
+            self.text.push('\n');
+            self.line += 1;
+            self.col = 1;
+            self.last = None;
+        }
+        let space = self
+            .last
+            .as_ref()
+            .is_some_and(|last| token.space || pastes(last, &token.text));
+        // Tokens after a macro expansion that made the line longer can't keep their columns
+        let col = pos.col.max(self.col + usize::from(space));
+        self.text.extend(std::iter::repeat_n(' ', col - self.col));
+        let start = self.text.len();
+        self.text += &token.text;
+        self.tokens.push((start..self.text.len(), origin));
+        self.col = col + token.text.chars().count();
+        self.last = Some(token.text.clone());
+    }
+    pub fn finish(mut self) -> (String, Vec<(Span, Origin)>) {
+        if !self.text.ends_with('\n') {
+            self.text.push('\n');
+        }
+        (self.text, self.tokens)
+    }
+}
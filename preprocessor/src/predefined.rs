@@ -0,0 +1,130 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use std::{
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use lexer::Standard;
+
+use crate::{
+    Preprocessor,
+    macros::{Builtin, Macro},
+};
+
+/// The name of the file the predefined macros are defined in.
+pub(crate) const BUILT_IN: &str = "<built-in>";
+
+/// The predefined macros that the standard requires and forbids defining or undefining, besides
+/// the built-in ones (C17 6.10.8p2).
+pub(crate) const STANDARD_PREDEFINED: &[&str] = &[
+    "__DATE__",
+    "__TIME__",
+    "__STDC__",
+    "__STDC_VERSION__",
+    "__STDC_HOSTED__",
+];
+
+/// Predefined macros with a fixed value.
+const PREDEFINED: &[(&str, &str)] = &[
+    ("__STDC__", "1"),
+    ("__STDC_HOSTED__", "1"),
+    ("__STDC_UTF_16__", "1"),
+    ("__STDC_UTF_32__", "1"),
+    ("__STDC_NO_ATOMICS__", "1"),
+    ("__STDC_NO_COMPLEX__", "1"),
+    ("__STDC_NO_THREADS__", "1"),
+    ("__STDC_NO_VLA__", "1"),
+    ("__x86_64__", "1"),
+    ("__x86_64", "1"),
+    ("__linux__", "1"),
+    ("__linux", "1"),
+    ("__unix__", "1"),
+    ("__unix", "1"),
+    ("__LP64__", "1"),
+    ("_LP64", "1"),
+    ("__CHAR_BIT__", "8"),
+    ("__SIZEOF_SHORT__", "2"),
+    ("__SIZEOF_INT__", "4"),
+    ("__SIZEOF_LONG__", "8"),
+    ("__SIZEOF_LONG_LONG__", "8"),
+    ("__SIZEOF_POINTER__", "8"),
+    ("__SIZEOF_FLOAT__", "4"),
+    ("__SIZEOF_DOUBLE__", "8"),
+    ("__SIZE_TYPE__", "unsigned long"),
+    ("__PTRDIFF_TYPE__", "long"),
+    ("__WCHAR_TYPE__", "int"),
+    ("__ORDER_LITTLE_ENDIAN__", "1234"),
+    ("__ORDER_BIG_ENDIAN__", "4321"),
+    ("__BYTE_ORDER__", "__ORDER_LITTLE_ENDIAN__"),
+];
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Converts days since the Unix epoch to a year, month and day.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+const fn civil_from_days(days: i64) -> (i64, usize, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month as usize, day)
+}
+
+impl Preprocessor {
+    /// Defines the dynamic predefined macros and returns the definitions of the rest.
+    pub(crate) fn predefined(&mut self) -> String {
+        for (name, builtin) in [
+            ("__FILE__", Builtin::File),
+            ("__LINE__", Builtin::Line),
+            ("__COUNTER__", Builtin::Counter),
+            ("__has_include", Builtin::HasInclude),
+            ("__has_include_next", Builtin::HasInclude),
+        ] {
+            self.macros
+                .insert(Rc::from(name), Rc::new(Macro::builtin(builtin)));
+        }
+        let version = match self.standard {
+            Standard::C17 => "201710L",
+            Standard::C23 => "202311L",
+        };
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs().cast_signed());
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+        let time = seconds.rem_euclid(86400);
+        let (hours, minutes, seconds) = (time / 3600, time / 60 % 60, time % 60);
+        let mut text = format!(
+            "#define __STDC_VERSION__ {version}\n\
+             #define __DATE__ \"{} {day:2} {year}\"\n\
+             #define __TIME__ \"{hours:02}:{minutes:02}:{seconds:02}\"\n",
+            MONTHS[month - 1]
+        );
+        for (name, value) in PREDEFINED {
+            text += &format!("#define {name} {value}\n");
+        }
+        text
+    }
+}
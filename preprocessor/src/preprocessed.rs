@@ -0,0 +1,160 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! The output of the preprocessor, along with where each of its tokens came from.
+
+use std::rc::Rc;
+
+use diagnostics::{AggregateError, ErrorComponent, ErrorLevel};
+use lexer::{LexError, Logos, Standard, Token};
+use source::{SourceFile, Span};
+
+/// Where a token was spelled, and the macro expansions it came out of.
+#[derive(Debug, Clone)]
+pub struct Origin {
+    /// The file the token was spelled in
+    pub source: SourceFile,
+    pub span: Span,
+    /// The innermost macro expansion the token came out of
+    pub expansion: Option<Rc<Expansion>>,
+}
+
+/// A single expansion of a macro.
+#[derive(Debug)]
+pub struct Expansion {
+    pub name: Rc<str>,
+    /// The name of the macro where it was expanded, which may itself come from an expansion
+    pub invocation: Origin,
+    /// The last token of the invocation, the `)` after the arguments of a function-like macro
+    pub end: Origin,
+}
+
+/// A preprocessed translation unit: the text with line markers and the tokens written to it.
+#[derive(Debug, Clone)]
+pub struct Preprocessed {
+    source: SourceFile,
+    /// The tokens in order, with their spans in [Self::source]
+    tokens: Vec<(Span, Origin)>,
+    /// The file that was preprocessed, as it was written
+    file: SourceFile,
+    /// The spans of the preprocessing tokens of [Self::file], directives included
+    spelled: Vec<Span>,
+    standard: Standard,
+}
+
+impl Preprocessed {
+    pub(crate) const fn new(
+        source: SourceFile,
+        tokens: Vec<(Span, Origin)>,
+        file: SourceFile,
+        spelled: Vec<Span>,
+        standard: Standard,
+    ) -> Self {
+        Self {
+            source,
+            tokens,
+            file,
+            spelled,
+            standard,
+        }
+    }
+    /// The text with line markers, which the spans of the tokens point into.
+    #[must_use]
+    pub const fn source(&self) -> &SourceFile {
+        &self.source
+    }
+    /// The file that was preprocessed, as it was written.
+    #[must_use]
+    pub const fn file(&self) -> &SourceFile {
+        &self.file
+    }
+    /// Converts the preprocessing tokens into tokens, translation phase 7 in C17 5.1.1.2.
+    ///
+    /// Adjacent string literals are left for [`lexer::ConcatStrings`] to join.
+    pub fn tokens(&self) -> impl Iterator<Item = (Result<Token<'_>, LexError>, Span)> {
+        let spans = self.tokens.iter().map(|(span, _)| span);
+        lex(self.source.text(), spans, self.standard)
+    }
+    /// The tokens of [Self::file] as they were written, including those of directives and of
+    /// groups that were skipped.
+    pub fn spelled_tokens(&self) -> impl Iterator<Item = (Result<Token<'_>, LexError>, Span)> {
+        lex(self.file.text(), self.spelled.iter(), self.standard)
+    }
+    /// Where the token at `offset` in [Self::source] was spelled.
+    #[must_use]
+    pub fn origin(&self, offset: usize) -> Option<&Origin> {
+        let idx = self.tokens.partition_point(|(span, _)| span.end <= offset);
+        let (span, origin) = self.tokens.get(idx)?;
+        span.contains(&offset).then_some(origin)
+    }
+    /// Maps a span of [Self::source] to where it was written in [Self::file].
+    ///
+    /// A span that starts or ends in a macro expansion is widened to the whole invocation, and
+    /// one that doesn't map to the file, as it came from a header, gives [None].
+    #[must_use]
+    pub fn original_span(&self, span: &Span) -> Option<Span> {
+        let mut start = self.origin(span.start)?;
+        while let Some(expansion) = &start.expansion {
+            start = &expansion.invocation;
+        }
+        let mut end = self.origin(span.end.checked_sub(1)?)?;
+        while let Some(expansion) = &end.expansion {
+            end = &expansion.end;
+        }
+        let in_file = start.source.is_same(&self.file) && end.source.is_same(&self.file);
+        (in_file && start.span.start < end.span.end).then_some(start.span.start..end.span.end)
+    }
+    /// Follows every diagnostic that points at a token from a macro expansion with a note for
+    /// each expansion, showing where the token was spelled and where that macro was invoked.
+    pub fn explain(&self, errors: &mut AggregateError) {
+        for component in std::mem::take(&mut errors.components) {
+            let explains =
+                component.level != ErrorLevel::Note && component.source().is_same(&self.source);
+            let start = component.highlight().start;
+            errors.components.push(component);
+            if !explains {
+                continue;
+            }
+            let mut origin = self.origin(start);
+            while let Some(Origin {
+                source,
+                span,
+                expansion: Some(expansion),
+            }) = origin
+            {
+                let message = format!("Expanded from macro `{}`", expansion.name);
+                errors
+                    .add_error(ErrorComponent::new(source.clone(), message, span.clone()))
+                    .set_level(ErrorLevel::Note);
+                origin = Some(&expansion.invocation);
+            }
+        }
+    }
+}
+
+/// Lexes the preprocessing tokens at `spans` in `text` into tokens.
+fn lex<'a>(
+    text: &'a str,
+    spans: impl Iterator<Item = &'a Span>,
+    standard: Standard,
+) -> impl Iterator<Item = (Result<Token<'a>, LexError>, Span)> {
+    spans.flat_map(move |span| {
+        let start = span.start;
+        Token::lexer_with_extras(&text[span.clone()], standard)
+            .spanned()
+            .map(move |(token, inner)| (token, start + inner.start..start + inner.end))
+    })
+}
@@ -0,0 +1,244 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use std::rc::Rc;
+
+use lexer::{Logos, Token};
+use source::Span;
+
+use crate::Expansion;
+
+/// A preprocessing token.
+///
+/// Tokens are kept as text until the preprocessor is done with them, and are only lexed into
+/// [`Token`]s once they have been written to the output.
+#[derive(Debug, Clone)]
+pub(crate) struct PpToken {
+    pub text: Rc<str>,
+    /// Whether the token was preceded by whitespace
+    pub space: bool,
+    /// Whether the token is the first on its line, only ever set for tokens read from a file
+    pub line_start: bool,
+    /// Index of the file the token was spelled in, which is the file of the macro definition
+    /// for tokens from a macro body
+    pub src: usize,
+    pub span: Span,
+    /// The innermost macro expansion the token came out of
+    pub expansion: Option<Rc<Expansion>>,
+    /// Where the token is placed in the output
+    pub pos: Pos,
+    /// The macros that may not be expanded again in this token
+    pub hide: Vec<Rc<str>>,
+}
+
+/// A presumed location, as affected by `#line`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Pos {
+    pub name: Rc<str>,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl PpToken {
+    pub fn is(&self, text: &str) -> bool {
+        *self.text == *text
+    }
+    pub fn is_ident(&self) -> bool {
+        self.text
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+    }
+    pub fn is_hash(&self) -> bool {
+        self.is("#") || self.is("%:")
+    }
+    pub fn is_hash_hash(&self) -> bool {
+        self.is("##") || self.is("%:%:")
+    }
+    pub fn is_string(&self) -> bool {
+        let text = self.text.trim_start_matches(['u', '8', 'U', 'L']);
+        text.starts_with('"') && text.len() >= 2 && text.ends_with('"')
+    }
+    /// Whether the token is a string literal or character constant.
+    pub fn is_literal(&self) -> bool {
+        let text = self.text.trim_start_matches(['u', '8', 'U', 'L']);
+        text.starts_with(['"', '\''])
+    }
+    pub fn hidden(&self, name: &str) -> bool {
+        self.hide.iter().any(|h| **h == *name)
+    }
+    /// A token with the given text that takes everything else from `self`.
+    pub fn with_text(&self, text: impl Into<Rc<str>>) -> Self {
+        Self {
+            text: text.into(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Performs translation phases 1 and 2 and the comment removal of phase 3.
+///
+/// Line splices and comments are replaced with spaces to keep the byte offsets of every token the
+/// same as in the original text. The newlines in block comments are kept.
+pub(crate) fn clean(text: &str) -> String {
+    #[derive(PartialEq)]
+    enum State {
+        Normal,
+        Quoted(u8),
+        LineComment,
+        BlockComment,
+    }
+    let mut bytes = text.as_bytes().to_vec();
+    let mut state = State::Normal;
+    let mut i = 0;
+    while i < bytes.len() {
+        // Line splices are removed before anything else
+        let splice = match &bytes[i..] {
+            [b'\\', b'\n', ..] => 2,
+            [b'\\', b'\r', b'\n', ..] => 3,
+            _ => 0,
+        };
+        if splice != 0 {
+            bytes[i..i + splice].fill(b' ');
+            i += splice;
+            continue;
+        }
+        let next = bytes.get(i + 1).copied();
+        match state {
+            State::Normal => match (bytes[i], next) {
+                (q @ (b'"' | b'\''), _) => state = State::Quoted(q),
+                (b'/', Some(b'/')) => {
+                    state = State::LineComment;
+                    continue;
+                }
+                (b'/', Some(b'*')) => {
+                    bytes[i..i + 2].fill(b' ');
+                    state = State::BlockComment;
+                    i += 2;
+                    continue;
+                }
+                _ => (),
+            },
+            State::Quoted(q) => match bytes[i] {
+                b'\\' => i += 1,
+                b'\n' => state = State::Normal,
+                b if b == q => state = State::Normal,
+                _ => (),
+            },
+            State::LineComment => {
+                if bytes[i] == b'\n' {
+                    state = State::Normal;
+                } else {
+                    bytes[i] = b' ';
+                }
+            }
+            State::BlockComment => {
+                if bytes[i] == b'*' && next == Some(b'/') {
+                    bytes[i..i + 2].fill(b' ');
+                    state = State::Normal;
+                    i += 2;
+                    continue;
+                }
+                if bytes[i] != b'\n' {
+                    bytes[i] = b' ';
+                }
+            }
+        }
+        i += 1;
+    }
+    // Only whole characters in comments were replaced
+    String::from_utf8(bytes).expect("cleaning should preserve UTF-8")
+}
+
+/// Splits cleaned text into preprocessing tokens, leaving [`PpToken::pos`] to be filled in once
+/// the token is read.
+pub(crate) fn tokenize(src: usize, text: &str) -> Vec<PpToken> {
+    let mut lexer = Token::lexer(text);
+    let mut spans = Vec::new();
+    let mut prev_end = 0;
+    // Lexer errors are reported once the tokens are converted by `Preprocessed::tokens`
+    while lexer.next().is_some() {
+        let span = lexer.span();
+        line_markers(text, prev_end..span.start, &mut spans);
+        prev_end = span.end;
+        spans.push(span);
+    }
+    line_markers(text, prev_end..text.len(), &mut spans);
+    let mut tokens = Vec::new();
+    let mut prev_end = 0;
+    let pos = Pos {
+        name: Rc::from(""),
+        line: 0,
+        col: 0,
+    };
+    for span in spans {
+        let gap = &text[prev_end..span.start];
+        tokens.push(PpToken {
+            text: Rc::from(&text[span.clone()]),
+            space: !gap.is_empty(),
+            line_start: tokens.is_empty() || gap.contains('\n'),
+            src,
+            span: span.clone(),
+            expansion: None,
+            pos: pos.clone(),
+            hide: Vec::new(),
+        });
+        prev_end = span.end;
+    }
+    tokens
+}
+
+/// Splits the line markers in text skipped by the lexer into the spans of their tokens, so that
+/// the preprocessor sees them as directives. Comments have been cleaned from the text, so any `#`
+/// in it starts a line marker, `# <line> "<file>" <flags>`.
+fn line_markers(text: &str, skipped: Span, spans: &mut Vec<Span>) {
+    let bytes = text.as_bytes();
+    let mut idx = skipped.start;
+    while idx < skipped.end {
+        let start = idx;
+        idx += 1;
+        match bytes[start] {
+            b'#' => (),
+            b'"' => {
+                while bytes[idx] != b'"' {
+                    idx += if bytes[idx] == b'\\' { 2 } else { 1 };
+                }
+                idx += 1;
+            }
+            b'0'..=b'9' => {
+                while idx < skipped.end && bytes[idx].is_ascii_digit() {
+                    idx += 1;
+                }
+            }
+            _ => continue,
+        }
+        spans.push(start..idx);
+    }
+}
+
+/// Whether two tokens would lex as something else when written without a space in between.
+pub(crate) fn pastes(left: &str, right: &str) -> bool {
+    let joined = format!("{left}{right}");
+    let mut lexer = Token::lexer(&joined);
+    lexer.next();
+    lexer.span().end != left.len()
+}
+
+/// Whether the text is exactly one preprocessing token.
+pub(crate) fn is_single_token(text: &str) -> bool {
+    let mut lexer = Token::lexer(text);
+    lexer.next().is_some() && lexer.span() == (0..text.len()) && lexer.next().is_none()
+}
@@ -17,11 +17,15 @@
 use std::{cell::OnceCell, num::NonZero, ops::Range, rc::Rc};
 
 mod lines;
+mod markers;
 pub use lines::*;
+use markers::LineMap;
+pub use markers::Location;
 
 /// A cheaply [Clone]able container for source code.
 ///
-/// Allows for efficiently mapping from byte offsets to line and column values.
+/// Allows for efficiently mapping from byte offsets to line and column values,
+/// and from lines of preprocessed text back to the files they came from.
 #[derive(Clone, Debug)]
 pub struct SourceFile(Rc<SourceStorage>);
 
@@ -31,6 +35,11 @@ impl SourceFile {
         let storage = SourceStorage::new(path, text);
         Self(Rc::new(storage))
     }
+    /// Whether both refer to the same file, rather than to files with the same contents.
+    #[must_use]
+    pub fn is_same(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
     #[must_use]
     pub fn path(&self) -> &str {
         &self.0.path
@@ -71,6 +80,20 @@ impl SourceFile {
             column_bytes,
         }
     }
+    /// Maps a 0-indexed line of the text to its original location, following any line markers
+    /// left by the preprocessor.
+    ///
+    /// Returns [None] for the line markers themselves and for lines out of bounds.
+    #[must_use]
+    pub fn line_location(&self, line: usize) -> Option<Location<'_>> {
+        self.0.line_map().location(line)
+    }
+    /// Returns the locations of the `#include`s through which a 0-indexed line was reached,
+    /// innermost first.
+    #[must_use]
+    pub fn included_from(&self, line: usize) -> Vec<Location<'_>> {
+        self.0.line_map().included_from(line)
+    }
     #[must_use]
     pub fn lines(&self) -> LineIterator<'_> {
         let starts = self.0.line_starts();
@@ -117,6 +140,7 @@ struct SourceStorage {
     text: String,
     path: String,
     line_starts: OnceCell<Box<[usize]>>,
+    line_map: OnceCell<LineMap>,
 }
 
 impl SourceStorage {
@@ -126,6 +150,7 @@ impl SourceStorage {
             text,
             path,
             line_starts: OnceCell::new(),
+            line_map: OnceCell::new(),
         }
     }
     fn line_starts(&self) -> &[usize] {
@@ -139,4 +164,8 @@ impl SourceStorage {
             core::iter::once(0).chain(newlines).collect()
         })
     }
+    fn line_map(&self) -> &LineMap {
+        self.line_map
+            .get_or_init(|| LineMap::new(&self.path, &self.text, self.line_starts()))
+    }
 }
@@ -0,0 +1,223 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! Support for the line markers emitted by the preprocessor.
+//!
+//! A line marker has the form `# <line> "<file>" <flags>` and states that the next line of the
+//! preprocessed text is line `<line>` of `<file>`. Flag `1` marks entering an included file and
+//! flag `2` marks returning to the including file.
+
+/// The location in the original sources that a line of preprocessed text came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location<'s> {
+    pub path: &'s str,
+    /// Starts at 1
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct MappedLine {
+    file: usize,
+    line: usize,
+    /// Index into [`LineMap::includes`] of the `#include` that brought this file in
+    include: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Include {
+    file: usize,
+    line: usize,
+    parent: Option<usize>,
+}
+
+#[derive(Debug)]
+pub(crate) struct LineMap {
+    files: Vec<String>,
+    /// One entry per line of the preprocessed text, [None] for the line markers themselves
+    lines: Box<[Option<MappedLine>]>,
+    includes: Vec<Include>,
+}
+
+impl LineMap {
+    pub(crate) fn new(path: &str, text: &str, line_starts: &[usize]) -> Self {
+        let mut files = vec![path.to_owned()];
+        let mut includes = Vec::new();
+        let mut current = MappedLine {
+            file: 0,
+            line: 1,
+            include: None,
+        };
+        let ends = line_starts.iter().skip(1).copied().chain([text.len()]);
+        let lines = line_starts
+            .iter()
+            .zip(ends)
+            .map(|(&start, end)| {
+                let Some(marker) = Marker::parse(&text[start..end]) else {
+                    let line = current;
+                    current.line += 1;
+                    return Some(line);
+                };
+                if marker.enters {
+                    includes.push(Include {
+                        file: current.file,
+                        line: current.line,
+                        parent: current.include,
+                    });
+                    current.include = Some(includes.len() - 1);
+                } else if marker.leaves {
+                    current.include = current.include.and_then(|idx| includes[idx].parent);
+                }
+                current.file = match files.iter().position(|f| *f == marker.path) {
+                    Some(idx) => idx,
+                    None => {
+                        files.push(marker.path);
+                        files.len() - 1
+                    }
+                };
+                current.line = marker.line;
+                None
+            })
+            .collect();
+        Self {
+            files,
+            lines,
+            includes,
+        }
+    }
+    pub(crate) fn location(&self, line: usize) -> Option<Location<'_>> {
+        let mapped = (*self.lines.get(line)?)?;
+        Some(Location {
+            path: &self.files[mapped.file],
+            line: mapped.line,
+        })
+    }
+    /// Returns the locations of the `#include`s leading to a line, innermost first.
+    pub(crate) fn included_from(&self, line: usize) -> Vec<Location<'_>> {
+        let mut include = self
+            .lines
+            .get(line)
+            .copied()
+            .flatten()
+            .and_then(|l| l.include);
+        let mut chain = Vec::new();
+        while let Some(idx) = include {
+            let Include { file, line, parent } = self.includes[idx];
+            chain.push(Location {
+                path: &self.files[file],
+                line,
+            });
+            include = parent;
+        }
+        chain
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Marker {
+    line: usize,
+    path: String,
+    enters: bool,
+    leaves: bool,
+}
+
+impl Marker {
+    fn parse(line: &str) -> Option<Self> {
+        let rest = line.strip_prefix('#')?.trim_start_matches([' ', '\t']);
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let number = rest[..digits].parse().ok()?;
+        let rest = rest[digits..].trim_start_matches([' ', '\t']);
+        let mut chars = rest.strip_prefix('"')?.char_indices();
+        let mut path = String::new();
+        let end = loop {
+            match chars.next()? {
+                (idx, '"') => break idx,
+                (_, '\\') => path.push(chars.next()?.1),
+                (_, '\n') => return None,
+                (_, c) => path.push(c),
+            }
+        };
+        let mut flags = rest[end + 2..].split_ascii_whitespace();
+        let flag = flags.next();
+        Some(Self {
+            line: number,
+            path,
+            enters: flag == Some("1"),
+            leaves: flag == Some("2"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Location, SourceFile};
+
+    const PREPROCESSED: &str = r#"# 0 "a.c"
+# 0 "<built-in>"
+# 0 "<command-line>"
+# 1 "/usr/include/stdc-predef.h" 1 3 4
+# 0 "<command-line>" 2
+# 1 "a.c"
+# 1 "b.h" 1
+# 1 "c.h" 1
+int y;
+# 2 "b.h" 2
+int x;
+# 2 "a.c" 2
+
+int main(void) {
+"#;
+
+    fn loc(path: &str, line: usize) -> Location<'_> {
+        Location { path, line }
+    }
+
+    #[test]
+    fn lines_map_to_original_files() {
+        let source = SourceFile::new("a.c.i".to_owned(), PREPROCESSED.to_owned());
+        assert_eq!(source.line_location(0), None);
+        assert_eq!(source.line_location(8), Some(loc("c.h", 1)));
+        assert_eq!(source.line_location(10), Some(loc("b.h", 2)));
+        assert_eq!(source.line_location(12), Some(loc("a.c", 2)));
+        assert_eq!(source.line_location(13), Some(loc("a.c", 3)));
+    }
+
+    #[test]
+    fn include_chain() {
+        let source = SourceFile::new("a.c.i".to_owned(), PREPROCESSED.to_owned());
+        assert_eq!(source.included_from(8), [loc("b.h", 1), loc("a.c", 1)]);
+        assert_eq!(source.included_from(10), [loc("a.c", 1)]);
+        assert_eq!(source.included_from(13), []);
+    }
+
+    #[test]
+    fn text_without_markers_maps_to_itself() {
+        let source = SourceFile::new("x.c".to_owned(), "int\n#define\nx;".to_owned());
+        assert_eq!(source.line_location(0), Some(loc("x.c", 1)));
+        assert_eq!(source.line_location(2), Some(loc("x.c", 3)));
+        assert_eq!(source.line_location(3), None);
+    }
+
+    #[test]
+    fn escaped_file_names() {
+        let source = SourceFile::new(
+            "x.i".to_owned(),
+            "# 7 \"dir\\\\a \\\"b\\\".h\"\nint x;".to_owned(),
+        );
+        assert_eq!(source.line_location(1), Some(loc("dir\\a \"b\".h", 7)));
+    }
+}
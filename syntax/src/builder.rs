@@ -0,0 +1,172 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! Builds the syntax tree along with the parser, which adds every token as it reads it and every
+//! node once it has parsed the tokens in it.
+
+use std::rc::Rc;
+
+use lexer::Token;
+use source::{SourceFile, Span};
+
+use crate::{
+    SyntaxKind,
+    green::{GreenElement, GreenNode, GreenToken},
+    red::SyntaxNode,
+    trivia,
+};
+
+/// An element of a tree that is still being built, with its span in the text.
+#[derive(Debug)]
+enum Element {
+    Token(SyntaxKind, Span),
+    Node(SyntaxKind, Span, Vec<Element>),
+}
+
+impl Element {
+    const fn span(&self) -> &Span {
+        match self {
+            Self::Token(_, span) | Self::Node(_, span, _) => span,
+        }
+    }
+    fn into_green(self, text: &str) -> GreenElement {
+        match self {
+            Self::Token(kind, span) => {
+                GreenElement::Token(Rc::new(GreenToken::new(kind, &text[span])))
+            }
+            Self::Node(kind, _, children) => {
+                let children = children.into_iter().map(|child| child.into_green(text));
+                GreenElement::Node(Rc::new(GreenNode::new(kind, children.collect())))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Builder {
+    source: SourceFile,
+    /// The elements that aren't inside a node yet, in order
+    elements: Vec<Element>,
+    /// The end of the last token
+    end: usize,
+}
+
+impl Builder {
+    #[must_use]
+    pub const fn new(source: SourceFile) -> Self {
+        Self {
+            source,
+            elements: Vec::new(),
+            end: 0,
+        }
+    }
+    /// Adds the next token, along with the trivia between it and the last one.
+    ///
+    /// The text may not have been preprocessed, so a `#` that starts a line is kept whole as a
+    /// preprocessing directive or line marker, taking in the tokens after it on its line.
+    pub fn token(&mut self, kind: SyntaxKind, span: Span) {
+        if span.start < self.end {
+            // The token is on the line of a directive, which may have to reach further to take
+            // in all of it
+            if span.end > self.end {
+                extend_last(&mut self.elements, span.end);
+                self.end = span.end;
+            }
+            return;
+        }
+        self.trivia(self.end..span.start);
+        let text = self.source.text();
+        let (kind, span) =
+            if kind == SyntaxKind::from(&Token::Hash) && trivia::starts_line(text, span.start) {
+                let end = trivia::logical_line_end(text, span.start).max(span.end);
+                let kind = if trivia::is_line_marker(&text[span.start..end]) {
+                    SyntaxKind::LineMarker
+                } else {
+                    SyntaxKind::Directive
+                };
+                (kind, span.start..end)
+            } else {
+                (kind, span)
+            };
+        self.end = span.end;
+        self.elements.push(Element::Token(kind, span));
+    }
+    /// Adds a node over the elements in `span`, such as the span of an `ast` node that was just
+    /// parsed. The nodes inside it have to be added first.
+    ///
+    /// Empty spans, which `ast` nodes that weren't written out have, are skipped.
+    pub fn node(&mut self, kind: SyntaxKind, span: &Span) {
+        if span.is_empty() {
+            return;
+        }
+        let mut elements = &mut self.elements;
+        loop {
+            let start = elements.partition_point(|element| element.span().end <= span.start);
+            let end = elements.partition_point(|element| element.span().start < span.end);
+            if start >= end {
+                return;
+            }
+            // A node can't cut through another, so it is put inside a node that covers it, or
+            // else takes in every element it overlaps
+            let covered = matches!(
+                &elements[start..end],
+                [Element::Node(_, outer, _)]
+                    if outer.start <= span.start && span.end <= outer.end && outer != span
+            );
+            if covered {
+                let Element::Node(_, _, children) = &mut elements[start] else {
+                    unreachable!("the covering element is a node")
+                };
+                elements = children;
+                continue;
+            }
+            let children: Vec<_> = elements.drain(start..end).collect();
+            let outer = children[0].span().start..children[children.len() - 1].span().end;
+            elements.insert(start, Element::Node(kind, outer, children));
+            return;
+        }
+    }
+    /// Finishes the tree with the trivia after the last token.
+    #[must_use]
+    pub fn finish(mut self) -> SyntaxNode {
+        self.trivia(self.end..self.source.text().len());
+        let text = self.source.text();
+        let children = self
+            .elements
+            .into_iter()
+            .map(|element| element.into_green(text));
+        let root = GreenNode::new(SyntaxKind::Program, children.collect());
+        SyntaxNode::new_root(Rc::new(root))
+    }
+    fn trivia(&mut self, span: Span) {
+        let elements = &mut self.elements;
+        trivia::split(self.source.text(), span, |kind, span| {
+            elements.push(Element::Token(kind, span));
+        });
+    }
+}
+
+/// Extends the last token in `elements`, and the nodes it is in, up to `end`.
+fn extend_last(elements: &mut [Element], end: usize) {
+    match elements.last_mut() {
+        Some(Element::Token(_, span)) => span.end = end,
+        Some(Element::Node(_, span, children)) => {
+            span.end = end;
+            extend_last(children, end);
+        }
+        None => (),
+    }
+}
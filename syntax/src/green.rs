@@ -0,0 +1,133 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! The green tree, which holds the kinds and text of the syntax tree without any positions, so
+//! identical subtrees can be shared.
+
+use std::{fmt, rc::Rc};
+
+use crate::SyntaxKind;
+
+/// A token, including trivia, along with its text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GreenToken {
+    kind: SyntaxKind,
+    text: Box<str>,
+}
+
+impl GreenToken {
+    #[must_use]
+    pub fn new(kind: SyntaxKind, text: &str) -> Self {
+        Self {
+            kind,
+            text: text.into(),
+        }
+    }
+    #[must_use]
+    pub const fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenElement {
+    #[must_use]
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            Self::Node(node) => node.kind(),
+            Self::Token(token) => token.kind(),
+        }
+    }
+    /// The length of the text of the element in bytes.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Node(node) => node.len(),
+            Self::Token(token) => token.len(),
+        }
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A node of the syntax tree, whose text is the text of its children.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GreenNode {
+    kind: SyntaxKind,
+    len: usize,
+    children: Vec<GreenElement>,
+}
+
+impl GreenNode {
+    #[must_use]
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Self {
+        let len = children.iter().map(GreenElement::len).sum();
+        Self {
+            kind,
+            len,
+            children,
+        }
+    }
+    #[must_use]
+    pub const fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+    /// The length of the text of the node in bytes.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    #[must_use]
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+}
+
+/// Writes the text the node was built from.
+impl fmt::Display for GreenNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for child in &self.children {
+            match child {
+                GreenElement::Node(node) => node.fmt(f)?,
+                GreenElement::Token(token) => f.write_str(token.text())?,
+            }
+        }
+        Ok(())
+    }
+}
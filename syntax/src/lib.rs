@@ -0,0 +1,238 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! A lossless syntax tree, which keeps every token of the text along with the whitespace,
+//! comments, line markers and preprocessing directives between them, so the text can be
+//! reproduced byte for byte.
+//!
+//! The tree is split in two like Roslyn's: the green tree only holds the kinds and text of its
+//! elements, and the red tree of [SyntaxNode]s is a view over it that knows their positions and
+//! parents.
+//!
+//! The parser builds the tree with a [Builder] as it goes, from the tokens it reads, and makes a
+//! node of every `ast` node it parses, so `ast::Program` is a typed view over the tree. Text the
+//! parser skipped over after an error is still in the tree, outside of any node.
+
+use lexer::Token;
+use source::{SourceFile, Span};
+
+mod builder;
+mod green;
+mod red;
+mod trivia;
+
+pub use builder::Builder;
+pub use green::{GreenElement, GreenNode, GreenToken};
+pub use red::{SyntaxElement, SyntaxNode, SyntaxToken};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    // Nodes, one for each kind of `ast` node with a span
+    Program,
+    Function,
+    Parameter,
+    Variable,
+    TypeDeclaration,
+    /// A member of a struct or union
+    Member,
+    Enumerator,
+    Statement,
+    Expression,
+    /// A brace-enclosed initializer list
+    InitializerList,
+    Designator,
+    // Tokens
+    /// A C token, with the name of its [lexer::Token] variant
+    Token(&'static str),
+    Whitespace,
+    Comment,
+    /// A line marker left by the preprocessor
+    LineMarker,
+    /// A preprocessing directive, with any lines it continues onto
+    Directive,
+    /// Text that isn't a valid token
+    Error,
+}
+
+impl SyntaxKind {
+    /// Whether the kind is of a token that the parser never sees.
+    #[must_use]
+    pub const fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            Self::Whitespace | Self::Comment | Self::LineMarker | Self::Directive
+        )
+    }
+}
+
+/// The kind of a token the parser reads, where [Token::Invalid] is text that failed to lex.
+impl From<&Token<'_>> for SyntaxKind {
+    fn from(token: &Token<'_>) -> Self {
+        match token {
+            Token::Invalid => Self::Error,
+            token => Self::Token(token.kind()),
+        }
+    }
+}
+
+/// Builds the syntax tree of the file that was preprocessed into the text `tree` was parsed
+/// from, out of `tokens`, the tokens of the file as it was written.
+///
+/// Unlike `tree`, the new tree has the directives, comments and macros of the file as they were
+/// written. Its nodes are those of `tree` with their spans mapped to the file by `original`,
+/// which should widen a span that starts or ends in a macro expansion to the whole invocation,
+/// and leave out spans that don't map to the file, such as those of nodes from headers.
+#[must_use]
+pub fn build_original(
+    source: SourceFile,
+    tokens: impl IntoIterator<Item = (SyntaxKind, Span)>,
+    tree: &SyntaxNode,
+    original: impl Fn(&Span) -> Option<Span>,
+) -> SyntaxNode {
+    let mut builder = Builder::new(source);
+    for (kind, span) in tokens {
+        builder.token(kind, span);
+    }
+    add_nodes(&mut builder, tree, &original);
+    builder.finish()
+}
+
+/// Adds the nodes inside `node` to `builder`, each after the nodes inside it.
+fn add_nodes(builder: &mut Builder, node: &SyntaxNode, original: &impl Fn(&Span) -> Option<Span>) {
+    for child in node.children() {
+        add_nodes(builder, &child, original);
+        if let Some(span) = original(&child.span()) {
+            builder.node(child.kind(), &span);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lexer::{Logos, Token};
+    use pretty_assertions::assert_eq;
+    use source::SourceFile;
+
+    use crate::{Builder, SyntaxKind};
+
+    /// A builder that was given every token of `text`.
+    fn tokens(text: &str) -> Builder {
+        let source = SourceFile::new("test.c".to_owned(), text.to_owned());
+        let mut builder = Builder::new(source);
+        for (token, span) in Token::lexer(text).spanned() {
+            let kind = token.map_or(SyntaxKind::Error, |token| SyntaxKind::from(&token));
+            builder.token(kind, span);
+        }
+        builder
+    }
+
+    #[test]
+    fn lossless() {
+        let text = "// header\n# 1 \"a.c\"\nint x = 1 /* one */ + 2;\n";
+        let mut builder = tokens(text);
+        builder.node(SyntaxKind::Expression, &(28..29));
+        builder.node(SyntaxKind::Expression, &(42..43));
+        builder.node(SyntaxKind::Expression, &(28..43));
+        builder.node(SyntaxKind::Variable, &(20..43));
+        // Nodes that weren't written out are left out
+        builder.node(SyntaxKind::Parameter, &(44..44));
+        let root = builder.finish();
+        assert_eq!(root.to_string(), text);
+        assert_eq!(
+            format!("{root:?}"),
+            r##"Program@0..45
+  Comment@0..9 "// header"
+  Whitespace@9..10 "\n"
+  LineMarker@10..19 "# 1 \"a.c\""
+  Whitespace@19..20 "\n"
+  Variable@20..43
+    Token("Int")@20..23 "int"
+    Whitespace@23..24 " "
+    Token("Ident")@24..25 "x"
+    Whitespace@25..26 " "
+    Token("Eq")@26..27 "="
+    Whitespace@27..28 " "
+    Expression@28..43
+      Expression@28..29
+        Token("IntLit")@28..29 "1"
+      Whitespace@29..30 " "
+      Comment@30..39 "/* one */"
+      Whitespace@39..40 " "
+      Token("Plus")@40..41 "+"
+      Whitespace@41..42 " "
+      Expression@42..43
+        Token("IntLit")@42..43 "2"
+  Token("Semicolon")@43..44 ";"
+  Whitespace@44..45 "\n"
+"##
+        );
+    }
+
+    #[test]
+    fn nodes_nest() {
+        let text = "f(a, b) + c;";
+        let mut builder = tokens(text);
+        builder.node(SyntaxKind::Expression, &(0..11));
+        // A node inside one that was already added goes inside it
+        builder.node(SyntaxKind::Expression, &(2..3));
+        // One that starts inside another takes it in
+        builder.node(SyntaxKind::Statement, &(8..12));
+        let root = builder.finish();
+        assert_eq!(root.to_string(), text);
+        let statement = root.children().next().unwrap();
+        assert_eq!(statement.kind(), SyntaxKind::Statement);
+        let expression = statement.children().next().unwrap();
+        assert_eq!(expression.to_string(), "f(a, b) + c");
+        assert_eq!(root.find(&(2..3)).unwrap().parent(), Some(&expression));
+        let errors: Vec<_> = tokens("int @ y;")
+            .finish()
+            .tokens()
+            .into_iter()
+            .filter(|t| t.kind() == SyntaxKind::Error)
+            .map(|t| t.text().to_owned())
+            .collect();
+        assert_eq!(errors, ["@"]);
+    }
+
+    #[test]
+    fn directives_and_markers() {
+        let text = "#include <stdio.h>\n  #  define X \\\n\t1\n# 12 \"a.c\" 2\n#line 4 \"b.c\"\n#pragma \"a\\\"b\"\n/*\n# not a directive */ int a # b;\n";
+        let root = tokens(text).finish();
+        assert_eq!(root.to_string(), text);
+        let tokens: Vec<_> = root
+            .tokens()
+            .into_iter()
+            .filter(|t| t.kind() != SyntaxKind::Whitespace)
+            .map(|t| (t.kind(), t.text().to_owned()))
+            .collect();
+        let expected = [
+            (SyntaxKind::Directive, "#include <stdio.h>"),
+            (SyntaxKind::Directive, "#  define X \\\n\t1"),
+            (SyntaxKind::LineMarker, "# 12 \"a.c\" 2"),
+            (SyntaxKind::Directive, "#line 4 \"b.c\""),
+            (SyntaxKind::Directive, "#pragma \"a\\\"b\""),
+            (SyntaxKind::Comment, "/*\n# not a directive */"),
+            (SyntaxKind::Token("Int"), "int"),
+            (SyntaxKind::Token("Ident"), "a"),
+            (SyntaxKind::Token("Hash"), "#"),
+            (SyntaxKind::Token("Ident"), "b"),
+            (SyntaxKind::Token("Semicolon"), ";"),
+        ]
+        .map(|(kind, text)| (kind, text.to_owned()));
+        assert_eq!(tokens, expected);
+        assert!(tokens[..6].iter().all(|(kind, _)| kind.is_trivia()));
+    }
+}
@@ -0,0 +1,218 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! The red tree, a view over the green tree that knows the position of every element and can
+//! walk back up to its parents. Red nodes are created as the tree is walked.
+
+use std::{fmt, rc::Rc};
+
+use source::Span;
+
+use crate::{
+    SyntaxKind,
+    green::{GreenElement, GreenNode, GreenToken},
+};
+
+struct NodeData {
+    green: Rc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    /// The offset of the node in the text of the tree
+    offset: usize,
+}
+
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+/// Nodes are equal if they are the same node of the same tree.
+impl PartialEq for SyntaxNode {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0.green, &other.0.green) && self.0.offset == other.0.offset
+    }
+}
+
+impl Eq for SyntaxNode {}
+
+impl SyntaxNode {
+    #[must_use]
+    pub fn new_root(green: Rc<GreenNode>) -> Self {
+        Self(Rc::new(NodeData {
+            green,
+            parent: None,
+            offset: 0,
+        }))
+    }
+    #[must_use]
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind()
+    }
+    #[must_use]
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+    #[must_use]
+    pub fn span(&self) -> Span {
+        self.0.offset..self.0.offset + self.0.green.len()
+    }
+    #[must_use]
+    pub fn parent(&self) -> Option<&Self> {
+        self.0.parent.as_ref()
+    }
+    /// The node and its parents, innermost first.
+    pub fn ancestors(&self) -> impl Iterator<Item = Self> + use<> {
+        core::iter::successors(Some(self.clone()), |node| node.parent().cloned())
+    }
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement> + use<> {
+        let parent = self.clone();
+        let mut offset = self.0.offset;
+        let children = self.0.green.children().to_vec();
+        children.into_iter().map(move |child| {
+            let start = offset;
+            offset += child.len();
+            match child {
+                GreenElement::Node(green) => SyntaxElement::Node(Self(Rc::new(NodeData {
+                    green,
+                    parent: Some(parent.clone()),
+                    offset: start,
+                }))),
+                GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                    green,
+                    parent: parent.clone(),
+                    offset: start,
+                }),
+            }
+        })
+    }
+    pub fn children(&self) -> impl Iterator<Item = Self> + use<> {
+        self.children_with_tokens().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+    /// Every token in the node, including trivia, in order.
+    #[must_use]
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+    /// The innermost node whose span contains `span`.
+    #[must_use]
+    pub fn covering_node(&self, span: &Span) -> Self {
+        let mut node = self.clone();
+        'descend: loop {
+            for child in node.children() {
+                let child_span = child.span();
+                if child_span.start <= span.start && span.end <= child_span.end {
+                    node = child;
+                    continue 'descend;
+                }
+            }
+            return node;
+        }
+    }
+    /// The outermost node with exactly the given span, such as the span of an `ast` node.
+    #[must_use]
+    pub fn find(&self, span: &Span) -> Option<Self> {
+        let covering = self.covering_node(span);
+        let mut nodes = covering.ancestors().filter(|node| node.span() == *span);
+        nodes.next().map(|first| nodes.last().unwrap_or(first))
+    }
+    fn fmt_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        writeln!(f, "{indent}{:?}@{:?}", self.kind(), self.span())?;
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) => node.fmt_tree(f, depth + 1)?,
+                SyntaxElement::Token(token) => writeln!(f, "{indent}  {token:?}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes the text the node was built from.
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.green.fmt(f)
+    }
+}
+
+/// Writes the kind and span of every element in the node, one per line.
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_tree(f, 0)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    parent: SyntaxNode,
+    offset: usize,
+}
+
+impl SyntaxToken {
+    #[must_use]
+    pub fn kind(&self) -> SyntaxKind {
+        self.green.kind()
+    }
+    #[must_use]
+    pub fn text(&self) -> &str {
+        self.green.text()
+    }
+    #[must_use]
+    pub fn span(&self) -> Span {
+        self.offset..self.offset + self.green.len()
+    }
+    #[must_use]
+    pub const fn parent(&self) -> &SyntaxNode {
+        &self.parent
+    }
+}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{:?} {:?}", self.kind(), self.span(), self.text())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxElement {
+    #[must_use]
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            Self::Node(node) => node.kind(),
+            Self::Token(token) => token.kind(),
+        }
+    }
+    #[must_use]
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Node(node) => node.span(),
+            Self::Token(token) => token.span(),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+//! Classifies the text between the tokens of the syntax tree, which the lexer skips over.
+
+use source::Span;
+
+use crate::SyntaxKind;
+
+/// Whether only spaces and tabs come before `offset` on its line.
+pub(crate) fn starts_line(text: &str, offset: usize) -> bool {
+    text[..offset]
+        .bytes()
+        .rev()
+        .take_while(|&b| b != b'\n')
+        .all(|b| b == b' ' || b == b'\t')
+}
+
+/// Finds the end of the line `start` is on, following backslash-newlines.
+pub(crate) fn logical_line_end(text: &str, start: usize) -> usize {
+    let mut end = start;
+    loop {
+        let Some(newline) = text[end..].find('\n') else {
+            return text.len();
+        };
+        let line = text[end..end + newline].trim_end_matches('\r');
+        end += newline;
+        if !line.ends_with('\\') {
+            return end;
+        }
+        end += 1;
+    }
+}
+
+/// Splits the text the lexer skipped between two tokens into whitespace, comments and line
+/// markers, passing each piece to `push`.
+pub(crate) fn split(text: &str, span: Span, mut push: impl FnMut(SyntaxKind, Span)) {
+    let mut start = span.start;
+    while start < span.end {
+        let rest = &text[start..span.end];
+        let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+        let (kind, len) = if rest.starts_with(|c: char| c.is_ascii_whitespace()) {
+            let len = rest
+                .find(|c: char| !c.is_ascii_whitespace())
+                .unwrap_or(rest.len());
+            (SyntaxKind::Whitespace, len)
+        } else if rest.starts_with("/*") {
+            let len = rest.find("*/").map_or(rest.len(), |end| end + 2);
+            (SyntaxKind::Comment, len)
+        } else if rest.starts_with("//") {
+            (SyntaxKind::Comment, line.len())
+        } else if is_line_marker(line) {
+            (SyntaxKind::LineMarker, line.len())
+        } else {
+            // The lexer reports everything else as an error token
+            (SyntaxKind::Error, rest.len())
+        };
+        push(kind, start..start + len);
+        start += len;
+    }
+}
+
+/// Whether `line` is a line marker, `# <line> "<file>" <flags>`.
+pub(crate) fn is_line_marker(line: &str) -> bool {
+    let Some(rest) = line.strip_prefix('#') else {
+        return false;
+    };
+    let rest = rest.trim_start_matches([' ', '\t']);
+    let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+    let rest = &rest[digits..];
+    let Some(name) = rest.trim_start_matches([' ', '\t']).strip_prefix('"') else {
+        return false;
+    };
+    if digits == 0 || rest.len() == name.len() + 1 {
+        return false;
+    }
+    // The name ends at the first quote that isn't escaped
+    let mut escaped = false;
+    let Some(close) = name.find(|c| {
+        let close = c == '"' && !escaped;
+        escaped = c == '\\' && !escaped;
+        close
+    }) else {
+        return false;
+    };
+    name[close + 1..]
+        .bytes()
+        .all(|b| b.is_ascii_digit() || b == b' ' || b == b'\t')
+}
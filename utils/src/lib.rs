@@ -16,6 +16,9 @@
 
 use std::{fmt::Display, ops::Range};
 
+mod literal;
+pub use literal::*;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum VarInt {
     Pos(u64),
@@ -43,6 +46,13 @@ impl<T> From<(T, Range<usize>)> for Spanned<T> {
     }
 }
 
+/// Displays the inner value, leaving out the span.
+impl<T: Display> Display for Spanned<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
 impl<T> Spanned<T> {
     #[must_use]
     pub const fn new(inner: T, span: Range<usize>) -> Self {
@@ -54,4 +64,7 @@ impl<T> Spanned<T> {
     pub fn split(self) -> (T, Range<usize>) {
         (self.inner, self.span)
     }
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Spanned<U> {
+        Spanned::new(f(self.inner), self.span)
+    }
 }
@@ -0,0 +1,293 @@
+// LICENSE NOTICE START
+// This file is part of CCcc, A simple x86-64 compiler for a tiny subset of C.
+// Copyright (C) 2026 CordlessCoder
+//
+// CCcc is free software: you can redistribute it and/or modify it under the terms
+// of the GNU General Public License as published by the Free Software Foundation,
+// either version 3 of the License, or (at your option) any later version.
+//
+// CCcc is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR
+// PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with CCcc.
+// If not, see <https://www.gnu.org/licenses/>.
+// LICENSE NOTICE END
+
+use std::fmt::{Display, Write};
+
+/// The type of an integer constant, chosen as described in C17 6.4.4.1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IntKind {
+    Int,
+    UInt,
+    Long,
+    ULong,
+    LongLong,
+    ULongLong,
+}
+
+impl IntKind {
+    #[must_use]
+    pub const fn is_signed(self) -> bool {
+        matches!(self, Self::Int | Self::Long | Self::LongLong)
+    }
+    /// The largest value representable by this type on x86-64.
+    #[must_use]
+    pub const fn max_value(self) -> u64 {
+        match self {
+            Self::Int => i32::MAX as u64,
+            Self::UInt => u32::MAX as u64,
+            Self::Long | Self::LongLong => i64::MAX as u64,
+            Self::ULong | Self::ULongLong => u64::MAX,
+        }
+    }
+    /// The suffix that gives an integer constant this type.
+    #[must_use]
+    pub const fn suffix(self) -> &'static str {
+        match self {
+            Self::Int => "",
+            Self::UInt => "u",
+            Self::Long => "l",
+            Self::ULong => "ul",
+            Self::LongLong => "ll",
+            Self::ULongLong => "ull",
+        }
+    }
+}
+
+/// An integer constant along with its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntLiteral {
+    pub value: u64,
+    pub kind: IntKind,
+}
+
+impl Display for IntLiteral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.value, self.kind.suffix())
+    }
+}
+
+/// The type of a floating constant, chosen by its suffix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FloatKind {
+    Float,
+    Double,
+    LongDouble,
+}
+
+impl FloatKind {
+    #[must_use]
+    pub const fn suffix(self) -> &'static str {
+        match self {
+            Self::Float => "f",
+            Self::Double => "",
+            Self::LongDouble => "l",
+        }
+    }
+}
+
+/// A floating constant along with its type.
+///
+/// `float` constants are rounded to single precision, so their value is always exactly
+/// representable as an `f32`. `long double` constants are currently rounded to double precision.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FloatLiteral {
+    pub value: f64,
+    pub kind: FloatKind,
+}
+
+impl Display for FloatLiteral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Debug formatting produces the shortest representation that round-trips
+        match self.kind {
+            #[allow(clippy::cast_possible_truncation)]
+            FloatKind::Float => write!(f, "{:?}", self.value as f32)?,
+            FloatKind::Double | FloatKind::LongDouble => write!(f, "{:?}", self.value)?,
+        }
+        f.write_str(self.kind.suffix())
+    }
+}
+
+/// The encoding prefix of a character constant or string literal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// No prefix, `char` in the execution character set (UTF-8)
+    Plain,
+    /// `u8`, UTF-8
+    Utf8,
+    /// `u`, UTF-16
+    Utf16,
+    /// `U`, UTF-32
+    Utf32,
+    /// `L`, `wchar_t`, which is UTF-32 on x86-64 Linux
+    Wide,
+}
+
+impl Encoding {
+    #[must_use]
+    pub const fn prefix(self) -> &'static str {
+        match self {
+            Self::Plain => "",
+            Self::Utf8 => "u8",
+            Self::Utf16 => "u",
+            Self::Utf32 => "U",
+            Self::Wide => "L",
+        }
+    }
+    /// The size of a single code unit in bytes.
+    #[must_use]
+    pub const fn unit_size(self) -> usize {
+        match self {
+            Self::Plain | Self::Utf8 => 1,
+            Self::Utf16 => 2,
+            Self::Utf32 | Self::Wide => 4,
+        }
+    }
+    /// The largest value of a single code unit.
+    #[must_use]
+    pub const fn max_unit(self) -> u32 {
+        match self {
+            Self::Plain | Self::Utf8 => u8::MAX as u32,
+            Self::Utf16 => u16::MAX as u32,
+            Self::Utf32 | Self::Wide => u32::MAX,
+        }
+    }
+    /// Appends the code units encoding `c` to `units`.
+    pub fn encode(self, c: char, units: &mut Vec<u32>) {
+        match self {
+            Self::Plain | Self::Utf8 => {
+                units.extend(c.encode_utf8(&mut [0; 4]).bytes().map(u32::from));
+            }
+            Self::Utf16 => units.extend(c.encode_utf16(&mut [0; 2]).iter().map(|&u| u32::from(u))),
+            Self::Utf32 | Self::Wide => units.push(u32::from(c)),
+        }
+    }
+}
+
+/// A single element of a character constant or string literal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrChar {
+    /// A character from the source, a simple escape sequence like `\n`, or a universal
+    /// character name. Gets encoded according to the encoding of the literal.
+    Char(char),
+    /// The value of an octal or hexadecimal escape sequence, which is a single code unit.
+    Unit(u32),
+}
+
+/// Writes the elements of a character constant or string literal with the given encoding,
+/// escaped so that they lex back to the same values between `quote`s.
+fn write_escaped(
+    f: &mut impl Write,
+    chars: impl IntoIterator<Item = StrChar>,
+    quote: char,
+    encoding: Encoding,
+) -> std::fmt::Result {
+    // A hexadecimal escape sequence goes on for as long as there are hex digits after it
+    let mut after_hex = false;
+    for c in chars {
+        match c {
+            StrChar::Char(c) if c == quote || c == '\\' => write!(f, "\\{c}")?,
+            StrChar::Char('\n') => f.write_str("\\n")?,
+            StrChar::Char('\t') => f.write_str("\\t")?,
+            StrChar::Char('\r') => f.write_str("\\r")?,
+            StrChar::Char('\x07') => f.write_str("\\a")?,
+            StrChar::Char('\x08') => f.write_str("\\b")?,
+            StrChar::Char('\x0b') => f.write_str("\\v")?,
+            StrChar::Char('\x0c') => f.write_str("\\f")?,
+            // Universal character names can't be used for control characters (C17 6.4.3p2), so
+            // they're written as the octal escapes of their code units instead
+            StrChar::Char(c) if c.is_control() || (after_hex && c.is_ascii_hexdigit()) => {
+                let mut units = Vec::new();
+                encoding.encode(c, &mut units);
+                for unit in units {
+                    write!(f, "\\{unit:03o}")?;
+                }
+            }
+            StrChar::Char(c) => f.write_char(c)?,
+            // Octal escapes are at most three digits long, so they can't swallow the next char
+            StrChar::Unit(u) if u <= 0o777 => write!(f, "\\{u:03o}")?,
+            StrChar::Unit(u) => write!(f, "\\x{u:x}")?,
+        }
+        after_hex = matches!(c, StrChar::Unit(u) if u > 0o777);
+    }
+    Ok(())
+}
+
+/// A string literal, after escape sequences have been interpreted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringLiteral {
+    pub encoding: Encoding,
+    pub chars: Vec<StrChar>,
+}
+
+impl StringLiteral {
+    /// Concatenates an adjacent string literal onto this one, as described in C17 6.4.5p5.
+    ///
+    /// Returns `false` if the literals have different encoding prefixes, neither of which is
+    /// empty.
+    pub fn concat(&mut self, other: Self) -> bool {
+        match (self.encoding, other.encoding) {
+            (a, b) if a == b => (),
+            (_, Encoding::Plain) => (),
+            (Encoding::Plain, b) => self.encoding = b,
+            _ => return false,
+        }
+        self.chars.extend(other.chars);
+        true
+    }
+    /// The code units of the string, without the terminating null character.
+    #[must_use]
+    pub fn units(&self) -> Vec<u32> {
+        let mut units = Vec::with_capacity(self.chars.len());
+        for &c in &self.chars {
+            match c {
+                StrChar::Char(c) => self.encoding.encode(c, &mut units),
+                StrChar::Unit(u) => units.push(u),
+            }
+        }
+        units
+    }
+}
+
+impl Display for StringLiteral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}\"", self.encoding.prefix())?;
+        write_escaped(f, self.chars.iter().copied(), '"', self.encoding)?;
+        f.write_char('"')
+    }
+}
+
+/// A character constant along with its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CharLiteral {
+    pub encoding: Encoding,
+    /// The value of the constant, converted to its type
+    pub value: i64,
+}
+
+impl Display for CharLiteral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}'", self.encoding.prefix())?;
+        let units: Vec<u32> = match self.encoding {
+            // Multi-character constants are stored big-endian
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            Encoding::Plain if !(-128..=127).contains(&self.value) => {
+                let bytes = (self.value as u32).to_be_bytes();
+                let start = bytes.iter().position(|&b| b != 0).unwrap_or(3);
+                bytes[start..].iter().map(|&b| u32::from(b)).collect()
+            }
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            Encoding::Plain => vec![u32::from(self.value as u8)],
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            _ => vec![self.value as u32],
+        };
+        let chars = units.into_iter().map(|unit| match char::from_u32(unit) {
+            Some(c) if unit < 0x80 || self.encoding != Encoding::Plain => StrChar::Char(c),
+            _ => StrChar::Unit(unit),
+        });
+        write_escaped(f, chars, '\'', self.encoding)?;
+        f.write_char('\'')
+    }
+}